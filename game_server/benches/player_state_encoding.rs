@@ -0,0 +1,248 @@
+// Slice 4D Shooter - the first multiplayer shooter set in 4D space
+// Copyright (C) 2023-2025  Timofei Molokov
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Packet size, encode/decode time and precision of the quantized player state.
+// Run with `cargo bench -p game_server --bench player_state_encoding`
+
+use std::{
+    hint::black_box,
+    time::Instant
+};
+
+use client_server_protocol::{
+    MapBounds,
+    NetMessageToPlayer,
+    QuantizedPlayerState,
+    RemoteMessage,
+    ServerMessage
+};
+
+const STATES_AMOUNT: usize = 1_000;
+const ITERATIONS: usize = 1_000;
+
+// size of the full precision state which was sent before the quantization:
+// transform ([f32;4], [f32;16]), input (bool,bool,bool,bool,bool), velocity [f32;4], time u128
+const FULL_PRECISION_STATE_SIZE: usize = 16 + 64 + 5 + 16 + 16;
+
+// position [u16;4], rotation [i16;12], velocity [i16;4], input u8, time u32
+const QUANTIZED_STATE_SIZE: usize = 8 + 24 + 8 + 1 + 4;
+
+type State = (([f32;4], [f32;16]), (bool,bool,bool,bool,bool), [f32;4], u128);
+
+fn main()
+{
+    let bounds = MapBounds {
+        min: [-41.0, -17.0, -66.0, -17.0],
+        max: [41.0, 40.0, 66.0, 32.0],
+    };
+
+    let states = generate_states(&bounds);
+
+    let packet = make_packet(&states[0], &bounds);
+
+    println!("player state message:");
+    println!("    full precision state: {} bytes", FULL_PRECISION_STATE_SIZE);
+    println!("    quantized state: {} bytes", QUANTIZED_STATE_SIZE);
+    println!("    packet with the envelope: {} bytes", packet.len());
+
+    let start = Instant::now();
+
+    for _ in 0..ITERATIONS
+    {
+        for state in &states
+        {
+            black_box(make_packet(black_box(state), &bounds));
+        }
+    }
+
+    println!(
+        "    encode: {:.1} ns per state",
+        start.elapsed().as_nanos() as f64 / (ITERATIONS * STATES_AMOUNT) as f64
+    );
+
+    let packets: Vec<Box<[u8]>> = states
+        .iter()
+        .map(|state| make_packet(state, &bounds))
+        .collect();
+
+    let start = Instant::now();
+
+    for _ in 0..ITERATIONS
+    {
+        for packet in &packets
+        {
+            black_box(decode_packet(black_box(packet.clone()), &bounds, 1_000_000));
+        }
+    }
+
+    println!(
+        "    decode: {:.1} ns per state (including the packet's clone)",
+        start.elapsed().as_nanos() as f64 / (ITERATIONS * STATES_AMOUNT) as f64
+    );
+
+    let mut max_position_error = 0f32;
+    let mut max_rotation_error = 0f32;
+    let mut max_velocity_error = 0f32;
+
+    for (state, packet) in states.iter().zip(packets.iter())
+    {
+        let (transform, input, velocity, time) = decode_packet(packet.clone(), &bounds, state.3 + 5_000)
+            .expect("can't decode the packet");
+
+        assert!(input == state.1, "input state is changed by the encoding");
+        assert!(time == state.3, "time is changed by the encoding");
+
+        for axis in 0..4
+        {
+            max_position_error = max_position_error.max((transform.0[axis] - state.0.0[axis]).abs());
+            max_velocity_error = max_velocity_error.max((velocity[axis] - state.2[axis]).abs());
+        }
+
+        for i in 0..16
+        {
+            max_rotation_error = max_rotation_error.max((transform.1[i] - state.0.1[i]).abs());
+        }
+    }
+
+    println!("    max position error: {}", max_position_error);
+    println!("    max rotation matrix element error: {}", max_rotation_error);
+    println!("    max velocity error: {}", max_velocity_error);
+}
+
+fn make_packet(state: &State, bounds: &MapBounds) -> Box<[u8]>
+{
+    let player_state = QuantizedPlayerState::new(state.0, state.1, state.2, state.3, bounds);
+
+    ServerMessage::NetMessageToPlayer(
+        u128::MAX,
+        NetMessageToPlayer::RemoteDirectMessage(
+            u128::MAX,
+            RemoteMessage::SetPlayerDollState(player_state)
+        )
+    ).to_packet()
+}
+
+fn decode_packet(packet: Box<[u8]>, bounds: &MapBounds, current_time: u128) -> Option<State>
+{
+    match ServerMessage::from_packet(packet)?
+    {
+        ServerMessage::NetMessageToPlayer(
+            _,
+            NetMessageToPlayer::RemoteDirectMessage(
+                _,
+                RemoteMessage::SetPlayerDollState(player_state)
+            )
+        ) =>
+        {
+            Some((
+                player_state.get_transform(bounds),
+                player_state.get_input(),
+                player_state.get_velocity(),
+                player_state.get_time(current_time),
+            ))
+        }
+        _ => None,
+    }
+}
+
+// deterministic states inside the bounds with random rotations in all six planes
+fn generate_states(bounds: &MapBounds) -> Vec<State>
+{
+    let mut seed = 0x2545f4914f6cdd1du64;
+
+    let mut random = move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+
+        (seed >> 40) as f32 / (1u64 << 24) as f32
+    };
+
+    let mut states = Vec::with_capacity(STATES_AMOUNT);
+
+    for i in 0..STATES_AMOUNT
+    {
+        let mut position = [0f32; 4];
+        let mut velocity = [0f32; 4];
+
+        for axis in 0..4
+        {
+            position[axis] = bounds.min[axis] + random() * (bounds.max[axis] - bounds.min[axis]);
+            velocity[axis] = (random() - 0.5) * 40.0;
+        }
+
+        let mut rotation = identity();
+
+        for plane in 0..6
+        {
+            rotation = multiply(&rotation, &plane_rotation(plane, random() * std::f32::consts::TAU));
+        }
+
+        let input = (random() > 0.5, random() > 0.5, random() > 0.5, random() > 0.5, random() > 0.5);
+
+        // times around the wrap of the low 32 bits
+        let time = (1u128 << 32) - 500 + i as u128;
+
+        states.push(((position, rotation), input, velocity, time));
+    }
+
+    states
+}
+
+fn identity() -> [f32; 16]
+{
+    let mut matrix = [0f32; 16];
+
+    for i in 0..4
+    {
+        matrix[i * 4 + i] = 1.0;
+    }
+
+    matrix
+}
+
+// rotation in one of the xy, xz, xw, yz, yw, zw planes, column-major
+fn plane_rotation(plane: usize, angle: f32) -> [f32; 16]
+{
+    let (a, b) = [(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)][plane];
+
+    let mut matrix = identity();
+
+    matrix[a * 4 + a] = angle.cos();
+    matrix[b * 4 + a] = -angle.sin();
+    matrix[a * 4 + b] = angle.sin();
+    matrix[b * 4 + b] = angle.cos();
+
+    matrix
+}
+
+fn multiply(lhs: &[f32; 16], rhs: &[f32; 16]) -> [f32; 16]
+{
+    let mut result = [0f32; 16];
+
+    for column in 0..4
+    {
+        for row in 0..4
+        {
+            for k in 0..4
+            {
+                result[column * 4 + row] += lhs[k * 4 + row] * rhs[column * 4 + k];
+            }
+        }
+    }
+
+    result
+}
@@ -21,6 +21,152 @@ type ActorID = u128;
 
 type SerializableTransform = ([f32; 4], [f32; 16]);
 
+// max amount of teams on the game server, Red and Blue teams included
+pub const MAX_TEAMS: usize = 8;
+
+// max amount of Move W bonus spots on the map,
+// collected spots are sent to a joined player as a u64 bit mask
+pub const MAX_MOVE_W_BONUS_SPOTS: usize = 64;
+
+// max length in bytes of the map's name (name of the map file without extension)
+pub const MAX_MAP_NAME_LENGTH: usize = 32;
+
+// space around the map's objects where players still can be,
+// positions outside the map bounds are clamped
+pub const MAP_BOUNDS_MARGIN: f32 = 16.0;
+
+// quantized velocity is stored in 1/VELOCITY_QUANTS_PER_UNIT units per second,
+// so it's clamped to about ±128 units per second on every axis
+const VELOCITY_QUANTS_PER_UNIT: f32 = 256.0;
+
+// version of the client-server protocol, must be increased
+// on every incompatible change of the messages
+pub const PROTOCOL_VERSION: u32 = 3;
+
+// optional features negotiated in the handshake, the game server
+// enables only the features both sides support
+pub const FEATURE_RECONNECT: u64 = 1 << 0;
+pub const FEATURE_VOTING: u64 = 1 << 1;
+
+// features this build of the protocol supports
+pub const SUPPORTED_FEATURES: u64 = FEATURE_RECONNECT | FEATURE_VOTING;
+
+// handshake packets begin with this magic, so they can't be
+// confused with alkahest-serialized messages
+const HANDSHAKE_MAGIC: &[u8; 4] = b"S4DH";
+
+// Handshake messages are not serialized by alkahest, their binary format
+// doesn't depend on the protocol version, so the game server and the client
+// can always read each other's version:
+//     magic            4 bytes, "S4DH"
+//     message kind     u8, 0 - Hello, 1 - Welcome, 2 - Rejected
+//     protocol version u32 little endian
+//     payload          u64 little endian features for Hello and Welcome,
+//                      u8 HandshakeRejectReason for Rejected
+#[derive(Clone, Copy, Debug)]
+pub enum HandshakeMessage {
+    // the first message of the client on the reliable channel,
+    // it's sent before the join request
+    Hello(
+        // client's PROTOCOL_VERSION
+        u32,
+        // features supported by the client
+        u64,
+    ),
+    // the game server accepted the client's protocol version
+    Welcome(
+        // game server's PROTOCOL_VERSION
+        u32,
+        // features enabled for this client
+        u64,
+    ),
+    // the game server drops all further packets of the client
+    Rejected(
+        // game server's PROTOCOL_VERSION
+        u32,
+        HandshakeRejectReason,
+    ),
+}
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug)]
+pub enum HandshakeRejectReason {
+    WrongProtocolVersion = 0,
+    // the client sent the join request without Hello
+    HandshakeRequired = 1,
+}
+
+impl HandshakeMessage {
+    pub fn to_packet(self) -> Packet {
+        let mut packet: Vec<u8> = Vec::with_capacity(17);
+
+        packet.extend_from_slice(HANDSHAKE_MAGIC);
+
+        match self
+        {
+            HandshakeMessage::Hello(protocol_version, features) =>
+            {
+                packet.push(0u8);
+                packet.extend_from_slice(&protocol_version.to_le_bytes());
+                packet.extend_from_slice(&features.to_le_bytes());
+            }
+            HandshakeMessage::Welcome(protocol_version, features) =>
+            {
+                packet.push(1u8);
+                packet.extend_from_slice(&protocol_version.to_le_bytes());
+                packet.extend_from_slice(&features.to_le_bytes());
+            }
+            HandshakeMessage::Rejected(protocol_version, reason) =>
+            {
+                packet.push(2u8);
+                packet.extend_from_slice(&protocol_version.to_le_bytes());
+                packet.push(reason as u8);
+            }
+        }
+
+        packet.into_boxed_slice()
+    }
+
+    // returns None if the packet is not a handshake message
+    pub fn from_packet(packet: &[u8]) -> Option<Self> {
+        if packet.len() < 9 || &packet[0..4] != HANDSHAKE_MAGIC
+        {
+            return None;
+        }
+
+        let protocol_version = u32::from_le_bytes(packet[5..9].try_into().ok()?);
+
+        match packet[4]
+        {
+            0 | 1 =>
+            {
+                let features = u64::from_le_bytes(packet.get(9..17)?.try_into().ok()?);
+
+                if packet[4] == 0
+                {
+                    Some(HandshakeMessage::Hello(protocol_version, features))
+                }
+                else
+                {
+                    Some(HandshakeMessage::Welcome(protocol_version, features))
+                }
+            }
+            2 =>
+            {
+                let reason = match packet.get(9)?
+                {
+                    0 => HandshakeRejectReason::WrongProtocolVersion,
+                    1 => HandshakeRejectReason::HandshakeRequired,
+                    _ => return None,
+                };
+
+                Some(HandshakeMessage::Rejected(protocol_version, reason))
+            }
+            _ => None,
+        }
+    }
+}
+
 #[repr(C)]
 #[alkahest(Formula, Serialize, Deserialize)]
 pub enum ClientMessage {
@@ -62,17 +208,22 @@ pub enum ServerMessage {
         FlagStatus,
         // status of the Blue Flag
         FlagStatus,
-        // status of the Move W Bonus
-        BonusSpotStatus,
-        // score of Red team
+        // bit mask of collected Move W bonus spots indexed by bonus spot index
+        u64,
+        // teams scores indexed by team id
+        [u32; MAX_TEAMS],
+        // score to win the game session (kill limit in Team Deathmatch and Free-for-all)
         u32,
-        // score of Blue team
-        u32
+        // game mode of the game server
+        GameMode,
+        // map of the current game session
+        MapInfo
     ),
 
     // u128 - time_in_millis_from_game_session_init
     // Team - Which team has this player added to
-    NewSessionStarted(u128, Team),
+    // MapInfo - map of the new game session
+    NewSessionStarted(u128, Team, MapInfo),
     
     // u128 - id of connected player
     PlayerConnected(u128),
@@ -83,6 +234,44 @@ pub enum ServerMessage {
     // u128 - id of message sender
     // NetMessageToPlayer - message
     NetMessageToPlayer(u128, NetMessageToPlayer),
+
+    // statistics of all players in the current game session
+    Scoreboard(Vec<PlayerStats>),
+
+    // all player or spectator slots (depending on the join request) are occupied
+    ServerIsFull,
+
+    // the peer was removed from the game session by the operator
+    // or for exceeding its traffic budget too many times in a row
+    YouAreKicked,
+
+    // the game server is going to exit, clients should
+    // go back to the matchmaking server
+    ServerShuttingDown(ShutdownReason),
+
+    // u128 - token to rejoin the game session after a lost connection
+    ReconnectToken(u128),
+
+    // sent to all players and spectators when a vote is started
+    // and every time a player casts a ballot
+    VoteState(
+        // what is voted for
+        VoteKind,
+        // id of the player who started the vote
+        u128,
+        // yes votes
+        u32,
+        // no votes
+        u32,
+        // yes votes required to pass the vote
+        u32,
+        // time left in millis
+        u128,
+    ),
+
+    // VoteKind - what was voted for
+    // bool - true if the vote is passed
+    VoteFinished(VoteKind, bool),
 }
 
 impl ServerMessage {
@@ -136,6 +325,34 @@ pub enum NetMessageToServer {
         // droped in space
         bool
     ),
+    IKilledPlayer(
+        // id of killed player
+        u128,
+    ),
+    IDied,
+    // the first message of a client after connection to the game server,
+    // the server doesn't add a client to the game session until it's received
+    JoinAsPlayer,
+    // sent by the observer clients instead of JoinAsPlayer,
+    // spectators have no team but receive all broadcast messages
+    JoinAsSpectator,
+    // sent instead of JoinAsPlayer after a lost connection, the player
+    // gets back the reserved team and statistics if the seat is still reserved
+    RejoinAsPlayer(
+        // reconnect token issued by the game server on join
+        u128
+    ),
+    // the player who starts the vote votes yes automatically,
+    // only one vote can be in progress at a time
+    StartVote(VoteKind),
+    // bool - true for yes
+    CastVote(bool),
+    // sent instead of JoinAsPlayer by the members of a party,
+    // the game server keeps the party on the same team where possible
+    JoinAsPartyMember(
+        // party id issued by the matchmaking server
+        u128
+    ),
 }
 
 #[repr(C)]
@@ -179,6 +396,22 @@ pub enum RemoteCommand {
         // explode final time
         f32,
     ),
+    SpawnObstacle(
+        // position
+        [f32;4],
+        //radius
+        f32,
+        // color
+        [f32;3],
+        // target size is reached
+        bool,
+        // target radius
+        f32,
+        // explode current time
+        f32,
+        // explode final time
+        f32,
+    ),
 }
 
 #[repr(C)]
@@ -212,16 +445,9 @@ pub enum RemoteMessage {
     ),
     Enable(bool),
     SetTransform(SerializableTransform),
-    SetPlayerDollState(
-        // Player's transform
-        SerializableTransform,
-        // simple input state (for extrapolation)
-        (bool,bool,bool,bool,bool),
-        // player's velocity
-        [f32;4],
-        // frame's server time
-        u128
-    ),
+    // player's transform, simple input state (for extrapolation),
+    // velocity and frame's server time
+    SetPlayerDollState(QuantizedPlayerState),
     SpawnHoleGunShotActor(
         // shot's impact position
         [f32;4],
@@ -294,9 +520,15 @@ pub enum RemoteMessage {
     ),
 
     UpdateTeamsScore(
-        // Red team score
+        // teams scores indexed by team id
+        [u32; MAX_TEAMS]
+    ),
+
+    // sent to each player personally in Free-for-all
+    UpdateFreeForAllScore(
+        // recipient's score
         u32,
-        // Blue team score
+        // best score among recipient's opponents
         u32
     ),
 
@@ -307,6 +539,23 @@ pub enum RemoteMessage {
     TeamWin(
         Team
     ),
+
+    // sent to each player personally in Free-for-all
+    PlayerWin(
+        // true if the recipient is the winner
+        bool
+    ),
+
+    SetMatchTimeLeft(
+        // time left until the end of the game session (or overtime) in millis
+        u128,
+        // full duration of the game session (or overtime) in millis
+        u128,
+        // is it overtime
+        bool
+    ),
+
+    Draw,
 }
 
 impl NetMessageToPlayer {
@@ -352,6 +601,36 @@ pub enum BonusSpotStatus
     ),
 }
 
+#[repr(C)]
+#[alkahest(Formula, Serialize, Deserialize)]
+#[derive(Clone, Copy)]
+pub struct PlayerStats
+{
+    pub player_id: u128,
+    pub team: Team,
+    pub kills: u32,
+    pub deaths: u32,
+    pub flag_captures: u32,
+    pub flag_returns: u32,
+    pub damage_dealt: u32,
+}
+
+impl PlayerStats
+{
+    pub fn new(player_id: u128, team: Team) -> Self
+    {
+        PlayerStats {
+            player_id,
+            team,
+            kills: 0u32,
+            deaths: 0u32,
+            flag_captures: 0u32,
+            flag_returns: 0u32,
+            damage_dealt: 0u32,
+        }
+    }
+}
+
 #[repr(C)]
 #[alkahest(Formula, Serialize, Deserialize)]
 #[derive(Clone, Copy)]
@@ -359,37 +638,517 @@ pub enum Team
 {
     Red,
     Blue,
+    // teams of maps with more than two teams,
+    // u8 is the team id (Red team's id is 0, Blue team's id is 1)
+    Extra(u8),
+    // player has no team in Free-for-all mode
+    NoTeam,
+}
+
+impl Team
+{
+    pub fn from_id(id: u8) -> Self
+    {
+        match id
+        {
+            0 => Team::Red,
+            1 => Team::Blue,
+            _ => Team::Extra(id),
+        }
+    }
+
+    // returns None for NoTeam
+    pub fn get_id(&self) -> Option<u8>
+    {
+        match self
+        {
+            Team::Red => Some(0),
+            Team::Blue => Some(1),
+            Team::Extra(id) => Some(*id),
+            Team::NoTeam => None,
+        }
+    }
+
+    // players without team are opponents for each other
+    pub fn is_opponent(&self, other: &Team) -> bool
+    {
+        match self
+        {
+            Team::NoTeam => true,
+            _ => self != other,
+        }
+    }
+}
+
+#[repr(C)]
+#[alkahest(Formula, Serialize, Deserialize)]
+#[derive(Clone, Copy)]
+pub struct MapInfo
+{
+    // utf-8 name of the map file without extension, padded with zeros
+    pub name: [u8; MAX_MAP_NAME_LENGTH],
+    // hash of the map file's content, clients compare it with the hash of their map file
+    pub content_hash: u64,
+    // players' positions are quantized relative to these bounds
+    pub bounds: MapBounds,
+}
+
+impl MapInfo
+{
+    pub fn new(name: &str, content: &[u8]) -> Result<Self, String>
+    {
+        let name_bytes = name.as_bytes();
+
+        if name_bytes.is_empty() || name_bytes.len() > MAX_MAP_NAME_LENGTH
+        {
+            return Err(format!(
+                "map name {} must be from 1 to {} bytes long",
+                name,
+                MAX_MAP_NAME_LENGTH
+            ));
+        }
+
+        let mut name = [0u8; MAX_MAP_NAME_LENGTH];
+        name[..name_bytes.len()].copy_from_slice(name_bytes);
+
+        Ok(MapInfo {
+            name,
+            content_hash: get_map_content_hash(content),
+            bounds: MapBounds::from_map_content(content)?,
+        })
+    }
+
+    pub fn get_name(&self) -> String
+    {
+        let length = self.name
+            .iter()
+            .position(|byte| *byte == 0u8)
+            .unwrap_or(MAX_MAP_NAME_LENGTH);
+
+        String::from_utf8_lossy(&self.name[..length]).into_owned()
+    }
+}
+
+// FNV-1a, the same function must be used by the game server and clients
+pub fn get_map_content_hash(content: &[u8]) -> u64
+{
+    let mut hash = 0xcbf29ce484222325u64;
+
+    for byte in content
+    {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3u64);
+    }
+
+    hash
+}
+
+#[repr(C)]
+#[alkahest(Formula, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug)]
+pub struct MapBounds
+{
+    pub min: [f32; 4],
+    pub max: [f32; 4],
+}
+
+impl MapBounds
+{
+    // the bounds contain every object of the map with MAP_BOUNDS_MARGIN around,
+    // objects' rotation is not taken into account, so the extent of an object
+    // is the length of its size vector
+    pub fn from_map_content(content: &[u8]) -> Result<Self, String>
+    {
+        let json: serde_json::Value = serde_json::from_slice(content)
+            .map_err(|e| format!("can't parse the map's JSON, err: {}", e))?;
+
+        let mut bounds = MapBounds {
+            min: [f32::MAX; 4],
+            max: [f32::MIN; 4],
+        };
+
+        expand_bounds_by_json_objects(&mut bounds, &json);
+
+        if bounds.min[0] > bounds.max[0]
+        {
+            return Err("the map has no objects with transform".to_string());
+        }
+
+        for axis in 0..4
+        {
+            bounds.min[axis] -= MAP_BOUNDS_MARGIN;
+            bounds.max[axis] += MAP_BOUNDS_MARGIN;
+        }
+
+        Ok(bounds)
+    }
+}
+
+fn expand_bounds_by_json_objects(bounds: &mut MapBounds, json: &serde_json::Value)
+{
+    match json
+    {
+        serde_json::Value::Object(object) =>
+        {
+            let position = object
+                .get("transform")
+                .and_then(|transform| transform.get("position"))
+                .map(parse_json_vec4);
+
+            if let Some(position) = position
+            {
+                let size = object
+                    .get("size")
+                    .map(parse_json_vec4)
+                    .unwrap_or([0.0; 4]);
+
+                let roundness = object
+                    .get("roundness")
+                    .and_then(|roundness| roundness.as_f64())
+                    .unwrap_or(0.0) as f32;
+
+                let extent = length4(size) + roundness;
+
+                for axis in 0..4
+                {
+                    bounds.min[axis] = bounds.min[axis].min(position[axis] - extent);
+                    bounds.max[axis] = bounds.max[axis].max(position[axis] + extent);
+                }
+            }
+
+            for value in object.values()
+            {
+                expand_bounds_by_json_objects(bounds, value);
+            }
+        }
+        serde_json::Value::Array(array) =>
+        {
+            for value in array
+            {
+                expand_bounds_by_json_objects(bounds, value);
+            }
+        }
+        _ => {}
+    }
+}
+
+// missing coordinates are zeros (a sphere's size has only x for example)
+fn parse_json_vec4(json: &serde_json::Value) -> [f32; 4]
+{
+    let mut vec = [0f32; 4];
+
+    for (axis, name) in ["x", "y", "z", "w"].iter().enumerate()
+    {
+        vec[axis] = json
+            .get(*name)
+            .and_then(|value| value.as_f64())
+            .unwrap_or(0.0) as f32;
+    }
+
+    vec
+}
+
+// Player's state for the unreliable channel, 45 bytes instead of 117 bytes
+// of the full precision transform, input, velocity and time
+#[repr(C)]
+#[alkahest(Formula, Serialize, Deserialize)]
+#[derive(Clone, Copy)]
+pub struct QuantizedPlayerState
+{
+    // position relative to the map bounds, 0 is the min bound, u16::MAX is the max bound
+    pub position: [u16; 4],
+    // the first three columns of the orthonormal 4D rotation matrix in 1/i16::MAX units,
+    // the fourth column is restored as the 4D cross product of them
+    pub rotation: [i16; 12],
+    // velocity in 1/VELOCITY_QUANTS_PER_UNIT units per second
+    pub velocity: [i16; 4],
+    // input state bits: move forward, move backward, move right, move left, will jump
+    pub input: u8,
+    // the low 32 bits of the frame's server time in millis
+    pub time: u32,
+}
+
+impl QuantizedPlayerState
+{
+    pub fn new(
+        transform: SerializableTransform,
+        input: (bool,bool,bool,bool,bool),
+        velocity: [f32; 4],
+        time: u128,
+        bounds: &MapBounds,
+    ) -> Self
+    {
+        let mut position = [0u16; 4];
+
+        for axis in 0..4
+        {
+            let extent = (bounds.max[axis] - bounds.min[axis]).max(f32::EPSILON);
+
+            let t = ((transform.0[axis] - bounds.min[axis]) / extent).clamp(0.0, 1.0);
+
+            position[axis] = (t * u16::MAX as f32).round() as u16;
+        }
+
+        let mut rotation = [0i16; 12];
+
+        for i in 0..12
+        {
+            rotation[i] = (transform.1[i].clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16;
+        }
+
+        let mut quantized_velocity = [0i16; 4];
+
+        for axis in 0..4
+        {
+            quantized_velocity[axis] = (velocity[axis] * VELOCITY_QUANTS_PER_UNIT)
+                .round()
+                .clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+        }
+
+        let input =
+            (input.0 as u8) |
+            ((input.1 as u8) << 1) |
+            ((input.2 as u8) << 2) |
+            ((input.3 as u8) << 3) |
+            ((input.4 as u8) << 4);
+
+        QuantizedPlayerState {
+            position,
+            rotation,
+            velocity: quantized_velocity,
+            input,
+            time: time as u32,
+        }
+    }
+
+    pub fn get_position(&self, bounds: &MapBounds) -> [f32; 4]
+    {
+        let mut position = [0f32; 4];
+
+        for axis in 0..4
+        {
+            let t = self.position[axis] as f32 / u16::MAX as f32;
+
+            position[axis] = bounds.min[axis] + t * (bounds.max[axis] - bounds.min[axis]);
+        }
+
+        position
+    }
+
+    // the restored columns are orthonormalized to remove the quantization error
+    pub fn get_rotation(&self) -> [f32; 16]
+    {
+        let mut columns = [[0f32; 4]; 3];
+
+        for i in 0..12
+        {
+            columns[i / 4][i % 4] = self.rotation[i] as f32 / i16::MAX as f32;
+        }
+
+        let a = normalize4(columns[0]);
+        let b = normalize4(sub4(columns[1], scale4(a, dot4(columns[1], a))));
+        let c = normalize4(sub4(
+            sub4(columns[2], scale4(a, dot4(columns[2], a))),
+            scale4(b, dot4(columns[2], b))
+        ));
+        let d = cross4(a, b, c);
+
+        let mut rotation = [0f32; 16];
+
+        for (index, column) in [a, b, c, d].iter().enumerate()
+        {
+            rotation[index * 4..index * 4 + 4].copy_from_slice(column);
+        }
+
+        rotation
+    }
+
+    pub fn get_transform(&self, bounds: &MapBounds) -> SerializableTransform
+    {
+        (self.get_position(bounds), self.get_rotation())
+    }
+
+    pub fn get_input(&self) -> (bool,bool,bool,bool,bool)
+    {
+        (
+            self.input & 1 != 0,
+            self.input & (1 << 1) != 0,
+            self.input & (1 << 2) != 0,
+            self.input & (1 << 3) != 0,
+            self.input & (1 << 4) != 0,
+        )
+    }
+
+    pub fn get_velocity(&self) -> [f32; 4]
+    {
+        let mut velocity = [0f32; 4];
+
+        for axis in 0..4
+        {
+            velocity[axis] = self.velocity[axis] as f32 / VELOCITY_QUANTS_PER_UNIT;
+        }
+
+        velocity
+    }
+
+    // restores the full server time from its low 32 bits,
+    // current_time must be within 24 days of the frame's time
+    pub fn get_time(&self, current_time: u128) -> u128
+    {
+        const WRAP: u128 = 1 << 32;
+
+        let time = (current_time & !(WRAP - 1)) | self.time as u128;
+
+        if time > current_time + WRAP / 2 && time >= WRAP
+        {
+            time - WRAP
+        }
+        else if time + WRAP / 2 < current_time
+        {
+            time + WRAP
+        }
+        else
+        {
+            time
+        }
+    }
+}
+
+fn dot4(a: [f32; 4], b: [f32; 4]) -> f32
+{
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2] + a[3] * b[3]
+}
+
+fn length4(a: [f32; 4]) -> f32
+{
+    dot4(a, a).sqrt()
+}
+
+fn scale4(a: [f32; 4], s: f32) -> [f32; 4]
+{
+    [a[0] * s, a[1] * s, a[2] * s, a[3] * s]
+}
+
+fn sub4(a: [f32; 4], b: [f32; 4]) -> [f32; 4]
+{
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2], a[3] - b[3]]
+}
+
+fn normalize4(a: [f32; 4]) -> [f32; 4]
+{
+    let length = length4(a);
+
+    if length > f32::EPSILON
+    {
+        scale4(a, 1.0 / length)
+    }
+    else
+    {
+        a
+    }
+}
+
+// the vector orthogonal to a, b and c, such that
+// the matrix with columns a, b, c and the result has determinant 1
+fn cross4(a: [f32; 4], b: [f32; 4], c: [f32; 4]) -> [f32; 4]
+{
+    let det3 = |r0: usize, r1: usize, r2: usize| {
+        a[r0] * (b[r1] * c[r2] - b[r2] * c[r1]) -
+        b[r0] * (a[r1] * c[r2] - a[r2] * c[r1]) +
+        c[r0] * (a[r1] * b[r2] - a[r2] * b[r1])
+    };
+
+    [
+        -det3(1, 2, 3),
+        det3(0, 2, 3),
+        -det3(0, 1, 3),
+        det3(0, 1, 2),
+    ]
+}
+
+#[repr(C)]
+#[alkahest(Formula, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug)]
+pub enum VoteKind
+{
+    // u128 - id of the player to kick, the player can't vote
+    KickPlayer(u128),
+    // start a new game session
+    RestartMatch,
+    NextMap,
+}
+
+#[repr(C)]
+#[alkahest(Formula, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug)]
+pub enum ShutdownReason
+{
+    // no peers on the game server for idle_shutdown_time
+    IdleTimeout,
+    // game server's WebRTC connection unexpectedly closed
+    ConnectionError,
+    ShutDownByOperator,
+}
+
+#[repr(C)]
+#[alkahest(Formula, Serialize, Deserialize)]
+#[derive(Clone, Copy)]
+pub enum GameMode
+{
+    CaptureTheFlag,
+    TeamDeathmatch,
+    FreeForAll,
+}
+
+impl PartialEq for GameMode
+{
+    fn eq(&self, other: &Self) -> bool {
+        match self {
+            GameMode::CaptureTheFlag =>
+            {
+                match other {
+                    GameMode::CaptureTheFlag => true,
+                    _ => false,
+                }
+            }
+            GameMode::TeamDeathmatch =>
+            {
+                match other {
+                    GameMode::TeamDeathmatch => true,
+                    _ => false,
+                }
+            }
+            GameMode::FreeForAll =>
+            {
+                match other {
+                    GameMode::FreeForAll => true,
+                    _ => false,
+                }
+            }
+        }
+    }
 }
 
 impl PartialEq for Team
 {
     fn eq(&self, other: &Self) -> bool {
         match self {
-            Team::Red =>
+            Team::NoTeam =>
             {
                 match other {
-                    Team::Red =>
+                    Team::NoTeam =>
                     {
                         true
                     }
-                    Team::Blue =>
+                    _ =>
                     {
                         false
                     }
                 }
             }
-            Team::Blue =>
+            _ =>
             {
-                match other {
-                    Team::Red =>
-                    {
-                        false
-                    }
-                    Team::Blue =>
-                    {
-                        true
-                    }
-                }
+                self.get_id() == other.get_id()
             }
         }
     }
@@ -407,4 +1166,17 @@ pub enum NetCommand {
     SendDirectNetMessageUnreliable(NetMessageToPlayer, u128),
     SendBoardcastNetMessageReliable(NetMessageToPlayer),
     SendBoardcastNetMessageUnreliable(NetMessageToPlayer),
+    // the net system quantizes the state relative to the current map's bounds
+    SendPlayerDollStateUnreliable(
+        // player's actor id
+        ActorID,
+        // player's transform
+        SerializableTransform,
+        // simple input state (for extrapolation)
+        (bool,bool,bool,bool,bool),
+        // player's velocity
+        [f32;4],
+        // frame's server time
+        u128
+    ),
 }
@@ -0,0 +1,782 @@
+// Slice 4D Shooter - the first multiplayer shooter set in 4D space
+// Copyright (C) 2023-2025  Timofei Molokov
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+
+use glam::Vec4;
+
+use client_server_protocol::{
+    NetMessageToPlayer,
+    RemoteMessage,
+    Team
+};
+
+use crate::PlayerInfo;
+
+// The game server does not simulate shooting, so hits are validated
+// against the shots each player broadcasts to the other players.
+// A hit is relayed only if the shooter has fired a weapon recently
+// that can deal this amount of damage, and the impact position is
+// close to the last known position of the target.
+
+// values are taken from the client's weapons (see src/actor/device)
+// with a tolerance for network jitter
+pub const MACHINEGUN_MAX_DAMAGE: u32 = 10;
+pub const MACHINEGUN_MAX_SHOTS_PER_SECOND: usize = 12;
+pub const MACHINEGUN_HIT_WINDOW: u128 = 600;
+
+pub const SHOTGUN_MAX_DAMAGE: u32 = 7;
+pub const SHOTGUN_MAX_HITS_PER_SHOT: u32 = 19;
+pub const SHOTGUN_MAX_SHOTS_PER_SECOND: usize = 2;
+// shotgun's laser shots are flying up to 200 units with 155.5 units/s speed
+pub const SHOTGUN_HIT_WINDOW: u128 = 2_000;
+
+// holegun damage is (radius * 54.0) / (1.0 + distance * 0.3) * energy_gun_damage_mult,
+// where energy_gun_damage_mult is 0.49, so the damage can't be greater than radius * 26.46
+pub const HOLEGUN_MAX_DAMAGE_PER_RADIUS: f32 = 27.0;
+// the radius is sent by the shooter, a real client's shot can't be bigger
+// than MAX_ENERGY * CHARGING_COEF (see src/actor/device/holegun.rs)
+pub const HOLEGUN_MAX_RADIUS: f32 = 60.0 * 0.7;
+pub const HOLEGUN_MAX_HITS_PER_SHOT: u32 = 16;
+pub const HOLEGUN_MAX_SHOTS_PER_SECOND: usize = 6;
+pub const HOLEGUN_HIT_WINDOW: u128 = 600;
+
+pub const MAX_IMPACT_DISTANCE_FROM_TARGET: f32 = 12.0;
+
+// clients are sending hit messages before the shot messages,
+// so an unconfirmed hit waits for the shot for this time
+pub const PENDING_HIT_TIMEOUT: u128 = 300;
+
+const FIRE_RATE_WINDOW: u128 = 1_000;
+
+// a kill is attributed to the player who dealt the last validated hit
+// to the victim within this time before the victim's death
+pub const KILL_ATTRIBUTION_WINDOW: u128 = 5_000;
+
+#[derive(Clone, Copy)]
+pub enum Weapon
+{
+    MachineGun,
+    Shotgun,
+    HoleGun(
+        // hole radius
+        f32
+    ),
+}
+
+impl Weapon
+{
+    fn max_damage(&self) -> u32
+    {
+        match self
+        {
+            Weapon::MachineGun => MACHINEGUN_MAX_DAMAGE,
+            Weapon::Shotgun => SHOTGUN_MAX_DAMAGE,
+            Weapon::HoleGun(radius) => (radius * HOLEGUN_MAX_DAMAGE_PER_RADIUS).ceil() as u32,
+        }
+    }
+
+    fn max_hits_per_shot(&self) -> u32
+    {
+        match self
+        {
+            Weapon::MachineGun => 1,
+            Weapon::Shotgun => SHOTGUN_MAX_HITS_PER_SHOT,
+            Weapon::HoleGun(_) => HOLEGUN_MAX_HITS_PER_SHOT,
+        }
+    }
+
+    fn max_shots_per_second(&self) -> usize
+    {
+        match self
+        {
+            Weapon::MachineGun => MACHINEGUN_MAX_SHOTS_PER_SECOND,
+            Weapon::Shotgun => SHOTGUN_MAX_SHOTS_PER_SECOND,
+            Weapon::HoleGun(_) => HOLEGUN_MAX_SHOTS_PER_SECOND,
+        }
+    }
+
+    fn hit_window(&self) -> u128
+    {
+        match self
+        {
+            Weapon::MachineGun => MACHINEGUN_HIT_WINDOW,
+            Weapon::Shotgun => SHOTGUN_HIT_WINDOW,
+            Weapon::HoleGun(_) => HOLEGUN_HIT_WINDOW,
+        }
+    }
+
+    fn is_same_weapon(&self, other: &Weapon) -> bool
+    {
+        match (self, other)
+        {
+            (Weapon::MachineGun, Weapon::MachineGun) => true,
+            (Weapon::Shotgun, Weapon::Shotgun) => true,
+            (Weapon::HoleGun(_), Weapon::HoleGun(_)) => true,
+            _ => false,
+        }
+    }
+
+    fn name(&self) -> &'static str
+    {
+        match self
+        {
+            Weapon::MachineGun => "machinegun",
+            Weapon::Shotgun => "shotgun",
+            Weapon::HoleGun(_) => "holegun",
+        }
+    }
+
+    // returns the weapon if the message is a shot which can deal damage
+    pub fn from_shot_message(message: &NetMessageToPlayer) -> Option<Weapon>
+    {
+        let message = match message
+        {
+            NetMessageToPlayer::RemoteDirectMessage(_, message) => message,
+            NetMessageToPlayer::RemoteBoardCastMessage(message) => message,
+            NetMessageToPlayer::RemoteCommand(_) => return None,
+        };
+
+        match message
+        {
+            RemoteMessage::SpawnMachineGunShot(_, _) => Some(Weapon::MachineGun),
+            RemoteMessage::SpawnShotgunShot(_, _, _, _, _) => Some(Weapon::Shotgun),
+            RemoteMessage::SpawnHoleGunShotActor(_, _, radius, _, _) =>
+            {
+                // bigger radiuses are capped, otherwise the damage of the shot is unlimited
+                Some(Weapon::HoleGun(radius.abs().min(HOLEGUN_MAX_RADIUS)))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum HitRejection
+{
+    UnknownShooter,
+    UnknownTarget,
+    WrongTeam,
+    FriendlyFire,
+    ImpactTooFar(f32),
+    DamageTooHigh(u32),
+    NoMatchingShot,
+    FireRateExceeded(&'static str),
+    BroadcastedDamage,
+}
+
+impl HitRejection
+{
+    fn description(&self) -> String
+    {
+        match self
+        {
+            HitRejection::UnknownShooter => "shooter is not in the session".to_string(),
+            HitRejection::UnknownTarget => "target is not in the session".to_string(),
+            HitRejection::WrongTeam => "damage dealer's team doesn't match shooter's team".to_string(),
+            HitRejection::FriendlyFire => "shooter and target are in the same team".to_string(),
+            HitRejection::ImpactTooFar(distance) => format!("impact position is {} units away from target", distance),
+            HitRejection::DamageTooHigh(damage) => format!("damage {} is greater than recent shots can deal", damage),
+            HitRejection::NoMatchingShot => "no matching shot".to_string(),
+            HitRejection::FireRateExceeded(weapon) => format!("{} fire rate exceeded", weapon),
+            HitRejection::BroadcastedDamage => "damage is broadcasted to all players".to_string(),
+        }
+    }
+
+    // the shot of the hit may not be received yet
+    fn is_waiting_for_shot(&self) -> bool
+    {
+        match self
+        {
+            HitRejection::NoMatchingShot => true,
+            HitRejection::DamageTooHigh(_) => true,
+            _ => false,
+        }
+    }
+}
+
+// DealDamageAndForce message extracted from the NetMessageToPlayer
+pub struct Hit
+{
+    pub damage: u32,
+    pub impact_position: [f32;4],
+    pub damage_dealer_team: Team,
+}
+
+impl Hit
+{
+    pub fn from_message(message: &NetMessageToPlayer) -> Option<Hit>
+    {
+        let message = match message
+        {
+            NetMessageToPlayer::RemoteDirectMessage(_, message) => message,
+            NetMessageToPlayer::RemoteBoardCastMessage(message) => message,
+            NetMessageToPlayer::RemoteCommand(_) => return None,
+        };
+
+        match message
+        {
+            RemoteMessage::DealDamageAndForce(damage, _, impact_position, team) =>
+            {
+                Some(Hit {
+                    damage: *damage,
+                    impact_position: *impact_position,
+                    damage_dealer_team: *team,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+struct ShotRecord
+{
+    weapon: Weapon,
+    time: u128,
+    hits_left: u32,
+}
+
+#[derive(Default)]
+struct PeerCombatState
+{
+    last_known_position: Option<[f32;4]>,
+    recent_shots: Vec<ShotRecord>,
+    // (shooter's id, time) of the last validated hit to this peer
+    last_validated_hit: Option<(u128, u128)>,
+    rejected_hits: u32,
+    relayed_hits: u32,
+    // shots over the weapon's fire rate
+    ignored_shots: u32,
+}
+
+pub struct PendingHit
+{
+    pub from_player: u128,
+    pub to_player: u128,
+    pub message: NetMessageToPlayer,
+    received_time: u128,
+}
+
+pub struct HitValidator
+{
+    peers: HashMap<u128, PeerCombatState>,
+    pending_hits: Vec<PendingHit>,
+}
+
+impl HitValidator
+{
+    pub fn new(max_players: u32) -> Self
+    {
+        HitValidator {
+            peers: HashMap::with_capacity(max_players as usize),
+            pending_hits: Vec::new(),
+        }
+    }
+
+    pub fn add_peer(&mut self, id: u128)
+    {
+        self.peers.insert(id, PeerCombatState::default());
+    }
+
+    pub fn remove_peer(&mut self, id: u128)
+    {
+        if let Some(state) = self.peers.remove(&id)
+        {
+            println!(
+                "INFO: player {} left the session with {} relayed hits, {} rejected hits and {} ignored shots",
+                id,
+                state.relayed_hits,
+                state.rejected_hits,
+                state.ignored_shots,
+            );
+        }
+
+        self.pending_hits.retain(|hit| hit.from_player != id && hit.to_player != id);
+    }
+
+    pub fn clear(&mut self)
+    {
+        for (_, state) in self.peers.iter_mut()
+        {
+            state.recent_shots.clear();
+            state.last_known_position = None;
+            state.last_validated_hit = None;
+        }
+
+        self.pending_hits.clear();
+    }
+
+    pub fn set_last_known_position(&mut self, id: u128, position: [f32;4])
+    {
+        if let Some(state) = self.peers.get_mut(&id)
+        {
+            state.last_known_position = Some(position);
+        }
+    }
+
+    pub fn register_shot(&mut self, id: u128, weapon: Weapon, current_time: u128)
+    {
+        let state = match self.peers.get_mut(&id)
+        {
+            Some(state) => state,
+            None => return,
+        };
+
+        state.recent_shots.retain(|shot| {
+            current_time - shot.time <= FIRE_RATE_WINDOW.max(shot.weapon.hit_window())
+        });
+
+        let shots_in_last_second = state.recent_shots
+            .iter()
+            .filter(|shot| {
+                shot.weapon.is_same_weapon(&weapon) &&
+                current_time - shot.time <= FIRE_RATE_WINDOW
+            })
+            .count();
+
+        if shots_in_last_second >= weapon.max_shots_per_second()
+        {
+            state.ignored_shots += 1;
+
+            println!(
+                "WARNING: player {} shot is ignored: {}",
+                id,
+                HitRejection::FireRateExceeded(weapon.name()).description(),
+            );
+
+            return;
+        }
+
+        state.recent_shots.push(ShotRecord {
+            weapon,
+            time: current_time,
+            hits_left: weapon.max_hits_per_shot(),
+        });
+    }
+
+    // returns Some(hit) if the hit should be relayed right now,
+    // hits without a matching shot are delayed until the shot is received
+    pub fn check_hit(
+        &mut self,
+        players: &HashMap<u128, PlayerInfo>,
+        from_player: u128,
+        to_player: u128,
+        message: NetMessageToPlayer,
+        hit: Hit,
+        current_time: u128,
+    ) -> Option<NetMessageToPlayer>
+    {
+        match self.validate_hit(
+            players,
+            from_player,
+            to_player,
+            &hit,
+            current_time,
+        )
+        {
+            Ok(()) =>
+            {
+                Some(message)
+            }
+            Err(reason) =>
+            {
+                if reason.is_waiting_for_shot()
+                {
+                    self.pending_hits.push(PendingHit {
+                        from_player,
+                        to_player,
+                        message,
+                        received_time: current_time,
+                    });
+                }
+                else
+                {
+                    self.reject_hit(from_player, to_player, reason);
+                }
+
+                None
+            }
+        }
+    }
+
+    // returns pending hits that were confirmed by a received shot
+    pub fn take_confirmed_hits(
+        &mut self,
+        players: &HashMap<u128, PlayerInfo>,
+        current_time: u128,
+    ) -> Vec<PendingHit>
+    {
+        let mut confirmed_hits = Vec::new();
+
+        let pending_hits = std::mem::take(&mut self.pending_hits);
+
+        for pending_hit in pending_hits
+        {
+            let hit = match Hit::from_message(&pending_hit.message)
+            {
+                Some(hit) => hit,
+                None => continue,
+            };
+
+            let result = self.validate_hit(
+                players,
+                pending_hit.from_player,
+                pending_hit.to_player,
+                &hit,
+                current_time,
+            );
+
+            match result
+            {
+                Ok(()) =>
+                {
+                    confirmed_hits.push(pending_hit);
+                }
+                Err(reason) =>
+                {
+                    if reason.is_waiting_for_shot() &&
+                        current_time - pending_hit.received_time <= PENDING_HIT_TIMEOUT
+                    {
+                        self.pending_hits.push(pending_hit);
+                    }
+                    else
+                    {
+                        self.reject_hit(pending_hit.from_player, pending_hit.to_player, reason);
+                    }
+                }
+            }
+        }
+
+        confirmed_hits
+    }
+
+    // returns the id of the player who dealt the last validated hit to the victim recently
+    pub fn take_last_validated_hit(&mut self, victim: u128, current_time: u128) -> Option<u128>
+    {
+        let (shooter, hit_time) = self.peers
+            .get_mut(&victim)?
+            .last_validated_hit
+            .take()?;
+
+        if current_time.saturating_sub(hit_time) > KILL_ATTRIBUTION_WINDOW ||
+            !self.peers.contains_key(&shooter)
+        {
+            return None;
+        }
+
+        Some(shooter)
+    }
+
+    pub fn reject_hit(&mut self, from_player: u128, to_player: u128, reason: HitRejection)
+    {
+        let rejected_hits = match self.peers.get_mut(&from_player)
+        {
+            Some(state) =>
+            {
+                state.rejected_hits += 1;
+                state.rejected_hits
+            }
+            None => 0,
+        };
+
+        println!(
+            "WARNING: hit from player {} to player {} is dropped: {} ({} rejected hits from this player)",
+            from_player,
+            to_player,
+            reason.description(),
+            rejected_hits,
+        );
+    }
+
+    fn validate_hit(
+        &mut self,
+        players: &HashMap<u128, PlayerInfo>,
+        from_player: u128,
+        to_player: u128,
+        hit: &Hit,
+        current_time: u128,
+    ) -> Result<(), HitRejection>
+    {
+        let from_player_team = players
+            .get(&from_player)
+            .ok_or(HitRejection::UnknownShooter)?
+            .team;
+
+        let to_player_team = players
+            .get(&to_player)
+            .ok_or(HitRejection::UnknownTarget)?
+            .team;
+
+        if hit.damage_dealer_team != from_player_team
+        {
+            return Err(HitRejection::WrongTeam);
+        }
+
+        if !from_player_team.is_opponent(&to_player_team)
+        {
+            return Err(HitRejection::FriendlyFire);
+        }
+
+        if let Some(target_position) = self.peers
+            .get(&to_player)
+            .ok_or(HitRejection::UnknownTarget)?
+            .last_known_position
+        {
+            let distance = Vec4::from_array(target_position)
+                .distance(Vec4::from_array(hit.impact_position));
+
+            if distance > MAX_IMPACT_DISTANCE_FROM_TARGET
+            {
+                return Err(HitRejection::ImpactTooFar(distance));
+            }
+        }
+
+        let shooter = self.peers
+            .get_mut(&from_player)
+            .ok_or(HitRejection::UnknownShooter)?;
+
+        let mut have_shots_with_hits_left = false;
+        let mut matching_shot_is_found = false;
+
+        for shot in shooter.recent_shots.iter_mut()
+        {
+            if shot.hits_left == 0 || current_time - shot.time > shot.weapon.hit_window()
+            {
+                continue;
+            }
+
+            have_shots_with_hits_left = true;
+
+            if hit.damage <= shot.weapon.max_damage()
+            {
+                shot.hits_left -= 1;
+                shooter.relayed_hits += 1;
+
+                matching_shot_is_found = true;
+
+                break;
+            }
+        }
+
+        if matching_shot_is_found
+        {
+            if let Some(target) = self.peers.get_mut(&to_player)
+            {
+                target.last_validated_hit = Some((from_player, current_time));
+            }
+
+            Ok(())
+        }
+        else if have_shots_with_hits_left
+        {
+            Err(HitRejection::DamageTooHigh(hit.damage))
+        }
+        else
+        {
+            Err(HitRejection::NoMatchingShot)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    use client_server_protocol::PlayerStats;
+    use matchbox_socket::PeerId;
+
+    const SHOOTER: u128 = 1;
+    const TARGET: u128 = 2;
+
+    fn get_players() -> HashMap<u128, PlayerInfo>
+    {
+        let mut players = HashMap::new();
+
+        for (id, team) in [(SHOOTER, Team::Red), (TARGET, Team::Blue)]
+        {
+            players.insert(id, PlayerInfo {
+                peer_id: PeerId(uuid::Uuid::from_u128(id)),
+                team,
+                captured_flag: false,
+                stats: PlayerStats::new(id, team),
+                reconnect_token: 0,
+                skill_rating: 0.0,
+                join_time: 0,
+                party_id: None,
+            });
+        }
+
+        players
+    }
+
+    fn get_hit_validator() -> HitValidator
+    {
+        let mut hit_validator = HitValidator::new(2);
+
+        hit_validator.add_peer(SHOOTER);
+        hit_validator.add_peer(TARGET);
+
+        hit_validator
+    }
+
+    fn hit(
+        hit_validator: &mut HitValidator,
+        players: &HashMap<u128, PlayerInfo>,
+        impact_position: [f32;4],
+        current_time: u128,
+    ) -> Option<NetMessageToPlayer>
+    {
+        let message = NetMessageToPlayer::RemoteDirectMessage(
+            TARGET,
+            RemoteMessage::DealDamageAndForce(MACHINEGUN_MAX_DAMAGE, [0.0;4], impact_position, Team::Red)
+        );
+
+        let hit = Hit::from_message(&message).unwrap();
+
+        hit_validator.check_hit(players, SHOOTER, TARGET, message, hit, current_time)
+    }
+
+    #[test]
+    fn shots_over_the_fire_rate_are_ignored()
+    {
+        let mut hit_validator = get_hit_validator();
+
+        for _ in 0..MACHINEGUN_MAX_SHOTS_PER_SECOND + 1
+        {
+            hit_validator.register_shot(SHOOTER, Weapon::MachineGun, 0);
+        }
+
+        let shooter = &hit_validator.peers[&SHOOTER];
+
+        assert_eq!(shooter.recent_shots.len(), MACHINEGUN_MAX_SHOTS_PER_SECOND);
+        assert_eq!(shooter.ignored_shots, 1);
+        assert_eq!(shooter.rejected_hits, 0);
+    }
+
+    #[test]
+    fn fire_rate_is_counted_per_weapon_and_second()
+    {
+        let mut hit_validator = get_hit_validator();
+
+        for _ in 0..SHOTGUN_MAX_SHOTS_PER_SECOND
+        {
+            hit_validator.register_shot(SHOOTER, Weapon::Shotgun, 0);
+        }
+
+        hit_validator.register_shot(SHOOTER, Weapon::MachineGun, 0);
+
+        assert_eq!(hit_validator.peers[&SHOOTER].recent_shots.len(), SHOTGUN_MAX_SHOTS_PER_SECOND + 1);
+
+        hit_validator.register_shot(SHOOTER, Weapon::Shotgun, FIRE_RATE_WINDOW + 1);
+
+        let shooter = &hit_validator.peers[&SHOOTER];
+
+        // the machinegun shot is forgotten after its hit window
+        assert_eq!(shooter.recent_shots.len(), SHOTGUN_MAX_SHOTS_PER_SECOND + 1);
+        assert_eq!(shooter.ignored_shots, 0);
+    }
+
+    #[test]
+    fn hit_of_a_shot_over_the_fire_rate_is_rejected()
+    {
+        let mut hit_validator = get_hit_validator();
+        let players = get_players();
+
+        for _ in 0..MACHINEGUN_MAX_SHOTS_PER_SECOND + 1
+        {
+            hit_validator.register_shot(SHOOTER, Weapon::MachineGun, 0);
+        }
+
+        for _ in 0..MACHINEGUN_MAX_SHOTS_PER_SECOND
+        {
+            assert!(hit(&mut hit_validator, &players, [0.0;4], 10).is_some());
+        }
+
+        // the hit waits for its shot, which was ignored
+        assert!(hit(&mut hit_validator, &players, [0.0;4], 10).is_none());
+        assert_eq!(hit_validator.pending_hits.len(), 1);
+
+        let confirmed_hits = hit_validator.take_confirmed_hits(&players, 10 + PENDING_HIT_TIMEOUT + 1);
+
+        assert!(confirmed_hits.is_empty());
+        assert!(hit_validator.pending_hits.is_empty());
+    }
+
+    #[test]
+    fn oversized_holegun_radius_is_capped()
+    {
+        let mut hit_validator = get_hit_validator();
+        let players = get_players();
+
+        let shot = NetMessageToPlayer::RemoteBoardCastMessage(
+            RemoteMessage::SpawnHoleGunShotActor([0.0;4], [0.0;4], 1_000_000.0, [1.0;3], 1.0)
+        );
+
+        let weapon = Weapon::from_shot_message(&shot).unwrap();
+
+        let max_damage = (HOLEGUN_MAX_RADIUS * HOLEGUN_MAX_DAMAGE_PER_RADIUS).ceil() as u32;
+
+        assert_eq!(weapon.max_damage(), max_damage);
+
+        hit_validator.register_shot(SHOOTER, weapon, 0);
+
+        let message = NetMessageToPlayer::RemoteDirectMessage(
+            TARGET,
+            RemoteMessage::DealDamageAndForce(max_damage + 1, [0.0;4], [0.0;4], Team::Red)
+        );
+
+        let hit = Hit::from_message(&message).unwrap();
+
+        // the hit waits for a shot which can deal this damage
+        assert!(hit_validator.check_hit(&players, SHOOTER, TARGET, message, hit, 10).is_none());
+        assert_eq!(hit_validator.peers[&SHOOTER].relayed_hits, 0);
+    }
+
+    #[test]
+    fn hit_far_from_the_target_is_rejected()
+    {
+        let mut hit_validator = get_hit_validator();
+        let players = get_players();
+
+        hit_validator.set_last_known_position(TARGET, [0.0;4]);
+        hit_validator.register_shot(SHOOTER, Weapon::MachineGun, 0);
+
+        let too_far = MAX_IMPACT_DISTANCE_FROM_TARGET + 1.0;
+
+        assert!(hit(&mut hit_validator, &players, [too_far, 0.0, 0.0, 0.0], 10).is_none());
+
+        // the hit isn't waiting for a shot, it's dropped at once
+        assert!(hit_validator.pending_hits.is_empty());
+        assert_eq!(hit_validator.peers[&SHOOTER].rejected_hits, 1);
+        assert_eq!(hit_validator.peers[&SHOOTER].recent_shots[0].hits_left, 1);
+    }
+
+    #[test]
+    fn hit_near_the_target_is_relayed()
+    {
+        let mut hit_validator = get_hit_validator();
+        let players = get_players();
+
+        hit_validator.set_last_known_position(TARGET, [0.0;4]);
+        hit_validator.register_shot(SHOOTER, Weapon::MachineGun, 0);
+
+        let near = MAX_IMPACT_DISTANCE_FROM_TARGET - 1.0;
+
+        assert!(hit(&mut hit_validator, &players, [0.0, near, 0.0, 0.0], 10).is_some());
+        assert_eq!(hit_validator.peers[&SHOOTER].rejected_hits, 0);
+    }
+}
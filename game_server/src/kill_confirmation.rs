@@ -0,0 +1,172 @@
+// Slice 4D Shooter - the first multiplayer shooter set in 4D space
+// Copyright (C) 2023-2025  Timofei Molokov
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+
+// max time between IKilledPlayer from the killer and IDied from the victim
+pub const KILL_CONFIRMATION_WINDOW: u128 = 3_000;
+
+// A kill is counted when the victim reports its death with IDied and is
+// attributed to the player who dealt the last validated hit to the victim
+// (see HitValidator). Killers' IKilledPlayer reports are only hints,
+// claims of players who didn't deal the last validated hit are logged.
+pub struct KillConfirmation
+{
+    // victim's id -> (killer's id, time of the claim) of every claim
+    claims: HashMap<u128, Vec<(u128, u128)>>,
+}
+
+impl KillConfirmation
+{
+    pub fn new(max_players: u32) -> Self
+    {
+        KillConfirmation {
+            claims: HashMap::with_capacity(max_players as usize),
+        }
+    }
+
+    pub fn clear(&mut self)
+    {
+        self.claims.clear();
+    }
+
+    pub fn remove_player(&mut self, id: u128)
+    {
+        self.claims.remove(&id);
+
+        for (_, claims) in self.claims.iter_mut()
+        {
+            claims.retain(|(killer, _)| *killer != id);
+        }
+    }
+
+    // last_hitter is the player who dealt the last validated hit to the victim,
+    // returns the killer's id
+    pub fn player_died(&mut self, victim: u128, last_hitter: Option<u128>, time: u128) -> Option<u128>
+    {
+        self.remove_expired(time);
+
+        let claims = self.claims.remove(&victim).unwrap_or_default();
+
+        for (claimer, _) in claims.iter()
+        {
+            if Some(*claimer) != last_hitter
+            {
+                println!(
+                    "WARNING: player {} claimed the kill of player {} without the last validated hit",
+                    claimer,
+                    victim
+                );
+            }
+        }
+
+        last_hitter
+    }
+
+    pub fn player_claimed_kill(&mut self, killer: u128, victim: u128, time: u128)
+    {
+        self.remove_expired(time);
+
+        let claims = self.claims.entry(victim).or_default();
+
+        if !claims.iter().any(|(claimer, _)| *claimer == killer)
+        {
+            claims.push((killer, time));
+        }
+    }
+
+    fn remove_expired(&mut self, time: u128)
+    {
+        for (_, claims) in self.claims.iter_mut()
+        {
+            claims.retain(|(_, claim_time)| {
+                time.saturating_sub(*claim_time) <= KILL_CONFIRMATION_WINDOW
+            });
+        }
+
+        self.claims.retain(|_, claims| !claims.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn kill_is_attributed_to_the_last_hitter_of_concurrent_claims()
+    {
+        let mut kill_confirmation = KillConfirmation::new(4);
+
+        kill_confirmation.player_claimed_kill(1, 3, 0);
+        kill_confirmation.player_claimed_kill(2, 3, 10);
+
+        assert_eq!(kill_confirmation.player_died(3, Some(2), 20), Some(2));
+    }
+
+    #[test]
+    fn claim_without_validated_hit_is_not_counted()
+    {
+        let mut kill_confirmation = KillConfirmation::new(4);
+
+        kill_confirmation.player_claimed_kill(1, 3, 0);
+
+        assert_eq!(kill_confirmation.player_died(3, None, 10), None);
+    }
+
+    #[test]
+    fn kill_is_counted_without_claim()
+    {
+        let mut kill_confirmation = KillConfirmation::new(4);
+
+        assert_eq!(kill_confirmation.player_died(3, Some(1), 0), Some(1));
+    }
+
+    #[test]
+    fn claims_are_taken_by_the_death()
+    {
+        let mut kill_confirmation = KillConfirmation::new(4);
+
+        kill_confirmation.player_claimed_kill(1, 3, 0);
+        kill_confirmation.player_died(3, Some(1), 10);
+
+        assert!(kill_confirmation.claims.is_empty());
+    }
+
+    #[test]
+    fn claims_expire()
+    {
+        let mut kill_confirmation = KillConfirmation::new(4);
+
+        kill_confirmation.player_claimed_kill(1, 3, 0);
+        kill_confirmation.player_claimed_kill(2, 4, KILL_CONFIRMATION_WINDOW + 1);
+
+        assert!(!kill_confirmation.claims.contains_key(&3));
+        assert!(kill_confirmation.claims.contains_key(&4));
+    }
+
+    #[test]
+    fn claims_of_a_removed_player_are_dropped()
+    {
+        let mut kill_confirmation = KillConfirmation::new(4);
+
+        kill_confirmation.player_claimed_kill(1, 3, 0);
+        kill_confirmation.player_claimed_kill(2, 3, 0);
+        kill_confirmation.remove_player(1);
+
+        assert_eq!(kill_confirmation.claims[&3], vec![(2, 0)]);
+    }
+}
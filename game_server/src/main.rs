@@ -14,10 +14,18 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-mod client_server_protocol;
+mod hit_validation;
+mod kill_confirmation;
+mod map_rotation;
+mod match_rules;
+mod rate_limiter;
+mod replay_recorder;
+mod server_config;
+mod team_balancing;
+mod voting;
 
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     env,
     net::{
         Ipv4Addr,
@@ -25,18 +33,55 @@ use std::{
         SocketAddrV4
     },
     process::exit,
-    str::FromStr, sync::{Arc, Mutex},
+    sync::{Arc, Mutex},
     time::{
         Duration,
         Instant
     }
 };
 use matchmaking_server_protocol::{
+    AdminCommand,
     GameServerMatchmakingServerProtocol,
-    GameServerMessage, MatchmakingServerMessageToGameServer
+    GameServerMessage, MatchmakingServerMessageToGameServer, name_to_bytes
 };
 use client_server_protocol::{
-    BonusSpotStatus, ClientMessage, FlagStatus, NetMessageToPlayer, NetMessageToServer, RemoteMessage, ServerMessage, Team
+    BonusSpotStatus, ClientMessage, FlagStatus, GameMode, HandshakeMessage, HandshakeRejectReason, NetMessageToPlayer, NetMessageToServer, PlayerStats, RemoteMessage, ServerMessage, ShutdownReason, Team, VoteKind, FEATURE_RECONNECT, FEATURE_VOTING, MAX_MOVE_W_BONUS_SPOTS, MAX_TEAMS, PROTOCOL_VERSION, SUPPORTED_FEATURES
+};
+
+use hit_validation::{
+    Hit,
+    HitRejection,
+    HitValidator,
+    Weapon
+};
+
+use kill_confirmation::KillConfirmation;
+
+use map_rotation::MapRotation;
+
+use match_rules::{
+    get_game_mode_name,
+    MatchRules,
+    TeamBalancing,
+    MATCH_RULES_USAGE
+};
+
+use voting::{
+    VoteOutcome,
+    Voting
+};
+
+use replay_recorder::{RecordedChannel, ReplayRecorder};
+
+use rate_limiter::{
+    PacketVerdict,
+    RateLimiter,
+    TrafficChannel
+};
+
+use server_config::{
+    GameServerConfig,
+    SERVER_CONFIG_USAGE
 };
 
 use fyrox_core::{
@@ -48,7 +93,6 @@ use matchbox_socket::{
     PeerId,
     PeerState::{Connected, Disconnected},
     RtcIceServerConfig,
-    WebRtcChannel,
     WebRtcSocket
 };
 use tokio::{
@@ -68,140 +112,6 @@ use tokio_tungstenite::connect_async;
 use tokio_tungstenite::tungstenite::Message;
 
 
-#[derive(Clone, Debug)]
-struct GameServerConfig {
-    signaling_port: u16,
-    game_servers_min_port_for_signaling_servers: u16,
-    game_servers_max_port_for_signaling_servers: u16,
-    game_servers_min_port_for_tcp_listener: u16,
-    game_servers_max_port_for_tcp_listener: u16,
-    matchmaking_server_ip: Ipv4Addr,
-    matchmaking_server_port: u16,
-    max_players: u32,
-    game_server_index: u16,
-    ice_urls: Vec<String>,
-    username: Option<String>,
-    credential: Option<String>
-}
-
-
-impl GameServerConfig {
-    fn new(args: Vec<String>) -> Result<Self, &'static str> {
-        if args.len() < 5 {
-            return Err(
-                "Usage: game_server
-    <signaling_port>
-    <game_servers_min_port_for_signaling_servers>
-    <game_servers_max_port_for_signaling_servers>
-    <game_servers_min_port_for_tcp_listener>
-    <game_servers_max_port_for_tcp_listener>
-    <matchmaking_server_ip>
-    <matchmaking_server_port>
-    <max_players_per_game_session>
-    <ice_servers_urls>
-    <turn_server_username>
-    <turn_server_credential>\n"
-            );
-        }
-
-        let signaling_port = args[1]
-            .parse()
-            .map_err(|_| "Invalid signaling port")?;
-
-        let game_servers_min_port_for_signaling_servers = args[2]
-            .parse()
-            .map_err(|_| "Invalid game_servers_min_port_for_signaling_servers")?;
-        
-        let game_servers_max_port_for_signaling_servers = args[3]
-            .parse()
-            .map_err(|_| "Invalid game_servers_max_port_for_signaling_servers")?;
-        
-        let game_servers_min_port_for_tcp_listener = args[4]
-            .parse()
-            .map_err(|_| "Invalid game_servers_min_port_for_tcp_listener")?;
-        
-        let game_servers_max_port_for_tcp_listener = args[5]
-            .parse()
-            .map_err(|_| "Invalid game_servers_max_port_for_tcp_listener")?;
-
-        
-        let matchmaking_server_ip = Ipv4Addr::from_str(&args[6])
-            .expect("Invalid matchmaking server ip address format");
-        
-        let matchmaking_server_port = args[7]
-            .parse()
-            .map_err(|_| "Invalid matchmaking server port")?;
-
-        let max_players = args[8]
-            .parse()
-            .map_err(|_| "Invalid matchmaking server port")?;
-
-        let ice_urls: Vec<String> = if args.len() > 9
-        {
-            args[9]
-                .split("|")
-                .into_iter()
-                .filter_map(|s|if s != "" {Some(s.to_string())} else {None})
-                .collect()
-        }
-        else
-        {
-            Vec::with_capacity(0)
-        };
-
-        let username = if args.len() > 10
-        {
-            if args[10] == ""
-            {
-                None
-            }
-            else
-            {
-                Some(args[10].clone())
-            }
-        }
-        else
-        {
-            None
-        };
-
-        let credential = if args.len() > 11
-        {
-            if args[11] == ""
-            {
-                None
-            }
-            else
-            {
-                Some(args[11].clone())
-            }
-        }
-        else
-        {
-            None
-        };
-
-        let config = GameServerConfig {
-            signaling_port,
-            game_servers_min_port_for_signaling_servers,
-            game_servers_max_port_for_signaling_servers,
-            game_servers_min_port_for_tcp_listener,
-            game_servers_max_port_for_tcp_listener,
-            matchmaking_server_ip,
-            matchmaking_server_port,
-            max_players,
-            game_server_index: signaling_port,
-            ice_urls,
-            username,
-            credential
-        };
-
-        println!("Game server config is: {:?}", config);
-
-        Ok(config)
-    }
-}
-
 use blink_alloc::GlobalBlinkAlloc;
 #[global_allocator]
 static GLOBAL_ALLOC: GlobalBlinkAlloc = GlobalBlinkAlloc::new();
@@ -211,7 +121,7 @@ fn main() -> Result<(), ()> {
 
     read_args(&args);
 
-    let config = match GameServerConfig::new(args) {
+    let config = match GameServerConfig::new(&args) {
         Ok(cfg) => cfg,
         Err(e) => {
             println!("bad args");
@@ -222,6 +132,14 @@ fn main() -> Result<(), ()> {
 
     println!("succesfully load config");
 
+    let map_rotation = match MapRotation::load(&config.match_rules.map_rotation, &config.maps_dir) {
+        Ok(map_rotation) => map_rotation,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            exit(1);
+        }
+    };
+
     let runtime = Arc::new(
         Builder::new_current_thread()
             .enable_all()
@@ -230,16 +148,27 @@ fn main() -> Result<(), ()> {
             .unwrap()
     );
 
-    runtime.block_on(async_main(runtime.clone(), config))
+    runtime.block_on(async_main(runtime.clone(), config, map_rotation))
 }
 
 
 async fn async_main(
     runtime: Arc<Runtime>,
-    config: GameServerConfig
+    config: GameServerConfig,
+    map_rotation: MapRotation,
 ) -> Result<(), ()> {
     
+    // all connected peers, spectators included
     let players_amount = Arc::new(Mutex::new(0u32));
+    let spectators_amount = Arc::new(Mutex::new(0u32));
+    // seats of disconnected players waiting for their reconnect
+    let reserved_seats_amount = Arc::new(Mutex::new(0u32));
+    // commands forwarded by the matchmaking server from the operator
+    let admin_commands = Arc::new(Mutex::new(Vec::<AdminCommand>::new()));
+    // shown in the clients' game servers list
+    let current_map_name = Arc::new(Mutex::new(map_rotation.get_current_map().get_name()));
+    // kicked and rejected peers, the signaling server disconnects them
+    let dropped_peers = Arc::new(Mutex::new(HashSet::<PeerId>::new()));
 
     let (matchmaking_server_listener, matchmaking_server_listener_port) =
         create_matchmaking_server_listener(config.clone())
@@ -248,6 +177,11 @@ async fn async_main(
 
     runtime.spawn(listening_to_matchmaking_server(
         players_amount.clone(),
+        spectators_amount.clone(),
+        reserved_seats_amount.clone(),
+        admin_commands.clone(),
+        current_map_name.clone(),
+        config.match_rules.game_mode,
         matchmaking_server_listener
     ));
 
@@ -256,7 +190,8 @@ async fn async_main(
     runtime.spawn(run_signaling_server(
         config.clone(),
         players_amount.clone(),
-        actual_signaling_server_port.clone()
+        actual_signaling_server_port.clone(),
+        dropped_peers.clone(),
     ));
 
     // waiting for initializtion of the singnaling server to get the actual signaling server port
@@ -264,7 +199,7 @@ async fn async_main(
 
     let (mut webrtc_socket, socket_future) =
         matchbox_socket::WebRtcSocketBuilder::new(
-            format!("ws://localhost:{}/", *actual_signaling_server_port.lock().unwrap())
+            get_signaling_server_url(*actual_signaling_server_port.lock().unwrap(), &config.join_code)
         )
         .ice_server(RtcIceServerConfig {
             urls: config.ice_urls.clone(),
@@ -302,6 +237,12 @@ async fn async_main(
     game_server_main_loop(
         webrtc_socket,
         config,
+        map_rotation,
+        spectators_amount,
+        reserved_seats_amount,
+        admin_commands,
+        current_map_name,
+        dropped_peers,
     ).await;
 
     Ok(())
@@ -310,16 +251,34 @@ async fn async_main(
 async fn game_server_main_loop(
     mut webrtc_socket: WebRtcSocket,
     config: GameServerConfig,
+    map_rotation: MapRotation,
+    spectators_amount: Arc<Mutex<u32>>,
+    reserved_seats_amount: Arc<Mutex<u32>>,
+    admin_commands: Arc<Mutex<Vec<AdminCommand>>>,
+    current_map_name: Arc<Mutex<String>>,
+    dropped_peers: Arc<Mutex<HashSet<PeerId>>>,
 ) {
-    let mut players_state = GameSessionState::new(&config); 
-
-    let mut relaible_channel = webrtc_socket
-        .take_channel(0)
-        .unwrap();
+    let mut players_state = GameSessionState::new(
+        &config,
+        map_rotation,
+        spectators_amount,
+        reserved_seats_amount,
+        dropped_peers
+    ); 
+
+    let mut relaible_channel = RecordedChannel::new(
+        webrtc_socket
+            .take_channel(0)
+            .unwrap(),
+        TrafficChannel::Reliable
+    );
 
-    let mut unrelaible_channel = webrtc_socket
-        .take_channel(1)
-        .unwrap();
+    let mut unrelaible_channel = RecordedChannel::new(
+        webrtc_socket
+            .take_channel(1)
+            .unwrap(),
+        TrafficChannel::Unreliable
+    );
 
     let server_start_time = Instant::now();
 
@@ -331,16 +290,41 @@ async fn game_server_main_loop(
             &mut unrelaible_channel,
             &mut players_state,
             &server_start_time,
+            &admin_commands,
         ).await;
 
         match command
         {
             Command::StartNewGameSession =>
+            {
+                players_state.map_rotation.switch_to_next_map();
+
+                *current_map_name.lock().unwrap() = players_state.map_rotation.get_current_map().get_name();
+
+                continue;
+            }
+            Command::RestartGameSession =>
             {
                 continue;
             }
-            Command::ShutDownServer(exit_code) =>
+            Command::ShutDownServer(exit_code, reason) =>
             {
+                notify_peers_about_shutdown(
+                    &mut webrtc_socket,
+                    &mut relaible_channel,
+                    reason
+                ).await;
+
+                record_sent_packets(
+                    &mut players_state,
+                    &mut relaible_channel,
+                    &mut unrelaible_channel,
+                    &server_start_time
+                );
+
+                // exit doesn't run destructors, so the replay file is flushed here
+                players_state.replay_recorder.finish_recording();
+
                 exit(exit_code);
             }
         }
@@ -349,14 +333,59 @@ async fn game_server_main_loop(
     
 }
 
+// connected clients go back to the matchmaking server
+// instead of waiting for the connection timeout
+async fn notify_peers_about_shutdown(
+    webrtc_socket: &mut WebRtcSocket,
+    relaible_channel: &mut RecordedChannel,
+    reason: ShutdownReason,
+)
+{
+    if webrtc_socket.any_channel_closed()
+    {
+        return;
+    }
+
+    let peers: Vec<PeerId> = webrtc_socket.connected_peers().collect();
+
+    if peers.is_empty()
+    {
+        return;
+    }
+
+    println!("INFO: notifying {} peers about the game server shutdown, reason: {:?}", peers.len(), reason);
+
+    let packet = ServerMessage::ServerShuttingDown(reason).to_packet();
+
+    for peer_id in peers
+    {
+        relaible_channel.send(
+            packet.clone(),
+            peer_id
+        );
+    }
+
+    // give the WebRTC socket time to deliver the messages before the process exits
+    tokio::time::sleep(Duration::from_millis(SHUTDOWN_NOTIFICATION_DELAY)).await;
+}
+
 pub enum Command
 {
+    // the new game session is played on the next map of the map rotation
     StartNewGameSession,
-    ShutDownServer(i32),
+    // the new game session is played on the same map
+    RestartGameSession,
+    ShutDownServer(
+        // exit code
+        i32,
+        ShutdownReason
+    ),
 }
 
 pub struct Hole
 {
+    // true for the obstacles spawned by ObstaclesGun
+    is_positive: bool,
     color: [f32;3],
     radius: f32,
     position: [f32;4],
@@ -367,6 +396,8 @@ pub struct Hole
 }
 
 const EXPLODE_TIME: f32 = 0.25;
+const HOLE_SHRINK_SPEED: f32 = 0.2;
+const OBSTACLE_SHRINK_SPEED: f32 = 0.35;
 
 impl Hole
 {
@@ -374,9 +405,11 @@ impl Hole
         radius: f32,
         position: [f32;4],
         color: [f32;3],
+        is_positive: bool,
     ) -> Self
     {
         Hole {
+            is_positive,
             color,
             radius: 0.01_f32,
             position,
@@ -394,7 +427,16 @@ impl Hole
     {
         if self.target_size_reached {
 
-            self.radius -= delta * 0.2;
+            let shrink_speed = if self.is_positive
+            {
+                OBSTACLE_SHRINK_SPEED
+            }
+            else
+            {
+                HOLE_SHRINK_SPEED
+            };
+
+            self.radius -= delta * shrink_speed;
 
             if self.radius <= 0.0
             {
@@ -425,28 +467,82 @@ impl Hole
 struct GameSessionState
 {
     players: HashMap<u128, PlayerInfo>,
-    red_team: HashMap<u128,()>,
-    blue_team: HashMap<u128,()>,
+    // observer clients, they are not in the teams
+    // but receive all broadcast messages
+    spectators: HashMap<u128, PeerId>,
+    spectators_amount: Arc<Mutex<u32>>,
+    // seats of disconnected players indexed by reconnect token
+    reserved_seats: HashMap<u128, ReservedSeat>,
+    reserved_seats_amount: Arc<Mutex<u32>>,
+    max_players: u32,
+    // indexed by team id, empty in Free-for-all
+    teams: Vec<HashMap<u128,()>>,
     holes: Vec<Hole>,
     temporal_holes: Vec<Hole>,
-    move_w_bonus: MoveWBonusSpot,
+    // indexed by bonus spot index, the game server doesn't know the map,
+    // so all possible spots are tracked
+    move_w_bonuses: [MoveWBonusSpot; MAX_MOVE_W_BONUS_SPOTS],
     red_flag: Flag,
     blue_flag: Flag,
-    red_team_score: u32,
-    blue_team_score: u32,
+    teams_score: [u32; MAX_TEAMS],
+    // players' kills in Free-for-all
+    players_score: HashMap<u128, u32>,
     previous_update_time: u128,
     game_state: GameState,
+    round_start_time: u128,
+    overtime_start_time: u128,
+    last_match_timer_sync_time: u128,
+    last_scoreboard_broadcast_time: u128,
+    // time when the operator paused the match
+    paused_since: Option<u128>,
+    hit_validator: HitValidator,
+    kill_confirmation: KillConfirmation,
+    rate_limiter: RateLimiter,
+    // kicked and rejected peers, shared with the signaling server
+    // which closes their connections and frees their slots
+    dropped_peers: Arc<Mutex<HashSet<PeerId>>>,
+    // peer's id -> features enabled for the peer in the handshake,
+    // peers join the game session only after the handshake
+    handshakes: HashMap<u128, u64>,
+    // connected peer -> time of its connection, the peer is dropped
+    // if it hasn't joined the game session in JOIN_TIMEOUT
+    joining_peers: HashMap<PeerId, u128>,
+    voting: Voting,
+    replay_recorder: ReplayRecorder,
+    map_rotation: MapRotation,
+    match_rules: MatchRules,
 }
 
+pub const MATCH_TIMER_SYNC_PERIOD: u128 = 5_000;
+
+pub const SCOREBOARD_BROADCAST_PERIOD: u128 = 5_000;
+
+pub const SHUTDOWN_NOTIFICATION_DELAY: u64 = 500;
+
+// the client sends JoinAsPlayer or JoinAsSpectator right after the handshake,
+// peers which haven't joined in this time only occupy slots at the signaling server
+pub const JOIN_TIMEOUT: u128 = 10_000;
+
 enum GameState
 {
     Playing,
-    RedWin(
+    // the next capture wins the game session
+    Overtime,
+    TeamWin(
+        // winner team
+        Team,
         // time when team win
         u128
     ),
-    BlueWin(
-        // time when team win
+    // player wins the game session in Free-for-all
+    PlayerWin(
+        // player's id
+        u128,
+        // time when player win
+        u128
+    ),
+    Draw(
+        // time when the game session ended in a draw
         u128
     ),
 }
@@ -457,19 +553,50 @@ struct PlayerInfo
     peer_id: PeerId,
     team: Team,
     captured_flag: bool,
+    // player's statistics in the current game session
+    stats: PlayerStats,
+    // the player rejoins the game session with this token after a lost connection
+    reconnect_token: u128,
+    // player's performance in the previous game sessions, used to balance teams
+    skill_rating: f32,
+    join_time: u128,
+    // party members are kept on the same team where possible
+    party_id: Option<u128>,
 }
 
-pub const MOVE_W_BONUS_RESPAWN_TIME: u128 = 30_000;
-pub const FLAG_RESPAWN_TIME: u128 = 10_200;
-pub const MAX_SCORE: u32 = 4;
-pub const TIME_IN_SESSION_AFTER_WIN: u128 = 12_000;
+// the team, statistics and score of a disconnected player are kept
+// for reconnect_grace_period, the seat is counted as an occupied player slot
+#[derive(Clone, Copy)]
+struct ReservedSeat
+{
+    team: Team,
+    stats: PlayerStats,
+    score: u32,
+    skill_rating: f32,
+    join_time: u128,
+    party_id: Option<u128>,
+    disconnect_time: u128,
+}
 
 impl GameSessionState {
-    pub fn new(config: &GameServerConfig) -> Self
+    pub fn new(
+        config: &GameServerConfig,
+        map_rotation: MapRotation,
+        spectators_amount: Arc<Mutex<u32>>,
+        reserved_seats_amount: Arc<Mutex<u32>>,
+        dropped_peers: Arc<Mutex<HashSet<PeerId>>>,
+    ) -> Self
     {
         let players = HashMap::with_capacity(config.max_players as usize);
-        let red_team = HashMap::with_capacity(config.max_players as usize);
-        let blue_team = HashMap::with_capacity(config.max_players as usize);
+        let spectators = HashMap::with_capacity(config.match_rules.max_spectators as usize);
+        let players_score = HashMap::with_capacity(config.max_players as usize);
+
+        let mut teams = Vec::with_capacity(MAX_TEAMS);
+
+        for _ in 0..config.match_rules.get_teams_amount()
+        {
+            teams.push(HashMap::with_capacity(config.max_players as usize));
+        }
         
         let red_flag = Flag {
             get_previous_status_time: 0u128,
@@ -481,10 +608,13 @@ impl GameSessionState {
             status: FlagStatus::OnTheBase,
             team: Team::Blue
         };
-        let move_w_bonus = MoveWBonusSpot {
-            get_previouse_status_time: 0u128,
-            status: BonusSpotStatus::BonusOnTheSpot
-        };
+        let move_w_bonuses = [
+            MoveWBonusSpot {
+                get_previouse_status_time: 0u128,
+                status: BonusSpotStatus::BonusOnTheSpot
+            };
+            MAX_MOVE_W_BONUS_SPOTS
+        ];
 
         let holes = Vec::new();
         let temporal_holes = Vec::new();
@@ -493,15 +623,41 @@ impl GameSessionState {
             holes,
             temporal_holes,
             players,
-            red_team,
+            spectators,
+            spectators_amount,
+            reserved_seats: HashMap::with_capacity(config.max_players as usize),
+            reserved_seats_amount,
+            max_players: config.max_players,
+            teams,
             red_flag,
-            blue_team,
             blue_flag,
-            move_w_bonus,
-            red_team_score: 0u32,
-            blue_team_score: 0u32,
+            move_w_bonuses,
+            teams_score: [0u32; MAX_TEAMS],
+            players_score,
             previous_update_time: 0u128,
             game_state: GameState::Playing,
+            round_start_time: 0u128,
+            overtime_start_time: 0u128,
+            last_match_timer_sync_time: 0u128,
+            last_scoreboard_broadcast_time: 0u128,
+            paused_since: None,
+            hit_validator: HitValidator::new(config.max_players),
+            kill_confirmation: KillConfirmation::new(config.max_players),
+            rate_limiter: RateLimiter::new(
+                &config.network_budgets,
+                config.max_players + config.match_rules.max_spectators
+            ),
+            dropped_peers,
+            handshakes: HashMap::with_capacity(
+                (config.max_players + config.match_rules.max_spectators) as usize
+            ),
+            joining_peers: HashMap::with_capacity(
+                (config.max_players + config.match_rules.max_spectators) as usize
+            ),
+            voting: Voting::new(config.max_players),
+            replay_recorder: ReplayRecorder::new(config.replay_dir.clone(), config.game_server_index),
+            map_rotation,
+            match_rules: config.match_rules.clone(),
         }
     }
 
@@ -527,7 +683,7 @@ impl GameSessionState {
     pub fn update_items(
         &mut self,
         server_start_time: &Instant,
-        relaible_channel: &mut WebRtcChannel,
+        relaible_channel: &mut RecordedChannel,
     ) {
         let current_time = server_start_time.elapsed().as_millis();
         
@@ -539,34 +695,45 @@ impl GameSessionState {
 
         self.previous_update_time = current_time;
 
-        // update move w bonus
-        match self.move_w_bonus.status
+        // items don't respawn while the match is paused
+        let items_respawn_is_paused = self.paused_since.is_some();
+
+        // update move w bonuses
+        for index in 0..MAX_MOVE_W_BONUS_SPOTS
         {
-            BonusSpotStatus::BonusCollected(_) =>
+            if items_respawn_is_paused
             {
-                if current_time - self.move_w_bonus.get_previouse_status_time
-                    >=
-                    MOVE_W_BONUS_RESPAWN_TIME
+                break;
+            }
+
+            match self.move_w_bonuses[index].status
+            {
+                BonusSpotStatus::BonusCollected(_) =>
                 {
-                    self.set_new_bonus_status_and_send_update_to_players(
-                        server_start_time,
-                        0,
-                        BonusSpotStatus::BonusOnTheSpot,
-                        relaible_channel
-                    );
+                    if current_time - self.move_w_bonuses[index].get_previouse_status_time
+                        >=
+                        self.match_rules.move_w_bonus_respawn_time
+                    {
+                        self.set_new_bonus_status_and_send_update_to_players(
+                            server_start_time,
+                            index,
+                            BonusSpotStatus::BonusOnTheSpot,
+                            relaible_channel
+                        );
+                    }
                 }
+                BonusSpotStatus::BonusOnTheSpot => {}
             }
-            BonusSpotStatus::BonusOnTheSpot => {}
         }
 
         // update red flag
         match self.red_flag.status
         {
-            FlagStatus::Droped(_) =>
+            FlagStatus::Droped(_) if !items_respawn_is_paused =>
             {
                 if current_time - self.red_flag.get_previous_status_time
                     >=
-                    FLAG_RESPAWN_TIME
+                    self.match_rules.flag_respawn_time
                 {
                     self.set_new_flag_status_and_send_update_to_players(
                         server_start_time,
@@ -576,6 +743,7 @@ impl GameSessionState {
                     );
                 }
             }
+            FlagStatus::Droped(_)   => {}
             FlagStatus::Captured(_) => {}
             FlagStatus::OnTheBase   => {}
         }
@@ -583,11 +751,11 @@ impl GameSessionState {
         // update blue flag
         match self.blue_flag.status
         {
-            FlagStatus::Droped(_) =>
+            FlagStatus::Droped(_) if !items_respawn_is_paused =>
             {
                 if current_time - self.blue_flag.get_previous_status_time
                     >=
-                    FLAG_RESPAWN_TIME
+                    self.match_rules.flag_respawn_time
                 {
                     self.set_new_flag_status_and_send_update_to_players(
                         server_start_time,
@@ -597,6 +765,7 @@ impl GameSessionState {
                     );
                 }
             }
+            FlagStatus::Droped(_)   => {}
             FlagStatus::Captured(_) => {}
             FlagStatus::OnTheBase   => {}
         }
@@ -617,7 +786,8 @@ impl GameSessionState {
             }
         }
 
-        self.holes.swap_with_slice(&mut self.temporal_holes);
+        // all holes are moved to temporal_holes at this point
+        std::mem::swap(&mut self.holes, &mut self.temporal_holes);
     }
 
     pub fn set_new_bonus_status_and_send_update_to_players(
@@ -625,15 +795,15 @@ impl GameSessionState {
         server_start_time: &Instant,
         index: usize,
         new_status: BonusSpotStatus,
-        relaible_channel: &mut WebRtcChannel,
+        relaible_channel: &mut RecordedChannel,
     ) {
-        self.move_w_bonus
+        self.move_w_bonuses[index]
             .get_previouse_status_time =
             server_start_time.elapsed().as_millis();
         
-        self.move_w_bonus.status = new_status;
+        self.move_w_bonuses[index].status = new_status;
 
-        for (_, player_info) in &self.players
+        for peer_id in self.get_broadcast_peers()
         {
             relaible_channel.send(
                 ServerMessage::NetMessageToPlayer(
@@ -645,17 +815,32 @@ impl GameSessionState {
                         )
                     )
                 ).to_packet(),
-                player_info.peer_id
+                peer_id
             );
         }
     }
 
+    pub fn get_collected_move_w_bonuses_mask(&self) -> u64
+    {
+        let mut mask = 0u64;
+
+        for (index, move_w_bonus) in self.move_w_bonuses.iter().enumerate()
+        {
+            if let BonusSpotStatus::BonusCollected(_) = move_w_bonus.status
+            {
+                mask |= 1u64 << index;
+            }
+        }
+
+        mask
+    }
+
     pub fn set_new_flag_status_and_send_update_to_players(
         &mut self,
         server_start_time: &Instant,
         flag_team: Team,
         new_status: FlagStatus,
-        relaible_channel: &mut WebRtcChannel,
+        relaible_channel: &mut RecordedChannel,
     ) {
         match flag_team
         {
@@ -675,6 +860,12 @@ impl GameSessionState {
                 
                 self.blue_flag.status = new_status;
             }
+            _ =>
+            {
+                println!("ERROR: only Red and Blue teams have flags");
+
+                return;
+            }
         }
         
         match new_status {
@@ -683,26 +874,18 @@ impl GameSessionState {
                 self.players.get_mut(&id).unwrap().captured_flag = true;
             }
             _ => {
-                match flag_team {
-                    Team::Red =>
-                    {
-                        for (id, _) in &self.blue_team
-                        {
-                            self.players.get_mut(id).unwrap().captured_flag = false;
-                        } 
-                    }
-                    Team::Blue =>
+                // flag can be captured only by the opponents team
+                for (_, player_info) in &mut self.players
+                {
+                    if player_info.team != flag_team
                     {
-                        for (id, _) in &self.red_team
-                        {
-                            self.players.get_mut(id).unwrap().captured_flag = false;
-                        } 
+                        player_info.captured_flag = false;
                     }
                 }
             }
         }
 
-        for (_, player_info) in &self.players
+        for peer_id in self.get_broadcast_peers()
         {
             relaible_channel.send(
                 ServerMessage::NetMessageToPlayer(
@@ -714,174 +897,1366 @@ impl GameSessionState {
                         )
                     )
                 ).to_packet(),
-                player_info.peer_id
+                peer_id
             );
         }
     }
 
-    pub fn add_score_for_team_and_send_upadate_for_players(
+    pub fn get_player_team(&self, player_id: u128) -> Option<Team>
+    {
+        self.players
+            .get(&player_id)
+            .map(|player_info| player_info.team)
+    }
+
+    // used when both the killer and the victim reported the kill,
+    // the kill is added to the players' stats in every game mode
+    // and to the score in Team Deathmatch and Free-for-all
+    pub fn count_kill_and_send_update_for_players(
         &mut self,
-        score_for_team: Team,
+        killer_id: u128,
+        victim_id: u128,
         server_start_time: &Instant,
-        relaible_channel: &mut WebRtcChannel,
+        relaible_channel: &mut RecordedChannel,
     )
     {
-        match self.game_state
+        let killer_team = match self.get_player_team(killer_id)
         {
-            GameState::BlueWin(_) => {}
+            Some(team) => team,
+            None => return,
+        };
 
-            GameState::RedWin(_) => {}
+        let victim_team = match self.get_player_team(victim_id)
+        {
+            Some(team) => team,
+            None => return,
+        };
 
-            GameState::Playing =>
-            {
-                match score_for_team {
-                    Team::Red =>
-                    {
-                        self.red_team_score += 1;
-                    }
-                    Team::Blue =>
-                    {
-                        self.blue_team_score += 1;
-                    }
-                }
-        
-                for (_, player_info) in &self.players
-                {
-                    relaible_channel.send(
-                        ServerMessage::NetMessageToPlayer(
-                            0u128,
-                            NetMessageToPlayer::RemoteBoardCastMessage(
-                                RemoteMessage::UpdateTeamsScore(
-                                    self.red_team_score,
-                                    self.blue_team_score,
-                                )
-                            )
-                        ).to_packet(),
-                        player_info.peer_id
-                    );
-                }
-        
-                if self.red_team_score >= MAX_SCORE
-                {
-                    self.game_state = GameState::RedWin(
-                        server_start_time.elapsed().as_millis()
-                    );
+        if !killer_team.is_opponent(&victim_team)
+        {
+            return;
+        }
 
-                    for (_, player_info) in &self.players
-                    {
-                        relaible_channel.send(
-                            ServerMessage::NetMessageToPlayer(
-                                0u128,
-                                NetMessageToPlayer::RemoteBoardCastMessage(
-                                    RemoteMessage::TeamWin(
-                                        Team::Red
-                                    )
-                                )
-                            ).to_packet(),
-                            player_info.peer_id
-                        );
-                    }
-                }
-                else if self.blue_team_score >= MAX_SCORE
-                {
-                    self.game_state = GameState::BlueWin(
-                        server_start_time.elapsed().as_millis()
-                    );
+        println!("INFO: player {} killed player {}", killer_id, victim_id);
 
-                    for (_, player_info) in &self.players
-                    {
-                        relaible_channel.send(
-                            ServerMessage::NetMessageToPlayer(
-                                0u128,
-                                NetMessageToPlayer::RemoteBoardCastMessage(
-                                    RemoteMessage::TeamWin(
-                                        Team::Blue
-                                    )
-                                )
-                            ).to_packet(),
-                            player_info.peer_id
-                        );
-                    }
-                }
-            }
+        if let Some(killer_info) = self.players.get_mut(&killer_id)
+        {
+            killer_info.stats.kills += 1;
         }
-    }
-
-    pub fn spawn_new_hole(
-        &mut self,
-        position: [f32; 4],
-        radius: f32,
-        color: [f32; 3],
-    )
-    {
-        self.holes.push(Hole::new(radius, position, color));
-    }
 
-    pub fn add_player(
-        &mut self,
-        id: u128,
-        player_info: PlayerInfo,
-    )
-    {
-        self.players.insert(id, player_info);
+        if self.match_rules.game_mode == GameMode::CaptureTheFlag
+        {
+            return;
+        }
 
-        match player_info.team
+        match killer_team
         {
-            Team::Red =>
+            Team::NoTeam =>
             {
-                self.red_team.insert(id, ());
+                self.add_score_for_player_and_send_upadate_for_players(
+                    killer_id,
+                    server_start_time,
+                    relaible_channel,
+                );
             }
-            Team::Blue =>
+            _ =>
             {
-                self.blue_team.insert(id, ());
+                self.add_score_for_team_and_send_upadate_for_players(
+                    killer_team,
+                    server_start_time,
+                    relaible_channel,
+                );
             }
         }
-
     }
 
-    pub fn remove_player(
+    pub fn add_score_for_team_and_send_upadate_for_players(
         &mut self,
-        id: u128,
+        score_for_team: Team,
+        server_start_time: &Instant,
+        relaible_channel: &mut RecordedChannel,
     )
     {
-        self.players.remove(&id);
-        self.red_team.remove(&id);
-        self.blue_team.remove(&id);
-    }
-}
+        // scores don't count while the match is paused
+        if self.paused_since.is_some()
+        {
+            return;
+        }
 
-struct Flag
-{
-    get_previous_status_time: u128,
-    status: FlagStatus,
-    team: Team,
-}
+        let is_overtime = match self.game_state
+        {
+            GameState::TeamWin(_, _) => return,
 
-struct MoveWBonusSpot
-{
-    get_previouse_status_time: u128,
-    status: BonusSpotStatus,
-}
+            GameState::PlayerWin(_, _) => return,
 
-async fn start_new_game_session(
-    webrtc_socket: &mut WebRtcSocket,
-    config: &GameServerConfig,
-    relaible_channel: &mut WebRtcChannel,
-    unrelaible_channel: &mut WebRtcChannel,
-    game_session_state: &mut GameSessionState,
+            GameState::Draw(_) => return,
+
+            GameState::Playing => false,
+
+            GameState::Overtime => true,
+        };
+
+        let team_id = match score_for_team.get_id()
+        {
+            Some(team_id) => team_id as usize,
+            None => return,
+        };
+
+        self.teams_score[team_id] += 1;
+
+        for peer_id in self.get_broadcast_peers()
+        {
+            relaible_channel.send(
+                ServerMessage::NetMessageToPlayer(
+                    0u128,
+                    NetMessageToPlayer::RemoteBoardCastMessage(
+                        RemoteMessage::UpdateTeamsScore(
+                            self.teams_score,
+                        )
+                    )
+                ).to_packet(),
+                peer_id
+            );
+        }
+
+        if self.teams_score[team_id] >= self.match_rules.get_score_limit()
+        {
+            self.set_team_win(score_for_team, server_start_time, relaible_channel);
+        }
+        else if is_overtime && self.get_leading_team() == Some(score_for_team)
+        {
+            // leading teams were tied when overtime started,
+            // so the first score of one of them wins
+            self.set_team_win(score_for_team, server_start_time, relaible_channel);
+        }
+    }
+
+    pub fn add_score_for_player_and_send_upadate_for_players(
+        &mut self,
+        player_id: u128,
+        server_start_time: &Instant,
+        relaible_channel: &mut RecordedChannel,
+    )
+    {
+        // scores don't count while the match is paused
+        if self.paused_since.is_some()
+        {
+            return;
+        }
+
+        let is_overtime = match self.game_state
+        {
+            GameState::TeamWin(_, _) => return,
+
+            GameState::PlayerWin(_, _) => return,
+
+            GameState::Draw(_) => return,
+
+            GameState::Playing => false,
+
+            GameState::Overtime => true,
+        };
+
+        let player_score = match self.players_score.get_mut(&player_id)
+        {
+            Some(player_score) =>
+            {
+                *player_score += 1;
+
+                *player_score
+            }
+            None => return,
+        };
+
+        for (id, player_info) in &self.players
+        {
+            relaible_channel.send(
+                self.get_free_for_all_score_message(*id).to_packet(),
+                player_info.peer_id
+            );
+        }
+
+        if player_score >= self.match_rules.get_score_limit()
+        {
+            self.set_player_win(player_id, server_start_time, relaible_channel);
+        }
+        else if is_overtime && self.get_leading_player() == Some(player_id)
+        {
+            // leading players were tied when overtime started,
+            // so the first kill of one of them wins
+            self.set_player_win(player_id, server_start_time, relaible_channel);
+        }
+    }
+
+    // Free-for-all score is personal for each player
+    pub fn get_free_for_all_score_message(&self, player_id: u128) -> ServerMessage
+    {
+        let player_score = self.players_score
+            .get(&player_id)
+            .copied()
+            .unwrap_or(0u32);
+
+        let best_opponent_score = self.players_score
+            .iter()
+            .filter(|(id, _)| **id != player_id)
+            .map(|(_, score)| *score)
+            .max()
+            .unwrap_or(0u32);
+
+        ServerMessage::NetMessageToPlayer(
+            0u128,
+            NetMessageToPlayer::RemoteBoardCastMessage(
+                RemoteMessage::UpdateFreeForAllScore(
+                    player_score,
+                    best_opponent_score,
+                )
+            )
+        )
+    }
+
+    // returns None if several teams share the best score
+    fn get_leading_team(&self) -> Option<Team>
+    {
+        let teams_score = &self.teams_score[..self.teams.len()];
+
+        let best_score = *teams_score.iter().max()?;
+
+        let mut leaders = teams_score
+            .iter()
+            .enumerate()
+            .filter(|(_, score)| **score == best_score);
+
+        let (leader_id, _) = leaders.next()?;
+
+        if leaders.next().is_some()
+        {
+            return None;
+        }
+
+        Some(Team::from_id(leader_id as u8))
+    }
+
+    // returns None if several players share the best score
+    fn get_leading_player(&self) -> Option<u128>
+    {
+        let best_score = *self.players_score.values().max()?;
+
+        let mut leaders = self.players_score
+            .iter()
+            .filter(|(_, score)| **score == best_score);
+
+        let (leader_id, _) = leaders.next()?;
+
+        if leaders.next().is_some()
+        {
+            return None;
+        }
+
+        Some(*leader_id)
+    }
+
+    fn set_team_win(
+        &mut self,
+        win_team: Team,
+        server_start_time: &Instant,
+        relaible_channel: &mut RecordedChannel,
+    )
+    {
+        self.game_state = GameState::TeamWin(win_team, server_start_time.elapsed().as_millis());
+
+        for peer_id in self.get_broadcast_peers()
+        {
+            relaible_channel.send(
+                ServerMessage::NetMessageToPlayer(
+                    0u128,
+                    NetMessageToPlayer::RemoteBoardCastMessage(
+                        RemoteMessage::TeamWin(
+                            win_team
+                        )
+                    )
+                ).to_packet(),
+                peer_id
+            );
+        }
+
+        self.send_scoreboard_to_players(server_start_time.elapsed().as_millis(), relaible_channel);
+    }
+
+    fn set_player_win(
+        &mut self,
+        win_player: u128,
+        server_start_time: &Instant,
+        relaible_channel: &mut RecordedChannel,
+    )
+    {
+        self.game_state = GameState::PlayerWin(win_player, server_start_time.elapsed().as_millis());
+
+        println!("INFO: player {} won the game session", win_player);
+
+        for (id, player_info) in &self.players
+        {
+            relaible_channel.send(
+                ServerMessage::NetMessageToPlayer(
+                    0u128,
+                    NetMessageToPlayer::RemoteBoardCastMessage(
+                        RemoteMessage::PlayerWin(
+                            *id == win_player
+                        )
+                    )
+                ).to_packet(),
+                player_info.peer_id
+            );
+        }
+
+        for (_, peer_id) in &self.spectators
+        {
+            relaible_channel.send(
+                ServerMessage::NetMessageToPlayer(
+                    0u128,
+                    NetMessageToPlayer::RemoteBoardCastMessage(
+                        RemoteMessage::PlayerWin(false)
+                    )
+                ).to_packet(),
+                *peer_id
+            );
+        }
+
+        self.send_scoreboard_to_players(server_start_time.elapsed().as_millis(), relaible_channel);
+    }
+
+    fn set_draw(
+        &mut self,
+        server_start_time: &Instant,
+        relaible_channel: &mut RecordedChannel,
+    )
+    {
+        self.game_state = GameState::Draw(server_start_time.elapsed().as_millis());
+
+        println!("INFO: game session ended in a draw");
+
+        for peer_id in self.get_broadcast_peers()
+        {
+            relaible_channel.send(
+                ServerMessage::NetMessageToPlayer(
+                    0u128,
+                    NetMessageToPlayer::RemoteBoardCastMessage(
+                        RemoteMessage::Draw
+                    )
+                ).to_packet(),
+                peer_id
+            );
+        }
+
+        self.send_scoreboard_to_players(server_start_time.elapsed().as_millis(), relaible_channel);
+    }
+
+    pub fn get_scoreboard(&self) -> Vec<PlayerStats>
+    {
+        self.players
+            .values()
+            .map(|player_info| {
+                let mut stats = player_info.stats;
+                // player's team can be changed during the game session
+                stats.team = player_info.team;
+                stats
+            })
+            .collect()
+    }
+
+    fn send_scoreboard_to_players(
+        &mut self,
+        current_time: u128,
+        relaible_channel: &mut RecordedChannel,
+    )
+    {
+        self.last_scoreboard_broadcast_time = current_time;
+
+        let packet = ServerMessage::Scoreboard(self.get_scoreboard()).to_packet();
+
+        for peer_id in self.get_broadcast_peers()
+        {
+            relaible_channel.send(
+                packet.clone(),
+                peer_id
+            );
+        }
+    }
+
+    pub fn update_scoreboard(
+        &mut self,
+        server_start_time: &Instant,
+        relaible_channel: &mut RecordedChannel,
+    )
+    {
+        let current_time = server_start_time.elapsed().as_millis();
+
+        match self.game_state
+        {
+            GameState::Playing | GameState::Overtime =>
+            {
+                if current_time - self.last_scoreboard_broadcast_time >= SCOREBOARD_BROADCAST_PERIOD
+                {
+                    self.send_scoreboard_to_players(current_time, relaible_channel);
+                }
+            }
+            // the final scoreboard is sent when the game session ends
+            _ => {}
+        }
+    }
+
+    fn add_flag_capture(&mut self, player_id: u128)
+    {
+        if let Some(player_info) = self.players.get_mut(&player_id)
+        {
+            player_info.stats.flag_captures += 1;
+        }
+    }
+
+    fn add_flag_return(&mut self, player_id: u128)
+    {
+        if let Some(player_info) = self.players.get_mut(&player_id)
+        {
+            player_info.stats.flag_returns += 1;
+        }
+    }
+
+    fn add_damage_dealt(&mut self, damage_dealer: u128, damage: u32)
+    {
+        if let Some(player_info) = self.players.get_mut(&damage_dealer)
+        {
+            player_info.stats.damage_dealt = player_info.stats.damage_dealt.saturating_add(damage);
+        }
+    }
+
+    pub fn get_match_time_left_message(&self, current_time: u128) -> Option<ServerMessage>
+    {
+        if self.match_rules.round_duration == 0
+        {
+            return None;
+        }
+
+        let (phase_start_time, phase_duration, is_overtime) = match self.game_state
+        {
+            GameState::Playing =>
+            {
+                (self.round_start_time, self.match_rules.round_duration, false)
+            }
+            GameState::Overtime =>
+            {
+                (self.overtime_start_time, self.match_rules.overtime_duration, true)
+            }
+            _ => return None,
+        };
+
+        // the match timer is frozen while the match is paused
+        let current_time = self.paused_since.unwrap_or(current_time);
+
+        let time_left = phase_duration.saturating_sub(current_time - phase_start_time);
+
+        Some(
+            ServerMessage::NetMessageToPlayer(
+                0u128,
+                NetMessageToPlayer::RemoteBoardCastMessage(
+                    RemoteMessage::SetMatchTimeLeft(
+                        time_left,
+                        phase_duration,
+                        is_overtime,
+                    )
+                )
+            )
+        )
+    }
+
+    fn send_match_time_left_to_players(
+        &mut self,
+        current_time: u128,
+        relaible_channel: &mut RecordedChannel,
+    )
+    {
+        self.last_match_timer_sync_time = current_time;
+
+        if let Some(message) = self.get_match_time_left_message(current_time)
+        {
+            let packet = message.to_packet();
+
+            for peer_id in self.get_broadcast_peers()
+            {
+                relaible_channel.send(
+                    packet.clone(),
+                    peer_id
+                );
+            }
+        }
+    }
+
+    // ends the game session when the time limit is reached: the leading team (or player
+    // in Free-for-all) wins, tied leaders play overtime and if nobody scores in overtime
+    // the session ends in a draw
+    pub fn update_match_timer(
+        &mut self,
+        server_start_time: &Instant,
+        relaible_channel: &mut RecordedChannel,
+    )
+    {
+        if self.match_rules.round_duration == 0
+        {
+            return;
+        }
+
+        let current_time = server_start_time.elapsed().as_millis();
+
+        if self.paused_since.is_some()
+        {
+            if current_time - self.last_match_timer_sync_time >= MATCH_TIMER_SYNC_PERIOD
+            {
+                self.send_match_time_left_to_players(current_time, relaible_channel);
+            }
+
+            return;
+        }
+
+        match self.game_state
+        {
+            GameState::Playing =>
+            {
+                if current_time - self.round_start_time >= self.match_rules.round_duration
+                {
+                    if self.match_rules.game_mode == GameMode::FreeForAll
+                    {
+                        if let Some(leader_id) = self.get_leading_player()
+                        {
+                            self.set_player_win(leader_id, server_start_time, relaible_channel);
+
+                            return;
+                        }
+                    }
+                    else if let Some(leader_team) = self.get_leading_team()
+                    {
+                        self.set_team_win(leader_team, server_start_time, relaible_channel);
+
+                        return;
+                    }
+
+                    if self.match_rules.overtime_duration > 0
+                    {
+                        println!("INFO: time limit is reached with tied score, overtime started");
+
+                        self.game_state = GameState::Overtime;
+                        self.overtime_start_time = current_time;
+
+                        self.send_match_time_left_to_players(current_time, relaible_channel);
+                    }
+                    else
+                    {
+                        self.set_draw(server_start_time, relaible_channel);
+                    }
+
+                    return;
+                }
+            }
+
+            GameState::Overtime =>
+            {
+                if current_time - self.overtime_start_time >= self.match_rules.overtime_duration
+                {
+                    self.set_draw(server_start_time, relaible_channel);
+
+                    return;
+                }
+            }
+
+            _ => return,
+        }
+
+        if current_time - self.last_match_timer_sync_time >= MATCH_TIMER_SYNC_PERIOD
+        {
+            self.send_match_time_left_to_players(current_time, relaible_channel);
+        }
+    }
+
+    pub fn pause_match(
+        &mut self,
+        server_start_time: &Instant,
+        relaible_channel: &mut RecordedChannel,
+    ) -> Result<(), String>
+    {
+        if self.paused_since.is_some()
+        {
+            return Err("the match is already paused".to_string());
+        }
+
+        let current_time = server_start_time.elapsed().as_millis();
+
+        self.paused_since = Some(current_time);
+
+        println!("INFO: the match is paused");
+
+        self.send_match_time_left_to_players(current_time, relaible_channel);
+
+        Ok(())
+    }
+
+    // the match timer and items respawn timers are shifted by the pause duration,
+    // so they continue from the moment the match was paused
+    pub fn resume_match(
+        &mut self,
+        server_start_time: &Instant,
+        relaible_channel: &mut RecordedChannel,
+    ) -> Result<(), String>
+    {
+        let paused_since = self.paused_since
+            .take()
+            .ok_or("the match is not paused".to_string())?;
+
+        let current_time = server_start_time.elapsed().as_millis();
+
+        let shift_time = |time: u128| -> u128
+        {
+            if time <= paused_since
+            {
+                time + (current_time - paused_since)
+            }
+            else
+            {
+                // the status was changed during the pause
+                current_time
+            }
+        };
+
+        self.round_start_time = shift_time(self.round_start_time);
+        self.overtime_start_time = shift_time(self.overtime_start_time);
+
+        self.red_flag.get_previous_status_time = shift_time(self.red_flag.get_previous_status_time);
+        self.blue_flag.get_previous_status_time = shift_time(self.blue_flag.get_previous_status_time);
+
+        for move_w_bonus in self.move_w_bonuses.iter_mut()
+        {
+            move_w_bonus.get_previouse_status_time = shift_time(move_w_bonus.get_previouse_status_time);
+        }
+
+        println!("INFO: the match is resumed after {} millis of pause", current_time - paused_since);
+
+        self.send_match_time_left_to_players(current_time, relaible_channel);
+
+        Ok(())
+    }
+
+    pub fn set_team_score_and_send_update_for_players(
+        &mut self,
+        team_id: u8,
+        score: u32,
+        server_start_time: &Instant,
+        relaible_channel: &mut RecordedChannel,
+    ) -> Result<(), String>
+    {
+        match self.game_state
+        {
+            GameState::Playing => {}
+            GameState::Overtime => {}
+            _ => return Err("the game session is already over".to_string()),
+        }
+
+        if team_id as usize >= self.match_rules.get_teams_amount()
+        {
+            return Err(format!("team {} doesn't exist in the current game mode", team_id));
+        }
+
+        self.teams_score[team_id as usize] = score;
+
+        println!("INFO: score of team {} is set to {}", team_id, score);
+
+        let packet = ServerMessage::NetMessageToPlayer(
+            0u128,
+            NetMessageToPlayer::RemoteBoardCastMessage(
+                RemoteMessage::UpdateTeamsScore(
+                    self.teams_score,
+                )
+            )
+        ).to_packet();
+
+        for peer_id in self.get_broadcast_peers()
+        {
+            relaible_channel.send(
+                packet.clone(),
+                peer_id
+            );
+        }
+
+        if score >= self.match_rules.get_score_limit()
+        {
+            self.set_team_win(Team::from_id(team_id), server_start_time, relaible_channel);
+        }
+
+        Ok(())
+    }
+
+    pub fn swap_player_team(
+        &mut self,
+        player_id: u128,
+        team_id: u8,
+        server_start_time: &Instant,
+        relaible_channel: &mut RecordedChannel,
+    ) -> Result<(), String>
+    {
+        if team_id as usize >= self.match_rules.get_teams_amount()
+        {
+            return Err(format!("team {} doesn't exist in the current game mode", team_id));
+        }
+
+        let new_team = Team::from_id(team_id);
+
+        let old_team = self
+            .get_player_team(player_id)
+            .ok_or(format!("player {} is not in the game session", player_id))?;
+
+        if old_team == new_team
+        {
+            return Err(format!("player {} is already in team {}", player_id, team_id));
+        }
+
+        self.return_flag_captured_by_player(player_id, server_start_time, relaible_channel);
+
+        if let Some(old_team_id) = old_team.get_id()
+        {
+            self.teams[old_team_id as usize].remove(&player_id);
+        }
+
+        self.teams[team_id as usize].insert(player_id, ());
+
+        let player_info = self.players.get_mut(&player_id).unwrap();
+
+        player_info.team = new_team;
+
+        println!("INFO: player {} is moved to team {}", player_id, team_id);
+
+        relaible_channel.send(
+            ServerMessage::NetMessageToPlayer(
+                0u128,
+                NetMessageToPlayer::RemoteBoardCastMessage(
+                    RemoteMessage::SetNewTeam(new_team)
+                )
+            ).to_packet(),
+            player_info.peer_id,
+        );
+
+        Ok(())
+    }
+
+    // amount of players who can vote in the current vote
+    fn get_voters_amount(&self) -> u32
+    {
+        match self.voting.get_current_vote()
+        {
+            Some(vote) =>
+            {
+                self.players
+                    .keys()
+                    .filter(|id| vote.can_vote(**id))
+                    .count() as u32
+            }
+            None => 0u32,
+        }
+    }
+
+    fn send_vote_state_to_players(
+        &self,
+        current_time: u128,
+        relaible_channel: &mut RecordedChannel,
+    )
+    {
+        let vote = match self.voting.get_current_vote()
+        {
+            Some(vote) => vote,
+            None => return,
+        };
+
+        let packet = ServerMessage::VoteState(
+            vote.kind,
+            vote.initiator,
+            vote.get_yes_votes(),
+            vote.get_no_votes(),
+            Voting::get_required_votes(vote.kind, self.get_voters_amount(), self.match_rules.vote_pass_percent),
+            (vote.start_time + self.match_rules.vote_duration).saturating_sub(current_time),
+        ).to_packet();
+
+        for peer_id in self.get_broadcast_peers()
+        {
+            relaible_channel.send(
+                packet.clone(),
+                peer_id
+            );
+        }
+    }
+
+    fn send_vote_result_to_players(
+        &self,
+        vote_kind: VoteKind,
+        passed: bool,
+        relaible_channel: &mut RecordedChannel,
+    )
+    {
+        let packet = ServerMessage::VoteFinished(vote_kind, passed).to_packet();
+
+        for peer_id in self.get_broadcast_peers()
+        {
+            relaible_channel.send(
+                packet.clone(),
+                peer_id
+            );
+        }
+    }
+
+    pub fn start_vote(
+        &mut self,
+        initiator: u128,
+        vote_kind: VoteKind,
+        server_start_time: &Instant,
+        relaible_channel: &mut RecordedChannel,
+    ) -> Result<(), String>
+    {
+        // voting is disabled on the game server or not supported by the player's client
+        if !self.peer_has_feature(initiator, FEATURE_VOTING)
+        {
+            return Err(format!("voting is not enabled for player {}", initiator));
+        }
+
+        if let VoteKind::KickPlayer(target) = vote_kind
+        {
+            if !self.players.contains_key(&target)
+            {
+                return Err(format!("player {} is not in the game session", target));
+            }
+        }
+
+        let current_time = server_start_time.elapsed().as_millis();
+
+        self.voting.start_vote(
+            vote_kind,
+            initiator,
+            self.players.len() as u32,
+            current_time,
+            self.match_rules.vote_cooldown
+        )?;
+
+        println!("INFO: player {} started the vote {:?}", initiator, vote_kind);
+
+        self.send_vote_state_to_players(current_time, relaible_channel);
+
+        Ok(())
+    }
+
+    pub fn cast_vote(
+        &mut self,
+        voter: u128,
+        yes: bool,
+        server_start_time: &Instant,
+        relaible_channel: &mut RecordedChannel,
+    ) -> Result<(), String>
+    {
+        self.voting.cast_vote(voter, yes)?;
+
+        self.send_vote_state_to_players(server_start_time.elapsed().as_millis(), relaible_channel);
+
+        Ok(())
+    }
+
+    // returns the kind of the vote if the vote is passed
+    pub fn update_vote(
+        &mut self,
+        server_start_time: &Instant,
+        relaible_channel: &mut RecordedChannel,
+    ) -> Option<VoteKind>
+    {
+        let outcome = self.voting.get_outcome(
+            self.get_voters_amount(),
+            self.match_rules.vote_pass_percent,
+            self.match_rules.vote_duration,
+            server_start_time.elapsed().as_millis()
+        );
+
+        let passed = match outcome
+        {
+            VoteOutcome::InProgress => return None,
+            VoteOutcome::Passed => true,
+            VoteOutcome::Failed => false,
+        };
+
+        let vote = self.voting.finish_vote().unwrap();
+
+        println!(
+            "INFO: the vote {:?} is {}, yes votes: {}, no votes: {}",
+            vote.kind,
+            if passed {"passed"} else {"failed"},
+            vote.get_yes_votes(),
+            vote.get_no_votes()
+        );
+
+        self.send_vote_result_to_players(vote.kind, passed, relaible_channel);
+
+        if passed
+        {
+            Some(vote.kind)
+        }
+        else
+        {
+            None
+        }
+    }
+
+    // the flag returns to its base when its carrier leaves the team or the game server
+    fn return_flag_captured_by_player(
+        &mut self,
+        player_id: u128,
+        server_start_time: &Instant,
+        relaible_channel: &mut RecordedChannel,
+    )
+    {
+        if !self.check_if_player_has_flag(player_id)
+        {
+            return;
+        }
+
+        match self.players.get(&player_id).unwrap().team
+        {
+            Team::Red =>
+            {
+                self.set_new_flag_status_and_send_update_to_players(
+                    server_start_time,
+                    Team::Blue,
+                    FlagStatus::OnTheBase,
+                    relaible_channel
+                );
+            }
+            Team::Blue =>
+            {
+                self.set_new_flag_status_and_send_update_to_players(
+                    server_start_time,
+                    Team::Red,
+                    FlagStatus::OnTheBase,
+                    relaible_channel
+                );
+            }
+            _ => {}
+        }
+    }
+
+    pub fn spawn_new_hole(
+        &mut self,
+        position: [f32; 4],
+        radius: f32,
+        color: [f32; 3],
+        is_positive: bool,
+    )
+    {
+        self.holes.push(Hole::new(radius.abs(), position, color, is_positive));
+    }
+
+    // store holes and obstacles to send them to players who join the session later
+    pub fn record_map_destruction(
+        &mut self,
+        message: &NetMessageToPlayer,
+    )
+    {
+        let message = match message
+        {
+            NetMessageToPlayer::RemoteDirectMessage(_, message) => message,
+            NetMessageToPlayer::RemoteBoardCastMessage(message) => message,
+            NetMessageToPlayer::RemoteCommand(_) => return,
+        };
+
+        match message
+        {
+            RemoteMessage::SpawnHoleGunShotActor(
+                position,
+                _shooted_from,
+                radius,
+                color,
+                _charging_volume_area
+            ) =>
+            {
+                self.spawn_new_hole(*position, *radius, *color, false);
+            }
+
+            RemoteMessage::SpawnObstacleGunShotActor(
+                position,
+                _shooted_from,
+                radius,
+                color,
+                _charging_volume_area
+            ) =>
+            {
+                self.spawn_new_hole(*position, *radius, *color, true);
+            }
+
+            _ => {}
+        }
+    }
+
+    // players and spectators receive all broadcast messages
+    // features which are enabled by the match rules
+    fn get_server_features(&self) -> u64
+    {
+        let mut features = SUPPORTED_FEATURES;
+
+        if self.match_rules.reconnect_grace_period == 0
+        {
+            features &= !FEATURE_RECONNECT;
+        }
+
+        if self.match_rules.vote_duration == 0
+        {
+            features &= !FEATURE_VOTING;
+        }
+
+        features
+    }
+
+    pub fn peer_has_feature(&self, id: u128, feature: u64) -> bool
+    {
+        self.handshakes
+            .get(&id)
+            .map_or(false, |features| features & feature != 0)
+    }
+
+    // the rate limiter drops all further packets from the peer and the signaling
+    // server disconnects it, so a client which ignores the message can't keep the slot
+    fn drop_peer(&mut self, peer_id: PeerId)
+    {
+        self.rate_limiter.block_peer(peer_id.0.as_u128());
+
+        self.dropped_peers.lock().unwrap().insert(peer_id);
+    }
+
+    fn drop_peers_not_joined_in_time(&mut self, current_time: u128)
+    {
+        let timed_out_peers: Vec<PeerId> = self.joining_peers
+            .iter()
+            .filter(|(_, connection_time)| current_time.saturating_sub(**connection_time) > JOIN_TIMEOUT)
+            .map(|(peer_id, _)| *peer_id)
+            .collect();
+
+        for peer_id in timed_out_peers
+        {
+            self.joining_peers.remove(&peer_id);
+
+            let id = peer_id.0.as_u128();
+
+            if self.players.contains_key(&id) || self.spectators.contains_key(&id)
+            {
+                continue;
+            }
+
+            println!("WARNING: peer {} is dropped, it hasn't joined the game session in {} ms", id, JOIN_TIMEOUT);
+
+            self.handshakes.remove(&id);
+
+            self.drop_peer(peer_id);
+        }
+    }
+
+    // the Rejected message is sent before the peer is dropped
+    fn reject_peer(
+        &mut self,
+        peer_id: PeerId,
+        reason: HandshakeRejectReason,
+        channel: &mut RecordedChannel,
+    )
+    {
+        channel.send(
+            HandshakeMessage::Rejected(PROTOCOL_VERSION, reason).to_packet(),
+            peer_id
+        );
+
+        self.drop_peer(peer_id);
+    }
+
+    pub fn process_handshake(
+        &mut self,
+        peer_id: PeerId,
+        message: HandshakeMessage,
+        channel: &mut RecordedChannel,
+    )
+    {
+        let id = peer_id.0.as_u128();
+
+        match message
+        {
+            HandshakeMessage::Hello(protocol_version, client_features) =>
+            {
+                if protocol_version != PROTOCOL_VERSION
+                {
+                    println!(
+                        "WARNING: peer {} is rejected, its protocol version is {}, the game server's protocol version is {}",
+                        id,
+                        protocol_version,
+                        PROTOCOL_VERSION
+                    );
+
+                    self.reject_peer(peer_id, HandshakeRejectReason::WrongProtocolVersion, channel);
+
+                    return;
+                }
+
+                let features = client_features & self.get_server_features();
+
+                println!(
+                    "INFO: peer {} completed the handshake, protocol version: {}, features: {:#b}",
+                    id,
+                    protocol_version,
+                    features
+                );
+
+                self.handshakes.insert(id, features);
+
+                channel.send(
+                    HandshakeMessage::Welcome(PROTOCOL_VERSION, features).to_packet(),
+                    peer_id
+                );
+            }
+            HandshakeMessage::Welcome(_, _) |
+            HandshakeMessage::Rejected(_, _) =>
+            {
+                println!("WARNING: peer {} sent the game server's handshake message {:?}", id, message);
+            }
+        }
+    }
+
+    fn get_broadcast_peers(&self) -> impl Iterator<Item = PeerId> + '_
+    {
+        self.players
+            .values()
+            .map(|player_info| player_info.peer_id)
+            .chain(self.spectators.values().copied())
+    }
+
+    pub fn add_spectator(
+        &mut self,
+        id: u128,
+        peer_id: PeerId,
+    )
+    {
+        self.spectators.insert(id, peer_id);
+
+        *self.spectators_amount.lock().unwrap() = self.spectators.len() as u32;
+    }
+
+    pub fn remove_spectator(
+        &mut self,
+        id: u128,
+    )
+    {
+        self.spectators.remove(&id);
+
+        *self.spectators_amount.lock().unwrap() = self.spectators.len() as u32;
+    }
+
+    fn reserve_seat(
+        &mut self,
+        player_info: &PlayerInfo,
+        score: u32,
+        current_time: u128,
+    )
+    {
+        self.reserved_seats.insert(
+            player_info.reconnect_token,
+            ReservedSeat {
+                team: player_info.team,
+                stats: player_info.stats,
+                score,
+                skill_rating: player_info.skill_rating,
+                join_time: player_info.join_time,
+                party_id: player_info.party_id,
+                disconnect_time: current_time,
+            }
+        );
+
+        *self.reserved_seats_amount.lock().unwrap() = self.reserved_seats.len() as u32;
+    }
+
+    fn take_reserved_seat(
+        &mut self,
+        reconnect_token: u128,
+    ) -> Option<ReservedSeat>
+    {
+        let reserved_seat = self.reserved_seats.remove(&reconnect_token);
+
+        *self.reserved_seats_amount.lock().unwrap() = self.reserved_seats.len() as u32;
+
+        reserved_seat
+    }
+
+    // returns true if some seats were released
+    pub fn release_expired_seats(
+        &mut self,
+        current_time: u128,
+    ) -> bool
+    {
+        let grace_period = self.match_rules.reconnect_grace_period;
+
+        let seats_amount = self.reserved_seats.len();
+
+        self.reserved_seats.retain(|_, seat| {
+            let is_expired = current_time - seat.disconnect_time >= grace_period;
+
+            if is_expired
+            {
+                println!("INFO: reserved seat of player {} is released", seat.stats.player_id);
+            }
+
+            !is_expired
+        });
+
+        *self.reserved_seats_amount.lock().unwrap() = self.reserved_seats.len() as u32;
+
+        self.reserved_seats.len() != seats_amount
+    }
+
+    pub fn add_player(
+        &mut self,
+        id: u128,
+        player_info: PlayerInfo,
+    )
+    {
+        self.players.insert(id, player_info);
+        self.players_score.insert(id, 0u32);
+        self.hit_validator.add_peer(id);
+
+        if let Some(team_id) = player_info.team.get_id()
+        {
+            self.teams[team_id as usize].insert(id, ());
+        }
+    }
+
+    pub fn remove_player(
+        &mut self,
+        id: u128,
+    )
+    {
+        self.players.remove(&id);
+        self.players_score.remove(&id);
+
+        for team in &mut self.teams
+        {
+            team.remove(&id);
+        }
+
+        self.hit_validator.remove_peer(id);
+        self.kill_confirmation.remove_player(id);
+    }
+
+    pub fn relay_confirmed_hits(
+        &mut self,
+        server_start_time: &Instant,
+        relaible_channel: &mut RecordedChannel,
+    )
+    {
+        let confirmed_hits = self.hit_validator.take_confirmed_hits(
+            &self.players,
+            server_start_time.elapsed().as_millis()
+        );
+
+        for hit in confirmed_hits
+        {
+            if let Some(player_info) = self.players.get(&hit.to_player)
+            {
+                let peer_id = player_info.peer_id;
+
+                if let Some(confirmed_hit) = Hit::from_message(&hit.message)
+                {
+                    self.add_damage_dealt(hit.from_player, confirmed_hit.damage);
+                }
+
+                relaible_channel.send(
+                    ServerMessage::NetMessageToPlayer(
+                        hit.from_player,
+                        hit.message
+                    ).to_packet(),
+                    peer_id
+                );
+            }
+        }
+    }
+}
+
+struct Flag
+{
+    get_previous_status_time: u128,
+    status: FlagStatus,
+    team: Team,
+}
+
+#[derive(Clone, Copy)]
+struct MoveWBonusSpot
+{
+    get_previouse_status_time: u128,
+    status: BonusSpotStatus,
+}
+
+// the packets sent since the last call are recorded with the current time
+fn record_sent_packets(
+    game_session_state: &mut GameSessionState,
+    relaible_channel: &mut RecordedChannel,
+    unrelaible_channel: &mut RecordedChannel,
+    server_start_time: &Instant,
+)
+{
+    let current_time = server_start_time.elapsed().as_millis();
+
+    game_session_state.replay_recorder.record_sent_packets(relaible_channel, current_time);
+    game_session_state.replay_recorder.record_sent_packets(unrelaible_channel, current_time);
+}
+
+async fn start_new_game_session(
+    webrtc_socket: &mut WebRtcSocket,
+    config: &GameServerConfig,
+    relaible_channel: &mut RecordedChannel,
+    unrelaible_channel: &mut RecordedChannel,
+    game_session_state: &mut GameSessionState,
     server_start_time: &Instant,
+    admin_commands: &Arc<Mutex<Vec<AdminCommand>>>,
 ) -> Command
 {
     game_session_state.game_state = GameState::Playing;
-    println!("New game session started!");
+    println!(
+        "New game session started! Map: {}",
+        game_session_state.map_rotation.get_current_map().get_name()
+    );
 
     let mut idle_timer: Option<Instant> = None;
 
     if webrtc_socket.any_channel_closed() {
         println!("ERROR: game server's WebRTC connection unexpectedly closed, server will shut down immediately");
-        return Command::ShutDownServer(1);
+        return Command::ShutDownServer(1, ShutdownReason::ConnectionError);
+    }
+
+    // the packets sent at the end of the previous game session go to its replay file
+    record_sent_packets(
+        game_session_state,
+        relaible_channel,
+        unrelaible_channel,
+        server_start_time
+    );
+
+    // every game session is recorded to its own replay file,
+    // the file starts with the peers which are already connected
+    game_session_state.replay_recorder.start_recording(server_start_time.elapsed().as_millis());
+
+    for peer_id in webrtc_socket.connected_peers()
+    {
+        game_session_state.replay_recorder.record_peer_connected(
+            peer_id.0.as_u128(),
+            server_start_time.elapsed().as_millis()
+        );
     }
 
+    // turns on keeping the sent packets for the new replay file
+    record_sent_packets(
+        game_session_state,
+        relaible_channel,
+        unrelaible_channel,
+        server_start_time
+    );
+
     init_game_session(
         game_session_state,
         relaible_channel,
@@ -890,121 +2265,269 @@ async fn start_new_game_session(
 
     loop {
 
+        record_sent_packets(
+            game_session_state,
+            relaible_channel,
+            unrelaible_channel,
+            server_start_time
+        );
+
         if webrtc_socket.any_channel_closed() {
             println!("ERROR: game server's WebRTC connection unexpectedly closed, server will shut down immediately");
-            return Command::ShutDownServer(1);
+            return Command::ShutDownServer(1, ShutdownReason::ConnectionError);
         }
 
         // shutdown the game server if no players on the server for more than 3 minutes
         if webrtc_socket.connected_peers().count() == 0 {
             if idle_timer.is_some() {
-                if idle_timer.unwrap().elapsed().as_secs() > 180 {
+                if idle_timer.unwrap().elapsed().as_secs() > config.match_rules.idle_shutdown_time {
 
                 println!("INFO: no players on the game server, server is shuting down");
-                return Command::ShutDownServer(0);
+                return Command::ShutDownServer(0, ShutdownReason::IdleTimeout);
                     
                 }
-            } else {
-                idle_timer = Some(Instant::now());
+            } else {
+                idle_timer = Some(Instant::now());
+            }
+        } else {
+            idle_timer = None;
+        }
+
+        let peers_state = webrtc_socket.update_peers();
+
+        for (id, state) in peers_state {
+            match state {
+                Connected =>
+                {
+                    // the peer joins the game session as a player or a spectator
+                    // after its JoinAsPlayer or JoinAsSpectator message
+                    println!("player {} is connected to p2p network", id.0.as_u128());
+
+                    game_session_state.joining_peers.insert(id, server_start_time.elapsed().as_millis());
+
+                    game_session_state.replay_recorder.record_peer_connected(
+                        id.0.as_u128(),
+                        server_start_time.elapsed().as_millis()
+                    );
+                }
+                Disconnected =>
+                {
+                    println!("player {} is disconnected to p2p network", id.0.as_u128());
+
+                    game_session_state.replay_recorder.record_peer_disconnected(
+                        id.0.as_u128(),
+                        server_start_time.elapsed().as_millis()
+                    );
+
+                    handle_player_disconnection(
+                        config,
+                        &server_start_time,
+                        relaible_channel,
+                        game_session_state,
+                        id,
+                        true
+                    ).await;
+
+                    game_session_state.rate_limiter.remove_peer(id.0.as_u128());
+                    game_session_state.handshakes.remove(&id.0.as_u128());
+                    game_session_state.joining_peers.remove(&id);
+                }
+            }
+        }
+
+        game_session_state.drop_peers_not_joined_in_time(server_start_time.elapsed().as_millis());
+
+        let recieved_messages = unrelaible_channel.receive();
+
+        for (from_player, packet) in recieved_messages {
+
+            let verdict = game_session_state.rate_limiter.check_packet(
+                from_player.0.as_u128(),
+                TrafficChannel::Unreliable,
+                packet.len(),
+                server_start_time.elapsed().as_millis()
+            );
+
+            match verdict
+            {
+                PacketVerdict::Accept =>
+                {
+                    game_session_state.replay_recorder.record_packet(
+                        from_player.0.as_u128(),
+                        TrafficChannel::Unreliable,
+                        &packet,
+                        server_start_time.elapsed().as_millis()
+                    );
+
+                    process_player_message(
+                        &server_start_time,
+                        unrelaible_channel,
+                        TrafficChannel::Unreliable,
+                        game_session_state,
+                        from_player,
+                        packet
+                    );
+                }
+                PacketVerdict::Drop => {}
+                PacketVerdict::Kick =>
+                {
+                    kick_peer(
+                        config,
+                        &server_start_time,
+                        relaible_channel,
+                        game_session_state,
+                        from_player,
+                        "for exceeding its traffic budget",
+                    ).await;
+                }
             }
-        } else {
-            idle_timer = None;
         }
 
-        let peers_state = webrtc_socket.update_peers();
+        let recieved_messages = relaible_channel.receive();
+        
+        for (from_player, packet) in recieved_messages {
 
-        for (id, state) in peers_state {
-            match state {
-                Connected =>
+            let verdict = game_session_state.rate_limiter.check_packet(
+                from_player.0.as_u128(),
+                TrafficChannel::Reliable,
+                packet.len(),
+                server_start_time.elapsed().as_millis()
+            );
+
+            match verdict
+            {
+                PacketVerdict::Accept =>
                 {
-                    println!("player {} is connected to p2p network", id.0.as_u128());
-                    handle_player_connection(
-                        config,
+                    game_session_state.replay_recorder.record_packet(
+                        from_player.0.as_u128(),
+                        TrafficChannel::Reliable,
+                        &packet,
+                        server_start_time.elapsed().as_millis()
+                    );
+
+                    process_player_message(
                         &server_start_time,
                         relaible_channel,
+                        TrafficChannel::Reliable,
                         game_session_state,
-                        id,
-                    ).await
+                        from_player,
+                        packet
+                    );
                 }
-                Disconnected =>
+                PacketVerdict::Drop => {}
+                PacketVerdict::Kick =>
                 {
-                    println!("player {} is disconnected to p2p network", id.0.as_u128());
-                    handle_player_disconnection(
+                    kick_peer(
                         config,
                         &server_start_time,
                         relaible_channel,
                         game_session_state,
-                        id
-                    ).await
+                        from_player,
+                        "for exceeding its traffic budget",
+                    ).await;
                 }
             }
         }
 
-        let recieved_messages = unrelaible_channel.receive();
-
-        for (from_player, packet) in recieved_messages {
-            
-            process_player_message(
+        if let Some(vote_kind) = game_session_state.update_vote(&server_start_time, relaible_channel)
+        {
+            let command = apply_vote_result(
+                config,
                 &server_start_time,
-                unrelaible_channel,
+                relaible_channel,
                 game_session_state,
-                from_player,
-                packet
-            );
+                vote_kind
+            ).await;
+
+            if let Some(command) = command
+            {
+                return command;
+            }
         }
 
-        let recieved_messages = relaible_channel.receive();
-        
-        for (from_player, packet) in recieved_messages {
-            
-            process_player_message(
+        let received_admin_commands = std::mem::take(&mut *admin_commands.lock().unwrap());
+
+        for admin_command in received_admin_commands
+        {
+            let command = process_admin_command(
+                config,
                 &server_start_time,
                 relaible_channel,
                 game_session_state,
-                from_player,
-                packet
-            );
+                admin_command
+            ).await;
+
+            if let Some(command) = command
+            {
+                return command;
+            }
+        }
+
+        game_session_state.rate_limiter.log_traffic_stats(server_start_time.elapsed().as_millis());
+
+        if game_session_state.release_expired_seats(server_start_time.elapsed().as_millis())
+        {
+            make_teams_equal(game_session_state, relaible_channel);
         }
 
+        game_session_state.relay_confirmed_hits(&server_start_time, relaible_channel);
+
         game_session_state.update_items(&server_start_time, relaible_channel);
 
+        game_session_state.update_match_timer(&server_start_time, relaible_channel);
+
+        game_session_state.update_scoreboard(&server_start_time, relaible_channel);
+
         match game_session_state.game_state
         {
             GameState::Playing => {}
-            GameState::BlueWin(win_time) =>
+            GameState::Overtime => {}
+            GameState::TeamWin(_, win_time) =>
             {
                 let time_since_win =
                     server_start_time.elapsed().as_millis()
                     -
                     win_time;
                 
-                if time_since_win > TIME_IN_SESSION_AFTER_WIN
+                if time_since_win > config.match_rules.time_in_session_after_win
                 {
                     return Command::StartNewGameSession;
                 }
             }
-            GameState::RedWin(win_time) =>
+            GameState::PlayerWin(_, win_time) =>
             {
                 let time_since_win =
                     server_start_time.elapsed().as_millis()
                     -
                     win_time;
                 
-                if time_since_win > TIME_IN_SESSION_AFTER_WIN
+                if time_since_win > config.match_rules.time_in_session_after_win
+                {
+                    return Command::StartNewGameSession;
+                }
+            }
+            GameState::Draw(draw_time) =>
+            {
+                let time_since_draw =
+                    server_start_time.elapsed().as_millis()
+                    -
+                    draw_time;
+                
+                if time_since_draw > config.match_rules.time_in_session_after_win
                 {
                     return Command::StartNewGameSession;
                 }
             }
         }
 
-        tokio::time::sleep(Duration::from_millis(16)).await;
+        tokio::time::sleep(Duration::from_millis(config.match_rules.server_tick_time)).await;
     }
 }
 
 
 fn init_game_session(
     game_session_state: &mut GameSessionState,
-    relaible_channel:&mut WebRtcChannel,
+    relaible_channel:&mut RecordedChannel,
     server_start_time: &Instant,
 )
 {
@@ -1019,16 +2542,57 @@ fn init_game_session(
     game_session_state.red_flag.status = FlagStatus::OnTheBase;
     game_session_state.red_flag.get_previous_status_time = 0u128;
 
-    game_session_state.move_w_bonus.status = BonusSpotStatus::BonusOnTheSpot;
-    game_session_state.move_w_bonus.get_previouse_status_time = 0u128;
+    for move_w_bonus in game_session_state.move_w_bonuses.iter_mut()
+    {
+        move_w_bonus.status = BonusSpotStatus::BonusOnTheSpot;
+        move_w_bonus.get_previouse_status_time = 0u128;
+    }
     
-    game_session_state.red_team_score = 0u32;
-    game_session_state.blue_team_score = 0u32;
+    game_session_state.teams_score = [0u32; MAX_TEAMS];
+
+    for (_, player_score) in &mut game_session_state.players_score
+    {
+        *player_score = 0u32;
+    }
+
+    for (id, player_info) in &mut game_session_state.players
+    {
+        player_info.skill_rating = team_balancing::update_skill_rating(
+            player_info.skill_rating,
+            &player_info.stats
+        );
+        player_info.stats = PlayerStats::new(*id, player_info.team);
+    }
+
+    for (_, reserved_seat) in &mut game_session_state.reserved_seats
+    {
+        reserved_seat.skill_rating = team_balancing::update_skill_rating(
+            reserved_seat.skill_rating,
+            &reserved_seat.stats
+        );
+        reserved_seat.stats = PlayerStats::new(reserved_seat.stats.player_id, reserved_seat.team);
+        reserved_seat.score = 0u32;
+    }
     
     game_session_state.holes.clear();
+
+    game_session_state.hit_validator.clear();
+    game_session_state.kill_confirmation.clear();
+
+    let current_time = server_start_time.elapsed().as_millis();
+
+    game_session_state.round_start_time = current_time;
+    game_session_state.last_match_timer_sync_time = current_time;
+    game_session_state.last_scoreboard_broadcast_time = current_time;
+    game_session_state.paused_since = None;
     
-    shuffle_teams(game_session_state);
+    match game_session_state.match_rules.team_balancing
+    {
+        TeamBalancing::Random => shuffle_teams(game_session_state),
+        TeamBalancing::Skill => split_teams_by_skill(game_session_state),
+    }
 
+    keep_parties_together(game_session_state);
 
     update_states_for_players(
         game_session_state,
@@ -1039,135 +2603,424 @@ fn init_game_session(
 
 fn update_states_for_players(
     game_session_state: &GameSessionState,
-    relaible_channel: &mut WebRtcChannel,
+    relaible_channel: &mut RecordedChannel,
     server_start_time: &Instant,
 )
 {
+    let match_time_left_packet = game_session_state
+        .get_match_time_left_message(server_start_time.elapsed().as_millis())
+        .map(|message| message.to_packet());
+
     for (_, player_info) in &game_session_state.players
     {
         relaible_channel.send(
             ServerMessage::NewSessionStarted(
                 server_start_time.elapsed().as_millis(),
-                player_info.team
+                player_info.team,
+                game_session_state.map_rotation.get_current_map()
             ).to_packet(),
             player_info.peer_id
         );
+
+        if let Some(packet) = &match_time_left_packet
+        {
+            relaible_channel.send(
+                packet.clone(),
+                player_info.peer_id
+            );
+        }
+    }
+
+    for (_, peer_id) in &game_session_state.spectators
+    {
+        relaible_channel.send(
+            ServerMessage::NewSessionStarted(
+                server_start_time.elapsed().as_millis(),
+                Team::NoTeam,
+                game_session_state.map_rotation.get_current_map()
+            ).to_packet(),
+            *peer_id
+        );
+
+        if let Some(packet) = &match_time_left_packet
+        {
+            relaible_channel.send(
+                packet.clone(),
+                *peer_id
+            );
+        }
+    }
+}
+
+fn shuffle_teams(players_state: &mut GameSessionState)
+{
+    for team in &mut players_state.teams
+    {
+        team.clear();
+    }
+
+    let mut keys = Vec::with_capacity(players_state.players.len());
+
+    for key in players_state.players.keys()
+    {
+        keys.push(*key);
+    }
+
+    let mut rng = fyrox_core::rand::thread_rng();
+
+    keys.shuffle(&mut rng);
+
+    let teams_amount = players_state.teams.len();
+
+    for (index, key) in keys.into_iter().enumerate()
+    {
+        let player_info = players_state.players
+            .get_mut(&key)
+            .unwrap();
+
+        if teams_amount == 0
+        {
+            // Free-for-all
+            player_info.team = Team::NoTeam;
+
+            continue;
+        }
+
+        let team_id = index % teams_amount;
+
+        player_info.team = Team::from_id(team_id as u8);
+
+        players_state.teams[team_id].insert(key, ());
+    }
+}
+
+fn split_teams_by_skill(players_state: &mut GameSessionState)
+{
+    for team in &mut players_state.teams
+    {
+        team.clear();
+    }
+
+    let teams_amount = players_state.teams.len();
+
+    if teams_amount == 0
+    {
+        // Free-for-all
+        for (_, player_info) in &mut players_state.players
+        {
+            player_info.team = Team::NoTeam;
+        }
+
+        return;
+    }
+
+    let players: Vec<(u128, f32)> = players_state.players
+        .iter()
+        .map(|(id, player_info)| (*id, player_info.skill_rating))
+        .collect();
+
+    for (key, team_id) in team_balancing::split_players_by_skill(players, teams_amount)
+    {
+        let player_info = players_state.players
+            .get_mut(&key)
+            .unwrap();
+
+        player_info.team = Team::from_id(team_id as u8);
+
+        players_state.teams[team_id].insert(key, ());
+    }
+}
+
+// moves the party members to the team with the most members of their party,
+// every moved member is swapped with the closest by skill player without a party
+// from that team, so team sizes stay the same
+fn keep_parties_together(players_state: &mut GameSessionState)
+{
+    let mut parties: HashMap<u128, Vec<u128>> = HashMap::new();
+
+    for (id, player_info) in &players_state.players
+    {
+        if let Some(party_id) = player_info.party_id
+        {
+            // Free-for-all players have no team
+            if player_info.team.get_id().is_some()
+            {
+                parties.entry(party_id).or_default().push(*id);
+            }
+        }
+    }
+
+    for (_, members) in parties
+    {
+        let mut members_by_team = vec![0usize; players_state.teams.len()];
+
+        for member in &members
+        {
+            let team_id = players_state.players[member].team.get_id().unwrap();
+
+            members_by_team[team_id as usize] += 1;
+        }
+
+        let party_team_id = members_by_team
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, members_amount)| **members_amount)
+            .map(|(team_id, _)| team_id)
+            .unwrap();
+
+        for member in members
+        {
+            let member_team_id = players_state.players[&member].team.get_id().unwrap() as usize;
+
+            if member_team_id == party_team_id
+            {
+                continue;
+            }
+
+            let member_skill_rating = players_state.players[&member].skill_rating;
+
+            let swapped_player = players_state.teams[party_team_id]
+                .keys()
+                .filter(|key| players_state.players[*key].party_id.is_none())
+                .min_by(|a, b| {
+                    let a = (players_state.players[*a].skill_rating - member_skill_rating).abs();
+                    let b = (players_state.players[*b].skill_rating - member_skill_rating).abs();
+
+                    a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal)
+                })
+                .copied();
+
+            // the team is full of party members
+            let swapped_player = match swapped_player
+            {
+                Some(swapped_player) => swapped_player,
+                None => continue,
+            };
+
+            players_state.teams[member_team_id].remove(&member);
+            players_state.teams[party_team_id].remove(&swapped_player);
+
+            players_state.teams[party_team_id].insert(member, ());
+            players_state.teams[member_team_id].insert(swapped_player, ());
+
+            players_state.players.get_mut(&member).unwrap().team = Team::from_id(party_team_id as u8);
+            players_state.players.get_mut(&swapped_player).unwrap().team = Team::from_id(member_team_id as u8);
+        }
+    }
+}
+
+// a party member joins the team of the party if this team is not full,
+// other players join one of the smallest teams
+fn choose_team_for_new_player(
+    game_session_state: &GameSessionState,
+    party_id: Option<u128>,
+) -> Team
+{
+    let smallest_team_size = match game_session_state.teams
+        .iter()
+        .map(|team| team.len())
+        .min()
+    {
+        Some(size) => size,
+        // Free-for-all
+        None => return Team::NoTeam,
+    };
+
+    if let Some(party_id) = party_id
+    {
+        let teams_amount = game_session_state.teams.len() as u32;
+
+        let max_team_size = ((game_session_state.max_players + teams_amount - 1) / teams_amount) as usize;
+
+        let party_team = game_session_state.players
+            .values()
+            .find(|player_info| player_info.party_id == Some(party_id))
+            .map(|player_info| player_info.team);
+
+        if let Some(party_team) = party_team
+        {
+            if let Some(team_id) = party_team.get_id()
+            {
+                if game_session_state.teams[team_id as usize].len() < max_team_size
+                {
+                    return party_team;
+                }
+            }
+        }
+    }
+
+    let smallest_teams: Vec<usize> = game_session_state.teams
+        .iter()
+        .enumerate()
+        .filter(|(_, team)| team.len() == smallest_team_size)
+        .map(|(team_id, _)| team_id)
+        .collect();
+
+    let mut rng = fyrox_core::rand::thread_rng();
+
+    let team_id = *smallest_teams.choose(&mut rng).unwrap();
+
+    Team::from_id(team_id as u8)
+}
+
+
+
+// sends the current state of the game session to a joined player or spectator
+fn send_game_session_state(
+    server_start_time: &Instant,
+    channel: &mut RecordedChannel,
+    game_session_state: &GameSessionState,
+    peer_id: PeerId,
+    team: Team,
+) {
+    channel.send(
+        ServerMessage::JoinTheMatch(
+            server_start_time.elapsed().as_millis(),
+            team,
+            game_session_state.red_flag.status,
+            game_session_state.blue_flag.status,
+            game_session_state.get_collected_move_w_bonuses_mask(),
+            game_session_state.teams_score,
+            game_session_state.match_rules.get_score_limit(),
+            game_session_state.match_rules.game_mode,
+            game_session_state.map_rotation.get_current_map(),
+        ).to_packet(),
+        peer_id
+    );
+
+    for hole in &game_session_state.holes
+    {
+        let command = if hole.is_positive
+        {
+            client_server_protocol::RemoteCommand::SpawnObstacle(
+                hole.position,
+                hole.radius,
+                hole.color,
+                hole.target_size_reached,
+                hole.target_size,
+                hole.explode_current_time,
+                hole.explode_final_time,
+            )
+        }
+        else
+        {
+            client_server_protocol::RemoteCommand::SpawnHole(
+                hole.position,
+                hole.radius,
+                hole.color,
+                hole.target_size_reached,
+                hole.target_size,
+                hole.explode_current_time,
+                hole.explode_final_time,
+            )
+        };
+
+        channel.send(
+            ServerMessage::NetMessageToPlayer(
+                0u128,
+                NetMessageToPlayer::RemoteCommand(command)
+            ).to_packet(),
+            peer_id
+        );
+    }
+
+    if let Some(message) = game_session_state.get_match_time_left_message(
+        server_start_time.elapsed().as_millis()
+    )
+    {
+        channel.send(
+            message.to_packet(),
+            peer_id
+        );
     }
 }
 
-use rand::{thread_rng, Rng};
-
-fn shuffle_teams(players_state: &mut GameSessionState)
-{
-    players_state.red_team.clear();    
-    players_state.blue_team.clear(); 
-
-    let mut keys = Vec::with_capacity(players_state.players.len());
 
-    for key in players_state.players.keys()
+fn handle_spectator_connection(
+    server_start_time: &Instant,
+    channel: &mut RecordedChannel,
+    game_session_state: &mut GameSessionState,
+    connected_spectator_id: PeerId,
+) {
+    if game_session_state.spectators.len() as u32 >= game_session_state.match_rules.max_spectators
     {
-        keys.push(*key);
-    }
-
-    let mut rng = fyrox_core::rand::thread_rng();
+        println!("INFO: no free spectator slots for spectator {}", connected_spectator_id.0.as_u128());
 
-    keys.shuffle(&mut rng);
+        channel.send(
+            ServerMessage::ServerIsFull.to_packet(),
+            connected_spectator_id
+        );
 
-    let mut team = Team::Red;
-    for key in keys
-    {
-        let player_info = players_state.players
-            .get_mut(&key)
-            .unwrap();
+        return;
+    }
 
-        player_info.team = team;
+    send_game_session_state(
+        server_start_time,
+        channel,
+        game_session_state,
+        connected_spectator_id,
+        Team::NoTeam,
+    );
 
-        match team {
-            Team::Red =>
-            {
-                players_state.red_team.insert(key, ());
-                team = Team::Blue;
-            }
-            Team::Blue =>
-            {
-                players_state.blue_team.insert(key, ());
-                team = Team::Red;
-            }
-        }
+    // spectators see all players, but players don't know about spectators
+    for (_ , player_info) in game_session_state.players.iter() {
+        channel.send(
+            ServerMessage::PlayerConnected(
+                player_info.peer_id.0.as_u128()
+            ).to_packet(),
+            connected_spectator_id
+        );
     }
-}
 
-fn choose_team_for_new_player(
-    game_session_state: &GameSessionState
-) -> Team
-{
-    if game_session_state.blue_team.len() >
-        game_session_state.red_team.len()
-    {
-        return Team::Red;
-    }
-    if game_session_state.blue_team.len() <
-        game_session_state.red_team.len()
-    {
-        return Team::Blue;
-    }
-    let mut rng = thread_rng();
+    game_session_state.add_spectator(
+        connected_spectator_id.0.as_u128(),
+        connected_spectator_id
+    );
 
-    if rng.gen_bool(0.5)
-    {
-        return Team::Red;
-    }
-    else
-    {
-        return Team::Blue;    
-    }
+    println!("INFO: spectator {} joined the game server", connected_spectator_id.0.as_u128());
 }
 
 
-
-async fn handle_player_connection(
-    config: &GameServerConfig,
+// the reserved seat is passed if the player rejoins the game session after a lost connection
+fn handle_player_connection(
     server_start_time: &Instant,
-    channel: &mut WebRtcChannel,
+    channel: &mut RecordedChannel,
     game_session_state: &mut GameSessionState,
     connected_player_id: PeerId,
+    reserved_seat: Option<ReservedSeat>,
+    party_id: Option<u128>,
 ) {
-    let new_player_team = choose_team_for_new_player(game_session_state);
-
-    channel.send(
-        ServerMessage::JoinTheMatch(
-            server_start_time.elapsed().as_millis(),
-            new_player_team,
-            game_session_state.red_flag.status,
-            game_session_state.blue_flag.status,
-            game_session_state.move_w_bonus.status,
-            game_session_state.red_team_score,
-            game_session_state.blue_team_score,
-        ).to_packet(),
-        connected_player_id
-    );
+    let occupied_slots = game_session_state.players.len() + game_session_state.reserved_seats.len();
 
-    for hole in &game_session_state.holes
+    if reserved_seat.is_none() && occupied_slots as u32 >= game_session_state.max_players
     {
+        println!("INFO: no free player slots for player {}", connected_player_id.0.as_u128());
+
         channel.send(
-            ServerMessage::NetMessageToPlayer(
-                0u128,
-                NetMessageToPlayer::RemoteCommand(
-                    client_server_protocol::RemoteCommand::SpawnHole(
-                        hole.position,
-                        hole.radius,
-                        hole.color,
-                        hole.target_size_reached,
-                        hole.target_size,
-                        hole.explode_current_time,
-                        hole.explode_final_time,
-                    )
-                )
-            ).to_packet(),
+            ServerMessage::ServerIsFull.to_packet(),
             connected_player_id
         );
+
+        return;
     }
-    
+
+    // the rejoined player stays in the party
+    let party_id = reserved_seat.map_or(party_id, |reserved_seat| reserved_seat.party_id);
+
+    let new_player_team = match &reserved_seat
+    {
+        Some(reserved_seat) => reserved_seat.team,
+        None => choose_team_for_new_player(game_session_state, party_id),
+    };
+
+    send_game_session_state(
+        server_start_time,
+        channel,
+        game_session_state,
+        connected_player_id,
+        new_player_team,
+    );
+
     for (_ , player_id) in game_session_state.players.iter() {
         channel.send(
             ServerMessage::PlayerConnected(
@@ -1184,256 +3037,540 @@ async fn handle_player_connection(
         );
     }
 
+    for (_, peer_id) in game_session_state.spectators.iter() {
+        channel.send(
+            ServerMessage::PlayerConnected(
+                connected_player_id.0.as_u128()
+            ).to_packet(),
+            *peer_id
+        );
+    }
+
+    let reconnect_token = fyrox_core::rand::random::<u128>();
+
+    let stats = match &reserved_seat
+    {
+        Some(reserved_seat) =>
+        {
+            // the player has a new id after reconnect
+            let mut stats = reserved_seat.stats;
+            stats.player_id = connected_player_id.0.as_u128();
+
+            stats
+        }
+        None => PlayerStats::new(connected_player_id.0.as_u128(), new_player_team),
+    };
+
     game_session_state.add_player(
         connected_player_id.0.as_u128(),
         PlayerInfo {
             peer_id: connected_player_id,
             team: new_player_team,
             captured_flag: false,
+            stats,
+            reconnect_token,
+            skill_rating: reserved_seat.map_or(0.0, |reserved_seat| reserved_seat.skill_rating),
+            join_time: reserved_seat.map_or(
+                server_start_time.elapsed().as_millis(),
+                |reserved_seat| reserved_seat.join_time
+            ),
+            party_id,
         }
     );
+
+    if let Some(reserved_seat) = &reserved_seat
+    {
+        game_session_state.players_score.insert(connected_player_id.0.as_u128(), reserved_seat.score);
+    }
+
+    if game_session_state.peer_has_feature(connected_player_id.0.as_u128(), FEATURE_RECONNECT)
+    {
+        channel.send(
+            ServerMessage::ReconnectToken(reconnect_token).to_packet(),
+            connected_player_id
+        );
+    }
+
+    if game_session_state.match_rules.game_mode == GameMode::FreeForAll
+    {
+        channel.send(
+            game_session_state
+                .get_free_for_all_score_message(connected_player_id.0.as_u128())
+                .to_packet(),
+            connected_player_id
+        );
+    }
 }
 
 
 fn make_teams_equal(
     game_session_state: &mut GameSessionState,
-    channel: &mut WebRtcChannel,
+    channel: &mut RecordedChannel,
 )
 {
+    for team in &mut game_session_state.teams
+    {
+        team.clear();
+    }
+
     for (player_id, player_info) in &game_session_state.players
     {
-        match player_info.team
+        if let Some(team_id) = player_info.team.get_id()
         {
-            Team::Red =>
-            {
-                game_session_state.red_team.insert(*player_id, ());
-                game_session_state.blue_team.remove(player_id);
-            }
-            Team::Blue =>
-            {
-                game_session_state.blue_team.insert(*player_id, ());
-                game_session_state.red_team.remove(player_id);
-            }
+            game_session_state.teams[team_id as usize].insert(*player_id, ());
         }
     }
 
+    let biggest_team_id = game_session_state.teams
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, team)| team.len())
+        .map(|(team_id, _)| team_id);
+
+    let smallest_team_id = game_session_state.teams
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, team)| team.len())
+        .map(|(team_id, _)| team_id);
+
+    let (biggest_team_id, smallest_team_id) = match (biggest_team_id, smallest_team_id)
+    {
+        (Some(biggest_team_id), Some(smallest_team_id)) => (biggest_team_id, smallest_team_id),
+        // Free-for-all
+        _ => return,
+    };
+
     let difference =
-        game_session_state.red_team.len() as i32 -
-        game_session_state.blue_team.len() as i32;
+        game_session_state.teams[biggest_team_id].len() as i32 -
+        game_session_state.teams[smallest_team_id].len() as i32;
     
-    if difference.abs() > 1
+    if difference > 1
     {
-        if difference < 0
+        // the flag carrier is never moved, party members are moved
+        // only if all other players of the team are in parties
+        let has_players_without_party = game_session_state
+            .teams[biggest_team_id]
+            .keys()
+            .any(|key| {
+                !game_session_state.check_if_player_has_flag(*key) &&
+                game_session_state.players.get(key).unwrap().party_id.is_none()
+            });
+
+        let candidates = game_session_state
+            .teams[biggest_team_id]
+            .keys()
+            .filter(|key| !game_session_state.check_if_player_has_flag(**key))
+            .map(|key| game_session_state.players.get(key).unwrap())
+            .filter(|player_info| !has_players_without_party || player_info.party_id.is_none());
+
+        let moved_player = match game_session_state.match_rules.team_balancing
         {
-            let mut keys = game_session_state
-                .blue_team
-                .keys();
-
-            let mut key = keys
-                .next()
-                .unwrap()
-                .clone();
-
-            if game_session_state.check_if_player_has_flag(key)
+            TeamBalancing::Random =>
+            {
+                candidates.max_by_key(|player_info| player_info.join_time)
+            }
+            TeamBalancing::Skill =>
             {
-                key = keys
-                    .next()
-                    .unwrap()
-                    .clone();
+                // the most recently joined player is moved if impacts are equal
+                candidates.min_by(|a, b| {
+                    team_balancing::get_player_impact(&a.stats)
+                        .partial_cmp(&team_balancing::get_player_impact(&b.stats))
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                        .then(b.join_time.cmp(&a.join_time))
+                })
             }
+        };
 
-            game_session_state
-                .blue_team
-                .remove(&key)
-                .unwrap();
+        let key = moved_player
+            .unwrap()
+            .peer_id
+            .0
+            .as_u128();
 
-            game_session_state
-                .red_team
-                .insert(key, ());
+        let new_team = Team::from_id(smallest_team_id as u8);
+
+        game_session_state
+            .teams[biggest_team_id]
+            .remove(&key)
+            .unwrap();
 
+        game_session_state
+            .teams[smallest_team_id]
+            .insert(key, ());
+
+        game_session_state
+            .players
+            .get_mut(&key)
+            .unwrap()
+            .team =
+            new_team;
+        
+        channel.send(
+            ServerMessage::NetMessageToPlayer(
+                0u128,
+                NetMessageToPlayer::RemoteBoardCastMessage(
+                    RemoteMessage::SetNewTeam(new_team)
+                )
+            ).to_packet(),
             game_session_state
                 .players
-                .get_mut(&key)
+                .get(&key)
                 .unwrap()
-                .team =
-                Team::Red;
-            
-            channel.send(
-                ServerMessage::NetMessageToPlayer(
-                    0u128,
-                    NetMessageToPlayer::RemoteBoardCastMessage(
-                        RemoteMessage::SetNewTeam(Team::Red)
-                    )
-                ).to_packet(),
-                game_session_state
-                    .players
-                    .get(&key)
-                    .unwrap()
-                    .peer_id,
+                .peer_id,
+        );
+    }
+}
+
+// the seat of the disconnected player is reserved for reconnect_grace_period
+// if reserve_seat is true (false for kicked players)
+async fn handle_player_disconnection(
+    config: &GameServerConfig,
+    server_start_time: &Instant,
+    relaible_channel: &mut RecordedChannel,
+    game_session_state: &mut GameSessionState,
+    disconnected_player_id: PeerId,
+    reserve_seat: bool,
+) {
+    if game_session_state.spectators.contains_key(&disconnected_player_id.0.as_u128())
+    {
+        game_session_state.remove_spectator(disconnected_player_id.0.as_u128());
+
+        println!("INFO: spectator {} left the game server", disconnected_player_id.0.as_u128());
+
+        return;
+    }
+
+    game_session_state.return_flag_captured_by_player(
+        disconnected_player_id.0.as_u128(),
+        server_start_time,
+        relaible_channel
+    );
+
+    let disconnected_player =
+        game_session_state.players.remove(&disconnected_player_id.0.as_u128());
+    
+    if disconnected_player.is_some()
+    {
+        let disconnected_player = disconnected_player.unwrap();
+
+        let score = game_session_state.players_score
+            .remove(&disconnected_player_id.0.as_u128())
+            .unwrap_or(0u32);
+
+        for team in &mut game_session_state.teams
+        {
+            team.remove(&disconnected_player_id.0.as_u128());
+        }
+
+        game_session_state.hit_validator.remove_peer(disconnected_player_id.0.as_u128());
+        game_session_state.kill_confirmation.remove_player(disconnected_player_id.0.as_u128());
+
+        if game_session_state.voting.remove_player(disconnected_player_id.0.as_u128())
+        {
+            println!("INFO: the vote to kick player {} is canceled, the player has left", disconnected_player_id.0.as_u128());
+
+            game_session_state.send_vote_result_to_players(
+                VoteKind::KickPlayer(disconnected_player_id.0.as_u128()),
+                false,
+                relaible_channel
+            );
+        }
+
+        if reserve_seat && config.match_rules.reconnect_grace_period > 0
+        {
+            game_session_state.reserve_seat(
+                &disconnected_player,
+                score,
+                server_start_time.elapsed().as_millis()
+            );
+
+            println!(
+                "INFO: seat of player {} is reserved for {} millis",
+                disconnected_player_id.0.as_u128(),
+                config.match_rules.reconnect_grace_period
             );
         }
         else
         {
-            let mut keys = game_session_state
-                .red_team
-                .keys();
+            // teams are balanced when the reserved seat is released
+            make_teams_equal(game_session_state, relaible_channel);
+        }
 
-            let mut key = keys
-                .next()
-                .unwrap()
-                .clone();
+        for peer_id in game_session_state.get_broadcast_peers() {
+            relaible_channel.send(
+                ServerMessage::PlayerDisconnected(
+                    disconnected_player_id.0.as_u128()
+                ).to_packet(),
+                peer_id
+            );
+        }
+    }
+    else
+    {
+        // the peer was disconnected before its join request
+        println!("INFO: disconected peer {} has not joined the game session", disconnected_player_id.0.as_u128());
+    }    
+}
+
+// the kicked peer is dropped after YouAreKicked message.
+// Players have no accounts, so the kicked player can join again with a new peer id
+async fn kick_peer(
+    config: &GameServerConfig,
+    server_start_time: &Instant,
+    relaible_channel: &mut RecordedChannel,
+    game_session_state: &mut GameSessionState,
+    kicked_peer_id: PeerId,
+    reason: &str,
+) {
+    println!("INFO: peer {} is kicked {}", kicked_peer_id.0.as_u128(), reason);
+
+    relaible_channel.send(
+        ServerMessage::YouAreKicked.to_packet(),
+        kicked_peer_id
+    );
+
+    game_session_state.drop_peer(kicked_peer_id);
 
-            if game_session_state.check_if_player_has_flag(key)
+    handle_player_disconnection(
+        config,
+        server_start_time,
+        relaible_channel,
+        game_session_state,
+        kicked_peer_id,
+        false
+    ).await;
+}
+
+// returns the command for the game server's main loop
+// if the admin command ends the game session
+async fn process_admin_command(
+    config: &GameServerConfig,
+    server_start_time: &Instant,
+    relaible_channel: &mut RecordedChannel,
+    game_session_state: &mut GameSessionState,
+    admin_command: AdminCommand,
+) -> Option<Command>
+{
+    println!("INFO: executing admin command {:?}", admin_command);
+
+    let result = match admin_command
+    {
+        AdminCommand::KickPlayer(id) =>
+        {
+            let peer_id = game_session_state.players
+                .get(&id)
+                .map(|player_info| player_info.peer_id)
+                .or(game_session_state.spectators.get(&id).copied());
+
+            match peer_id
             {
-                key = keys
-                    .next()
-                    .unwrap()
-                    .clone();
+                Some(peer_id) =>
+                {
+                    kick_peer(
+                        config,
+                        server_start_time,
+                        relaible_channel,
+                        game_session_state,
+                        peer_id,
+                        "by the operator",
+                    ).await;
+
+                    Ok(())
+                }
+                None => Err(format!("peer {} is not in the game session", id)),
             }
+        }
 
-            game_session_state
-                .red_team
-                .remove(&key)
-                .unwrap();
+        AdminCommand::SwapPlayerTeam(id, team_id) =>
+        {
+            game_session_state.swap_player_team(
+                id,
+                team_id,
+                server_start_time,
+                relaible_channel
+            )
+        }
 
-            game_session_state
-                .blue_team
-                .insert(key, ());
+        AdminCommand::StartNewGameSession =>
+        {
+            println!("INFO: new game session is started by the operator");
 
-            game_session_state
-                .players
-                .get_mut(&key)
-                .unwrap()
-                .team =
-                Team::Blue;
-            
-            channel.send(
-                ServerMessage::NetMessageToPlayer(
-                    0u128,
-                    NetMessageToPlayer::RemoteBoardCastMessage(
-                        RemoteMessage::SetNewTeam(Team::Blue)
-                    )
-                ).to_packet(),
-                game_session_state
-                    .players
-                    .get(&key)
-                    .unwrap()
-                    .peer_id,
-            );
+            return Some(Command::RestartGameSession);
+        }
+
+        AdminCommand::SetTeamScore(team_id, score) =>
+        {
+            game_session_state.set_team_score_and_send_update_for_players(
+                team_id,
+                score,
+                server_start_time,
+                relaible_channel
+            )
+        }
+
+        AdminCommand::PauseMatch =>
+        {
+            game_session_state.pause_match(server_start_time, relaible_channel)
+        }
+
+        AdminCommand::ResumeMatch =>
+        {
+            game_session_state.resume_match(server_start_time, relaible_channel)
+        }
+
+        AdminCommand::ShutDown =>
+        {
+            println!("INFO: the game server is shut down by the operator");
+
+            return Some(Command::ShutDownServer(0, ShutdownReason::ShutDownByOperator));
         }
+    };
+
+    if let Err(e) = result
+    {
+        println!("ERROR: can't execute admin command, err: {}", e);
     }
+
+    None
 }
 
-async fn handle_player_disconnection(
+// returns the command for the game server's main loop
+// if the passed vote ends the game session
+async fn apply_vote_result(
     config: &GameServerConfig,
     server_start_time: &Instant,
-    relaible_channel: &mut WebRtcChannel,
+    relaible_channel: &mut RecordedChannel,
     game_session_state: &mut GameSessionState,
-    disconnected_player_id: PeerId
-) {
-    if game_session_state.check_if_player_has_flag(
-        disconnected_player_id.0.as_u128()
-    )
+    vote_kind: VoteKind,
+) -> Option<Command>
+{
+    match vote_kind
     {
-        match game_session_state.players
-            .get(&disconnected_player_id.0.as_u128())
-            .unwrap()
-            .team
+        VoteKind::KickPlayer(id) =>
         {
-            Team::Red =>
+            if let Some(player_info) = game_session_state.players.get(&id)
             {
-                game_session_state.set_new_flag_status_and_send_update_to_players(
+                let peer_id = player_info.peer_id;
+
+                kick_peer(
+                    config,
                     server_start_time,
-                    Team::Blue,
-                    FlagStatus::OnTheBase,
-                    relaible_channel
-                );
+                    relaible_channel,
+                    game_session_state,
+                    peer_id,
+                    "by the players' vote",
+                ).await;
             }
-            Team::Blue =>
-            {
-                game_session_state.set_new_flag_status_and_send_update_to_players(
-                    server_start_time,
-                    Team::Red,
-                    FlagStatus::OnTheBase,
-                    relaible_channel
-                );
 
-            }
+            None
         }
-    }
 
-    let disconnected_player =
-        game_session_state.players.remove(&disconnected_player_id.0.as_u128());
-    
-    if disconnected_player.is_some()
-    {
-        let disconnected_player = disconnected_player.unwrap();
+        VoteKind::RestartMatch =>
+        {
+            println!("INFO: the game session is restarted by the players' vote");
 
-        game_session_state.red_team.remove(&disconnected_player_id.0.as_u128());
-        game_session_state.blue_team.remove(&disconnected_player_id.0.as_u128());
+            Some(Command::RestartGameSession)
+        }
 
-        make_teams_equal(game_session_state, relaible_channel);
+        VoteKind::NextMap =>
+        {
+            println!("INFO: next map is started by the players' vote");
 
-        for (_ , player_id) in game_session_state.players.iter() {
-            relaible_channel.send(
-                ServerMessage::PlayerDisconnected(
-                    disconnected_player_id.0.as_u128()
-                ).to_packet(),
-                player_id.peer_id
-            );
+            Some(Command::StartNewGameSession)
         }
     }
-    else
-    {
-        println!("ERROR: disconected player is not exist in game_session_state!");
-    }    
 }
 
-
 fn process_player_message(
     server_start_time: &Instant,
-    channel: &mut WebRtcChannel,
+    channel: &mut RecordedChannel,
+    traffic_channel: TrafficChannel,
     game_session_state: &mut GameSessionState,
     from_player: PeerId,
     packet: Box<[u8]>,
 ) {
+    // the handshake is accepted only by the reliable channel, it is sent before the join request
+    if let TrafficChannel::Reliable = traffic_channel
+    {
+        if let Some(handshake) = HandshakeMessage::from_packet(&packet)
+        {
+            game_session_state.process_handshake(from_player, handshake, channel);
+
+            return;
+        }
+    }
+
     if let Some(message) = ClientMessage::from_packet(packet) {
+        // join requests are the only messages accepted from peers which are not players,
+        // spectators only watch the game session
+        let is_join_request = matches!(
+            message,
+            ClientMessage::MessageToServer(NetMessageToServer::JoinAsPlayer) |
+            ClientMessage::MessageToServer(NetMessageToServer::JoinAsSpectator) |
+            ClientMessage::MessageToServer(NetMessageToServer::RejoinAsPlayer(_)) |
+            ClientMessage::MessageToServer(NetMessageToServer::JoinAsPartyMember(_))
+        );
+
+        if is_join_request && !game_session_state.handshakes.contains_key(&from_player.0.as_u128())
+        {
+            println!(
+                "WARNING: peer {} is rejected, it sent the join request without the handshake",
+                from_player.0.as_u128()
+            );
+
+            game_session_state.reject_peer(from_player, HandshakeRejectReason::HandshakeRequired, channel);
+
+            return;
+        }
+
+        if !is_join_request && !game_session_state.players.contains_key(&from_player.0.as_u128())
+        {
+            return;
+        }
+
         match message
         {
             ClientMessage::DirectMessageToPlayer(to_player, message) => {
                 let player_info = game_session_state.players.get(&to_player);
 
-                // match &message
-                // {
-                //     NetMessageToPlayer::RemoteBoardCastMessage(message) =>
-                //     {
-                //         match message
-                //         {
-                //             RemoteMessage::SpawnHoleGunShotActor(
-                //                 position,
-                //                 _shoooted_from,
-                //                 radius,
-                //                 color,
-                //                 _charging_volume_area
-                //             ) =>
-                //             {
-                //                 let hole = Hole::new(*radius, *position, *color);
-
-                //                 game_session_state.holes.push(hole);
-                //             }
-                //             _ => {}
-                //         }
-                //     }
-                //     _ => {}
-                // }
-
                 if player_info.is_some() {
+                    let peer_id = player_info.unwrap().peer_id;
+
+                    let message = match Hit::from_message(&message)
+                    {
+                        Some(hit) =>
+                        {
+                            let damage = hit.damage;
+
+                            let checked_message = game_session_state.hit_validator.check_hit(
+                                &game_session_state.players,
+                                from_player.0.as_u128(),
+                                to_player,
+                                message,
+                                hit,
+                                server_start_time.elapsed().as_millis(),
+                            );
+
+                            match checked_message
+                            {
+                                Some(message) =>
+                                {
+                                    game_session_state.add_damage_dealt(from_player.0.as_u128(), damage);
+                                    message
+                                }
+                                // hit is dropped or waiting for the shot
+                                None => return,
+                            }
+                        }
+                        None => message,
+                    };
+
                     channel.send(
                         ServerMessage::NetMessageToPlayer(
                             from_player.0.as_u128(),
                             message
                         ).to_packet(),
-                        player_info.unwrap().peer_id
+                        peer_id
                     );
                 } else {
                     channel.send(
@@ -1446,14 +3583,50 @@ fn process_player_message(
             }
 
             ClientMessage::BoardcastMessageToPlayers(message) => {
-                for (index, player_info) in game_session_state.players.iter() {
-                    if *index != from_player.0.as_u128() {
+                if Hit::from_message(&message).is_some()
+                {
+                    // clients never deal damage to all players at once
+                    game_session_state.hit_validator.reject_hit(
+                        from_player.0.as_u128(),
+                        0u128,
+                        HitRejection::BroadcastedDamage
+                    );
+
+                    return;
+                }
+
+                game_session_state.record_map_destruction(&message);
+
+                if let Some(weapon) = Weapon::from_shot_message(&message)
+                {
+                    game_session_state.hit_validator.register_shot(
+                        from_player.0.as_u128(),
+                        weapon,
+                        server_start_time.elapsed().as_millis()
+                    );
+                }
+
+                if let NetMessageToPlayer::RemoteDirectMessage(
+                    _,
+                    RemoteMessage::SetPlayerDollState(player_state)
+                ) = &message
+                {
+                    let map_bounds = game_session_state.map_rotation.get_current_map().bounds;
+
+                    game_session_state.hit_validator.set_last_known_position(
+                        from_player.0.as_u128(),
+                        player_state.get_position(&map_bounds)
+                    );
+                }
+
+                for peer_id in game_session_state.get_broadcast_peers() {
+                    if peer_id != from_player {
                         channel.send(
                             ServerMessage::NetMessageToPlayer(
                                 from_player.0.as_u128(),
                                 message.clone()
                             ).to_packet(),
-                            player_info.peer_id
+                            peer_id
                         );
                     }
                 }
@@ -1466,21 +3639,17 @@ fn process_player_message(
                         time_of_attempt,
                     ) =>
                     {
-                        let team_trying_to_captured_flag = {
-                            if game_session_state.red_team.contains_key(&from_player.0.as_u128())
-                            {
-                                Team::Red
-                            }
-                            else if game_session_state.blue_team.contains_key(&from_player.0.as_u128())
-                            {
-                                Team::Blue
-                            }
-                            else
-                            {
-                                // this case means that the player whose message was recieved
-                                // by the server is already disconnected
-                                return;    
-                            }
+                        if game_session_state.match_rules.game_mode != GameMode::CaptureTheFlag
+                        {
+                            return;
+                        }
+
+                        let team_trying_to_captured_flag = match game_session_state.get_player_team(from_player.0.as_u128())
+                        {
+                            Some(team) => team,
+                            // this case means that the player whose message was recieved
+                            // by the server is already disconnected
+                            None => return,
                         };
 
                         match team_trying_to_captured_flag
@@ -1526,6 +3695,8 @@ fn process_player_message(
                                     }
                                 }
                             }
+
+                            _ => {}
                         }
                     }
 
@@ -1533,21 +3704,17 @@ fn process_player_message(
                         time_of_attempt,
                     ) =>
                     {
-                        let team_trying_to_return_flag = {
-                            if game_session_state.red_team.contains_key(&from_player.0.as_u128())
-                            {
-                                Team::Red
-                            }
-                            else if game_session_state.blue_team.contains_key(&from_player.0.as_u128())
-                            {
-                                Team::Blue
-                            }
-                            else
-                            {
-                                // this case means that the player whose message was recieved
-                                // by the server is already disconnected
-                                return;    
-                            }
+                        if game_session_state.match_rules.game_mode != GameMode::CaptureTheFlag
+                        {
+                            return;
+                        }
+
+                        let team_trying_to_return_flag = match game_session_state.get_player_team(from_player.0.as_u128())
+                        {
+                            Some(team) => team,
+                            // this case means that the player whose message was recieved
+                            // by the server is already disconnected
+                            None => return,
                         };
 
                         match team_trying_to_return_flag
@@ -1566,6 +3733,8 @@ fn process_player_message(
                                                 FlagStatus::OnTheBase,
                                                 channel,
                                             );
+
+                                            game_session_state.add_flag_return(from_player.0.as_u128());
                                         }
                                         _ => {}
                                     }
@@ -1586,11 +3755,15 @@ fn process_player_message(
                                                 FlagStatus::OnTheBase,
                                                 channel,
                                             );
+
+                                            game_session_state.add_flag_return(from_player.0.as_u128());
                                         }
                                         _ => {}
                                     }
                                 }
                             }
+
+                            _ => {}
                         }
                     }
 
@@ -1598,21 +3771,17 @@ fn process_player_message(
                         time_of_attempt
                     ) =>
                     {
-                        let team_trying_to_get_score = {
-                            if game_session_state.red_team.contains_key(&from_player.0.as_u128())
-                            {
-                                Team::Red
-                            }
-                            else if game_session_state.blue_team.contains_key(&from_player.0.as_u128())
-                            {
-                                Team::Blue
-                            }
-                            else
-                            {
-                                // this case means that the player whose message was recieved
-                                // by the server is already disconnected
-                                return;    
-                            }
+                        if game_session_state.match_rules.game_mode != GameMode::CaptureTheFlag
+                        {
+                            return;
+                        }
+
+                        let team_trying_to_get_score = match game_session_state.get_player_team(from_player.0.as_u128())
+                        {
+                            Some(team) => team,
+                            // this case means that the player whose message was recieved
+                            // by the server is already disconnected
+                            None => return,
                         };
 
                         match team_trying_to_get_score
@@ -1634,6 +3803,8 @@ fn process_player_message(
                                                     channel,
                                                 );
 
+                                                game_session_state.add_flag_capture(from_player.0.as_u128());
+
                                                 game_session_state.add_score_for_team_and_send_upadate_for_players(
                                                     Team::Red,
                                                     server_start_time,
@@ -1663,6 +3834,8 @@ fn process_player_message(
                                                     channel,
                                                 );
 
+                                                game_session_state.add_flag_capture(from_player.0.as_u128());
+
                                                 game_session_state.add_score_for_team_and_send_upadate_for_players(
                                                     Team::Blue,
                                                     server_start_time,
@@ -1674,6 +3847,8 @@ fn process_player_message(
                                     }
                                 }
                             }
+
+                            _ => {}
                         }
                     }
 
@@ -1682,10 +3857,20 @@ fn process_player_message(
                         bonus_spot_index,
                     ) =>
                     {
-                        match game_session_state.move_w_bonus.status {
+                        if bonus_spot_index as usize >= MAX_MOVE_W_BONUS_SPOTS
+                        {
+                            println!(
+                                "ERROR: player {} tried to get Move W bonus with wrong index {}",
+                                from_player.0.as_u128(),
+                                bonus_spot_index
+                            );
+                            return;
+                        }
+
+                        match game_session_state.move_w_bonuses[bonus_spot_index as usize].status {
                             BonusSpotStatus::BonusOnTheSpot =>
                             {
-                                if game_session_state.move_w_bonus.get_previouse_status_time <
+                                if game_session_state.move_w_bonuses[bonus_spot_index as usize].get_previouse_status_time <
                                     time_of_attempt
                                 {
                                     game_session_state.set_new_bonus_status_and_send_update_to_players(
@@ -1706,6 +3891,11 @@ fn process_player_message(
                         droped_in_space,
                     ) =>
                     {
+                        if game_session_state.match_rules.game_mode != GameMode::CaptureTheFlag
+                        {
+                            return;
+                        }
+
                         match team
                         {
                             Team::Red =>
@@ -1770,12 +3960,195 @@ fn process_player_message(
                                     _ => {}
                                 }
                             }
+
+                            _ => {}
+                        }
+                    }
+
+                    NetMessageToServer::IKilledPlayer(victim_id) =>
+                    {
+                        let killer_id = from_player.0.as_u128();
+
+                        if killer_id == victim_id
+                        {
+                            return;
+                        }
+
+                        // the kill is counted when the victim reports its death
+                        game_session_state.kill_confirmation.player_claimed_kill(
+                            killer_id,
+                            victim_id,
+                            server_start_time.elapsed().as_millis()
+                        );
+                    }
+
+                    NetMessageToServer::JoinAsPlayer =>
+                    {
+                        if game_session_state.players.contains_key(&from_player.0.as_u128()) ||
+                            game_session_state.spectators.contains_key(&from_player.0.as_u128())
+                        {
+                            return;
+                        }
+
+                        handle_player_connection(
+                            server_start_time,
+                            channel,
+                            game_session_state,
+                            from_player,
+                            None,
+                            None,
+                        );
+                    }
+
+                    NetMessageToServer::JoinAsPartyMember(party_id) =>
+                    {
+                        if game_session_state.players.contains_key(&from_player.0.as_u128()) ||
+                            game_session_state.spectators.contains_key(&from_player.0.as_u128())
+                        {
+                            return;
+                        }
+
+                        println!(
+                            "INFO: player {} joins the game session as a member of party {}",
+                            from_player.0.as_u128(),
+                            party_id
+                        );
+
+                        handle_player_connection(
+                            server_start_time,
+                            channel,
+                            game_session_state,
+                            from_player,
+                            None,
+                            Some(party_id),
+                        );
+                    }
+
+                    NetMessageToServer::RejoinAsPlayer(reconnect_token) =>
+                    {
+                        if game_session_state.players.contains_key(&from_player.0.as_u128()) ||
+                            game_session_state.spectators.contains_key(&from_player.0.as_u128())
+                        {
+                            return;
+                        }
+
+                        let reserved_seat = game_session_state.take_reserved_seat(reconnect_token);
+
+                        match &reserved_seat
+                        {
+                            Some(reserved_seat) =>
+                            {
+                                println!(
+                                    "INFO: player {} rejoined the game session as player {}",
+                                    reserved_seat.stats.player_id,
+                                    from_player.0.as_u128()
+                                );
+                            }
+                            None =>
+                            {
+                                println!(
+                                    "INFO: seat of player {} is not reserved, the player joins as a new player",
+                                    from_player.0.as_u128()
+                                );
+                            }
+                        }
+
+                        handle_player_connection(
+                            server_start_time,
+                            channel,
+                            game_session_state,
+                            from_player,
+                            reserved_seat,
+                            None,
+                        );
+                    }
+
+                    NetMessageToServer::JoinAsSpectator =>
+                    {
+                        if game_session_state.players.contains_key(&from_player.0.as_u128()) ||
+                            game_session_state.spectators.contains_key(&from_player.0.as_u128())
+                        {
+                            return;
+                        }
+
+                        handle_spectator_connection(
+                            server_start_time,
+                            channel,
+                            game_session_state,
+                            from_player,
+                        );
+                    }
+
+                    NetMessageToServer::IDied =>
+                    {
+                        let victim_id = from_player.0.as_u128();
+                        let current_time = server_start_time.elapsed().as_millis();
+
+                        // suicides and deaths without a validated hit are counted too
+                        if let Some(victim_info) = game_session_state.players.get_mut(&victim_id)
+                        {
+                            victim_info.stats.deaths += 1;
+                        }
+
+                        let last_hitter = game_session_state.hit_validator.take_last_validated_hit(
+                            victim_id,
+                            current_time
+                        );
+
+                        let killer_id = game_session_state.kill_confirmation.player_died(
+                            victim_id,
+                            last_hitter,
+                            current_time
+                        );
+
+                        if let Some(killer_id) = killer_id
+                        {
+                            game_session_state.count_kill_and_send_update_for_players(
+                                killer_id,
+                                victim_id,
+                                server_start_time,
+                                channel
+                            );
+                        }
+                    }
+
+                    NetMessageToServer::StartVote(vote_kind) =>
+                    {
+                        let result = game_session_state.start_vote(
+                            from_player.0.as_u128(),
+                            vote_kind,
+                            server_start_time,
+                            channel
+                        );
+
+                        if let Err(e) = result
+                        {
+                            println!("WARNING: can't start the vote {:?}, err: {}", vote_kind, e);
+                        }
+                    }
+
+                    NetMessageToServer::CastVote(yes) =>
+                    {
+                        let result = game_session_state.cast_vote(
+                            from_player.0.as_u128(),
+                            yes,
+                            server_start_time,
+                            channel
+                        );
+
+                        if let Err(e) = result
+                        {
+                            println!("WARNING: can't cast the ballot of player {}, err: {}", from_player.0.as_u128(), e);
                         }
                     }
                 }
             }
         }
     }
+    else
+    {
+        game_session_state.rate_limiter.count_unknown_message(from_player.0.as_u128(), traffic_channel);
+    }
 }
 
 enum PlayerAmountChangedEvent
@@ -1785,10 +4158,12 @@ enum PlayerAmountChangedEvent
 }
 
 
+// the listener accepts admin commands without authentication, so it's available
+// only on the local machine, the matchmaking server always spawns game servers locally
 async fn create_matchmaking_server_listener(config: GameServerConfig) -> Option<(TcpListener, u16)> {
     for port in config.game_servers_min_port_for_tcp_listener..=config.game_servers_max_port_for_tcp_listener
     {
-        match TcpListener::bind(("0.0.0.0", port)).await
+        match TcpListener::bind(("127.0.0.1", port)).await
         {    
             Ok(listener) => return Some((listener, port)),
             Err(_) => continue,
@@ -1801,6 +4176,11 @@ async fn create_matchmaking_server_listener(config: GameServerConfig) -> Option<
 
 async fn listening_to_matchmaking_server(
     players_amount: Arc<Mutex<u32>>,
+    spectators_amount: Arc<Mutex<u32>>,
+    reserved_seats_amount: Arc<Mutex<u32>>,
+    admin_commands: Arc<Mutex<Vec<AdminCommand>>>,
+    current_map_name: Arc<Mutex<String>>,
+    game_mode: GameMode,
     tcp_listener: TcpListener,
 )
 {
@@ -1808,7 +4188,12 @@ async fn listening_to_matchmaking_server(
     {
         let mut buf = Vec::new();
 
-        stream.read_buf(&mut buf).await.unwrap();
+        if let Err(e) = stream.read_buf(&mut buf).await
+        {
+            println!("WARNING: can't read the matchmaking server's message, err: {}", e);
+
+            continue;
+        }
 
         let message = alkahest::deserialize::<GameServerMatchmakingServerProtocol, GameServerMatchmakingServerProtocol>(&buf);
 
@@ -1822,11 +4207,43 @@ async fn listening_to_matchmaking_server(
                         match msg {
                             MatchmakingServerMessageToGameServer::GiveMePlayersAmount =>
                             {
+                                // spectators don't occupy player slots,
+                                // reserved seats of disconnected players do
+                                let players_amount = players_amount
+                                    .lock()
+                                    .unwrap()
+                                    .saturating_sub(*spectators_amount.lock().unwrap())
+                                    + *reserved_seats_amount.lock().unwrap();
+
+                                let map_name = name_to_bytes(&current_map_name.lock().unwrap());
+
+                                let message = GameServerMatchmakingServerProtocol::GameServerMessage(
+                                    GameServerMessage::GameServerStatusIs(
+                                        players_amount,
+                                        map_name,
+                                        name_to_bytes(get_game_mode_name(game_mode))
+                                    )
+                                );
+
+                                if let Err(e) = stream.write_all(&message.to_packet()).await
+                                {
+                                    println!("WARNING: can't send the game server's status, err: {}", e);
+                                }
+                            }
+
+                            MatchmakingServerMessageToGameServer::AdminCommand(admin_command) =>
+                            {
+                                // the command is executed by the game server's main loop
+                                admin_commands.lock().unwrap().push(admin_command);
+
                                 let message = GameServerMatchmakingServerProtocol::GameServerMessage(
-                                    GameServerMessage::PlayersAmoutIs(*players_amount.lock().unwrap())
+                                    GameServerMessage::AdminCommandAccepted
                                 );
 
-                                stream.write_all(&message.to_packet()).await.unwrap();
+                                if let Err(e) = stream.write_all(&message.to_packet()).await
+                                {
+                                    println!("WARNING: can't confirm the admin command, err: {}", e);
+                                }
                             }
                         }
                     }
@@ -1851,17 +4268,42 @@ async fn listening_to_matchmaking_server(
     exit(1);
 }
 
+// the game server's own socket connects to the signaling server
+// with the join code too, because it's checked for every peer
+fn get_signaling_server_url(signaling_server_port: u16, join_code: &Option<String>) -> String
+{
+    match join_code
+    {
+        Some(join_code) => format!("ws://localhost:{}/?join_code={}", signaling_server_port, join_code),
+        None => format!("ws://localhost:{}/", signaling_server_port),
+    }
+}
+
+// every peer is accepted by a public game server
+fn is_join_code_correct(peers_join_code: Option<&String>, join_code: &Option<String>) -> bool
+{
+    match join_code
+    {
+        Some(join_code) => peers_join_code == Some(join_code),
+        None => true,
+    }
+}
+
 async fn  run_signaling_server(
     config: GameServerConfig,
     players_amount: Arc<Mutex<u32>>,
     actual_signaling_server_port: Arc<Mutex<u16>>,
+    dropped_peers: Arc<Mutex<HashSet<PeerId>>>,
 ) {
 
     let players_amount_1 = players_amount.clone();
     let players_amount_2 = players_amount.clone();
     let players_amount_3 = players_amount.clone();
+    let join_code = config.join_code.clone();
 
-    let max_players = config.max_players;
+    // roles of the peers are known only after their join requests,
+    // so the game server checks the player and spectator slots separately
+    let max_peers = config.max_players + config.match_rules.max_spectators;
 
     {
         *actual_signaling_server_port.lock().unwrap() = config.signaling_port;
@@ -1875,7 +4317,11 @@ async fn  run_signaling_server(
         )
 
         .on_connection_request(move |connection| {
-            if *players_amount_1.lock().unwrap() >= max_players {
+            if !is_join_code_correct(connection.query_params.get("join_code"), &join_code) {
+                println!("WARNING: peer without the join code is rejected by the signaling server");
+
+                Ok(false)
+            } else if *players_amount_1.lock().unwrap() >= max_peers {
                 Ok(false)
             } else {
                 Ok(true)
@@ -1893,6 +4339,8 @@ async fn  run_signaling_server(
         .on_client_disconnected(move |id| {
             *players_amount_3.lock().unwrap() -= 1;
         })
+
+        .dropped_clients(dropped_peers.clone())
         
         .build();
     
@@ -1909,6 +4357,7 @@ async fn  run_signaling_server(
             let players_amount_1 = players_amount.clone();
             let players_amount_2 = players_amount.clone();
             let players_amount_3 = players_amount.clone();
+            let join_code = config.join_code.clone();
 
             let server = 
                 SignalingServer::client_server_builder(
@@ -1918,7 +4367,11 @@ async fn  run_signaling_server(
                 )
 
                 .on_connection_request(move |connection| {
-                    if *players_amount_1.lock().unwrap() >= max_players {
+                    if !is_join_code_correct(connection.query_params.get("join_code"), &join_code) {
+                        println!("WARNING: peer without the join code is rejected by the signaling server");
+
+                        Ok(false)
+                    } else if *players_amount_1.lock().unwrap() >= max_peers {
                         Ok(false)
                     } else {
                         Ok(true)
@@ -1937,6 +4390,8 @@ async fn  run_signaling_server(
                     *players_amount_3.lock().unwrap() -= 1;
                 })
 
+                .dropped_clients(dropped_peers.clone())
+
                 .build();
 
             if server.serve().await.is_err()
@@ -1972,6 +4427,10 @@ pub fn read_args(args: &Vec<String>)
                 println!("Usage: ./game_server [OPTIONS]");
                 println!();
                 println!("  -v --v -version, --version,  Show current game server version");
+                println!();
+                print!("{}", SERVER_CONFIG_USAGE);
+                println!();
+                print!("{}", MATCH_RULES_USAGE);
 
                 std::process::exit(0);
             }
@@ -0,0 +1,105 @@
+// Slice 4D Shooter - the first multiplayer shooter set in 4D space
+// Copyright (C) 2023-2025  Timofei Molokov
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::path::PathBuf;
+
+use client_server_protocol::MapInfo;
+
+// the same directories the game client searches for map files
+const DEFAULT_MAPS_DIRS: [&str; 3] = [
+    "./",
+    "./assets/maps",
+    "./src/assets/maps",
+];
+
+// Maps of the match rules' map rotation are played one after another,
+// every new game session (except a restart) goes to the next map.
+pub struct MapRotation
+{
+    maps: Vec<MapInfo>,
+    current_map_index: usize,
+}
+
+impl MapRotation
+{
+    // reads every map file to get its content hash,
+    // the game server doesn't start if some map file is missing
+    pub fn load(map_names: &[String], maps_dir: &Option<String>) -> Result<Self, String>
+    {
+        let mut maps = Vec::with_capacity(map_names.len());
+
+        for map_name in map_names
+        {
+            let content = read_map_file(map_name, maps_dir)?;
+
+            let map_info = MapInfo::new(map_name, &content)?;
+
+            println!("INFO: map {} is loaded, content hash: {:x}", map_name, map_info.content_hash);
+
+            maps.push(map_info);
+        }
+
+        Ok(MapRotation {
+            maps,
+            current_map_index: 0usize,
+        })
+    }
+
+    pub fn get_current_map(&self) -> MapInfo
+    {
+        self.maps[self.current_map_index]
+    }
+
+    pub fn switch_to_next_map(&mut self)
+    {
+        self.current_map_index = (self.current_map_index + 1) % self.maps.len();
+    }
+}
+
+fn read_map_file(map_name: &str, maps_dir: &Option<String>) -> Result<Vec<u8>, String>
+{
+    let file_name = format!("{}.json", map_name);
+
+    let paths: Vec<PathBuf> = match maps_dir
+    {
+        Some(maps_dir) => vec![PathBuf::from(maps_dir).join(&file_name)],
+        None =>
+        {
+            DEFAULT_MAPS_DIRS
+                .iter()
+                .map(|dir| PathBuf::from(dir).join(&file_name))
+                .collect()
+        }
+    };
+
+    for path in &paths
+    {
+        if let Ok(content) = std::fs::read(path)
+        {
+            return Ok(content);
+        }
+    }
+
+    Err(format!(
+        "Can't find map file {}, searched in: {}",
+        file_name,
+        paths
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect::<Vec<String>>()
+            .join(", ")
+    ))
+}
@@ -0,0 +1,669 @@
+// Slice 4D Shooter - the first multiplayer shooter set in 4D space
+// Copyright (C) 2023-2025  Timofei Molokov
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use serde_json::{
+    Map,
+    Value
+};
+
+use client_server_protocol::{GameMode, MAX_TEAMS};
+
+pub const DEFAULT_MOVE_W_BONUS_RESPAWN_TIME: u128 = 30_000;
+pub const DEFAULT_FLAG_RESPAWN_TIME: u128 = 10_200;
+pub const DEFAULT_MAX_SCORE: u32 = 4;
+pub const DEFAULT_TIME_IN_SESSION_AFTER_WIN: u128 = 12_000;
+pub const DEFAULT_IDLE_SHUTDOWN_TIME: u64 = 180;
+pub const DEFAULT_SERVER_TICK_TIME: u64 = 16;
+pub const DEFAULT_ROUND_DURATION: u128 = 0;
+pub const DEFAULT_OVERTIME_DURATION: u128 = 120_000;
+pub const DEFAULT_GAME_MODE: GameMode = GameMode::CaptureTheFlag;
+pub const DEFAULT_KILL_LIMIT: u32 = 30;
+pub const DEFAULT_TEAMS_AMOUNT: u8 = 2;
+pub const DEFAULT_MAX_SPECTATORS: u32 = 4;
+pub const DEFAULT_RECONNECT_GRACE_PERIOD: u128 = 30_000;
+pub const DEFAULT_TEAM_BALANCING: TeamBalancing = TeamBalancing::Random;
+pub const DEFAULT_VOTE_DURATION: u128 = 30_000;
+pub const DEFAULT_VOTE_PASS_PERCENT: u32 = 51;
+pub const DEFAULT_VOTE_COOLDOWN: u128 = 60_000;
+pub const DEFAULT_MAP: &str = "map";
+
+// network budgets were match rules before, now they are game server options
+const MOVED_TO_SERVER_CONFIG_FIELDS: [&str; 6] = [
+    "reliable_messages_per_second",
+    "reliable_bytes_per_second",
+    "unreliable_messages_per_second",
+    "unreliable_bytes_per_second",
+    "max_packet_size",
+    "max_traffic_violations",
+];
+
+// how players are split into teams at the start of a game session
+// and who is moved to another team when the teams become unequal during the match
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TeamBalancing
+{
+    // players are shuffled randomly, the most recently joined player is moved
+    Random,
+    // players are split by their recent performance so teams have similar total skill,
+    // the player with the lowest impact in the current game session is moved
+    Skill,
+}
+
+pub const MATCH_RULES_USAGE: &str =
+"Match rules options (placed after positional arguments):
+    --match-rules <path>                      JSON file with match rules
+    --game-mode <ctf|tdm|ffa>                 capture the flag, team deathmatch or free-for-all
+    --kill-limit <u32>                        kills to win the game session in team deathmatch and free-for-all
+    --teams-amount <u8>                       amount of teams in team deathmatch, the map must have spawns for every team
+    --max-score <u32>                         score to win the game session
+    --flag-respawn-time <millis>              time until a dropped flag returns to its base
+    --move-w-bonus-respawn-time <millis>      time until a collected Move W bonus respawns
+    --time-in-session-after-win <millis>      time before a new game session after a team wins
+    --idle-shutdown-time <secs>               shut down the game server after this time without players
+    --server-tick-time <millis>               game server's main loop sleep time
+    --round-duration <millis>                 game session time limit, 0 (default) means no time limit
+    --overtime-duration <millis>              overtime after a tied time limit, 0 means draw immediately
+    --max-spectators <u32>                    spectator slots for observer clients, not counted in max players
+    --reconnect-grace-period <millis>         time a disconnected player's seat is reserved, 0 disables reconnects
+    --team-balancing <random|skill>           split players into teams randomly (default) or by recent performance
+    --vote-duration <millis>                  time to cast ballots in a player vote, 0 disables voting
+    --vote-pass-percent <1-100>               percent of players who must vote yes to pass a vote
+    --vote-cooldown <millis>                  time before a player can start another vote
+    --map-rotation <map,map,...>              maps played one after another, names of the map files without extension
+Options passed in the command line override values from the match rules file.\n";
+
+#[derive(Clone)]
+pub struct MatchRules
+{
+    pub game_mode: GameMode,
+    pub max_score: u32,
+    pub kill_limit: u32,
+    pub teams_amount: u8,
+    pub flag_respawn_time: u128,
+    pub move_w_bonus_respawn_time: u128,
+    pub time_in_session_after_win: u128,
+    pub idle_shutdown_time: u64,
+    pub server_tick_time: u64,
+    pub round_duration: u128,
+    pub overtime_duration: u128,
+    pub max_spectators: u32,
+    pub reconnect_grace_period: u128,
+    pub team_balancing: TeamBalancing,
+    pub vote_duration: u128,
+    pub vote_pass_percent: u32,
+    pub vote_cooldown: u128,
+    pub map_rotation: Vec<String>,
+}
+
+impl Default for MatchRules
+{
+    fn default() -> Self
+    {
+        MatchRules {
+            game_mode: DEFAULT_GAME_MODE,
+            max_score: DEFAULT_MAX_SCORE,
+            kill_limit: DEFAULT_KILL_LIMIT,
+            teams_amount: DEFAULT_TEAMS_AMOUNT,
+            flag_respawn_time: DEFAULT_FLAG_RESPAWN_TIME,
+            move_w_bonus_respawn_time: DEFAULT_MOVE_W_BONUS_RESPAWN_TIME,
+            time_in_session_after_win: DEFAULT_TIME_IN_SESSION_AFTER_WIN,
+            idle_shutdown_time: DEFAULT_IDLE_SHUTDOWN_TIME,
+            server_tick_time: DEFAULT_SERVER_TICK_TIME,
+            round_duration: DEFAULT_ROUND_DURATION,
+            overtime_duration: DEFAULT_OVERTIME_DURATION,
+            max_spectators: DEFAULT_MAX_SPECTATORS,
+            reconnect_grace_period: DEFAULT_RECONNECT_GRACE_PERIOD,
+            team_balancing: DEFAULT_TEAM_BALANCING,
+            vote_duration: DEFAULT_VOTE_DURATION,
+            vote_pass_percent: DEFAULT_VOTE_PASS_PERCENT,
+            vote_cooldown: DEFAULT_VOTE_COOLDOWN,
+            map_rotation: vec![DEFAULT_MAP.to_string()],
+        }
+    }
+}
+
+impl MatchRules
+{
+    // args is the list of named options, for example ["--max-score", "5"]
+    pub fn from_args(args: &[String]) -> Result<Self, String>
+    {
+        let mut rules = MatchRules::default();
+
+        // the match rules file is applied first, so the other options can override it
+        let mut i = 0usize;
+        while i < args.len()
+        {
+            if args[i] == "--match-rules"
+            {
+                let path = get_option_value(args, i)?;
+
+                rules = MatchRules::load_from_file(path)?;
+            }
+            i += 1;
+        }
+
+        let mut i = 0usize;
+        while i < args.len()
+        {
+            match args[i].as_str()
+            {
+                "--match-rules" => {}
+
+                "--game-mode" =>
+                {
+                    let value = get_option_value(args, i)?;
+
+                    rules.game_mode = parse_game_mode(value)?;
+                }
+
+                "--max-score" =>
+                {
+                    rules.max_score = parse_option_value(args, i)?;
+                }
+
+                "--kill-limit" =>
+                {
+                    rules.kill_limit = parse_option_value(args, i)?;
+                }
+
+                "--teams-amount" =>
+                {
+                    rules.teams_amount = parse_option_value(args, i)?;
+                }
+
+                "--flag-respawn-time" =>
+                {
+                    rules.flag_respawn_time = parse_option_value(args, i)?;
+                }
+
+                "--move-w-bonus-respawn-time" =>
+                {
+                    rules.move_w_bonus_respawn_time = parse_option_value(args, i)?;
+                }
+
+                "--time-in-session-after-win" =>
+                {
+                    rules.time_in_session_after_win = parse_option_value(args, i)?;
+                }
+
+                "--idle-shutdown-time" =>
+                {
+                    rules.idle_shutdown_time = parse_option_value(args, i)?;
+                }
+
+                "--server-tick-time" =>
+                {
+                    rules.server_tick_time = parse_option_value(args, i)?;
+                }
+
+                "--round-duration" =>
+                {
+                    rules.round_duration = parse_option_value(args, i)?;
+                }
+
+                "--overtime-duration" =>
+                {
+                    rules.overtime_duration = parse_option_value(args, i)?;
+                }
+
+                "--max-spectators" =>
+                {
+                    rules.max_spectators = parse_option_value(args, i)?;
+                }
+
+                "--reconnect-grace-period" =>
+                {
+                    rules.reconnect_grace_period = parse_option_value(args, i)?;
+                }
+
+                "--team-balancing" =>
+                {
+                    let value = get_option_value(args, i)?;
+
+                    rules.team_balancing = parse_team_balancing(value)?;
+                }
+
+                "--vote-duration" =>
+                {
+                    rules.vote_duration = parse_option_value(args, i)?;
+                }
+
+                "--vote-pass-percent" =>
+                {
+                    rules.vote_pass_percent = parse_option_value(args, i)?;
+                }
+
+                "--vote-cooldown" =>
+                {
+                    rules.vote_cooldown = parse_option_value(args, i)?;
+                }
+
+                "--map-rotation" =>
+                {
+                    let value = get_option_value(args, i)?;
+
+                    rules.map_rotation = value
+                        .split(",")
+                        .filter(|name| !name.is_empty())
+                        .map(|name| name.to_string())
+                        .collect();
+                }
+
+                _ =>
+                {
+                    if args[i].starts_with("--")
+                    {
+                        return Err(format!("Unknown option {}", args[i]));
+                    }
+
+                    i += 1;
+                    continue;
+                }
+            }
+
+            // skip the option's value
+            i += 2;
+        }
+
+        rules.validate()?;
+
+        Ok(rules)
+    }
+
+    pub fn load_from_file(path: &str) -> Result<Self, String>
+    {
+        let file_content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Can't read match rules file {}, err: {}", path, e))?;
+
+        let json_rules: Value = serde_json::from_str(&file_content)
+            .map_err(|e| format!("Can't parse match rules file {}, err: {}", path, e))?;
+
+        let object = json_rules
+            .as_object()
+            .ok_or(format!("Wrong JSON format of match rules file {}", path))?;
+
+        MatchRules::parse_json(object)
+    }
+
+    // fields which are not specified in the JSON object get default values
+    pub fn parse_json(object: &Map<String, Value>) -> Result<Self, String>
+    {
+        let default_rules = MatchRules::default();
+
+        for field in MOVED_TO_SERVER_CONFIG_FIELDS
+        {
+            if object.contains_key(field)
+            {
+                println!(
+                    "WARNING: {} is ignored in match rules, it's a game server option now",
+                    field
+                );
+            }
+        }
+
+        let game_mode = match object.get("game_mode")
+        {
+            Some(value) =>
+            {
+                let value = value
+                    .as_str()
+                    .ok_or("game_mode is not string value in match rules".to_string())?;
+
+                parse_game_mode(value)?
+            }
+            None => default_rules.game_mode,
+        };
+
+        let team_balancing = match object.get("team_balancing")
+        {
+            Some(value) =>
+            {
+                let value = value
+                    .as_str()
+                    .ok_or("team_balancing is not string value in match rules".to_string())?;
+
+                parse_team_balancing(value)?
+            }
+            None => default_rules.team_balancing,
+        };
+
+        let map_rotation = match object.get("map_rotation")
+        {
+            Some(value) =>
+            {
+                let array = value
+                    .as_array()
+                    .ok_or("map_rotation is not array value in match rules".to_string())?;
+
+                let mut map_rotation = Vec::with_capacity(array.len());
+
+                for map_name in array
+                {
+                    map_rotation.push(
+                        map_name
+                            .as_str()
+                            .ok_or("map_rotation members are not string values in match rules".to_string())?
+                            .to_string()
+                    );
+                }
+
+                map_rotation
+            }
+            None => default_rules.map_rotation.clone(),
+        };
+
+        let rules = MatchRules {
+            game_mode,
+
+            max_score: get_json_u64(object, "max_score")?
+                .map_or(default_rules.max_score, |v| v as u32),
+
+            kill_limit: get_json_u64(object, "kill_limit")?
+                .map_or(default_rules.kill_limit, |v| v as u32),
+
+            teams_amount: get_json_u64(object, "teams_amount")?
+                .map_or(default_rules.teams_amount, |v| v.min(u8::MAX as u64) as u8),
+
+            flag_respawn_time: get_json_u64(object, "flag_respawn_time")?
+                .map_or(default_rules.flag_respawn_time, |v| v as u128),
+
+            move_w_bonus_respawn_time: get_json_u64(object, "move_w_bonus_respawn_time")?
+                .map_or(default_rules.move_w_bonus_respawn_time, |v| v as u128),
+
+            time_in_session_after_win: get_json_u64(object, "time_in_session_after_win")?
+                .map_or(default_rules.time_in_session_after_win, |v| v as u128),
+
+            idle_shutdown_time: get_json_u64(object, "idle_shutdown_time")?
+                .unwrap_or(default_rules.idle_shutdown_time),
+
+            server_tick_time: get_json_u64(object, "server_tick_time")?
+                .unwrap_or(default_rules.server_tick_time),
+
+            round_duration: get_json_u64(object, "round_duration")?
+                .map_or(default_rules.round_duration, |v| v as u128),
+
+            overtime_duration: get_json_u64(object, "overtime_duration")?
+                .map_or(default_rules.overtime_duration, |v| v as u128),
+
+            max_spectators: get_json_u64(object, "max_spectators")?
+                .map_or(default_rules.max_spectators, |v| v.min(u32::MAX as u64) as u32),
+
+            reconnect_grace_period: get_json_u64(object, "reconnect_grace_period")?
+                .map_or(default_rules.reconnect_grace_period, |v| v as u128),
+
+            team_balancing,
+
+            vote_duration: get_json_u64(object, "vote_duration")?
+                .map_or(default_rules.vote_duration, |v| v as u128),
+
+            vote_pass_percent: get_json_u64(object, "vote_pass_percent")?
+                .map_or(default_rules.vote_pass_percent, |v| v.min(u32::MAX as u64) as u32),
+
+            vote_cooldown: get_json_u64(object, "vote_cooldown")?
+                .map_or(default_rules.vote_cooldown, |v| v as u128),
+
+            map_rotation,
+        };
+
+        rules.validate()?;
+
+        Ok(rules)
+    }
+
+    fn validate(&self) -> Result<(), String>
+    {
+        if self.max_score == 0
+        {
+            return Err("max_score must be greater than 0".to_string());
+        }
+
+        if self.kill_limit == 0
+        {
+            return Err("kill_limit must be greater than 0".to_string());
+        }
+
+        if self.server_tick_time == 0
+        {
+            return Err("server_tick_time must be greater than 0".to_string());
+        }
+
+        if self.vote_pass_percent == 0 || self.vote_pass_percent > 100
+        {
+            return Err("vote_pass_percent must be from 1 to 100".to_string());
+        }
+
+        if self.map_rotation.is_empty()
+        {
+            return Err("map_rotation must contain at least one map".to_string());
+        }
+
+        if self.teams_amount < 2 || self.teams_amount as usize > MAX_TEAMS
+        {
+            return Err(format!("teams_amount must be from 2 to {}", MAX_TEAMS));
+        }
+
+        // flags exist only for Red and Blue teams
+        if self.game_mode == GameMode::CaptureTheFlag && self.teams_amount != 2
+        {
+            return Err("capture the flag mode supports only 2 teams".to_string());
+        }
+
+        Ok(())
+    }
+
+    // score limit of the current game mode
+    pub fn get_score_limit(&self) -> u32
+    {
+        match self.game_mode
+        {
+            GameMode::CaptureTheFlag => self.max_score,
+            GameMode::TeamDeathmatch => self.kill_limit,
+            GameMode::FreeForAll => self.kill_limit,
+        }
+    }
+
+    // amount of teams players are distributed among, 0 in free-for-all
+    pub fn get_teams_amount(&self) -> usize
+    {
+        match self.game_mode
+        {
+            GameMode::CaptureTheFlag => 2,
+            GameMode::TeamDeathmatch => self.teams_amount as usize,
+            GameMode::FreeForAll => 0,
+        }
+    }
+}
+
+impl std::fmt::Debug for MatchRules
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MatchRules")
+            .field("game_mode", &get_game_mode_name(self.game_mode))
+            .field("max_score", &self.max_score)
+            .field("kill_limit", &self.kill_limit)
+            .field("teams_amount", &self.teams_amount)
+            .field("flag_respawn_time", &self.flag_respawn_time)
+            .field("move_w_bonus_respawn_time", &self.move_w_bonus_respawn_time)
+            .field("time_in_session_after_win", &self.time_in_session_after_win)
+            .field("idle_shutdown_time", &self.idle_shutdown_time)
+            .field("server_tick_time", &self.server_tick_time)
+            .field("round_duration", &self.round_duration)
+            .field("overtime_duration", &self.overtime_duration)
+            .field("max_spectators", &self.max_spectators)
+            .field("reconnect_grace_period", &self.reconnect_grace_period)
+            .field("team_balancing", &get_team_balancing_name(self.team_balancing))
+            .field("vote_duration", &self.vote_duration)
+            .field("vote_pass_percent", &self.vote_pass_percent)
+            .field("vote_cooldown", &self.vote_cooldown)
+            .field("map_rotation", &self.map_rotation)
+            .finish()
+    }
+}
+
+fn parse_game_mode(value: &str) -> Result<GameMode, String>
+{
+    match value
+    {
+        "ctf" | "capture_the_flag" => Ok(GameMode::CaptureTheFlag),
+        "tdm" | "team_deathmatch" => Ok(GameMode::TeamDeathmatch),
+        "ffa" | "free_for_all" => Ok(GameMode::FreeForAll),
+        _ => Err(format!("Unknown game mode {}, expected ctf, tdm or ffa", value)),
+    }
+}
+
+pub fn get_game_mode_name(game_mode: GameMode) -> &'static str
+{
+    match game_mode
+    {
+        GameMode::CaptureTheFlag => "capture_the_flag",
+        GameMode::TeamDeathmatch => "team_deathmatch",
+        GameMode::FreeForAll => "free_for_all",
+    }
+}
+
+fn parse_team_balancing(value: &str) -> Result<TeamBalancing, String>
+{
+    match value
+    {
+        "random" => Ok(TeamBalancing::Random),
+        "skill" => Ok(TeamBalancing::Skill),
+        _ => Err(format!("Unknown team balancing {}, expected random or skill", value)),
+    }
+}
+
+fn get_team_balancing_name(team_balancing: TeamBalancing) -> &'static str
+{
+    match team_balancing
+    {
+        TeamBalancing::Random => "random",
+        TeamBalancing::Skill => "skill",
+    }
+}
+
+fn get_option_value<'a>(args: &'a [String], option_index: usize) -> Result<&'a str, String>
+{
+    args
+        .get(option_index + 1)
+        .map(|value| value.as_str())
+        .ok_or(format!("Have not value for {} option", args[option_index]))
+}
+
+fn parse_option_value<T: std::str::FromStr>(args: &[String], option_index: usize) -> Result<T, String>
+{
+    let value = get_option_value(args, option_index)?;
+
+    value
+        .parse()
+        .map_err(|_| format!("Invalid value {} for {} option", value, args[option_index]))
+}
+
+fn get_json_u64(object: &Map<String, Value>, key: &str) -> Result<Option<u64>, String>
+{
+    match object.get(key)
+    {
+        Some(value) =>
+        {
+            value
+                .as_u64()
+                .map(|v| Some(v))
+                .ok_or(format!("{} is not a positive integer value in match rules", key))
+        }
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    // every test writes its own file, tests are run in parallel
+    fn write_match_rules_file(test_name: &str, content: &str) -> String
+    {
+        let path = std::env::temp_dir().join(format!(
+            "slice_match_rules_{}_{}.json",
+            std::process::id(),
+            test_name
+        ));
+
+        std::fs::write(&path, content).unwrap();
+
+        path.to_string_lossy().to_string()
+    }
+
+    fn to_args(args: &[&str]) -> Vec<String>
+    {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn defaults_are_used_without_file_and_options()
+    {
+        let rules = MatchRules::from_args(&[]).unwrap();
+
+        assert_eq!(rules.max_score, DEFAULT_MAX_SCORE);
+        assert_eq!(rules.kill_limit, DEFAULT_KILL_LIMIT);
+        assert_eq!(rules.map_rotation, vec![DEFAULT_MAP.to_string()]);
+    }
+
+    #[test]
+    fn file_overrides_defaults()
+    {
+        let path = write_match_rules_file(
+            "file_overrides_defaults",
+            r#"{ "max_score": 7, "map_rotation": ["map_2d_3d"] }"#
+        );
+
+        let rules = MatchRules::from_args(&to_args(&["--match-rules", &path])).unwrap();
+
+        assert_eq!(rules.max_score, 7);
+        assert_eq!(rules.map_rotation, vec!["map_2d_3d".to_string()]);
+        assert_eq!(rules.kill_limit, DEFAULT_KILL_LIMIT);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn options_override_the_file_in_any_order()
+    {
+        let path = write_match_rules_file(
+            "options_override_the_file_in_any_order",
+            r#"{ "max_score": 7, "kill_limit": 20 }"#
+        );
+
+        let rules = MatchRules::from_args(
+            &to_args(&["--max-score", "9", "--match-rules", &path])
+        ).unwrap();
+
+        assert_eq!(rules.max_score, 9);
+        assert_eq!(rules.kill_limit, 20);
+
+        let rules = MatchRules::from_args(
+            &to_args(&["--match-rules", &path, "--kill-limit", "25"])
+        ).unwrap();
+
+        assert_eq!(rules.max_score, 7);
+        assert_eq!(rules.kill_limit, 25);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn unknown_option_is_an_error()
+    {
+        assert!(MatchRules::from_args(&to_args(&["--max-scor", "9"])).is_err());
+    }
+}
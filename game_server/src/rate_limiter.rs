@@ -0,0 +1,450 @@
+// Slice 4D Shooter - the first multiplayer shooter set in 4D space
+// Copyright (C) 2023-2025  Timofei Molokov
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+
+use crate::server_config::NetworkBudgets;
+
+// Every peer has a budget of messages and bytes per second for each channel.
+// Packets over the budget or bigger than the max packet size are dropped
+// before deserialization. A peer which exceeds its budget in several
+// windows in a row is kicked from the game server.
+
+const BUDGET_WINDOW: u128 = 1_000;
+
+pub const TRAFFIC_STATS_LOG_PERIOD: u128 = 60_000;
+
+#[derive(Clone, Copy)]
+pub enum TrafficChannel
+{
+    Reliable,
+    Unreliable,
+}
+
+impl TrafficChannel
+{
+    fn get_name(&self) -> &'static str
+    {
+        match self
+        {
+            TrafficChannel::Reliable => "reliable",
+            TrafficChannel::Unreliable => "unreliable",
+        }
+    }
+}
+
+pub enum PacketVerdict
+{
+    Accept,
+    Drop,
+    Kick,
+}
+
+#[derive(Clone, Copy)]
+struct ChannelBudget
+{
+    messages_per_second: u32,
+    bytes_per_second: u32,
+}
+
+#[derive(Default)]
+struct ChannelTraffic
+{
+    window_start_time: u128,
+    window_messages: u32,
+    window_bytes: u32,
+    window_is_exceeded: bool,
+    total_messages: u64,
+    total_bytes: u64,
+    dropped_messages: u64,
+    // accepted messages which can't be deserialized
+    unknown_messages: u64,
+}
+
+#[derive(Default)]
+struct PeerTraffic
+{
+    reliable: ChannelTraffic,
+    unreliable: ChannelTraffic,
+    // windows in a row in which the peer exceeded its budget
+    violations_in_a_row: u32,
+    is_kicked: bool,
+}
+
+impl PeerTraffic
+{
+    fn get_channel_mut(&mut self, channel: TrafficChannel) -> &mut ChannelTraffic
+    {
+        match channel
+        {
+            TrafficChannel::Reliable => &mut self.reliable,
+            TrafficChannel::Unreliable => &mut self.unreliable,
+        }
+    }
+}
+
+pub struct RateLimiter
+{
+    reliable_budget: ChannelBudget,
+    unreliable_budget: ChannelBudget,
+    max_packet_size: usize,
+    max_violations_in_a_row: u32,
+    peers: HashMap<u128, PeerTraffic>,
+    last_stats_log_time: u128,
+}
+
+impl RateLimiter
+{
+    pub fn new(network_budgets: &NetworkBudgets, max_peers: u32) -> Self
+    {
+        RateLimiter {
+            reliable_budget: ChannelBudget {
+                messages_per_second: network_budgets.reliable_messages_per_second,
+                bytes_per_second: network_budgets.reliable_bytes_per_second,
+            },
+            unreliable_budget: ChannelBudget {
+                messages_per_second: network_budgets.unreliable_messages_per_second,
+                bytes_per_second: network_budgets.unreliable_bytes_per_second,
+            },
+            max_packet_size: network_budgets.max_packet_size as usize,
+            max_violations_in_a_row: network_budgets.max_traffic_violations,
+            peers: HashMap::with_capacity(max_peers as usize),
+            last_stats_log_time: 0u128,
+        }
+    }
+
+    pub fn remove_peer(&mut self, id: u128)
+    {
+        self.peers.remove(&id);
+    }
+
+    // all further packets from the peer are dropped
+    pub fn block_peer(&mut self, id: u128)
+    {
+        self.peers.entry(id).or_default().is_kicked = true;
+    }
+
+    pub fn check_packet(
+        &mut self,
+        id: u128,
+        channel: TrafficChannel,
+        packet_size: usize,
+        current_time: u128,
+    ) -> PacketVerdict
+    {
+        let budget = match channel
+        {
+            TrafficChannel::Reliable => self.reliable_budget,
+            TrafficChannel::Unreliable => self.unreliable_budget,
+        };
+
+        let peer = self.peers.entry(id).or_default();
+
+        if peer.is_kicked
+        {
+            return PacketVerdict::Drop;
+        }
+
+        let window_start_time = peer.get_channel_mut(channel).window_start_time;
+
+        if current_time - window_start_time >= BUDGET_WINDOW
+        {
+            let window_was_exceeded = peer.get_channel_mut(channel).window_is_exceeded;
+
+            if !window_was_exceeded && !peer_other_channel_is_exceeded(peer, channel)
+            {
+                peer.violations_in_a_row = 0;
+            }
+
+            let traffic = peer.get_channel_mut(channel);
+
+            traffic.window_start_time = current_time;
+            traffic.window_messages = 0;
+            traffic.window_bytes = 0;
+            traffic.window_is_exceeded = false;
+        }
+
+        let traffic = peer.get_channel_mut(channel);
+
+        traffic.window_messages += 1;
+        traffic.window_bytes = traffic.window_bytes.saturating_add(packet_size as u32);
+        traffic.total_messages += 1;
+        traffic.total_bytes += packet_size as u64;
+
+        let packet_is_too_big = packet_size > self.max_packet_size;
+
+        let budget_is_exceeded =
+            traffic.window_messages > budget.messages_per_second ||
+            traffic.window_bytes > budget.bytes_per_second;
+
+        if !packet_is_too_big && !budget_is_exceeded
+        {
+            return PacketVerdict::Accept;
+        }
+
+        traffic.dropped_messages += 1;
+
+        // the violation is counted once per window,
+        // but every too big packet is a violation
+        if traffic.window_is_exceeded && !packet_is_too_big
+        {
+            return PacketVerdict::Drop;
+        }
+
+        traffic.window_is_exceeded = true;
+
+        let (window_messages, window_bytes) = (traffic.window_messages, traffic.window_bytes);
+
+        peer.violations_in_a_row += 1;
+
+        if packet_is_too_big
+        {
+            println!(
+                "WARNING: peer {} sent {} bytes packet by {} channel, max packet size is {} bytes (violation {} of {})",
+                id,
+                packet_size,
+                channel.get_name(),
+                self.max_packet_size,
+                peer.violations_in_a_row,
+                self.max_violations_in_a_row,
+            );
+        }
+        else
+        {
+            println!(
+                "WARNING: peer {} exceeded {} channel budget: {} of {} messages, {} of {} bytes per second (violation {} of {})",
+                id,
+                channel.get_name(),
+                window_messages,
+                budget.messages_per_second,
+                window_bytes,
+                budget.bytes_per_second,
+                peer.violations_in_a_row,
+                self.max_violations_in_a_row,
+            );
+        }
+
+        if peer.violations_in_a_row >= self.max_violations_in_a_row
+        {
+            peer.is_kicked = true;
+
+            return PacketVerdict::Kick;
+        }
+
+        PacketVerdict::Drop
+    }
+
+    // a peer with an incompatible protocol version sends only unknown messages,
+    // the warning is printed once per peer and channel, the rest are counted
+    pub fn count_unknown_message(&mut self, id: u128, channel: TrafficChannel)
+    {
+        let traffic = self.peers.entry(id).or_default().get_channel_mut(channel);
+
+        traffic.unknown_messages += 1;
+
+        if traffic.unknown_messages == 1
+        {
+            println!(
+                "WARNING: peer {} sent a message by {} channel which can't be deserialized, the peer may use another protocol version",
+                id,
+                channel.get_name(),
+            );
+        }
+    }
+
+    pub fn log_traffic_stats(&mut self, current_time: u128)
+    {
+        if current_time - self.last_stats_log_time < TRAFFIC_STATS_LOG_PERIOD
+        {
+            return;
+        }
+
+        self.last_stats_log_time = current_time;
+
+        for (id, peer) in &self.peers
+        {
+            println!(
+                "INFO: peer {} traffic: reliable {} messages ({} bytes, {} dropped, {} unknown), unreliable {} messages ({} bytes, {} dropped, {} unknown)",
+                id,
+                peer.reliable.total_messages,
+                peer.reliable.total_bytes,
+                peer.reliable.dropped_messages,
+                peer.reliable.unknown_messages,
+                peer.unreliable.total_messages,
+                peer.unreliable.total_bytes,
+                peer.unreliable.dropped_messages,
+                peer.unreliable.unknown_messages,
+            );
+        }
+    }
+}
+
+fn peer_other_channel_is_exceeded(peer: &PeerTraffic, channel: TrafficChannel) -> bool
+{
+    match channel
+    {
+        TrafficChannel::Reliable => peer.unreliable.window_is_exceeded,
+        TrafficChannel::Unreliable => peer.reliable.window_is_exceeded,
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    const PEER: u128 = 1;
+
+    fn get_rate_limiter(max_traffic_violations: u32) -> RateLimiter
+    {
+        let network_budgets = NetworkBudgets {
+            reliable_messages_per_second: 3,
+            reliable_bytes_per_second: 100,
+            unreliable_messages_per_second: 3,
+            unreliable_bytes_per_second: 100,
+            max_packet_size: 50,
+            max_traffic_violations,
+        };
+
+        RateLimiter::new(&network_budgets, 2)
+    }
+
+    fn send(rate_limiter: &mut RateLimiter, packet_size: usize, time: u128) -> PacketVerdict
+    {
+        rate_limiter.check_packet(PEER, TrafficChannel::Reliable, packet_size, time)
+    }
+
+    #[test]
+    fn messages_over_the_budget_are_dropped_until_the_window_rolls_over()
+    {
+        let mut rate_limiter = get_rate_limiter(5);
+
+        for _ in 0..3
+        {
+            assert!(matches!(send(&mut rate_limiter, 10, 0), PacketVerdict::Accept));
+        }
+
+        assert!(matches!(send(&mut rate_limiter, 10, 500), PacketVerdict::Drop));
+        assert!(matches!(send(&mut rate_limiter, 10, BUDGET_WINDOW - 1), PacketVerdict::Drop));
+
+        assert!(matches!(send(&mut rate_limiter, 10, BUDGET_WINDOW), PacketVerdict::Accept));
+    }
+
+    #[test]
+    fn bytes_over_the_budget_are_dropped_until_the_window_rolls_over()
+    {
+        let mut rate_limiter = get_rate_limiter(5);
+
+        assert!(matches!(send(&mut rate_limiter, 50, 0), PacketVerdict::Accept));
+        assert!(matches!(send(&mut rate_limiter, 50, 0), PacketVerdict::Accept));
+        assert!(matches!(send(&mut rate_limiter, 1, 0), PacketVerdict::Drop));
+
+        assert!(matches!(send(&mut rate_limiter, 50, BUDGET_WINDOW), PacketVerdict::Accept));
+    }
+
+    #[test]
+    fn channels_have_separate_budgets()
+    {
+        let mut rate_limiter = get_rate_limiter(5);
+
+        for _ in 0..3
+        {
+            send(&mut rate_limiter, 10, 0);
+        }
+
+        assert!(matches!(send(&mut rate_limiter, 10, 0), PacketVerdict::Drop));
+        assert!(matches!(
+            rate_limiter.check_packet(PEER, TrafficChannel::Unreliable, 10, 0),
+            PacketVerdict::Accept
+        ));
+    }
+
+    #[test]
+    fn peer_is_kicked_after_exceeding_the_budget_in_windows_in_a_row()
+    {
+        let mut rate_limiter = get_rate_limiter(2);
+
+        for window in 0..2
+        {
+            let time = window * BUDGET_WINDOW;
+
+            for _ in 0..3
+            {
+                assert!(matches!(send(&mut rate_limiter, 10, time), PacketVerdict::Accept));
+            }
+
+            // the violation is counted once per window
+            let verdict = send(&mut rate_limiter, 10, time);
+            assert!(matches!(send(&mut rate_limiter, 10, time), PacketVerdict::Drop));
+
+            if window == 0
+            {
+                assert!(matches!(verdict, PacketVerdict::Drop));
+            }
+            else
+            {
+                assert!(matches!(verdict, PacketVerdict::Kick));
+            }
+        }
+
+        assert!(matches!(send(&mut rate_limiter, 10, 2 * BUDGET_WINDOW), PacketVerdict::Drop));
+    }
+
+    #[test]
+    fn window_within_the_budget_resets_violations()
+    {
+        let mut rate_limiter = get_rate_limiter(2);
+
+        for _ in 0..4
+        {
+            send(&mut rate_limiter, 10, 0);
+        }
+
+        // the next window is within the budget, the violations are reset
+        // when it rolls over
+        assert!(matches!(send(&mut rate_limiter, 10, BUDGET_WINDOW), PacketVerdict::Accept));
+
+        for _ in 0..3
+        {
+            assert!(matches!(send(&mut rate_limiter, 10, 2 * BUDGET_WINDOW), PacketVerdict::Accept));
+        }
+
+        assert!(matches!(send(&mut rate_limiter, 10, 2 * BUDGET_WINDOW), PacketVerdict::Drop));
+    }
+
+    #[test]
+    fn every_too_big_packet_is_a_violation()
+    {
+        let mut rate_limiter = get_rate_limiter(2);
+
+        assert!(matches!(send(&mut rate_limiter, 51, 0), PacketVerdict::Drop));
+        assert!(matches!(send(&mut rate_limiter, 51, 0), PacketVerdict::Kick));
+    }
+
+    #[test]
+    fn blocked_peer_packets_are_dropped()
+    {
+        let mut rate_limiter = get_rate_limiter(2);
+
+        rate_limiter.block_peer(PEER);
+
+        assert!(matches!(send(&mut rate_limiter, 10, 0), PacketVerdict::Drop));
+
+        rate_limiter.remove_peer(PEER);
+
+        assert!(matches!(send(&mut rate_limiter, 10, 0), PacketVerdict::Accept));
+    }
+}
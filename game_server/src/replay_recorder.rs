@@ -0,0 +1,389 @@
+// Slice 4D Shooter - the first multiplayer shooter set in 4D space
+// Copyright (C) 2023-2025  Timofei Molokov
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::{
+    fs::File,
+    io::{
+        BufWriter,
+        Write
+    },
+    path::PathBuf,
+    time::{
+        SystemTime,
+        UNIX_EPOCH
+    }
+};
+
+use matchbox_socket::{
+    Packet,
+    PeerId,
+    WebRtcChannel
+};
+
+use crate::rate_limiter::TrafficChannel;
+
+// Replay file format (all numbers are little endian):
+//
+// header:
+//     magic               4 bytes, "S4DR"
+//     format version      u16
+//     game server version u8 length + utf-8 string (alkahest packets depend on it)
+//     game server index   u16
+//     unix time in millis u64, when the recording was started
+//
+// records until the end of the file:
+//     time                u64, millis since the recording was started
+//     record kind         u8, see RecordKind
+//     peer id             u128, the sender of Packet records, the receiver of SentPacket records
+//     channel             u8, 0 - reliable, 1 - unreliable (0 for peer events)
+//     payload length      u32
+//     payload             alkahest-serialized ClientMessage or HandshakeMessage packet
+//                         for Packet records, ServerMessage or HandshakeMessage packet
+//                         for SentPacket records, empty otherwise
+
+pub const REPLAY_MAGIC: &[u8; 4] = b"S4DR";
+// version 2 added SentPacket records
+pub const REPLAY_FORMAT_VERSION: u16 = 2;
+pub const REPLAY_FILE_EXTENSION: &str = "s4dreplay";
+
+#[repr(u8)]
+#[derive(Clone, Copy)]
+pub enum RecordKind
+{
+    PeerConnected = 0,
+    PeerDisconnected = 1,
+    // packet received from the peer and accepted by the rate limiter
+    Packet = 2,
+    // packet sent by the game server to the peer
+    SentPacket = 3,
+}
+
+// The game server sends all packets through it, copies of the sent packets
+// are kept while the game session is recorded until ReplayRecorder takes them.
+pub struct RecordedChannel
+{
+    channel: WebRtcChannel,
+    traffic_channel: TrafficChannel,
+    is_recording: bool,
+    sent_packets: Vec<(PeerId, Packet)>,
+}
+
+impl RecordedChannel
+{
+    pub fn new(channel: WebRtcChannel, traffic_channel: TrafficChannel) -> Self
+    {
+        RecordedChannel {
+            channel,
+            traffic_channel,
+            is_recording: false,
+            sent_packets: Vec::new(),
+        }
+    }
+
+    pub fn send(&mut self, packet: Packet, peer: PeerId)
+    {
+        if self.is_recording
+        {
+            self.sent_packets.push((peer, packet.clone()));
+        }
+
+        self.channel.send(packet, peer);
+    }
+
+    pub fn receive(&mut self) -> Vec<(PeerId, Packet)>
+    {
+        self.channel.receive()
+    }
+}
+
+// Writes one replay file per game session if the replay directory is set.
+// Write errors are logged and stop the recording of the current game session,
+// they never stop the game server.
+pub struct ReplayRecorder
+{
+    replay_dir: Option<PathBuf>,
+    game_server_index: u16,
+    writer: Option<BufWriter<File>>,
+    recording_start_time: u128,
+}
+
+impl ReplayRecorder
+{
+    pub fn new(replay_dir: Option<String>, game_server_index: u16) -> Self
+    {
+        ReplayRecorder {
+            replay_dir: replay_dir.map(PathBuf::from),
+            game_server_index,
+            writer: None,
+            recording_start_time: 0u128,
+        }
+    }
+
+    // finishes the previous recording and creates a new replay file,
+    // current_time is the time since the game server start
+    pub fn start_recording(&mut self, current_time: u128)
+    {
+        self.finish_recording();
+
+        let replay_dir = match &self.replay_dir
+        {
+            Some(replay_dir) => replay_dir,
+            None => return,
+        };
+
+        let unix_time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0u128, |duration| duration.as_millis());
+
+        let path = replay_dir.join(format!(
+            "session_{}_server_{}.{}",
+            unix_time,
+            self.game_server_index,
+            REPLAY_FILE_EXTENSION
+        ));
+
+        let file = match File::create(&path)
+        {
+            Ok(file) => file,
+            Err(e) =>
+            {
+                println!("ERROR: can't create replay file {}, err: {}", path.display(), e);
+                return;
+            }
+        };
+
+        let mut writer = BufWriter::new(file);
+
+        if let Err(e) = write_header(&mut writer, self.game_server_index, unix_time as u64)
+        {
+            println!("ERROR: can't write replay file {} header, err: {}", path.display(), e);
+            return;
+        }
+
+        println!("INFO: recording the game session to {}", path.display());
+
+        self.writer = Some(writer);
+        self.recording_start_time = current_time;
+    }
+
+    pub fn is_recording(&self) -> bool
+    {
+        self.writer.is_some()
+    }
+
+    pub fn finish_recording(&mut self)
+    {
+        if let Some(mut writer) = self.writer.take()
+        {
+            if let Err(e) = writer.flush()
+            {
+                println!("ERROR: can't flush replay file, err: {}", e);
+            }
+        }
+    }
+
+    pub fn record_peer_connected(&mut self, peer_id: u128, current_time: u128)
+    {
+        self.write_record(RecordKind::PeerConnected, peer_id, 0u8, &[], current_time);
+    }
+
+    pub fn record_peer_disconnected(&mut self, peer_id: u128, current_time: u128)
+    {
+        self.write_record(RecordKind::PeerDisconnected, peer_id, 0u8, &[], current_time);
+    }
+
+    pub fn record_packet(
+        &mut self,
+        peer_id: u128,
+        channel: TrafficChannel,
+        packet: &[u8],
+        current_time: u128,
+    )
+    {
+        self.write_record(RecordKind::Packet, peer_id, traffic_channel_to_byte(channel), packet, current_time);
+    }
+
+    pub fn record_sent_packet(
+        &mut self,
+        peer_id: u128,
+        channel: TrafficChannel,
+        packet: &[u8],
+        current_time: u128,
+    )
+    {
+        self.write_record(RecordKind::SentPacket, peer_id, traffic_channel_to_byte(channel), packet, current_time);
+    }
+
+    // records the packets sent through the channel since the last call,
+    // the channel keeps copies of the sent packets only while the recording is on
+    pub fn record_sent_packets(&mut self, channel: &mut RecordedChannel, current_time: u128)
+    {
+        for (peer_id, packet) in std::mem::take(&mut channel.sent_packets)
+        {
+            self.record_sent_packet(peer_id.0.as_u128(), channel.traffic_channel, &packet, current_time);
+        }
+
+        channel.is_recording = self.is_recording();
+    }
+
+    fn write_record(
+        &mut self,
+        kind: RecordKind,
+        peer_id: u128,
+        channel: u8,
+        payload: &[u8],
+        current_time: u128,
+    )
+    {
+        let writer = match &mut self.writer
+        {
+            Some(writer) => writer,
+            None => return,
+        };
+
+        let time = current_time.saturating_sub(self.recording_start_time) as u64;
+
+        if let Err(e) = write_record(writer, time, kind, peer_id, channel, payload)
+        {
+            println!("ERROR: can't write to replay file, recording is stopped, err: {}", e);
+
+            self.writer = None;
+        }
+    }
+}
+
+fn traffic_channel_to_byte(channel: TrafficChannel) -> u8
+{
+    match channel
+    {
+        TrafficChannel::Reliable => 0u8,
+        TrafficChannel::Unreliable => 1u8,
+    }
+}
+
+fn write_header(
+    writer: &mut BufWriter<File>,
+    game_server_index: u16,
+    unix_time: u64,
+) -> std::io::Result<()>
+{
+    let version = env!("CARGO_PKG_VERSION").as_bytes();
+
+    writer.write_all(REPLAY_MAGIC)?;
+    writer.write_all(&REPLAY_FORMAT_VERSION.to_le_bytes())?;
+    writer.write_all(&[version.len() as u8])?;
+    writer.write_all(version)?;
+    writer.write_all(&game_server_index.to_le_bytes())?;
+    writer.write_all(&unix_time.to_le_bytes())
+}
+
+fn write_record(
+    writer: &mut BufWriter<File>,
+    time: u64,
+    kind: RecordKind,
+    peer_id: u128,
+    channel: u8,
+    payload: &[u8],
+) -> std::io::Result<()>
+{
+    writer.write_all(&time.to_le_bytes())?;
+    writer.write_all(&[kind as u8])?;
+    writer.write_all(&peer_id.to_le_bytes())?;
+    writer.write_all(&[channel])?;
+    writer.write_all(&(payload.len() as u32).to_le_bytes())?;
+    writer.write_all(payload)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn take<'a>(bytes: &mut &'a [u8], length: usize) -> &'a [u8]
+    {
+        let (taken, rest) = bytes.split_at(length);
+
+        *bytes = rest;
+
+        taken
+    }
+
+    fn take_array<const N: usize>(bytes: &mut &[u8]) -> [u8; N]
+    {
+        take(bytes, N).try_into().unwrap()
+    }
+
+    #[test]
+    fn header_and_records_are_read_back()
+    {
+        // every test run writes to its own directory
+        let replay_dir = std::env::temp_dir().join(format!("slice_replay_recorder_test_{}", std::process::id()));
+
+        std::fs::create_dir_all(&replay_dir).unwrap();
+
+        let mut recorder = ReplayRecorder::new(Some(replay_dir.to_string_lossy().to_string()), 7);
+
+        recorder.start_recording(1_000);
+        recorder.record_peer_connected(42, 1_010);
+        recorder.record_packet(42, TrafficChannel::Unreliable, &[1, 2, 3], 1_020);
+        recorder.record_sent_packet(42, TrafficChannel::Reliable, &[4, 5], 1_030);
+        recorder.record_peer_disconnected(42, 1_040);
+        recorder.finish_recording();
+
+        let replay_path = std::fs::read_dir(&replay_dir).unwrap().next().unwrap().unwrap().path();
+
+        let replay = std::fs::read(&replay_path).unwrap();
+
+        std::fs::remove_dir_all(&replay_dir).unwrap();
+
+        let mut bytes = replay.as_slice();
+
+        assert_eq!(take(&mut bytes, 4), REPLAY_MAGIC);
+        assert_eq!(u16::from_le_bytes(take_array(&mut bytes)), REPLAY_FORMAT_VERSION);
+
+        let version_length = take(&mut bytes, 1)[0] as usize;
+
+        assert_eq!(take(&mut bytes, version_length), env!("CARGO_PKG_VERSION").as_bytes());
+        assert_eq!(u16::from_le_bytes(take_array(&mut bytes)), 7);
+
+        // unix time of the recording start
+        take(&mut bytes, 8);
+
+        let mut records = Vec::new();
+
+        while !bytes.is_empty()
+        {
+            let time = u64::from_le_bytes(take_array(&mut bytes));
+            let kind = take(&mut bytes, 1)[0];
+            let peer_id = u128::from_le_bytes(take_array(&mut bytes));
+            let channel = take(&mut bytes, 1)[0];
+            let payload_length = u32::from_le_bytes(take_array(&mut bytes)) as usize;
+            let payload = take(&mut bytes, payload_length).to_vec();
+
+            records.push((time, kind, peer_id, channel, payload));
+        }
+
+        assert_eq!(
+            records,
+            vec![
+                (10, RecordKind::PeerConnected as u8, 42, 0, vec![]),
+                (20, RecordKind::Packet as u8, 42, 1, vec![1, 2, 3]),
+                (30, RecordKind::SentPacket as u8, 42, 0, vec![4, 5]),
+                (40, RecordKind::PeerDisconnected as u8, 42, 0, vec![]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,702 @@
+// Slice 4D Shooter - the first multiplayer shooter set in 4D space
+// Copyright (C) 2023-2025  Timofei Molokov
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::{
+    collections::HashMap,
+    str::FromStr
+};
+
+use serde_json::Value;
+
+use crate::match_rules::MatchRules;
+
+// Every game server option can be set in the config file (--config <path>),
+// by the environment variable SLICE_GAME_SERVER_<KEY> or by the command line option.
+// Command line options override environment variables and environment variables
+// override the config file. Options which are not game server options
+// are passed to the match rules.
+
+pub const ENV_PREFIX: &str = "SLICE_GAME_SERVER_";
+
+pub const DEFAULT_RELIABLE_MESSAGES_PER_SECOND: u32 = 200;
+pub const DEFAULT_RELIABLE_BYTES_PER_SECOND: u32 = 131_072;
+pub const DEFAULT_UNRELIABLE_MESSAGES_PER_SECOND: u32 = 500;
+pub const DEFAULT_UNRELIABLE_BYTES_PER_SECOND: u32 = 524_288;
+pub const DEFAULT_MAX_PACKET_SIZE: u32 = 8_192;
+pub const DEFAULT_MAX_TRAFFIC_VIOLATIONS: u32 = 5;
+
+// (key, command line option)
+// the key is the field name in the config file and the suffix of the environment variable
+const SERVER_OPTIONS: [(&str, &str); 18] = [
+    ("signaling_port", "--signaling-port"),
+    ("min_signaling_port", "--min-signaling-port"),
+    ("max_signaling_port", "--max-signaling-port"),
+    ("min_tcp_listener_port", "--min-tcp-listener-port"),
+    ("max_tcp_listener_port", "--max-tcp-listener-port"),
+    ("max_players", "--max-players"),
+    ("ice_urls", "--ice-url"),
+    ("turn_server_username", "--turn-server-username"),
+    ("turn_server_credential", "--turn-server-credential"),
+    ("replay_dir", "--replay-dir"),
+    ("maps_dir", "--maps-dir"),
+    ("join_code", "--join-code"),
+    ("reliable_messages_per_second", "--reliable-messages-per-second"),
+    ("reliable_bytes_per_second", "--reliable-bytes-per-second"),
+    ("unreliable_messages_per_second", "--unreliable-messages-per-second"),
+    ("unreliable_bytes_per_second", "--unreliable-bytes-per-second"),
+    ("max_packet_size", "--max-packet-size"),
+    ("max_traffic_violations", "--max-traffic-violations"),
+];
+
+pub const SERVER_CONFIG_USAGE: &str =
+"Game server options:
+    --config <path>                           JSON file with game server options
+    --signaling-port <u16>                    port of the signaling server, required
+    --min-signaling-port <u16>                start of the port range for the signaling server, required
+    --max-signaling-port <u16>                end of the port range for the signaling server, required
+    --min-tcp-listener-port <u16>             start of the port range for the matchmaking server listener, required
+    --max-tcp-listener-port <u16>             end of the port range for the matchmaking server listener, required
+    --max-players <u32>                       max players in the game session, required
+    --ice-url <url>                           STUN or TURN server url, the option can be repeated
+    --turn-server-username <string>           TURN server username
+    --turn-server-credential <string>         TURN server credential
+    --replay-dir <path>                       record every game session to a replay file in this directory
+    --maps-dir <path>                         directory with map files of the map rotation, by default
+                                              ./, ./assets/maps and ./src/assets/maps are searched
+    --join-code <string>                      private game server, peers must connect with ?join_code=<string>
+    --reliable-messages-per-second <u32>      peer's budget of messages per second on the reliable channel
+    --reliable-bytes-per-second <u32>         peer's budget of bytes per second on the reliable channel
+    --unreliable-messages-per-second <u32>    peer's budget of messages per second on the unreliable channel
+    --unreliable-bytes-per-second <u32>       peer's budget of bytes per second on the unreliable channel
+    --max-packet-size <bytes>                 bigger packets from peers are dropped
+    --max-traffic-violations <u32>            kick a peer after exceeding its budget this many seconds in a row
+Every option can be set in the config file by the field with the option's name in snake case
+(max_players, ice_urls is an array of strings) or by the environment variable SLICE_GAME_SERVER_<FIELD>
+(SLICE_GAME_SERVER_MAX_PLAYERS, ice urls are separated by commas). Command line options
+override environment variables, environment variables override the config file.\n";
+
+#[derive(Clone)]
+pub struct GameServerConfig {
+    pub signaling_port: u16,
+    pub game_servers_min_port_for_signaling_servers: u16,
+    pub game_servers_max_port_for_signaling_servers: u16,
+    pub game_servers_min_port_for_tcp_listener: u16,
+    pub game_servers_max_port_for_tcp_listener: u16,
+    pub max_players: u32,
+    pub game_server_index: u16,
+    pub ice_urls: Vec<String>,
+    pub username: Option<String>,
+    pub credential: Option<String>,
+    // game sessions are not recorded if it's None
+    pub replay_dir: Option<String>,
+    // map files are searched in the default directories if it's None
+    pub maps_dir: Option<String>,
+    // the game server is private if it's Some, the signaling server
+    // accepts only peers with this join code in the url's query
+    pub join_code: Option<String>,
+    pub network_budgets: NetworkBudgets,
+    pub match_rules: MatchRules,
+}
+
+// the config is printed to the log, the TURN credential and the join code
+// are only marked as set
+impl std::fmt::Debug for GameServerConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let hidden = |secret: &Option<String>| secret.as_ref().map(|_| "<hidden>");
+
+        f.debug_struct("GameServerConfig")
+            .field("signaling_port", &self.signaling_port)
+            .field("game_servers_min_port_for_signaling_servers", &self.game_servers_min_port_for_signaling_servers)
+            .field("game_servers_max_port_for_signaling_servers", &self.game_servers_max_port_for_signaling_servers)
+            .field("game_servers_min_port_for_tcp_listener", &self.game_servers_min_port_for_tcp_listener)
+            .field("game_servers_max_port_for_tcp_listener", &self.game_servers_max_port_for_tcp_listener)
+            .field("max_players", &self.max_players)
+            .field("game_server_index", &self.game_server_index)
+            .field("ice_urls", &self.ice_urls)
+            .field("username", &self.username)
+            .field("credential", &hidden(&self.credential))
+            .field("replay_dir", &self.replay_dir)
+            .field("maps_dir", &self.maps_dir)
+            .field("join_code", &hidden(&self.join_code))
+            .field("network_budgets", &self.network_budgets)
+            .field("match_rules", &self.match_rules)
+            .finish()
+    }
+}
+
+// limits of the traffic every peer may send to the game server,
+// they depend on the host's bandwidth, not on the match
+#[derive(Clone, Debug)]
+pub struct NetworkBudgets {
+    pub reliable_messages_per_second: u32,
+    pub reliable_bytes_per_second: u32,
+    pub unreliable_messages_per_second: u32,
+    pub unreliable_bytes_per_second: u32,
+    pub max_packet_size: u32,
+    pub max_traffic_violations: u32,
+}
+
+impl Default for NetworkBudgets {
+    fn default() -> Self {
+        NetworkBudgets {
+            reliable_messages_per_second: DEFAULT_RELIABLE_MESSAGES_PER_SECOND,
+            reliable_bytes_per_second: DEFAULT_RELIABLE_BYTES_PER_SECOND,
+            unreliable_messages_per_second: DEFAULT_UNRELIABLE_MESSAGES_PER_SECOND,
+            unreliable_bytes_per_second: DEFAULT_UNRELIABLE_BYTES_PER_SECOND,
+            max_packet_size: DEFAULT_MAX_PACKET_SIZE,
+            max_traffic_violations: DEFAULT_MAX_TRAFFIC_VIOLATIONS,
+        }
+    }
+}
+
+// raw value of the option and where it came from, used in error messages
+struct OptionValue
+{
+    value: String,
+    source: String,
+}
+
+impl GameServerConfig {
+    pub fn new(args: &[String]) -> Result<Self, String> {
+        Self::from_args_and_env(args, |env_name| std::env::var(env_name).ok())
+    }
+
+    // environment variables are read by get_env_var, so tests don't depend on the process environment
+    fn from_args_and_env(
+        args: &[String],
+        get_env_var: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, String> {
+        let mut config_file_path: Option<String> = None;
+        let mut command_line_values: Vec<(&'static str, OptionValue)> = Vec::new();
+        let mut match_rules_args: Vec<String> = Vec::new();
+
+        // the first argument is the path of the executable
+        let mut i = 1usize;
+        while i < args.len()
+        {
+            let option = &args[i];
+
+            if !option.starts_with("--")
+            {
+                return Err(format!(
+                    "Unexpected argument {}, all game server options are named, run game_server --help to see them",
+                    option
+                ));
+            }
+
+            let value = args
+                .get(i + 1)
+                .ok_or(format!("Have not value for {} option", option))?
+                .clone();
+
+            if option == "--config"
+            {
+                config_file_path = Some(value);
+            }
+            else if let Some((key, _)) = SERVER_OPTIONS.iter().find(|(_, name)| *name == option.as_str())
+            {
+                command_line_values.push((
+                    *key,
+                    OptionValue {
+                        value,
+                        source: format!("{} option", option),
+                    }
+                ));
+            }
+            else
+            {
+                match_rules_args.push(option.clone());
+                match_rules_args.push(value);
+            }
+
+            i += 2;
+        }
+
+        let mut values: HashMap<&'static str, OptionValue> = HashMap::new();
+
+        if let Some(path) = &config_file_path
+        {
+            load_config_file(path, &mut values)?;
+        }
+
+        for (key, _) in SERVER_OPTIONS
+        {
+            let env_name = get_env_name(key);
+
+            if let Some(value) = get_env_var(&env_name)
+            {
+                values.insert(
+                    key,
+                    OptionValue {
+                        value,
+                        source: format!("{} environment variable", env_name),
+                    }
+                );
+            }
+        }
+
+        // repeated --ice-url options are collected into one list
+        // which replaces ice urls from the config file and the environment
+        let mut command_line_ice_urls: Vec<String> = Vec::new();
+
+        for (key, option_value) in command_line_values
+        {
+            if key == "ice_urls"
+            {
+                command_line_ice_urls.push(option_value.value);
+
+                values.insert(
+                    key,
+                    OptionValue {
+                        value: command_line_ice_urls.join(","),
+                        source: option_value.source,
+                    }
+                );
+            }
+            else
+            {
+                values.insert(key, option_value);
+            }
+        }
+
+        let signaling_port = parse_required_value(&values, "signaling_port")?;
+
+        let game_servers_min_port_for_signaling_servers =
+            parse_required_value(&values, "min_signaling_port")?;
+
+        let game_servers_max_port_for_signaling_servers =
+            parse_required_value(&values, "max_signaling_port")?;
+
+        let game_servers_min_port_for_tcp_listener =
+            parse_required_value(&values, "min_tcp_listener_port")?;
+
+        let game_servers_max_port_for_tcp_listener =
+            parse_required_value(&values, "max_tcp_listener_port")?;
+
+        let max_players: u32 = parse_required_value(&values, "max_players")?;
+
+        let ice_urls: Vec<String> = values
+            .get("ice_urls")
+            .map_or(Vec::with_capacity(0), |option_value| {
+                option_value.value
+                    .split(",")
+                    .filter_map(|s| if s != "" {Some(s.to_string())} else {None})
+                    .collect()
+            });
+
+        let username = values
+            .get("turn_server_username")
+            .map(|option_value| option_value.value.clone())
+            .filter(|value| value != "");
+
+        let credential = values
+            .get("turn_server_credential")
+            .map(|option_value| option_value.value.clone())
+            .filter(|value| value != "");
+
+        let replay_dir = values
+            .get("replay_dir")
+            .map(|option_value| option_value.value.clone())
+            .filter(|value| value != "");
+
+        if let Some(replay_dir) = &replay_dir
+        {
+            if !std::path::Path::new(replay_dir).is_dir()
+            {
+                return Err(format!(
+                    "replay_dir {} is not a directory ({})",
+                    replay_dir,
+                    values.get("replay_dir").unwrap().source
+                ));
+            }
+        }
+
+        let maps_dir = values
+            .get("maps_dir")
+            .map(|option_value| option_value.value.clone())
+            .filter(|value| value != "");
+
+        let join_code = values
+            .get("join_code")
+            .map(|option_value| option_value.value.clone())
+            .filter(|value| value != "");
+
+        if game_servers_min_port_for_signaling_servers > game_servers_max_port_for_signaling_servers
+        {
+            return Err("min_signaling_port must not be greater than max_signaling_port".to_string());
+        }
+
+        if game_servers_min_port_for_tcp_listener > game_servers_max_port_for_tcp_listener
+        {
+            return Err("min_tcp_listener_port must not be greater than max_tcp_listener_port".to_string());
+        }
+
+        if max_players == 0
+        {
+            return Err(format!(
+                "max_players must be greater than 0 ({})",
+                values.get("max_players").unwrap().source
+            ));
+        }
+
+        let network_budgets = parse_network_budgets(&values)?;
+
+        let match_rules = MatchRules::from_args(&match_rules_args)?;
+
+        let config = GameServerConfig {
+            signaling_port,
+            game_servers_min_port_for_signaling_servers,
+            game_servers_max_port_for_signaling_servers,
+            game_servers_min_port_for_tcp_listener,
+            game_servers_max_port_for_tcp_listener,
+            max_players,
+            game_server_index: signaling_port,
+            ice_urls,
+            username,
+            credential,
+            replay_dir,
+            maps_dir,
+            join_code,
+            network_budgets,
+            match_rules,
+        };
+
+        println!("Game server config is: {:?}", config);
+
+        Ok(config)
+    }
+}
+
+// options which are not set get default values, all budgets must be greater than 0
+fn parse_network_budgets(
+    values: &HashMap<&'static str, OptionValue>
+) -> Result<NetworkBudgets, String>
+{
+    let default_budgets = NetworkBudgets::default();
+
+    let budgets = NetworkBudgets {
+        reliable_messages_per_second: parse_value(values, "reliable_messages_per_second")?
+            .unwrap_or(default_budgets.reliable_messages_per_second),
+        reliable_bytes_per_second: parse_value(values, "reliable_bytes_per_second")?
+            .unwrap_or(default_budgets.reliable_bytes_per_second),
+        unreliable_messages_per_second: parse_value(values, "unreliable_messages_per_second")?
+            .unwrap_or(default_budgets.unreliable_messages_per_second),
+        unreliable_bytes_per_second: parse_value(values, "unreliable_bytes_per_second")?
+            .unwrap_or(default_budgets.unreliable_bytes_per_second),
+        max_packet_size: parse_value(values, "max_packet_size")?
+            .unwrap_or(default_budgets.max_packet_size),
+        max_traffic_violations: parse_value(values, "max_traffic_violations")?
+            .unwrap_or(default_budgets.max_traffic_violations),
+    };
+
+    let budget_values = [
+        ("reliable_messages_per_second", budgets.reliable_messages_per_second),
+        ("reliable_bytes_per_second", budgets.reliable_bytes_per_second),
+        ("unreliable_messages_per_second", budgets.unreliable_messages_per_second),
+        ("unreliable_bytes_per_second", budgets.unreliable_bytes_per_second),
+        ("max_packet_size", budgets.max_packet_size),
+        ("max_traffic_violations", budgets.max_traffic_violations),
+    ];
+
+    for (key, value) in budget_values
+    {
+        if value == 0
+        {
+            return Err(format!(
+                "{} must be greater than 0 ({})",
+                key,
+                values.get(key).unwrap().source
+            ));
+        }
+    }
+
+    Ok(budgets)
+}
+
+fn load_config_file(
+    path: &str,
+    values: &mut HashMap<&'static str, OptionValue>
+) -> Result<(), String>
+{
+    let file_content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Can't read game server config file {}, err: {}", path, e))?;
+
+    let json_config: Value = serde_json::from_str(&file_content)
+        .map_err(|e| format!("Can't parse game server config file {}, err: {}", path, e))?;
+
+    let object = json_config
+        .as_object()
+        .ok_or(format!("Wrong JSON format of game server config file {}", path))?;
+
+    for (field, json_value) in object
+    {
+        let key = SERVER_OPTIONS
+            .iter()
+            .map(|(key, _)| *key)
+            .find(|key| *key == field.as_str())
+            .ok_or(format!("Unknown field {} in game server config file {}", field, path))?;
+
+        let value = match json_value
+        {
+            Value::String(value) => value.clone(),
+            Value::Number(value) => value.to_string(),
+            Value::Array(array) =>
+            {
+                let mut urls = Vec::with_capacity(array.len());
+
+                for url in array
+                {
+                    urls.push(
+                        url
+                            .as_str()
+                            .ok_or(format!("{} members are not string values in game server config file {}", field, path))?
+                    );
+                }
+
+                urls.join(",")
+            }
+            _ => return Err(format!("{} has wrong type in game server config file {}", field, path)),
+        };
+
+        values.insert(
+            key,
+            OptionValue {
+                value,
+                source: format!("{} field of config file {}", field, path),
+            }
+        );
+    }
+
+    Ok(())
+}
+
+fn get_env_name(key: &str) -> String
+{
+    format!("{}{}", ENV_PREFIX, key.to_uppercase())
+}
+
+fn get_option_name(key: &str) -> &'static str
+{
+    SERVER_OPTIONS
+        .iter()
+        .find(|(option_key, _)| *option_key == key)
+        .map(|(_, option)| *option)
+        .unwrap()
+}
+
+fn parse_value<T: FromStr>(
+    values: &HashMap<&'static str, OptionValue>,
+    key: &str
+) -> Result<Option<T>, String>
+{
+    match values.get(key)
+    {
+        Some(option_value) =>
+        {
+            option_value.value
+                .parse()
+                .map(|value| Some(value))
+                .map_err(|_| format!(
+                    "Invalid value {} for {} (set by {})",
+                    option_value.value,
+                    key,
+                    option_value.source
+                ))
+        }
+        None => Ok(None),
+    }
+}
+
+fn parse_required_value<T: FromStr>(
+    values: &HashMap<&'static str, OptionValue>,
+    key: &str
+) -> Result<T, String>
+{
+    parse_value(values, key)?.ok_or(format!(
+        "{} is not specified, use {} option, {} field in the config file or {} environment variable",
+        key,
+        get_option_name(key),
+        key,
+        get_env_name(key)
+    ))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    const CONFIG_FILE: &str = r#"{
+        "signaling_port": 45126,
+        "min_signaling_port": 45126,
+        "max_signaling_port": 45129,
+        "min_tcp_listener_port": 45126,
+        "max_tcp_listener_port": 45129,
+        "max_players": 4,
+        "ice_urls": ["stun:file.example:19302"],
+        "max_packet_size": 1024
+    }"#;
+
+    // every test writes its own file, tests are run in parallel
+    fn write_config_file(test_name: &str) -> String
+    {
+        let path = std::env::temp_dir().join(format!(
+            "slice_game_server_config_{}_{}.json",
+            std::process::id(),
+            test_name
+        ));
+
+        std::fs::write(&path, CONFIG_FILE).unwrap();
+
+        path.to_string_lossy().to_string()
+    }
+
+    fn to_args(args: &[&str]) -> Vec<String>
+    {
+        // the first argument is the path of the executable
+        std::iter::once("game_server")
+            .chain(args.iter().copied())
+            .map(|arg| arg.to_string())
+            .collect()
+    }
+
+    fn get_env_var<'a>(env: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a
+    {
+        move |env_name| {
+            env
+                .iter()
+                .find(|(name, _)| *name == env_name)
+                .map(|(_, value)| value.to_string())
+        }
+    }
+
+    #[test]
+    fn config_file_values_are_used()
+    {
+        let path = write_config_file("config_file_values_are_used");
+
+        let config = GameServerConfig::from_args_and_env(
+            &to_args(&["--config", &path]),
+            get_env_var(&[])
+        ).unwrap();
+
+        assert_eq!(config.signaling_port, 45126);
+        assert_eq!(config.max_players, 4);
+        assert_eq!(config.ice_urls, vec!["stun:file.example:19302".to_string()]);
+        assert_eq!(config.network_budgets.max_packet_size, 1024);
+        assert_eq!(config.network_budgets.max_traffic_violations, DEFAULT_MAX_TRAFFIC_VIOLATIONS);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn environment_overrides_config_file()
+    {
+        let path = write_config_file("environment_overrides_config_file");
+
+        let config = GameServerConfig::from_args_and_env(
+            &to_args(&["--config", &path]),
+            get_env_var(&[
+                ("SLICE_GAME_SERVER_MAX_PLAYERS", "6"),
+                ("SLICE_GAME_SERVER_ICE_URLS", "stun:env.example:19302,stun:env.example:19303"),
+            ])
+        ).unwrap();
+
+        assert_eq!(config.max_players, 6);
+        assert_eq!(config.ice_urls.len(), 2);
+        assert_eq!(config.network_budgets.max_packet_size, 1024);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn command_line_overrides_environment_and_config_file()
+    {
+        let path = write_config_file("command_line_overrides_environment_and_config_file");
+
+        let config = GameServerConfig::from_args_and_env(
+            &to_args(&[
+                "--config", &path,
+                "--max-players", "8",
+                "--ice-url", "stun:cli.example:19302",
+                "--ice-url", "stun:cli.example:19303",
+            ]),
+            get_env_var(&[
+                ("SLICE_GAME_SERVER_MAX_PLAYERS", "6"),
+                ("SLICE_GAME_SERVER_ICE_URLS", "stun:env.example:19302"),
+                ("SLICE_GAME_SERVER_MAX_PACKET_SIZE", "2048"),
+            ])
+        ).unwrap();
+
+        assert_eq!(config.max_players, 8);
+        assert_eq!(
+            config.ice_urls,
+            vec!["stun:cli.example:19302".to_string(), "stun:cli.example:19303".to_string()]
+        );
+        assert_eq!(config.network_budgets.max_packet_size, 2048);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn other_options_are_passed_to_match_rules()
+    {
+        let path = write_config_file("other_options_are_passed_to_match_rules");
+
+        let config = GameServerConfig::from_args_and_env(
+            &to_args(&["--config", &path, "--max-score", "9"]),
+            get_env_var(&[])
+        ).unwrap();
+
+        assert_eq!(config.match_rules.max_score, 9);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn secrets_are_not_printed()
+    {
+        let path = write_config_file("secrets_are_not_printed");
+
+        let config = GameServerConfig::from_args_and_env(
+            &to_args(&[
+                "--config", &path,
+                "--turn-server-credential", "turn-secret",
+                "--join-code", "K7QX2M",
+            ]),
+            get_env_var(&[])
+        ).unwrap();
+
+        let printed_config = format!("{:?}", config);
+
+        assert!(!printed_config.contains("turn-secret"));
+        assert!(!printed_config.contains("K7QX2M"));
+        assert!(printed_config.contains("<hidden>"));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn missing_required_option_is_an_error()
+    {
+        let result = GameServerConfig::from_args_and_env(
+            &to_args(&["--signaling-port", "45126"]),
+            get_env_var(&[])
+        );
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,89 @@
+// Slice 4D Shooter - the first multiplayer shooter set in 4D space
+// Copyright (C) 2023-2025  Timofei Molokov
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use fyrox_core::rand::seq::SliceRandom;
+
+use client_server_protocol::PlayerStats;
+
+// weights of the player's statistics in the player's impact on the game session
+const KILL_IMPACT: f32 = 1.0;
+const DEATH_IMPACT: f32 = -0.5;
+const FLAG_CAPTURE_IMPACT: f32 = 3.0;
+const FLAG_RETURN_IMPACT: f32 = 1.0;
+const DAMAGE_POINT_IMPACT: f32 = 0.01;
+
+// weight of the last game session in the player's skill rating,
+// the rest is the rating from the previous game sessions
+const LAST_SESSION_WEIGHT: f32 = 0.5;
+
+pub fn get_player_impact(stats: &PlayerStats) -> f32
+{
+    stats.kills as f32 * KILL_IMPACT +
+    stats.deaths as f32 * DEATH_IMPACT +
+    stats.flag_captures as f32 * FLAG_CAPTURE_IMPACT +
+    stats.flag_returns as f32 * FLAG_RETURN_IMPACT +
+    stats.damage_dealt as f32 * DAMAGE_POINT_IMPACT
+}
+
+// called at the end of every game session with the statistics of this session
+pub fn update_skill_rating(skill_rating: f32, last_session_stats: &PlayerStats) -> f32
+{
+    skill_rating * (1.0 - LAST_SESSION_WEIGHT) +
+    get_player_impact(last_session_stats) * LAST_SESSION_WEIGHT
+}
+
+// players is the list of (player's id, skill rating),
+// returns the list of (player's id, team id).
+// Team sizes differ by one at most, the strongest remaining player
+// always goes to the weakest of the smallest teams.
+pub fn split_players_by_skill(
+    mut players: Vec<(u128, f32)>,
+    teams_amount: usize,
+) -> Vec<(u128, usize)>
+{
+    // players with equal ratings (new players for example)
+    // get different teams from session to session
+    let mut rng = fyrox_core::rand::thread_rng();
+    players.shuffle(&mut rng);
+
+    players.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut teams_size = vec![0usize; teams_amount];
+    let mut teams_rating = vec![0f32; teams_amount];
+
+    let mut result = Vec::with_capacity(players.len());
+
+    for (player_id, skill_rating) in players
+    {
+        let smallest_team_size = *teams_size.iter().min().unwrap();
+
+        let team_id = (0..teams_amount)
+            .filter(|team_id| teams_size[*team_id] == smallest_team_size)
+            .min_by(|a, b| {
+                teams_rating[*a]
+                    .partial_cmp(&teams_rating[*b])
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .unwrap();
+
+        teams_size[team_id] += 1;
+        teams_rating[team_id] += skill_rating;
+
+        result.push((player_id, team_id));
+    }
+
+    result
+}
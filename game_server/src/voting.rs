@@ -0,0 +1,329 @@
+// Slice 4D Shooter - the first multiplayer shooter set in 4D space
+// Copyright (C) 2023-2025  Timofei Molokov
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+
+use client_server_protocol::VoteKind;
+
+// a kick vote needs at least this many players in the game session
+// (the target included) and at least this many yes votes,
+// so a player can't kick the only other player alone
+pub const MIN_PLAYERS_FOR_KICK_VOTE: u32 = 3;
+pub const MIN_YES_VOTES_FOR_KICK: u32 = 2;
+
+pub enum VoteOutcome
+{
+    InProgress,
+    Passed,
+    Failed,
+}
+
+pub struct Vote
+{
+    pub kind: VoteKind,
+    pub initiator: u128,
+    pub start_time: u128,
+    // voter's id -> true for yes
+    ballots: HashMap<u128, bool>,
+}
+
+impl Vote
+{
+    pub fn get_yes_votes(&self) -> u32
+    {
+        self.ballots.values().filter(|yes| **yes).count() as u32
+    }
+
+    pub fn get_no_votes(&self) -> u32
+    {
+        self.ballots.values().filter(|yes| !**yes).count() as u32
+    }
+
+    // the player who is voted to be kicked can't vote
+    pub fn can_vote(&self, voter: u128) -> bool
+    {
+        match self.kind
+        {
+            VoteKind::KickPlayer(target) => target != voter,
+            _ => true,
+        }
+    }
+}
+
+// Players vote for one thing at a time. The vote passes when
+// vote_pass_percent of the players who can vote have voted yes and fails when
+// it can't pass anymore or the vote_duration runs out.
+pub struct Voting
+{
+    current_vote: Option<Vote>,
+    // initiator's id -> start time of the initiator's last vote
+    last_vote_time: HashMap<u128, u128>,
+}
+
+impl Voting
+{
+    pub fn new(max_players: u32) -> Self
+    {
+        Voting {
+            current_vote: None,
+            last_vote_time: HashMap::with_capacity(max_players as usize),
+        }
+    }
+
+    pub fn get_current_vote(&self) -> Option<&Vote>
+    {
+        self.current_vote.as_ref()
+    }
+
+    // players_amount is the amount of players in the game session
+    pub fn start_vote(
+        &mut self,
+        kind: VoteKind,
+        initiator: u128,
+        players_amount: u32,
+        current_time: u128,
+        vote_cooldown: u128,
+    ) -> Result<(), String>
+    {
+        if self.current_vote.is_some()
+        {
+            return Err("another vote is in progress".to_string());
+        }
+
+        if let Some(last_vote_time) = self.last_vote_time.get(&initiator)
+        {
+            if current_time.saturating_sub(*last_vote_time) < vote_cooldown
+            {
+                return Err(format!("player {} started a vote recently", initiator));
+            }
+        }
+
+        if let VoteKind::KickPlayer(target) = kind
+        {
+            if target == initiator
+            {
+                return Err(format!("player {} can't vote to kick itself", initiator));
+            }
+
+            if players_amount < MIN_PLAYERS_FOR_KICK_VOTE
+            {
+                return Err(format!(
+                    "a kick vote needs at least {} players in the game session",
+                    MIN_PLAYERS_FOR_KICK_VOTE
+                ));
+            }
+        }
+
+        let mut ballots = HashMap::new();
+        ballots.insert(initiator, true);
+
+        self.current_vote = Some(Vote {
+            kind,
+            initiator,
+            start_time: current_time,
+            ballots,
+        });
+
+        self.last_vote_time.insert(initiator, current_time);
+
+        Ok(())
+    }
+
+    // a player can change the ballot while the vote is in progress
+    pub fn cast_vote(&mut self, voter: u128, yes: bool) -> Result<(), String>
+    {
+        let vote = self.current_vote
+            .as_mut()
+            .ok_or("no vote is in progress".to_string())?;
+
+        if !vote.can_vote(voter)
+        {
+            return Err(format!("player {} can't vote for its own kick", voter));
+        }
+
+        vote.ballots.insert(voter, yes);
+
+        Ok(())
+    }
+
+    // returns true if the vote is canceled because the player was the kick target
+    pub fn remove_player(&mut self, id: u128) -> bool
+    {
+        self.last_vote_time.remove(&id);
+
+        if let Some(vote) = &mut self.current_vote
+        {
+            vote.ballots.remove(&id);
+
+            if let VoteKind::KickPlayer(target) = vote.kind
+            {
+                if target == id
+                {
+                    self.current_vote = None;
+
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+
+    // yes votes required to pass the vote
+    pub fn get_required_votes(kind: VoteKind, voters_amount: u32, vote_pass_percent: u32) -> u32
+    {
+        let min_yes_votes = match kind
+        {
+            VoteKind::KickPlayer(_) => MIN_YES_VOTES_FOR_KICK,
+            _ => 1,
+        };
+
+        ((voters_amount * vote_pass_percent + 99) / 100).max(min_yes_votes)
+    }
+
+    // voters_amount is the amount of players who can vote in the current vote
+    pub fn get_outcome(
+        &self,
+        voters_amount: u32,
+        vote_pass_percent: u32,
+        vote_duration: u128,
+        current_time: u128,
+    ) -> VoteOutcome
+    {
+        let vote = match &self.current_vote
+        {
+            Some(vote) => vote,
+            None => return VoteOutcome::InProgress,
+        };
+
+        let required_votes = Voting::get_required_votes(vote.kind, voters_amount, vote_pass_percent);
+
+        let yes_votes = vote.get_yes_votes();
+        let no_votes = vote.get_no_votes();
+
+        if yes_votes >= required_votes
+        {
+            return VoteOutcome::Passed;
+        }
+
+        let not_voted = voters_amount.saturating_sub(yes_votes + no_votes);
+
+        if yes_votes + not_voted < required_votes ||
+            current_time.saturating_sub(vote.start_time) >= vote_duration
+        {
+            return VoteOutcome::Failed;
+        }
+
+        VoteOutcome::InProgress
+    }
+
+    pub fn finish_vote(&mut self) -> Option<Vote>
+    {
+        self.current_vote.take()
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    const PASS_PERCENT: u32 = 51;
+    const DURATION: u128 = 30_000;
+    const COOLDOWN: u128 = 60_000;
+
+    #[test]
+    fn kick_vote_needs_three_players()
+    {
+        let mut voting = Voting::new(2);
+
+        assert!(voting.start_vote(VoteKind::KickPlayer(2), 1, 2, 0, COOLDOWN).is_err());
+        assert!(voting.get_current_vote().is_none());
+
+        assert!(voting.start_vote(VoteKind::KickPlayer(2), 1, 3, 0, COOLDOWN).is_ok());
+    }
+
+    #[test]
+    fn kick_vote_is_not_passed_by_a_single_yes_vote()
+    {
+        let mut voting = Voting::new(3);
+
+        voting.start_vote(VoteKind::KickPlayer(3), 1, 3, 0, COOLDOWN).unwrap();
+
+        // the target can't vote, so there are 2 voters and 51% of them is 1 vote
+        assert_eq!(Voting::get_required_votes(VoteKind::KickPlayer(3), 2, PASS_PERCENT), 2);
+        assert!(matches!(voting.get_outcome(2, PASS_PERCENT, DURATION, 0), VoteOutcome::InProgress));
+
+        assert!(voting.cast_vote(3, false).is_err());
+
+        voting.cast_vote(2, true).unwrap();
+        assert!(matches!(voting.get_outcome(2, PASS_PERCENT, DURATION, 0), VoteOutcome::Passed));
+    }
+
+    #[test]
+    fn kick_vote_fails_when_it_cant_pass_anymore()
+    {
+        let mut voting = Voting::new(3);
+
+        voting.start_vote(VoteKind::KickPlayer(3), 1, 3, 0, COOLDOWN).unwrap();
+        voting.cast_vote(2, false).unwrap();
+
+        assert!(matches!(voting.get_outcome(2, PASS_PERCENT, DURATION, 0), VoteOutcome::Failed));
+    }
+
+    #[test]
+    fn other_votes_need_one_yes_vote()
+    {
+        assert_eq!(Voting::get_required_votes(VoteKind::NextMap, 1, PASS_PERCENT), 1);
+        assert_eq!(Voting::get_required_votes(VoteKind::RestartMatch, 4, PASS_PERCENT), 3);
+        assert_eq!(Voting::get_required_votes(VoteKind::RestartMatch, 4, 100), 4);
+    }
+
+    #[test]
+    fn vote_fails_after_the_duration()
+    {
+        let mut voting = Voting::new(4);
+
+        voting.start_vote(VoteKind::NextMap, 1, 4, 0, COOLDOWN).unwrap();
+
+        assert!(matches!(voting.get_outcome(4, PASS_PERCENT, DURATION, DURATION - 1), VoteOutcome::InProgress));
+        assert!(matches!(voting.get_outcome(4, PASS_PERCENT, DURATION, DURATION), VoteOutcome::Failed));
+    }
+
+    #[test]
+    fn player_cant_start_a_vote_during_the_cooldown()
+    {
+        let mut voting = Voting::new(4);
+
+        voting.start_vote(VoteKind::NextMap, 1, 4, 0, COOLDOWN).unwrap();
+        voting.finish_vote();
+
+        assert!(voting.start_vote(VoteKind::NextMap, 1, 4, COOLDOWN - 1, COOLDOWN).is_err());
+        assert!(voting.start_vote(VoteKind::NextMap, 2, 4, COOLDOWN - 1, COOLDOWN).is_ok());
+    }
+
+    #[test]
+    fn kick_vote_is_canceled_when_the_target_leaves()
+    {
+        let mut voting = Voting::new(3);
+
+        voting.start_vote(VoteKind::KickPlayer(3), 1, 3, 0, COOLDOWN).unwrap();
+
+        assert!(!voting.remove_player(2));
+        assert!(voting.remove_player(3));
+        assert!(voting.get_current_vote().is_none());
+    }
+}
@@ -15,7 +15,11 @@ use axum::extract::ws::Message;
 use futures::{select, FutureExt, StreamExt};
 use futures_timer::Delay;
 use matchbox_protocol::{JsonPeerEvent, PeerId, PeerRequest};
-use std::{collections::HashMap, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 use tracing::{error, info, warn};
 
 /// A client server network topology
@@ -58,10 +62,19 @@ impl SignalingServerBuilder<ClientServer, ClientServerCallbacks, ClientServerSta
         self.callbacks.on_host_disconnected = Callback::from(callback);
         self
     }
+
+    /// Set the clients to be disconnected by the signaling server.
+    /// The host adds a client's id to the set to drop the client.
+    pub fn dropped_clients(mut self, dropped_clients: Arc<Mutex<HashSet<PeerId>>>) -> Self {
+        self.state.dropped_clients = dropped_clients;
+        self
+    }
 }
 
 const TIME_TO_DISCONNECT_PEER_WITHOUT_PACKETS: f32 = 5.0;
 
+const DROPPED_CLIENTS_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
 #[async_trait]
 impl SignalingTopology<ClientServerCallbacks, ClientServerState> for ClientServer {
     async fn state_machine(upgrade: WsStateMeta<ClientServerCallbacks, ClientServerState>) {
@@ -123,10 +136,31 @@ impl SignalingTopology<ClientServerCallbacks, ClientServerState> for ClientServe
             Duration::from_secs(15)
         ).fuse();
 
+        // the host drops kicked and rejected clients, otherwise they would
+        // keep their slots on the signaling server until they disconnect themselves.
+        // The client is disconnected on the check after the one which found it dropped,
+        // so the host's last message has time to reach the client
+        let mut dropped_clients_check = Delay::new(DROPPED_CLIENTS_CHECK_INTERVAL).fuse();
+        let mut is_dropped = false;
+
         loop
         {
             select!
             {
+                _ = dropped_clients_check =>
+                {
+                    if is_dropped
+                    {
+                        info!("Client {peer_id} is dropped by the host");
+                        break;
+                    }
+
+                    is_dropped = !is_host && state.is_client_dropped(&peer_id);
+
+                    dropped_clients_check = Delay::new(DROPPED_CLIENTS_CHECK_INTERVAL).fuse();
+                }
+
+
                 _ = timeout =>
                 {
                     if last_packet_time.elapsed().as_secs_f32() > TIME_TO_DISCONNECT_PEER_WITHOUT_PACKETS
@@ -237,6 +271,7 @@ impl SignalingCallbacks for ClientServerCallbacks {}
 pub struct ClientServerState {
     pub(crate) host: StateObj<Option<(PeerId, SignalingChannel)>>,
     pub(crate) clients: StateObj<HashMap<PeerId, SignalingChannel>>,
+    pub(crate) dropped_clients: StateObj<HashSet<PeerId>>,
 }
 impl SignalingState for ClientServerState {}
 
@@ -269,6 +304,12 @@ impl ClientServerState {
             }
         }
         self.clients.lock().as_mut().unwrap().remove(peer_id);
+        self.dropped_clients.lock().as_mut().unwrap().remove(peer_id);
+    }
+
+    /// Check whether the host asked to drop the client.
+    pub fn is_client_dropped(&self, peer_id: &PeerId) -> bool {
+        self.dropped_clients.lock().unwrap().contains(peer_id)
     }
 
     /// Send a message to a peer without blocking.
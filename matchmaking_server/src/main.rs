@@ -17,17 +17,23 @@
 pub mod matchmaking_server_protocol;
 
 use matchmaking_server_protocol::{
+    AdminCommand,
     GameServerMatchmakingServerProtocol,
     ClientMatchmakingServerProtocol,
     MatchmakingServerMessage,
     GameServerMessage,
     ClientMessage,
-    GameVersion
+    GameServerListEntry,
+    GameVersion,
+    name_from_bytes,
+    JOIN_CODE_ALPHABET,
+    JOIN_CODE_LENGTH,
+    MAX_GAME_SERVER_INFO_NAME_LENGTH
 };
 
 use core::panic;
 use std::{
-    env, io::{Read, Write}, net::Ipv4Addr, os::linux::raw::stat, process::Stdio, str::FromStr, sync::Arc, time::Duration
+    collections::hash_map::RandomState, env, hash::{BuildHasher, Hasher}, io::{Read, Write}, net::Ipv4Addr, os::linux::raw::stat, process::Stdio, str::FromStr, sync::Arc, time::{Duration, Instant, SystemTime, UNIX_EPOCH}
 };
 use tokio::{
     fs::File, io::{
@@ -38,8 +44,11 @@ use tokio::{
         ChildStdout,
         Command
     }, runtime::Runtime, sync::{
+        mpsc,
+        oneshot,
         Mutex,
-        MutexGuard
+        MutexGuard,
+        Notify
     }
 };
 
@@ -53,6 +62,19 @@ use std::collections::HashMap;
 
 use crate::matchmaking_server_protocol::GameType;
 
+const DEFAULT_MIN_PLAYERS_TO_START_GAME_SERVER: u32 = 1;
+
+// a new game server is started for the queued clients of one game version and
+// game type after the first of them has waited this long, even if there are
+// fewer of them than min_players_to_start_game_server
+const MAX_QUEUE_WAIT_TIME: Duration = Duration::from_secs(30);
+
+// queued clients get QueueStatus with this interval
+const QUEUE_STATUS_INTERVAL: Duration = Duration::from_secs(1);
+
+// weight of the last matched client's wait in the average wait time of the queue
+const QUEUE_WAIT_TIME_WEIGHT: f32 = 0.2;
+
 #[derive(Clone, Debug)]
 struct Config
 {
@@ -66,6 +88,12 @@ struct Config
 
     pub current_game_version: GameVersion,
 
+    // clients of older versions are answered with WrongGameVersionCorrectIs
+    pub min_supported_game_version: GameVersion,
+
+    // one pool of game servers per supported game version
+    pub game_server_pools: Vec<GameServerPoolConfig>,
+
     pub game_servers_public_ip: Ipv4Addr,
 
     pub game_servers_min_port_for_signaling_servers: u16,
@@ -79,6 +107,34 @@ struct Config
     pub max_game_sessions: u32,
 
     pub max_players_per_game_session: u32,
+
+    // queued clients of one game version and game type wait
+    // until there are enough of them to start a new game server
+    pub min_players_to_start_game_server: u32,
+
+    // private game servers are created by any client without waiting in the queue,
+    // so they are limited separately to leave game servers for public matches
+    pub max_private_game_sessions: u32,
+
+    pub game_servers_match_rules_file: Option<String>,
+
+    pub matchmaking_server_port_for_admin: Option<u16>,
+}
+
+impl Config
+{
+    // returns None if the clients of this game version can't be matched
+    pub fn get_game_server_pool(&self, game_version: GameVersion) -> Option<&GameServerPoolConfig>
+    {
+        if game_version < self.min_supported_game_version
+        {
+            return None;
+        }
+
+        self.game_server_pools
+            .iter()
+            .find(|pool| pool.game_version == game_version)
+    }
 }
 
 trait ToOption<T> {
@@ -96,7 +152,7 @@ impl ToOption<String> for String {
 
 #[derive(Clone, Debug)]
 struct GameServersIceConfig {
-    urls: String,
+    urls: Vec<String>,
     username: String,
     credential: String,
 }
@@ -125,9 +181,9 @@ impl GameServersIceConfig {
                     s
                         .as_str()
                         .expect("ERROR: urls members is not string value in game_servers_ice_config in matchmaking-server-config.json")
-                        .to_string() + "|"
+                        .to_string()
                 })
-                .collect::<String>()
+                .collect::<Vec<String>>()
                 // .none_if_zero()
         };
 
@@ -159,6 +215,104 @@ impl GameServersIceConfig {
     }
 }
 
+#[derive(Clone, Debug)]
+struct GameServerPoolConfig {
+    game_version: GameVersion,
+    // path to the game server binary built for this game version
+    game_server_binary: String,
+    // limit of game servers of this version,
+    // all pools together are limited by max_game_sessions
+    max_game_sessions: u32,
+}
+
+impl GameServerPoolConfig {
+    // the game_server_pools array is optional, without it the matchmaking server
+    // runs ./game_server for the current game version only
+    pub fn parse_json(
+        object: &Map<String, Value>,
+        current_game_version: GameVersion,
+        max_game_sessions: u32,
+    ) -> Vec<Self>
+    {
+        let pools = match object.get("game_server_pools")
+        {
+            Some(pools) =>
+            {
+                pools
+                    .as_array()
+                    .expect("ERROR: game_server_pools is not array value in matchmaking-server-config.json")
+            }
+            None =>
+            {
+                return vec![GameServerPoolConfig {
+                    game_version: current_game_version,
+                    game_server_binary: "./game_server".to_string(),
+                    max_game_sessions,
+                }];
+            }
+        };
+
+        let mut game_server_pools: Vec<GameServerPoolConfig> = Vec::with_capacity(pools.len());
+
+        for pool in pools
+        {
+            let pool = pool
+                .as_object()
+                .expect("ERROR: game_server_pools members is not object value in matchmaking-server-config.json");
+
+            let game_version = {
+                pool
+                    .get("game_version")
+                    .expect("ERROR: Have not game_version in game_server_pools in matchmaking-server-config.json")
+                    .as_str()
+                    .expect("ERROR: game_version is not string value in game_server_pools in matchmaking-server-config.json")
+            };
+
+            let game_version = GameVersion::from(game_version);
+
+            let game_server_binary = {
+                pool
+                    .get("game_server_binary")
+                    .expect("ERROR: Have not game_server_binary in game_server_pools in matchmaking-server-config.json")
+                    .as_str()
+                    .expect("ERROR: game_server_binary is not string value in game_server_pools in matchmaking-server-config.json")
+                    .to_string()
+            };
+
+            // optional field, the pool is limited only by the total max_game_sessions if it is not specified
+            let pool_max_game_sessions = {
+                pool
+                    .get("max_game_sessions")
+                    .map(|value| {
+                        value
+                            .as_i64()
+                            .expect("ERROR: max_game_sessions is not number value in game_server_pools in matchmaking-server-config.json")
+                            as u32
+                    })
+                    .unwrap_or(max_game_sessions)
+            };
+
+            if game_server_pools.iter().any(|pool| pool.game_version == game_version)
+            {
+                panic!("ERROR: game version {:?} has several pools in game_server_pools in matchmaking-server-config.json", game_version);
+            }
+
+            game_server_pools.push(GameServerPoolConfig {
+                game_version,
+                game_server_binary,
+                max_game_sessions: pool_max_game_sessions,
+            });
+        }
+
+        if !game_server_pools.iter().any(|pool| pool.game_version == current_game_version)
+        {
+            println!("WARNING: game_server_pools has not a pool for the current game version {:?}", current_game_version);
+        }
+
+        game_server_pools
+    }
+}
+
 
 #[derive(Clone)]
 struct GameServerInfo {
@@ -168,6 +322,14 @@ struct GameServerInfo {
     players_amount_by_game_server: u32,
     max_amount_of_players: u32,
     game_server_game_version: GameVersion,
+
+    // reported by the game server, empty until the first status check
+    map_name: [u8; MAX_GAME_SERVER_INFO_NAME_LENGTH],
+    game_mode: [u8; MAX_GAME_SERVER_INFO_NAME_LENGTH],
+
+    // private game servers are joined only by the join code,
+    // they are not shown in the game servers list
+    join_code: Option<[u8; JOIN_CODE_LENGTH]>,
     
     game_server_ip_address: Ipv4Addr,
     game_server_main_port: u16,
@@ -179,11 +341,172 @@ struct GameServerInfo {
 }
 
 
-type GameServersState = Arc<Mutex<HashMap<u16,GameServerInfo>>>;
+// game server which is being spawned, its port and its place in the limits
+// are taken while the game servers state is unlocked
+struct ReservedGameServer {
+    game_version: GameVersion,
+    join_code: Option<[u8; JOIN_CODE_LENGTH]>,
+}
+
+
+struct GameServers {
+    // by the server index
+    running: HashMap<u16, GameServerInfo>,
+    // by the port passed to the spawned game server
+    reserved: HashMap<u16, ReservedGameServer>,
+}
+
+impl GameServers
+{
+    fn private_game_sessions(&self) -> usize
+    {
+        let running = self.running
+            .values()
+            .filter(|server_info| server_info.join_code.is_some())
+            .count();
+
+        let reserved = self.reserved
+            .values()
+            .filter(|reserved_server| reserved_server.join_code.is_some())
+            .count();
+
+        running + reserved
+    }
+
+    fn is_join_code_used(&self, join_code: &[u8; JOIN_CODE_LENGTH]) -> bool
+    {
+        self.running.values().any(|server_info| server_info.join_code == Some(*join_code))
+        ||
+        self.reserved.values().any(|reserved_server| reserved_server.join_code == Some(*join_code))
+    }
+}
+
+
+type GameServersState = Arc<Mutex<GameServers>>;
+
+
+// the party exists until the leader starts it or leaves,
+// the members wait on their connections for the game server's address
+struct Party {
+    game_version: GameVersion,
+    game_type: GameType,
+
+    // every member gets the packets with the party id and the game server's address
+    members: Vec<oneshot::Sender<Vec<Vec<u8>>>>,
+}
+
+
+type PartiesState = Arc<Mutex<HashMap<[u8; JOIN_CODE_LENGTH], Party>>>;
+
+
+// client waiting in the matchmaking queue for a game server
+struct QueuedClient {
+    game_version: GameVersion,
+    game_type: GameType,
+    enqueue_time: Instant,
+
+    // QueueStatus messages and the game server's address
+    // are sent to the client's connection through it
+    sender: mpsc::UnboundedSender<Vec<u8>>,
+}
+
+
+struct MatchmakingQueue {
+    // in the order of arrival
+    clients: Vec<QueuedClient>,
+
+    // the latest config, updated by every client's request
+    config: Config,
+
+    // seconds, moving average of the recent matched clients' waits
+    average_wait_time: f32,
+}
+
+impl MatchmakingQueue
+{
+    // removes up to amount first clients of the game version and game type
+    fn take_clients(
+        &mut self,
+        game_version: &GameVersion,
+        game_type: &GameType,
+        amount: u32,
+    ) -> Vec<QueuedClient>
+    {
+        let mut taken_clients = Vec::with_capacity(amount as usize);
+
+        for client in std::mem::take(&mut self.clients)
+        {
+            if taken_clients.len() < amount as usize &&
+                client.game_version == *game_version &&
+                client.game_type == *game_type
+            {
+                taken_clients.push(client);
+            }
+            else
+            {
+                self.clients.push(client);
+            }
+        }
+
+        taken_clients
+    }
+
+    // the client's connection is closed after the game server's address is sent
+    fn send_to_matched_client(&mut self, client: QueuedClient, packet: Vec<u8>)
+    {
+        let wait_time = client.enqueue_time.elapsed().as_secs_f32();
+
+        self.average_wait_time =
+            self.average_wait_time * (1.0 - QUEUE_WAIT_TIME_WEIGHT) +
+            wait_time * QUEUE_WAIT_TIME_WEIGHT;
+
+        // the client may have left the queue
+        let _ = client.sender.send(packet);
+    }
+
+    fn send_queue_status(&self)
+    {
+        for (index, client) in self.clients.iter().enumerate()
+        {
+            let same_group = |other: &&QueuedClient| {
+                other.game_version == client.game_version && other.game_type == client.game_type
+            };
+
+            let position = self.clients[..index].iter().filter(same_group).count() as u32 + 1;
+
+            let players_waiting = self.clients.iter().filter(same_group).count() as u32;
+
+            // there is no estimate until some queued client is matched
+            let estimated_wait = if self.average_wait_time > 0.0
+            {
+                (self.average_wait_time - client.enqueue_time.elapsed().as_secs_f32())
+                    .ceil()
+                    .max(1.0) as u32
+            }
+            else
+            {
+                0u32
+            };
+
+            let packet = ClientMatchmakingServerProtocol::MatchmakingServerMessage(
+                MatchmakingServerMessage::QueueStatus(position, players_waiting, estimated_wait)
+            )
+            .to_packet();
+
+            let _ = client.sender.send(packet);
+        }
+    }
+}
+
+
+type QueueState = Arc<Mutex<MatchmakingQueue>>;
 
 async fn handle_client_connection(
     stream: tokio::net::TcpStream,
     state: GameServersState,
+    parties: PartiesState,
+    queue: QueueState,
+    queue_notify: Arc<Notify>,
     mut config: Config,
     async_rutime: Arc<Runtime>,
 )
@@ -209,245 +532,964 @@ async fn handle_client_connection(
                     match client_message {
                         ClientMessage::RequestToConnectToGameServer(clients_game_version, client_game_type) => {
 
-                            // update current game version and max game sessions amount
-
                             println!("INFO: Client is requesting to connect to a game server");
 
                             let clients_game_version = GameVersion::from(clients_game_version);
 
+                            // update config to change supported game versions, max game sessions
+                            // and max players per session dynamically
+                            config = load_config().await;
+
+                            if config.get_game_server_pool(clients_game_version).is_none()
+                            {
+                                println!(
+                                    "WARNING: Client's game version {:?} is not supported, min supported version is {:?}",
+                                    clients_game_version,
+                                    config.min_supported_game_version
+                                );
+
+                                let message = ClientMatchmakingServerProtocol::MatchmakingServerMessage(
+                                    MatchmakingServerMessage::WrongGameVersionCorrectIs(config.current_game_version.clone().into())
+                                );
+
+                                let message: Vec<u8> = message.to_packet();
+
+                                sender_to_client
+                                    .send(tokio_tungstenite::tungstenite::Message::binary(message))
+                                    .await
+                                    .unwrap();
+
+                                return ;
+                            }
+
+                            let (queue_sender, mut queue_receiver) = mpsc::unbounded_channel::<Vec<u8>>();
+
+                            {
+                                let mut locked_queue = queue.lock().await;
+
+                                locked_queue.config = config.clone();
+
+                                locked_queue.clients.push(QueuedClient {
+                                    game_version: clients_game_version,
+                                    game_type: client_game_type,
+                                    enqueue_time: Instant::now(),
+                                    sender: queue_sender,
+                                });
+
+                                println!("INFO: Client is added to the matchmaking queue, {} clients are waiting", locked_queue.clients.len());
+                            }
+
+                            // the queue is processed right away to give a free game server without waiting
+                            queue_notify.notify_one();
+
+                            // QueueStatus messages are sent until the queue sends the game server's address
+                            // and drops the sender, the client is removed from the queue
+                            // when the receiver is dropped
+                            while let Some(packet) = queue_receiver.recv().await
+                            {
+                                if sender_to_client
+                                    .send(tokio_tungstenite::tungstenite::Message::binary(packet))
+                                    .await
+                                    .is_err()
+                                {
+                                    println!("INFO: Client left the matchmaking queue");
+
+                                    return ;
+                                }
+                            }
+
+                            continue ;
+                        }
+
+                        ClientMessage::ListGameServers =>
+                        {
+                            println!("INFO: Client is requesting the game servers list");
+
+                            let mut game_servers_list: Vec<GameServerListEntry> = state
+                                .lock()
+                                .await
+                                .running
+                                .values()
+                                .filter(|server_info| server_info.join_code.is_none())
+                                .map(|server_info| GameServerListEntry {
+                                    server_index: server_info.server_index,
+                                    game_type: server_info.game_type.clone(),
+                                    map_name: server_info.map_name,
+                                    game_mode: server_info.game_mode,
+                                    players_amount: server_info.players_amount_by_matchmaking_server,
+                                    max_players: server_info.max_amount_of_players,
+                                    game_version: server_info.game_server_game_version.into(),
+                                })
+                                .collect();
+
+                            game_servers_list.sort_by_key(|entry| entry.server_index);
+
+                            let message = ClientMatchmakingServerProtocol::MatchmakingServerMessage(
+                                MatchmakingServerMessage::GameServersList(game_servers_list)
+                            );
+
+                            let message: Vec<u8> = message.to_packet();
+
+                            sender_to_client
+                                .send(tokio_tungstenite::tungstenite::Message::binary(message))
+                                .await
+                                .unwrap();
+
+                            continue ;
+                        }
+
+                        ClientMessage::RequestToConnectToSpecificServer(clients_game_version, client_game_type, server_index) =>
+                        {
+                            println!("INFO: Client is requesting to connect to [{}] game server", server_index);
+
+                            let clients_game_version = GameVersion::from(clients_game_version);
+
                             let mut locked_state = state.lock().await;
-                            
-                            let finded_server = locked_state.values_mut().find(
-                                |server_info| {
-                                    println!(
-                                        "[{}] server has {} players by matchmaking server, max players per server is {}",
-                                        server_info.server_index,
-                                        server_info.players_amount_by_matchmaking_server,
-                                        server_info.max_amount_of_players
+
+                            let message = match locked_state.running.get_mut(&server_index)
+                            {
+                                // private game servers can be joined only by the join code
+                                None | Some(GameServerInfo { join_code: Some(_), .. }) =>
+                                {
+                                    println!("WARNING: [{}] game server is not running", server_index);
+
+                                    ClientMatchmakingServerProtocol::MatchmakingServerMessage(
+                                        MatchmakingServerMessage::GameServerNotFound
+                                    )
+                                }
+                                Some(server_info) if server_info.game_type != client_game_type =>
+                                {
+                                    println!("WARNING: [{}] game server runs another game type than the client", server_index);
+
+                                    ClientMatchmakingServerProtocol::MatchmakingServerMessage(
+                                        MatchmakingServerMessage::GameServerNotFound
+                                    )
+                                }
+                                Some(server_info) if server_info.game_server_game_version != clients_game_version =>
+                                {
+                                    println!("WARNING: [{}] game server has another game version than the client", server_index);
+
+                                    ClientMatchmakingServerProtocol::MatchmakingServerMessage(
+                                        MatchmakingServerMessage::WrongGameVersionCorrectIs(server_info.game_server_game_version.into())
+                                    )
+                                }
+                                Some(server_info) if server_info.players_amount_by_matchmaking_server >= server_info.max_amount_of_players =>
+                                {
+                                    println!("WARNING: [{}] game server is full", server_index);
+
+                                    ClientMatchmakingServerProtocol::MatchmakingServerMessage(
+                                        MatchmakingServerMessage::NoFreeServers
+                                    )
+                                }
+                                Some(server_info) =>
+                                {
+                                    server_info.players_amount_by_matchmaking_server += 1;
+
+                                    get_game_server_address_message(&config, server_info)
+                                }
+                            };
+
+                            let message: Vec<u8> = message.to_packet();
+
+                            sender_to_client
+                                .send(tokio_tungstenite::tungstenite::Message::binary(message))
+                                .await
+                                .unwrap();
+
+                            continue ;
+                        }
+
+                        ClientMessage::RequestToCreatePrivateGameServer(clients_game_version, client_game_type) =>
+                        {
+                            println!("INFO: Client is requesting to create a private game server");
+
+                            let clients_game_version = GameVersion::from(clients_game_version);
+
+                            config = load_config().await;
+
+                            let pool = match config.get_game_server_pool(clients_game_version)
+                            {
+                                Some(pool) => pool.clone(),
+                                None =>
+                                {
+                                    println!("WARNING: Client's game version {:?} is not supported", clients_game_version);
+
+                                    let message = ClientMatchmakingServerProtocol::MatchmakingServerMessage(
+                                        MatchmakingServerMessage::WrongGameVersionCorrectIs(config.current_game_version.clone().into())
                                     );
 
-                                    server_info.players_amount_by_matchmaking_server < server_info.max_amount_of_players
-                                    &&
-                                    server_info.game_server_game_version == clients_game_version 
-                                    &&
-                                    server_info.game_type == client_game_type
+                                    sender_to_client
+                                        .send(tokio_tungstenite::tungstenite::Message::binary(message.to_packet()))
+                                        .await
+                                        .unwrap();
+
+                                    return ;
                                 }
+                            };
+
+                            let mut locked_state = state.lock().await;
+
+                            let private_game_sessions = locked_state.private_game_sessions();
+
+                            let join_code = generate_join_code(|join_code| locked_state.is_join_code_used(join_code));
+
+                            let free_port = if private_game_sessions < config.max_private_game_sessions as usize
+                            {
+                                reserve_free_server_port(
+                                    &mut locked_state,
+                                    &pool,
+                                    config.max_game_sessions,
+                                    config.game_servers_min_port_for_signaling_servers,
+                                    config.game_servers_max_port_for_signaling_servers,
+                                    Some(join_code),
+                                )
+                            }
+                            else
+                            {
+                                println!(
+                                    "WARNING: {} private game servers are running, max_private_game_sessions is reached",
+                                    private_game_sessions
+                                );
+
+                                None
+                            };
+
+                            // other clients' requests are handled while the game server is starting
+                            drop(locked_state);
+
+                            let server_info = match free_port
+                            {
+                                Some(new_port) =>
+                                {
+                                    spawn_reserved_game_server(
+                                        new_port,
+                                        &config,
+                                        &pool,
+                                        async_rutime.clone(),
+                                        state.clone(),
+                                        client_game_type,
+                                        1,
+                                    ).await
+                                }
+                                None => Err(()),
+                            };
+
+                            let server_info = match server_info
+                            {
+                                Ok(server_info) => server_info,
+                                Err(_) =>
+                                {
+                                    println!("WARNING: Can not create new private game server because is out of the limit");
+
+                                    let message = ClientMatchmakingServerProtocol::MatchmakingServerMessage(
+                                        MatchmakingServerMessage::NoFreeServers
+                                    );
+
+                                    sender_to_client
+                                        .send(tokio_tungstenite::tungstenite::Message::binary(message.to_packet()))
+                                        .await
+                                        .unwrap();
+
+                                    return ;
+                                }
+                            };
+
+                            println!(
+                                "INFO: New private game server [{}] is created, join code is {}",
+                                server_info.server_index,
+                                String::from_utf8_lossy(&join_code)
                             );
 
-                            match finded_server {
+                            let message = ClientMatchmakingServerProtocol::MatchmakingServerMessage(
+                                MatchmakingServerMessage::PrivateGameServerCreated(join_code)
+                            );
+
+                            sender_to_client
+                                .send(tokio_tungstenite::tungstenite::Message::binary(message.to_packet()))
+                                .await
+                                .unwrap();
+
+                            let message = get_game_server_address_message(&config, &server_info);
+
+                            sender_to_client
+                                .send(tokio_tungstenite::tungstenite::Message::binary(message.to_packet()))
+                                .await
+                                .unwrap();
+
+                            continue ;
+                        }
+
+                        ClientMessage::RequestToConnectToPrivateGameServer(clients_game_version, join_code) =>
+                        {
+                            println!("INFO: Client is requesting to connect to a private game server");
+
+                            let clients_game_version = GameVersion::from(clients_game_version);
 
-                                Some(server_info) => {
+                            let mut locked_state = state.lock().await;
 
-                                    println!("INFO: Free game server is finded, send to the client server's addres");
+                            let finded_server = locked_state
+                                .running
+                                .values_mut()
+                                .find(|server_info| server_info.join_code == Some(join_code));
 
+                            let message = match finded_server
+                            {
+                                None =>
+                                {
+                                    println!("WARNING: private game server with the join code is not running");
+
+                                    ClientMatchmakingServerProtocol::MatchmakingServerMessage(
+                                        MatchmakingServerMessage::GameServerNotFound
+                                    )
+                                }
+                                Some(server_info) if server_info.game_server_game_version != clients_game_version =>
+                                {
+                                    println!("WARNING: [{}] private game server has another game version than the client", server_info.server_index);
+
+                                    ClientMatchmakingServerProtocol::MatchmakingServerMessage(
+                                        MatchmakingServerMessage::WrongGameVersionCorrectIs(server_info.game_server_game_version.into())
+                                    )
+                                }
+                                Some(server_info) if server_info.players_amount_by_matchmaking_server >= server_info.max_amount_of_players =>
+                                {
+                                    println!("WARNING: [{}] private game server is full", server_info.server_index);
+
+                                    ClientMatchmakingServerProtocol::MatchmakingServerMessage(
+                                        MatchmakingServerMessage::NoFreeServers
+                                    )
+                                }
+                                Some(server_info) =>
+                                {
                                     server_info.players_amount_by_matchmaking_server += 1;
 
-                                    if config.clients_connecting_via_proxy_server 
-                                    {
-                                        println!(
-                                            "DEBUG: CLIENT PATH: ws://{}:{}/ws/{} (via proxy)",
-                                            config.proxy_server_ip,
-                                            config.proxy_server_port,
-                                            server_info.game_server_main_port
-                                        );
-                                    } 
-                                    else 
-                                    {
-                                        println!(
-                                            "DEBUG: CLIENT PATH: ws://{}:{}/ (direct)",
-                                            server_info.game_server_ip_address,
-                                            server_info.game_server_main_port
-                                        );
-                                    }
-                                    let message = if config.clients_connecting_via_proxy_server {
-                                        ClientMatchmakingServerProtocol::MatchmakingServerMessage(
-                                            MatchmakingServerMessage::GameServerAddressThroughProxy((
-                                                config.proxy_server_ip.octets(),
-                                                config.proxy_server_port,
-                                                server_info.game_server_main_port,
-                                            )),
-                                        )
-                                    } else {
-                                        ClientMatchmakingServerProtocol::MatchmakingServerMessage(
-                                            MatchmakingServerMessage::GameServerAddress((
-                                                server_info.game_server_ip_address.octets(),
-                                                server_info.game_server_main_port,
-                                            )),
-                                        )
-                                    };
+                                    get_game_server_address_message(&config, server_info)
+                                }
+                            };
+
+                            sender_to_client
+                                .send(tokio_tungstenite::tungstenite::Message::binary(message.to_packet()))
+                                .await
+                                .unwrap();
 
-                                    let message: Vec<u8> = message.to_packet();
+                            continue ;
+                        }
+
+                        ClientMessage::CreateParty(clients_game_version, client_game_type) =>
+                        {
+                            println!("INFO: Client is creating a party");
+
+                            let clients_game_version = GameVersion::from(clients_game_version);
+
+                            config = load_config().await;
+
+                            let pool = match config.get_game_server_pool(clients_game_version)
+                            {
+                                Some(pool) => pool.clone(),
+                                None =>
+                                {
+                                    println!("WARNING: Client's game version {:?} is not supported", clients_game_version);
+
+                                    let message = ClientMatchmakingServerProtocol::MatchmakingServerMessage(
+                                        MatchmakingServerMessage::WrongGameVersionCorrectIs(config.current_game_version.clone().into())
+                                    );
 
                                     sender_to_client
-                                        .send(tokio_tungstenite::tungstenite::Message::binary( message))
+                                        .send(tokio_tungstenite::tungstenite::Message::binary(message.to_packet()))
                                         .await
                                         .unwrap();
 
-                                    continue ;
+                                    return ;
+                                }
+                            };
+
+                            let party_code = {
+                                let mut locked_parties = parties.lock().await;
+
+                                let party_code = generate_join_code(|party_code| locked_parties.contains_key(party_code));
+
+                                locked_parties.insert(
+                                    party_code,
+                                    Party {
+                                        game_version: clients_game_version,
+                                        game_type: client_game_type.clone(),
+                                        members: Vec::new(),
+                                    }
+                                );
+
+                                party_code
+                            };
+
+                            println!("INFO: Party {} is created", String::from_utf8_lossy(&party_code));
+
+                            let message = ClientMatchmakingServerProtocol::MatchmakingServerMessage(
+                                MatchmakingServerMessage::PartyCreated(party_code)
+                            );
+
+                            sender_to_client
+                                .send(tokio_tungstenite::tungstenite::Message::binary(message.to_packet()))
+                                .await
+                                .unwrap();
+
+                            // the party is disbanded if the leader disconnects
+                            // or sends anything but StartPartyMatchmaking
+                            let start_request = loop
+                            {
+                                match receiver_from_client.next().await
+                                {
+                                    Some(Ok(msg)) if msg.is_ping() || msg.is_pong() => continue,
+                                    start_request => break start_request,
                                 }
+                            };
 
-                                None => {
+                            // members' senders are dropped with the party if it is disbanded,
+                            // so the members get PartyDisbanded
+                            let party = parties.lock().await.remove(&party_code).unwrap();
 
-                                    println!("INFO: Free game server is not finded, creating new one");
+                            let is_start_request = match start_request
+                            {
+                                Some(Ok(msg)) =>
+                                {
+                                    matches!(
+                                        alkahest::deserialize::<ClientMatchmakingServerProtocol, ClientMatchmakingServerProtocol>(&msg.into_data()),
+                                        Ok(ClientMatchmakingServerProtocol::ClientMessage(ClientMessage::StartPartyMatchmaking))
+                                    )
+                                }
+                                _ => false,
+                            };
 
-                                    // update config to change max_game_sessions, max_players_per_session and current_game_version dynamically
-                                    config = load_config().await;
-                                    println!("DEBUG: CONFIG UPDATED: {:#?}", config);
+                            if !is_start_request
+                            {
+                                println!("INFO: Party {} is disbanded, the leader left", String::from_utf8_lossy(&party_code));
 
-                                    if clients_game_version != config.current_game_version
+                                return ;
+                            }
+
+                            let party_size = party.members.len() as u32 + 1;
+
+                            println!(
+                                "INFO: Party {} of {} players is looking for a game server",
+                                String::from_utf8_lossy(&party_code),
+                                party_size
+                            );
+
+                            let mut locked_state = state.lock().await;
+
+                            // the party's slots are taken on the finded game server right away
+                            let finded_server = locked_state
+                                .running
+                                .values_mut()
+                                .find(|server_info| {
+                                    server_info.players_amount_by_matchmaking_server + party_size <= server_info.max_amount_of_players
+                                    &&
+                                    server_info.game_server_game_version == party.game_version
+                                    &&
+                                    server_info.game_type == party.game_type
+                                    &&
+                                    server_info.join_code.is_none()
+                                })
+                                .map(|server_info| {
+                                    server_info.players_amount_by_matchmaking_server += party_size;
+
+                                    server_info.clone()
+                                });
+
+                            let free_port = match finded_server
+                            {
+                                None if party_size <= config.max_players_per_game_session =>
+                                {
+                                    reserve_free_server_port(
+                                        &mut locked_state,
+                                        &pool,
+                                        config.max_game_sessions,
+                                        config.game_servers_min_port_for_signaling_servers,
+                                        config.game_servers_max_port_for_signaling_servers,
+                                        None,
+                                    )
+                                }
+                                _ => None,
+                            };
+
+                            // other clients' requests are handled while the game server is starting
+                            drop(locked_state);
+
+                            let server_info = match (finded_server, free_port)
+                            {
+                                (Some(server_info), _) => Ok(server_info),
+                                (None, Some(new_port)) =>
+                                {
+                                    spawn_reserved_game_server(
+                                        new_port,
+                                        &config,
+                                        &pool,
+                                        async_rutime.clone(),
+                                        state.clone(),
+                                        party.game_type.clone(),
+                                        party_size,
+                                    ).await
+                                }
+                                (None, None) => Err(()),
+                            };
+
+                            let server_info = match server_info
+                            {
+                                Ok(server_info) => server_info,
+                                Err(_) =>
+                                {
+                                    println!("WARNING: There is no game server for the party of {} players", party_size);
+
+                                    let message = ClientMatchmakingServerProtocol::MatchmakingServerMessage(
+                                        MatchmakingServerMessage::NoFreeServers
+                                    )
+                                    .to_packet();
+
+                                    for member in party.members
                                     {
-                                        println!("WARNING: Client's game version is not correct");
+                                        let _ = member.send(vec![message.clone()]);
+                                    }
 
-                                        let message = ClientMatchmakingServerProtocol::MatchmakingServerMessage(
-                                            MatchmakingServerMessage::WrongGameVersionCorrectIs(config.current_game_version.clone().into())
-                                        );
+                                    sender_to_client
+                                        .send(tokio_tungstenite::tungstenite::Message::binary(message))
+                                        .await
+                                        .unwrap();
+
+                                    return ;
+                                }
+                            };
+
+                            let party_id = (get_random_u64() as u128) << 64 | get_random_u64() as u128;
+
+                            println!(
+                                "INFO: Party {} goes to [{}] game server",
+                                String::from_utf8_lossy(&party_code),
+                                server_info.server_index
+                            );
+
+                            let packets = vec![
+                                ClientMatchmakingServerProtocol::MatchmakingServerMessage(
+                                    MatchmakingServerMessage::PartyIdIs(party_id)
+                                )
+                                .to_packet(),
+                                get_game_server_address_message(&config, &server_info).to_packet(),
+                            ];
+
+                            for member in party.members
+                            {
+                                // the member is disconnected if the send fails,
+                                // its slot is freed by the next game servers status check
+                                let _ = member.send(packets.clone());
+                            }
+
+                            for packet in packets
+                            {
+                                sender_to_client
+                                    .send(tokio_tungstenite::tungstenite::Message::binary(packet))
+                                    .await
+                                    .unwrap();
+                            }
+
+                            continue ;
+                        }
+
+                        ClientMessage::JoinParty(clients_game_version, party_code) =>
+                        {
+                            println!("INFO: Client is joining party {}", String::from_utf8_lossy(&party_code));
+
+                            let clients_game_version = GameVersion::from(clients_game_version);
+
+                            let (member_sender, member_receiver) = oneshot::channel::<Vec<Vec<u8>>>();
+
+                            let message = match parties.lock().await.get_mut(&party_code)
+                            {
+                                None =>
+                                {
+                                    println!("WARNING: Party {} doesn't exist", String::from_utf8_lossy(&party_code));
+
+                                    MatchmakingServerMessage::PartyNotFound
+                                }
+                                Some(party) if party.game_version != clients_game_version =>
+                                {
+                                    println!("WARNING: Party {} has another game version than the client", String::from_utf8_lossy(&party_code));
+
+                                    MatchmakingServerMessage::WrongGameVersionCorrectIs(party.game_version.into())
+                                }
+                                Some(party) if party.members.len() as u32 + 1 >= config.max_players_per_game_session =>
+                                {
+                                    println!("WARNING: Party {} is full", String::from_utf8_lossy(&party_code));
+
+                                    MatchmakingServerMessage::NoFreeServers
+                                }
+                                Some(party) =>
+                                {
+                                    party.members.push(member_sender);
+
+                                    MatchmakingServerMessage::PartyJoined(party.members.len() as u32 + 1)
+                                }
+                            };
+
+                            let is_joined = matches!(message, MatchmakingServerMessage::PartyJoined(_));
+
+                            sender_to_client
+                                .send(tokio_tungstenite::tungstenite::Message::binary(
+                                    ClientMatchmakingServerProtocol::MatchmakingServerMessage(message).to_packet()
+                                ))
+                                .await
+                                .unwrap();
+
+                            if !is_joined
+                            {
+                                return ;
+                            }
+
+                            let packets = match member_receiver.await
+                            {
+                                Ok(packets) => packets,
+                                Err(_) =>
+                                {
+                                    vec![
+                                        ClientMatchmakingServerProtocol::MatchmakingServerMessage(
+                                            MatchmakingServerMessage::PartyDisbanded
+                                        )
+                                        .to_packet()
+                                    ]
+                                }
+                            };
+
+                            for packet in packets
+                            {
+                                // the member may have left while waiting
+                                if sender_to_client
+                                    .send(tokio_tungstenite::tungstenite::Message::binary(packet))
+                                    .await
+                                    .is_err()
+                                {
+                                    return ;
+                                }
+                            }
+
+                            continue ;
+                        }
+
+                        ClientMessage::StartPartyMatchmaking =>
+                        {
+                            println!("WARNING: Client is not a party leader, but it's starting the party");
+
+                            return ;
+                        }
+                    }
+                },
+                _ => {
+                    println!("WARNING: Incorrect request to the matchmaking server on client's port ");
+                    return ;
+                }
+            }
+        }
+    }
+}
+
+
+// Clients of the same game version and game type wait in the queue together.
+// Every QUEUE_STATUS_INTERVAL (or right after a new client is queued) the queue
+// first gives free slots of running public game servers to the first clients,
+// then starts a new game server for a group of at least
+// min_players_to_start_game_server clients (or a group waiting longer than
+// MAX_QUEUE_WAIT_TIME) and sends QueueStatus to the rest.
+// The game server is spawned without holding the queue and the game servers
+// state, so one new game server is started per pass.
+async fn process_matchmaking_queue(
+    queue: QueueState,
+    queue_notify: Arc<Notify>,
+    state: GameServersState,
+    async_rutime: Arc<Runtime>,
+)
+{
+    loop
+    {
+        let _ = tokio::time::timeout(QUEUE_STATUS_INTERVAL, queue_notify.notified()).await;
+
+        let mut locked_queue = queue.lock().await;
+
+        // the receiver is dropped when the client disconnects
+        locked_queue.clients.retain(|client| !client.sender.is_closed());
+
+        if locked_queue.clients.is_empty()
+        {
+            continue;
+        }
+
+        let config = locked_queue.config.clone();
+
+        let mut locked_state = state.lock().await;
+
+        // filling partially full game servers takes priority over starting new ones
+        let clients = std::mem::take(&mut locked_queue.clients);
+
+        for client in clients
+        {
+            let finded_server = locked_state.running.values_mut().find(|server_info| {
+                server_info.players_amount_by_matchmaking_server < server_info.max_amount_of_players
+                &&
+                server_info.game_server_game_version == client.game_version
+                &&
+                server_info.game_type == client.game_type
+                &&
+                server_info.join_code.is_none()
+            });
+
+            match finded_server
+            {
+                Some(server_info) =>
+                {
+                    println!("INFO: Free game server [{}] is finded for the queued client", server_info.server_index);
+
+                    server_info.players_amount_by_matchmaking_server += 1;
+
+                    let packet = get_game_server_address_message(&config, server_info).to_packet();
+
+                    locked_queue.send_to_matched_client(client, packet);
+                }
+                None => locked_queue.clients.push(client),
+            }
+        }
+
+        let mut queue_groups: Vec<(GameVersion, GameType)> = Vec::new();
+
+        for client in &locked_queue.clients
+        {
+            if !queue_groups
+                .iter()
+                .any(|(game_version, game_type)| *game_version == client.game_version && *game_type == client.game_type)
+            {
+                queue_groups.push((client.game_version, client.game_type.clone()));
+            }
+        }
+
+        let mut new_game_server = None;
+
+        for (game_version, game_type) in queue_groups
+        {
+            let group_size = locked_queue.clients
+                .iter()
+                .filter(|client| client.game_version == game_version && client.game_type == game_type)
+                .count() as u32;
+
+            // clients are in the order of arrival
+            let group_waited_too_long = locked_queue.clients
+                .iter()
+                .find(|client| client.game_version == game_version && client.game_type == game_type)
+                .map_or(false, |client| client.enqueue_time.elapsed() >= MAX_QUEUE_WAIT_TIME);
+
+            if group_size < config.min_players_to_start_game_server.max(1) && !group_waited_too_long
+            {
+                continue;
+            }
+
+            let pool = match config.get_game_server_pool(game_version)
+            {
+                Some(pool) => pool.clone(),
+                // the version is dropped from the config while the clients are waiting
+                None =>
+                {
+                    println!("WARNING: Game version {:?} of the queued clients is not supported anymore", game_version);
+
+                    let packet = ClientMatchmakingServerProtocol::MatchmakingServerMessage(
+                        MatchmakingServerMessage::WrongGameVersionCorrectIs(config.current_game_version.clone().into())
+                    )
+                    .to_packet();
+
+                    for client in locked_queue.take_clients(&game_version, &game_type, group_size)
+                    {
+                        let _ = client.sender.send(packet.clone());
+                    }
+
+                    continue;
+                }
+            };
+
+            let free_port = reserve_free_server_port(
+                &mut locked_state,
+                &pool,
+                config.max_game_sessions,
+                config.game_servers_min_port_for_signaling_servers,
+                config.game_servers_max_port_for_signaling_servers,
+                None,
+            );
+
+            // the clients keep waiting until some game server is stopped
+            let new_port = match free_port
+            {
+                Some(new_port) => new_port,
+                None => continue,
+            };
+
+            println!(
+                "INFO: {} clients of {:?} version are waiting, creating new game server",
+                group_size,
+                game_version
+            );
+
+            let matched_clients_amount = group_size.min(config.max_players_per_game_session);
+
+            let clients = locked_queue.take_clients(&game_version, &game_type, matched_clients_amount);
+
+            new_game_server = Some((new_port, pool, game_type, clients));
+
+            break;
+        }
+
+        drop(locked_state);
+
+        locked_queue.send_queue_status();
+
+        drop(locked_queue);
+
+        let (new_port, pool, game_type, clients) = match new_game_server
+        {
+            Some(new_game_server) => new_game_server,
+            None => continue,
+        };
+
+        // the port is reserved in the game servers state, so private game servers
+        // and parties started meanwhile count the new game server in the limits
+        let server_info = spawn_reserved_game_server(
+            new_port,
+            &config,
+            &pool,
+            async_rutime.clone(),
+            state.clone(),
+            game_type,
+            clients.len() as u32,
+        ).await;
+
+        match server_info
+        {
+            Ok(server_info) =>
+            {
+                println!("INFO: New game server [{}] is successfully created for {} queued clients", server_info.server_index, clients.len());
+
+                let packet = get_game_server_address_message(&config, &server_info).to_packet();
+
+                let mut locked_queue = queue.lock().await;
+
+                for client in clients
+                {
+                    locked_queue.send_to_matched_client(client, packet.clone());
+                }
+            }
+            Err(_) =>
+            {
+                println!("WARNING: Can not create new game server for the queued clients");
+
+                let packet = ClientMatchmakingServerProtocol::MatchmakingServerMessage(
+                    MatchmakingServerMessage::NoFreeServers
+                )
+                .to_packet();
+
+                for client in clients
+                {
+                    let _ = client.sender.send(packet.clone());
+                }
+            }
+        }
+    }
+}
+
+
+fn get_game_server_address_message(
+    config: &Config,
+    server_info: &GameServerInfo,
+) -> ClientMatchmakingServerProtocol
+{
+    if config.clients_connecting_via_proxy_server
+    {
+        ClientMatchmakingServerProtocol::MatchmakingServerMessage(
+            MatchmakingServerMessage::GameServerAddressThroughProxy((
+                config.proxy_server_ip.octets(),
+                config.proxy_server_port,
+                server_info.game_server_main_port,
+            ))
+        )
+    }
+    else
+    {
+        ClientMatchmakingServerProtocol::MatchmakingServerMessage(
+            MatchmakingServerMessage::GameServerAddress((
+                server_info.game_server_ip_address.octets(),
+                server_info.game_server_main_port,
+            ))
+        )
+    }
+}
 
-                                        let message: Vec<u8> = message.to_packet();
 
-                                        sender_to_client
-                                            .send(tokio_tungstenite::tungstenite::Message::binary(message))
-                                            .await
-                                            .unwrap();
+fn get_random_u64() -> u64
+{
+    // RandomState is seeded randomly for every matchmaking server process
+    let mut hasher = RandomState::new().build_hasher();
 
-                                        return ;
-                                    }
-                                    println!("INFO: Client's game version is correct");
+    hasher.write_u128(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0u128, |duration| duration.as_nanos())
+    );
 
-                                    let free_port = get_free_server_port(
-                                        &mut locked_state,
-                                        config.max_game_sessions,
-                                        config.game_servers_min_port_for_signaling_servers,
-                                        config.game_servers_max_port_for_signaling_servers,
-                                    );
+    hasher.finish()
+}
 
-                                    match free_port {
-                                        Some(new_port) =>
-                                        {
-                                            let server_info = spawn_game_server(
-                                                new_port,
-                                                &config,
-                                                async_rutime.clone(),
-                                                state.clone(),
-                                                client_game_type
-                                            ).await;
-
-                                            if server_info.is_err()
-                                            {
-                                                println!("WARNING: Can not create new game server because is out of the limit");
 
-                                                let message = ClientMatchmakingServerProtocol::MatchmakingServerMessage(
-                                                    MatchmakingServerMessage::NoFreeServers
-                                                );
-            
-                                                let message: Vec<u8> = message.to_packet();
-            
-                                                sender_to_client
-                                                    .send(tokio_tungstenite::tungstenite::Message::binary( message))
-                                                    .await
-                                                    .unwrap();
-            
-                                                return ;
-                                            }
+// random join code for which is_code_used returns false
+fn generate_join_code(
+    is_code_used: impl Fn(&[u8; JOIN_CODE_LENGTH]) -> bool,
+) -> [u8; JOIN_CODE_LENGTH]
+{
+    loop
+    {
+        let mut random = get_random_u64();
 
-                                            let server_info = server_info.unwrap();
-        
-                                            println!("INFO: New game server is successfully created, send to the client server's addres");
-        
-                                            locked_state.insert(server_info.server_index, server_info.clone());
+        let mut join_code = [0u8; JOIN_CODE_LENGTH];
 
-                                            if config.clients_connecting_via_proxy_server 
-                                            {
-                                                println!(
-                                                    "DEBUG: CLIENT PATH: ws://{}:{}/ws/{} (via proxy)",
-                                                    config.proxy_server_ip,
-                                                    config.proxy_server_port,
-                                                    server_info.game_server_main_port
-                                                );
-                                            } 
-                                            else 
-                                            {
-                                                println!(
-                                                    "DEBUG: CLIENT PATH: ws://{}:{}/ (direct)",
-                                                    server_info.game_server_ip_address,
-                                                    server_info.game_server_main_port
-                                                );
-                                            }
+        for byte in join_code.iter_mut()
+        {
+            *byte = JOIN_CODE_ALPHABET[(random % JOIN_CODE_ALPHABET.len() as u64) as usize];
 
-                                            let message = if config.clients_connecting_via_proxy_server
-                                            {
-                                                ClientMatchmakingServerProtocol::MatchmakingServerMessage(
-                                                    MatchmakingServerMessage::GameServerAddressThroughProxy((
-                                                        config.proxy_server_ip.octets(),
-                                                        config.proxy_server_port,
-                                                        server_info.game_server_main_port
-                                                    ))
-                                                )
-                                            }
-                                            else
-                                            {
-                                                ClientMatchmakingServerProtocol::MatchmakingServerMessage(
-                                                    MatchmakingServerMessage::GameServerAddress((
-                                                        server_info.game_server_ip_address.octets(),
-                                                        server_info.game_server_main_port
-                                                    ))
-                                                )
-                                            };
-        
-                                            let message: Vec<u8> = message.to_packet();
-        
-                                            sender_to_client
-                                                .send(tokio_tungstenite::tungstenite::Message::binary( message))
-                                                .await
-                                                .unwrap();
-        
-                                            continue ;
-                                        }
-                                        None =>
-                                        {
-                                            println!("WARNING: Can not create new game server because is out of the limit");
+            random /= JOIN_CODE_ALPHABET.len() as u64;
+        }
 
-                                            let message = ClientMatchmakingServerProtocol::MatchmakingServerMessage(
-                                                MatchmakingServerMessage::NoFreeServers
-                                            );
-        
-                                            let message: Vec<u8> = message.to_packet();
-        
-                                            sender_to_client
-                                                .send(tokio_tungstenite::tungstenite::Message::binary( message))
-                                                .await
-                                                .unwrap();
-        
-                                            return ;
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                },
-                _ => {
-                    println!("WARNING: Incorrect request to the matchmaking server on client's port ");
-                    return ;
-                }
-            }
+        if !is_code_used(&join_code)
+        {
+            return join_code;
         }
     }
 }
 
 
 fn get_free_server_port(
-    locked_state:  &mut MutexGuard<'_, HashMap<u16, GameServerInfo>>,
+    locked_state:  &MutexGuard<'_, GameServers>,
+    pool: &GameServerPoolConfig,
     max_game_sessions: u32,
     min_port: u16,
     max_port: u16,
 ) -> Option<u16>
 {
-    if locked_state.len() >= max_game_sessions as usize {
+    if locked_state.running.len() + locked_state.reserved.len() >= max_game_sessions as usize {
+        return None;
+    }
+
+    let pool_game_sessions = locked_state.running
+        .values()
+        .filter(|server_info| server_info.game_server_game_version == pool.game_version)
+        .count()
+        +
+        locked_state.reserved
+        .values()
+        .filter(|reserved_server| reserved_server.game_version == pool.game_version)
+        .count();
+
+    if pool_game_sessions >= pool.max_game_sessions as usize {
         return None;
     }
 
     for port in min_port..=max_port {
-        if !locked_state.contains_key(&port) {
+        if !locked_state.running.contains_key(&port) && !locked_state.reserved.contains_key(&port) {
             return Some(port);
         }
     }
@@ -455,33 +1497,151 @@ fn get_free_server_port(
 }
 
 
+// the found port is reserved until spawn_reserved_game_server
+// replaces the reservation with the spawned game server
+fn reserve_free_server_port(
+    locked_state:  &mut MutexGuard<'_, GameServers>,
+    pool: &GameServerPoolConfig,
+    max_game_sessions: u32,
+    min_port: u16,
+    max_port: u16,
+    join_code: Option<[u8; JOIN_CODE_LENGTH]>,
+) -> Option<u16>
+{
+    let port = get_free_server_port(locked_state, pool, max_game_sessions, min_port, max_port)?;
+
+    locked_state.reserved.insert(
+        port,
+        ReservedGameServer {
+            game_version: pool.game_version,
+            join_code,
+        }
+    );
+
+    Some(port)
+}
+
+
+// spawns the game server with the reservation's join code without holding
+// the game servers state, then the reservation is replaced by the game server
+// with players_amount players given by the matchmaking server,
+// or removed if the spawn fails
+async fn spawn_reserved_game_server(
+    port: u16,
+    config: &Config,
+    pool: &GameServerPoolConfig,
+    async_rutime: Arc<Runtime>,
+    state: GameServersState,
+    game_type: GameType,
+    players_amount: u32,
+) -> Result<GameServerInfo, ()>
+{
+    let join_code = state
+        .lock()
+        .await
+        .reserved
+        .get(&port)
+        .and_then(|reserved_server| reserved_server.join_code);
+
+    let server_info = spawn_game_server(
+        port,
+        config,
+        pool,
+        async_rutime,
+        state.clone(),
+        game_type,
+        join_code
+    ).await;
+
+    let mut locked_state = state.lock().await;
+
+    locked_state.reserved.remove(&port);
+
+    let mut server_info = server_info?;
+
+    server_info.players_amount_by_matchmaking_server = players_amount;
+
+    locked_state.running.insert(server_info.server_index, server_info.clone());
+
+    Ok(server_info)
+}
+
+
 async fn spawn_game_server(
     port: u16,
     config: &Config,
+    pool: &GameServerPoolConfig,
     async_rutime: Arc<Runtime>,
     state: GameServersState,
     game_type: GameType,
+    join_code: Option<[u8; JOIN_CODE_LENGTH]>,
 ) -> Result<GameServerInfo, ()>
 {
-    let server_process = Command::new("./game_server")
-        .arg(port.to_string())
-        .arg(config.game_servers_min_port_for_signaling_servers.to_string())
-        .arg(config.game_servers_max_port_for_signaling_servers.to_string())
-        .arg(config.game_servers_min_port_for_tcp_listener.to_string())
-        .arg(config.game_servers_max_port_for_tcp_listener.to_string())
-        .arg("127.0.0.1")
-        .arg(config.matchmaking_server_port_for_servers.to_string())
-        .arg(config.max_players_per_game_session.to_string())
-        .arg(config.game_servers_ice_config.urls.clone())
-        .arg(config.game_servers_ice_config.username.clone())
-        .arg(config.game_servers_ice_config.credential.clone())
+    let mut server_command = Command::new(&pool.game_server_binary);
+    
+    server_command
+        .arg("--signaling-port").arg(port.to_string())
+        .arg("--min-signaling-port").arg(config.game_servers_min_port_for_signaling_servers.to_string())
+        .arg("--max-signaling-port").arg(config.game_servers_max_port_for_signaling_servers.to_string())
+        .arg("--min-tcp-listener-port").arg(config.game_servers_min_port_for_tcp_listener.to_string())
+        .arg("--max-tcp-listener-port").arg(config.game_servers_max_port_for_tcp_listener.to_string())
+        .arg("--max-players").arg(config.max_players_per_game_session.to_string());
+
+    for url in &config.game_servers_ice_config.urls
+    {
+        server_command
+            .arg("--ice-url")
+            .arg(url);
+    }
+
+    if !config.game_servers_ice_config.username.is_empty()
+    {
+        server_command
+            .arg("--turn-server-username")
+            .arg(&config.game_servers_ice_config.username);
+    }
+
+    if !config.game_servers_ice_config.credential.is_empty()
+    {
+        server_command
+            .arg("--turn-server-credential")
+            .arg(&config.game_servers_ice_config.credential);
+    }
+
+    if let Some(match_rules_file) = &config.game_servers_match_rules_file
+    {
+        server_command
+            .arg("--match-rules")
+            .arg(match_rules_file);
+    }
+
+    if let Some(join_code) = &join_code
+    {
+        server_command
+            .arg("--join-code")
+            .arg(String::from_utf8_lossy(join_code).to_string());
+    }
+
+    // the 3D example client can load only its own map
+    if game_type == GameType::Slice3DExample
+    {
+        server_command
+            .arg("--map-rotation")
+            .arg("map_2d_3d");
+    }
+
+    let server_process = server_command
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .spawn();
 
     let mut server_process = match server_process {
         Ok(server_process) => server_process,
-        Err(e) => return Err(()), 
+        Err(e) =>
+        {
+            println!("ERROR: can't spawn {} game server, err: {}", pool.game_server_binary, e);
+            return Err(());
+        }
     };
 
     let server_stdout = server_process.stdout.take().unwrap();
@@ -498,7 +1658,12 @@ async fn spawn_game_server(
             let game_server_main_port: u16 = lines[1].parse().unwrap();
             let matchmaking_server_listener_port: u16 = lines[2].parse().unwrap();
 
-            println!("INFO: spawn new game server [{}] on {} port", game_server_main_port, game_server_main_port);
+            println!(
+                "INFO: spawn new game server [{}] on {} port, version {:?}",
+                game_server_main_port,
+                game_server_main_port,
+                pool.game_version
+            );
 
             async_rutime.spawn(
                 keep_server_process(
@@ -516,7 +1681,10 @@ async fn spawn_game_server(
                 players_amount_by_matchmaking_server: 1_u32,
                 players_amount_by_game_server: 0_u32,
                 max_amount_of_players: config.max_players_per_game_session,
-                game_server_game_version: config.current_game_version,
+                game_server_game_version: pool.game_version,
+                map_name: [0u8; MAX_GAME_SERVER_INFO_NAME_LENGTH],
+                game_mode: [0u8; MAX_GAME_SERVER_INFO_NAME_LENGTH],
+                join_code,
                 game_server_main_port,
                 matchmaking_server_listener_port,
                 server_index: game_server_main_port,
@@ -540,7 +1708,7 @@ async fn spawn_game_server(
 
 
 async fn keep_server_process(
-    server_process: Child,
+    mut server_process: Child,
     mut server_stdout_reader: Lines<BufReader<ChildStdout>>,
     mut server_stderr: ChildStderr,
     server_index: u16,
@@ -553,13 +1721,20 @@ async fn keep_server_process(
 
     let mut locked_state = state.lock().await;
 
-    locked_state.remove(&server_index);
+    locked_state.running.remove(&server_index);
 
     let mut err = String::new();
 
     server_stderr.read_to_string(&mut err).await.unwrap();
 
     println!("{}", err);
+
+    // the exited game server is waited for, otherwise it stays a zombie process
+    match server_process.wait().await
+    {
+        Ok(exit_status) => println!("INFO: [{}] server exited with {}", server_index, exit_status),
+        Err(e) => println!("WARNING: [{}] can't get the server's exit status, err: {}", server_index, e),
+    }
 }
 
 fn main() {
@@ -581,9 +1756,40 @@ async fn async_main(
     async_runtime: Arc<Runtime>
 ) {
     let config = load_config().await;
-    println!("DEBUG: CONFIG UPDATED: {:#?}", config);
 
-    let game_servers_state = Arc::new(Mutex::new(HashMap::<u16, GameServerInfo>::new()));
+    // the whole config isn't printed because it contains the TURN server's credentials
+    println!(
+        "INFO: current game version: {:?}, min supported game version: {:?}, max game sessions: {}, max players per game session: {}",
+        config.current_game_version,
+        config.min_supported_game_version,
+        config.max_game_sessions,
+        config.max_players_per_game_session,
+    );
+
+    for pool in &config.game_server_pools
+    {
+        println!(
+            "INFO: game server pool {:?}: {}, max game sessions: {}",
+            pool.game_version,
+            pool.game_server_binary,
+            pool.max_game_sessions,
+        );
+    }
+
+    let game_servers_state = Arc::new(Mutex::new(GameServers {
+        running: HashMap::new(),
+        reserved: HashMap::new(),
+    }));
+
+    let parties_state = Arc::new(Mutex::new(HashMap::<[u8; JOIN_CODE_LENGTH], Party>::new()));
+
+    let queue_state = Arc::new(Mutex::new(MatchmakingQueue {
+        clients: Vec::new(),
+        config: config.clone(),
+        average_wait_time: 0.0,
+    }));
+
+    let queue_notify = Arc::new(Notify::new());
     
     let clients_listener = TcpListener::bind(
         &("0.0.0.0:".to_string() + &config.matchmaking_server_port_for_clients.to_string())
@@ -593,6 +1799,29 @@ async fn async_main(
         game_servers_state.clone(),
         async_runtime.clone(),
     ));
+
+    async_runtime.spawn(process_matchmaking_queue(
+        queue_state.clone(),
+        queue_notify.clone(),
+        game_servers_state.clone(),
+        async_runtime.clone(),
+    ));
+
+    if let Some(admin_port) = config.matchmaking_server_port_for_admin
+    {
+        // the admin port is available only on the local machine
+        let admin_listener = TcpListener::bind(("127.0.0.1", admin_port))
+            .await
+            .expect("ERROR: can't bind the matchmaking server's admin port");
+
+        async_runtime.spawn(listening_to_admin(
+            admin_listener,
+            game_servers_state.clone(),
+            async_runtime.clone(),
+        ));
+
+        println!("INFO: admin commands are accepted on 127.0.0.1:{}", admin_port);
+    }
     
     loop
     {
@@ -603,6 +1832,9 @@ async fn async_main(
                     handle_client_connection(
                         stream,
                         game_servers_state.clone(),
+                        parties_state.clone(),
+                        queue_state.clone(),
+                        queue_notify.clone(),
                         config.clone(),
                         async_runtime.clone(),
                     )
@@ -629,7 +1861,7 @@ async fn check_game_servers_status(
 
         let mut game_servers_list_to_be_stopped = Vec::new();
 
-        for (game_server_index, game_server_info) in locked_state.iter_mut()
+        for (game_server_index, game_server_info) in locked_state.running.iter_mut()
         {
             let mut stop_server = false;
 
@@ -682,6 +1914,18 @@ async fn check_game_servers_status(
                                                             println!("[{}] game server has {} of {} players", game_server_info.server_index, players_amount, game_server_info.max_amount_of_players);
 
                                                         }
+                                                        GameServerMessage::GameServerStatusIs(players_amount, map_name, game_mode) =>
+                                                        {
+                                                            game_server_info.players_amount_by_game_server = players_amount;
+                                                            game_server_info.map_name = map_name;
+                                                            game_server_info.game_mode = game_mode;
+
+                                                            println!("[{}] game server has {} of {} players", game_server_info.server_index, players_amount, game_server_info.max_amount_of_players);
+                                                        }
+                                                        GameServerMessage::AdminCommandAccepted =>
+                                                        {
+                                                            println!("ERROR, matchmaking server recieved unexpected AdminCommandAccepted from [{}] game server", game_server_info.server_index);
+                                                        }
                                                     }
                                                 }
                                                 GameServerMatchmakingServerProtocol::MatchmakingServerMessageToGameServer(_) =>
@@ -729,7 +1973,7 @@ async fn check_game_servers_status(
 
         while let Some((game_server_index, game_server_pid)) = game_servers_list_to_be_stopped.pop()
         {
-            locked_state.remove(&game_server_index);
+            locked_state.running.remove(&game_server_index);
             println!("[{}] game server is stopped", game_server_index);
             
             async_runtime.spawn(stop_game_server(game_server_pid));
@@ -737,20 +1981,256 @@ async fn check_game_servers_status(
 
         let mut total_players_amount = 0u32;
 
-        for (_, game_server_info) in locked_state.iter_mut()
+        for (_, game_server_info) in locked_state.running.iter_mut()
         {
             game_server_info.players_amount_by_matchmaking_server = game_server_info.players_amount_by_game_server;
 
             total_players_amount += game_server_info.players_amount_by_game_server;
         }
 
-        println!("{} game servers is running with {} player in total", locked_state.len(), total_players_amount);
+        println!("{} game servers is running with {} player in total", locked_state.running.len(), total_players_amount);
         
         tokio::time::sleep(Duration::from_secs(4)).await;
     }
 }
 
 
+pub const ADMIN_USAGE: &str =
+"Admin commands (one command per line):
+    list                                        show running game servers
+    <server_index> kick <player_id>             kick a player or a spectator
+    <server_index> swap-team <player_id> <team_id>
+                                                move a player to another team
+    <server_index> new-session                  start a new game session
+    <server_index> set-score <team_id> <score>  set a team's score
+    <server_index> pause                        pause the match timer, items respawn and scoring
+    <server_index> resume                       resume the paused match
+    <server_index> shutdown                     shut down the game server
+    help                                        show this message\n";
+
+async fn listening_to_admin(
+    admin_listener: TcpListener,
+    game_servers_state: GameServersState,
+    async_runtime: Arc<Runtime>,
+)
+{
+    loop
+    {
+        match admin_listener.accept().await
+        {
+            Ok((stream, _)) =>
+            {
+                async_runtime.spawn(handle_admin_connection(
+                    stream,
+                    game_servers_state.clone(),
+                ));
+            }
+            Err(e) =>
+            {
+                println!("ERROR: admin listener error, err: {}", e);
+            }
+        }
+    }
+}
+
+// admin connection is a plain text session, for example `nc 127.0.0.1 <admin port>`
+async fn handle_admin_connection(
+    stream: TcpStream,
+    game_servers_state: GameServersState,
+)
+{
+    let (reader, mut writer) = stream.into_split();
+
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Ok(Some(line)) = lines.next_line().await
+    {
+        let line = line.trim();
+
+        if line.is_empty()
+        {
+            continue;
+        }
+
+        let answer = match line
+        {
+            "help" => ADMIN_USAGE.to_string(),
+
+            "list" =>
+            {
+                let locked_state = game_servers_state.lock().await;
+
+                let mut answer = format!("{} game servers is running\n", locked_state.running.len());
+
+                for (server_index, server_info) in locked_state.running.iter()
+                {
+                    answer += &format!(
+                        "[{}] {}game server has {} of {} players, map {}, mode {}, version {:?}, pid {}\n",
+                        server_index,
+                        if server_info.join_code.is_some() {"private "} else {""},
+                        server_info.players_amount_by_game_server,
+                        server_info.max_amount_of_players,
+                        name_from_bytes(&server_info.map_name),
+                        name_from_bytes(&server_info.game_mode),
+                        server_info.game_server_game_version,
+                        server_info.game_server_pid,
+                    );
+                }
+
+                answer
+            }
+
+            _ =>
+            {
+                match parse_admin_command(line)
+                {
+                    Ok((server_index, admin_command)) =>
+                    {
+                        println!("INFO: admin command {:?} for [{}] game server", admin_command, server_index);
+
+                        match send_admin_command_to_game_server(
+                            &game_servers_state,
+                            server_index,
+                            admin_command
+                        ).await
+                        {
+                            Ok(_) => "OK\n".to_string(),
+                            Err(e) => format!("ERROR: {}\n", e),
+                        }
+                    }
+                    Err(e) => format!("ERROR: {}\n", e),
+                }
+            }
+        };
+
+        if writer.write_all(answer.as_bytes()).await.is_err()
+        {
+            return;
+        }
+    }
+}
+
+fn parse_admin_command(line: &str) -> Result<(u16, AdminCommand), String>
+{
+    let words: Vec<&str> = line.split_whitespace().collect();
+
+    let server_index: u16 = words[0]
+        .parse()
+        .map_err(|_| format!("invalid game server index {}, type help to see admin commands", words[0]))?;
+
+    let command_name = words
+        .get(1)
+        .ok_or("have not command after the game server index".to_string())?;
+
+    let admin_command = match *command_name
+    {
+        "kick" =>
+        {
+            let player_id = get_admin_command_arg(&words, 0, "player_id")?;
+
+            AdminCommand::KickPlayer(
+                player_id.parse().map_err(|_| format!("invalid player_id {}", player_id))?
+            )
+        }
+
+        "swap-team" =>
+        {
+            let player_id = get_admin_command_arg(&words, 0, "player_id")?;
+            let team_id = get_admin_command_arg(&words, 1, "team_id")?;
+
+            AdminCommand::SwapPlayerTeam(
+                player_id.parse().map_err(|_| format!("invalid player_id {}", player_id))?,
+                team_id.parse().map_err(|_| format!("invalid team_id {}", team_id))?,
+            )
+        }
+
+        "new-session" => AdminCommand::StartNewGameSession,
+
+        "set-score" =>
+        {
+            let team_id = get_admin_command_arg(&words, 0, "team_id")?;
+            let score = get_admin_command_arg(&words, 1, "score")?;
+
+            AdminCommand::SetTeamScore(
+                team_id.parse().map_err(|_| format!("invalid team_id {}", team_id))?,
+                score.parse().map_err(|_| format!("invalid score {}", score))?,
+            )
+        }
+
+        "pause" => AdminCommand::PauseMatch,
+
+        "resume" => AdminCommand::ResumeMatch,
+
+        "shutdown" => AdminCommand::ShutDown,
+
+        _ => return Err(format!("unknown admin command {}, type help to see admin commands", command_name)),
+    };
+
+    Ok((server_index, admin_command))
+}
+
+// words of the admin command are <server_index> <command> <args...>
+fn get_admin_command_arg<'a>(words: &[&'a str], arg_index: usize, arg_name: &str) -> Result<&'a str, String>
+{
+    words
+        .get(2 + arg_index)
+        .copied()
+        .ok_or(format!("have not {} for {} command", arg_name, words[1]))
+}
+
+// the game server only queues the command, the result of the command
+// is printed to the game server's stdout
+async fn send_admin_command_to_game_server(
+    game_servers_state: &GameServersState,
+    server_index: u16,
+    admin_command: AdminCommand,
+) -> Result<(), String>
+{
+    let listener_port = game_servers_state
+        .lock()
+        .await
+        .running
+        .get(&server_index)
+        .map(|server_info| server_info.matchmaking_server_listener_port)
+        .ok_or(format!("[{}] game server is not running", server_index))?;
+
+    let mut stream = match tokio::time::timeout(
+        Duration::from_millis(300),
+        TcpStream::connect(("127.0.0.1", listener_port))
+    ).await
+    {
+        Ok(Ok(stream)) => stream,
+        _ => return Err(format!("can't connect to [{}] game server", server_index)),
+    };
+
+    let message = GameServerMatchmakingServerProtocol::MatchmakingServerMessageToGameServer(
+        matchmaking_server_protocol::MatchmakingServerMessageToGameServer::AdminCommand(admin_command)
+    );
+
+    stream
+        .write_all(&message.to_packet())
+        .await
+        .map_err(|e| format!("can't send the command to [{}] game server, err: {}", server_index, e))?;
+
+    let mut buf = Vec::new();
+
+    match tokio::time::timeout(
+        Duration::from_millis(300),
+        stream.read_buf(&mut buf)
+    ).await
+    {
+        Ok(Ok(_)) => {}
+        _ => return Err(format!("[{}] game server has not answered", server_index)),
+    }
+
+    match alkahest::deserialize::<GameServerMatchmakingServerProtocol, GameServerMatchmakingServerProtocol>(&buf)
+    {
+        Ok(GameServerMatchmakingServerProtocol::GameServerMessage(GameServerMessage::AdminCommandAccepted)) => Ok(()),
+        _ => Err(format!("unexpected answer from [{}] game server", server_index)),
+    }
+}
+
+
 async fn stop_game_server(pid: u32)
 {
     let kill_cmd = Command::new("kill")
@@ -935,11 +2415,81 @@ fn parse_json_matchmaking_config(json_config: Value) -> Config
 
     let current_game_version = GameVersion::from(current_game_version);
 
+    // optional field, only the current game version is supported if it is not specified
+    let min_supported_game_version = {
+        object
+            .get("min_supported_game_version")
+            .map(|value| {
+                GameVersion::from(
+                    value
+                        .as_str()
+                        .expect("ERROR: min_supported_game_version is not string value in matchmaking-server-config.json")
+                )
+            })
+            .unwrap_or(current_game_version)
+    };
+
+    let game_server_pools = GameServerPoolConfig::parse_json(
+        object,
+        current_game_version,
+        max_game_sessions,
+    );
+
     let game_servers_ice_config = GameServersIceConfig::parse_json(object);
 
+    // optional field, game servers use default match rules if it is not specified
+    let game_servers_match_rules_file = {
+        object
+            .get("game_servers_match_rules_file")
+            .map(|value| {
+                value
+                    .as_str()
+                    .expect("ERROR: game_servers_match_rules_file is not string value in matchmaking-server-config.json")
+                    .to_string()
+            })
+    };
+
+    // optional field
+    let min_players_to_start_game_server = {
+        object
+            .get("min_players_to_start_game_server")
+            .map_or(DEFAULT_MIN_PLAYERS_TO_START_GAME_SERVER, |value| {
+                value
+                    .as_i64()
+                    .expect("ERROR: min_players_to_start_game_server is not number value in matchmaking-server-config.json")
+                    as u32
+            })
+    };
+
+    // optional field, half of the game sessions can be private by default
+    let max_private_game_sessions = {
+        object
+            .get("max_private_game_sessions")
+            .map_or((max_game_sessions / 2).max(1), |value| {
+                value
+                    .as_i64()
+                    .expect("ERROR: max_private_game_sessions is not number value in matchmaking-server-config.json")
+                    as u32
+            })
+    };
+
+    // optional field, the admin port is disabled if it is not specified
+    let matchmaking_server_port_for_admin = {
+        object
+            .get("matchmaking_server_port_for_admin")
+            .map(|value| {
+                value
+                    .as_i64()
+                    .expect("ERROR: matchmaking_server_port_for_admin is not number value in matchmaking-server-config.json")
+                    as u16
+            })
+    };
+
     Config {
         matchmaking_server_ip,
         current_game_version,
+        min_supported_game_version,
+        game_server_pools,
         matchmaking_server_port_for_clients,
         matchmaking_server_port_for_servers,
         clients_connecting_via_proxy_server,
@@ -952,7 +2502,11 @@ fn parse_json_matchmaking_config(json_config: Value) -> Config
         game_servers_max_port_for_tcp_listener,
         game_servers_ice_config,
         max_game_sessions,
-        max_players_per_game_session,  
+        max_players_per_game_session,
+        min_players_to_start_game_server,
+        max_private_game_sessions,
+        game_servers_match_rules_file,
+        matchmaking_server_port_for_admin,
     }
 }
 
@@ -16,6 +16,52 @@
 
 use alkahest::{alkahest, Serialize};
 
+pub const MAX_GAME_SERVER_INFO_NAME_LENGTH: usize = 32;
+
+// join codes of private game servers and parties consist of these characters,
+// similar looking ones (I and 1, O and 0) are skipped
+pub const JOIN_CODE_LENGTH: usize = 6;
+pub const JOIN_CODE_ALPHABET: &[u8; 32] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+// returns None if the join code has wrong length or characters, lowercase is allowed
+pub fn parse_join_code(value: &str) -> Option<[u8; JOIN_CODE_LENGTH]>
+{
+    let value = value.trim().to_uppercase();
+
+    if value.len() != JOIN_CODE_LENGTH ||
+        !value.bytes().all(|byte| JOIN_CODE_ALPHABET.contains(&byte))
+    {
+        return None;
+    }
+
+    let mut join_code = [0u8; JOIN_CODE_LENGTH];
+    join_code.copy_from_slice(value.as_bytes());
+
+    Some(join_code)
+}
+
+// utf-8 name padded with zeros, a longer name is cut
+pub fn name_to_bytes(name: &str) -> [u8; MAX_GAME_SERVER_INFO_NAME_LENGTH]
+{
+    let name_bytes = name.as_bytes();
+    let length = name_bytes.len().min(MAX_GAME_SERVER_INFO_NAME_LENGTH);
+
+    let mut bytes = [0u8; MAX_GAME_SERVER_INFO_NAME_LENGTH];
+    bytes[..length].copy_from_slice(&name_bytes[..length]);
+
+    bytes
+}
+
+pub fn name_from_bytes(bytes: &[u8; MAX_GAME_SERVER_INFO_NAME_LENGTH]) -> String
+{
+    let length = bytes
+        .iter()
+        .position(|byte| *byte == 0u8)
+        .unwrap_or(MAX_GAME_SERVER_INFO_NAME_LENGTH);
+
+    String::from_utf8_lossy(&bytes[..length]).to_string()
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct GameVersion {
     pub major: u32,
@@ -143,7 +189,57 @@ pub enum MatchmakingServerMessage
     GameServerAddressThroughProxy(([u8;4],u16,u16)),
     GameServerAddress(([u8;4],u16)),
     NoFreeServers,
-    WrongGameVersionCorrectIs((u32,u32,u32))
+    WrongGameVersionCorrectIs((u32,u32,u32)),
+    // answer to ListGameServers
+    GameServersList(Vec<GameServerListEntry>),
+    // the game server requested with RequestToConnectToSpecificServer
+    // or RequestToConnectToPrivateGameServer is not running
+    // or runs another game type than the client
+    GameServerNotFound,
+    // join code of the private game server, the game server's address is sent after it
+    PrivateGameServerCreated([u8; JOIN_CODE_LENGTH]),
+    // join code of the created party, the leader's connection is kept open
+    // until the leader sends StartPartyMatchmaking
+    PartyCreated([u8; JOIN_CODE_LENGTH]),
+    // answer to JoinParty, the member's connection is kept open until the party is started
+    PartyJoined(
+        // amount of players in the party including the leader
+        u32
+    ),
+    // sent to every party member and the leader before the game server's address,
+    // the client joins the game server with JoinAsPartyMember with this id
+    PartyIdIs(u128),
+    // the party with the join code doesn't exist
+    PartyNotFound,
+    // the leader left before the party was started
+    PartyDisbanded,
+    // sent periodically while the client waits in the matchmaking queue
+    // for RequestToConnectToGameServer, the game server's address is sent after the wait
+    QueueStatus(
+        // client's position in the queue, starting from 1
+        u32,
+        // amount of clients of the same game version and game type in the queue
+        u32,
+        // estimated wait in seconds, 0 if it is unknown
+        u32,
+    ),
+}
+
+// live game server as it is shown in the client's game servers list
+#[repr(C)]
+#[alkahest(Formula, Serialize, Deserialize)]
+#[derive(Clone)]
+pub struct GameServerListEntry
+{
+    pub server_index: u16,
+    pub game_type: GameType,
+    // map's name, empty if the game server doesn't report it (old game server versions)
+    pub map_name: [u8; MAX_GAME_SERVER_INFO_NAME_LENGTH],
+    // game mode's name, empty if the game server doesn't report it
+    pub game_mode: [u8; MAX_GAME_SERVER_INFO_NAME_LENGTH],
+    pub players_amount: u32,
+    pub max_players: u32,
+    pub game_version: (u32,u32,u32),
 }
 
 #[repr(C)]
@@ -188,7 +284,53 @@ pub enum ClientMessage
         (u32,u32,u32),
         // game type of connecting client
         GameType
-    )
+    ),
+
+    // asks for every running game server
+    ListGameServers,
+
+    RequestToConnectToSpecificServer(
+        // game version of connecting client
+        (u32,u32,u32),
+        // game type of connecting client
+        GameType,
+        // index of the game server from the game servers list
+        u16
+    ),
+
+    // spawns a new game server which accepts only clients with its join code
+    RequestToCreatePrivateGameServer(
+        // game version of connecting client
+        (u32,u32,u32),
+        // game type of connecting client
+        GameType
+    ),
+
+    RequestToConnectToPrivateGameServer(
+        // game version of connecting client
+        (u32,u32,u32),
+        // join code of the private game server
+        [u8; JOIN_CODE_LENGTH]
+    ),
+
+    // the sender becomes the leader of a new party
+    CreateParty(
+        // game version of the leader's client
+        (u32,u32,u32),
+        // game type of the leader's client
+        GameType
+    ),
+
+    JoinParty(
+        // game version of connecting client
+        (u32,u32,u32),
+        // join code of the party
+        [u8; JOIN_CODE_LENGTH]
+    ),
+
+    // sent by the party leader, the whole party gets
+    // one game server with enough free player slots
+    StartPartyMatchmaking,
 }
 
 #[repr(C)]
@@ -232,13 +374,55 @@ impl GameServerMatchmakingServerProtocol
 pub enum GameServerMessage
 {
     PlayersAmoutIs(u32),
+    // the answer of newer game servers to GiveMePlayersAmount,
+    // old game servers answer with PlayersAmoutIs
+    GameServerStatusIs(
+        // players amount
+        u32,
+        // current map's name
+        [u8; MAX_GAME_SERVER_INFO_NAME_LENGTH],
+        // game mode's name
+        [u8; MAX_GAME_SERVER_INFO_NAME_LENGTH]
+    ),
+    // the admin command is queued and will be executed
+    // by the game server's main loop
+    AdminCommandAccepted,
 }
 
 #[repr(C)]
 #[alkahest(Formula, Serialize, Deserialize)]
 pub enum MatchmakingServerMessageToGameServer
 {
-    GiveMePlayersAmount
+    GiveMePlayersAmount,
+    AdminCommand(AdminCommand),
+}
+
+// commands of the game server's operator,
+// the matchmaking server forwards them from the admin port
+#[repr(C)]
+#[alkahest(Formula, Serialize, Deserialize)]
+#[derive(Clone, Debug)]
+pub enum AdminCommand
+{
+    // u128 - id of the player or the spectator
+    KickPlayer(u128),
+
+    // u128 - id of the player
+    // u8 - id of the new team
+    SwapPlayerTeam(u128, u8),
+
+    StartNewGameSession,
+
+    // u8 - id of the team
+    // u32 - new score of the team
+    SetTeamScore(u8, u32),
+
+    // stops the match timer, items respawn and scoring
+    PauseMatch,
+
+    ResumeMatch,
+
+    ShutDown,
 }
 
 
@@ -63,9 +63,11 @@ use crate::{
 };
 
 use client_server_protocol::{
-    NetCommand, NetMessageToPlayer, RemoteMessage, Team
+    NetCommand, NetMessageToPlayer, RemoteMessage
 };
 
+use crate::engine::world::level::TeamLook;
+
 pub struct HoleGun {
     shooted_on_this_charge: bool,
     is_charging: bool,
@@ -200,7 +202,7 @@ impl HoleGun {
                 {
                     if let Some(hited_team) = hit.hited_actors_team
                     {
-                        if hited_team != player.team
+                        if hited_team.is_opponent(&player.team)
                         {                            
                             let dist_to_hited_point = {
                                 hit.hit_point.distance(position)
@@ -359,10 +361,7 @@ impl Device for HoleGun {
         delta: f32,
     )
     {
-        let color = match player.team {
-            Team::Red => HOLE_GUN_RED_COLOR,
-            Team::Blue => HOLE_GUN_BLUE_COLOR,
-        };
+        let color = player.get_team_color(player_id);
 
         if input.first_mouse.is_action_pressed() {
 
@@ -536,9 +535,9 @@ impl Device for HoleGun {
             self.energy = self.energy.clamp(0.0, MAX_ENERGY);
         }
 
-        let bar = match player.team {
-            Team::Red => ui_system.get_mut_ui_element(&UIElementType::EnergyGunBarRed),
-            Team::Blue => ui_system.get_mut_ui_element(&UIElementType::EnergyGunBarBlue),
+        let bar = match player.get_team_look() {
+            TeamLook::Red => ui_system.get_mut_ui_element(&UIElementType::EnergyGunBarRed),
+            TeamLook::Blue => ui_system.get_mut_ui_element(&UIElementType::EnergyGunBarBlue),
         };
 
         if let UIElement::ProgressBar(bar) = bar {
@@ -573,10 +572,7 @@ impl Device for HoleGun {
         engine_handle: &mut EngineHandle,
         screen_effects: &mut PlayerScreenEffects,
     ) {
-        let color = match player.team {
-            Team::Red => HOLE_GUN_RED_COLOR,
-            Team::Blue => HOLE_GUN_BLUE_COLOR,
-        };
+        let color = player.get_team_color(player_id);
 
         self.shooted_on_this_charge = false;
         
@@ -643,9 +639,9 @@ impl Device for HoleGun {
             img.ui_data.is_visible = true;
         }
 
-        match player.team
+        match player.get_team_look()
         {
-            Team::Red =>
+            TeamLook::Red =>
             {
                 let bar = ui_system.get_mut_ui_element(&UIElementType::EnergyGunBarRed);
 
@@ -660,7 +656,7 @@ impl Device for HoleGun {
                 }
             }
 
-            Team::Blue =>
+            TeamLook::Blue =>
             {
                 let bar = ui_system.get_mut_ui_element(&UIElementType::EnergyGunBarBlue);
 
@@ -49,9 +49,11 @@ use crate::{
 };
 
 use client_server_protocol::{
-    NetCommand, NetMessageToPlayer, RemoteMessage, Team
+    NetCommand, NetMessageToPlayer, RemoteMessage
 };
 
+use crate::engine::world::level::TeamLook;
+
 use super::{Device, DeviceType};
 
 
@@ -173,7 +175,7 @@ impl MachineGun {
 
                 if let Some(hited_actors_team) = hit.hited_actors_team
                 {
-                    if hited_actors_team != player.team
+                    if hited_actors_team.is_opponent(&player.team)
                     {                       
                         let force = hit.hit_normal * -self.machinegun_add_force;
             
@@ -328,9 +330,9 @@ impl Device for MachineGun {
             }
         }
 
-        let bar = match player.team {
-            Team::Red => ui_system.get_mut_ui_element(&UIElementType::MachinegunBarRed),
-            Team::Blue => ui_system.get_mut_ui_element(&UIElementType::MachinegunBarBlue),
+        let bar = match player.get_team_look() {
+            TeamLook::Red => ui_system.get_mut_ui_element(&UIElementType::MachinegunBarRed),
+            TeamLook::Blue => ui_system.get_mut_ui_element(&UIElementType::MachinegunBarBlue),
         };
 
         if let UIElement::ProgressBar(bar) = bar {
@@ -371,9 +373,9 @@ impl Device for MachineGun {
             self.cool_machinegun(delta);
             self.time_from_prev_shot += delta;
 
-            let bar = match player.team {
-                Team::Red => ui_system.get_mut_ui_element(&UIElementType::MachinegunBarRed),
-                Team::Blue => ui_system.get_mut_ui_element(&UIElementType::MachinegunBarBlue),
+            let bar = match player.get_team_look() {
+                TeamLook::Red => ui_system.get_mut_ui_element(&UIElementType::MachinegunBarRed),
+                TeamLook::Blue => ui_system.get_mut_ui_element(&UIElementType::MachinegunBarBlue),
             };
 
             if let UIElement::ProgressBar(bar) = bar {
@@ -431,9 +433,9 @@ impl Device for MachineGun {
                 img.ui_data.is_visible = true;
             }
     
-            match player.team
+            match player.get_team_look()
             {
-                Team::Red =>
+                TeamLook::Red =>
                 {
                     let bar = ui_system.get_mut_ui_element(&UIElementType::MachinegunBarRed);
     
@@ -448,7 +450,7 @@ impl Device for MachineGun {
                     }
                 }
     
-                Team::Blue =>
+                TeamLook::Blue =>
                 {
                     let bar = ui_system.get_mut_ui_element(&UIElementType::MachinegunBarBlue);
     
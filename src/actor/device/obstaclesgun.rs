@@ -54,9 +54,11 @@ use crate::{
 };
 
 use client_server_protocol::{
-    NetCommand, NetMessageToPlayer, RemoteMessage, Team
+    NetCommand, NetMessageToPlayer, RemoteMessage
 };
 
+use crate::engine::world::level::TeamLook;
+
 pub struct ObstaclesGun {
     shooted_on_this_charge: bool,
     is_charging: bool,
@@ -75,8 +77,6 @@ pub struct ObstaclesGun {
     visual_element: ChildVisualElement,
 }
 
-pub const HOLE_GUN_BLUE_COLOR: Vec3 = Vec3::new(0.05, 0.6, 1.6);
-pub const HOLE_GUN_RED_COLOR: Vec3 = Vec3::new(2.1, 0.45, 0.05);
 pub const CHARGING_COEF: f32 = 0.7;
 pub const MAX_CHARGING_TIME: f32 = 3.4;
 
@@ -305,10 +305,7 @@ impl Device for ObstaclesGun {
         delta: f32,
     )
     {
-        let color = match player.team {
-            Team::Red => HOLE_GUN_RED_COLOR,
-            Team::Blue => HOLE_GUN_BLUE_COLOR,
-        };
+        let color = player.get_team_color(player_id);
 
         if input.first_mouse.is_action_pressed() {
 
@@ -482,9 +479,9 @@ impl Device for ObstaclesGun {
             self.energy = self.energy.clamp(0.0, MAX_ENERGY);
         }
 
-        let bar = match player.team {
-            Team::Red => ui_system.get_mut_ui_element(&UIElementType::ObstacleGunBarRed),
-            Team::Blue => ui_system.get_mut_ui_element(&UIElementType::ObstacleGunBarBlue),
+        let bar = match player.get_team_look() {
+            TeamLook::Red => ui_system.get_mut_ui_element(&UIElementType::ObstacleGunBarRed),
+            TeamLook::Blue => ui_system.get_mut_ui_element(&UIElementType::ObstacleGunBarBlue),
         };
 
         if let UIElement::ProgressBar(bar) = bar {
@@ -519,10 +516,7 @@ impl Device for ObstaclesGun {
         engine_handle: &mut EngineHandle,
         screen_effects: &mut PlayerScreenEffects,
     ) {
-        let color = match player.team {
-            Team::Red => HOLE_GUN_RED_COLOR,
-            Team::Blue => HOLE_GUN_BLUE_COLOR,
-        };
+        let color = player.get_team_color(player_id);
 
         self.shooted_on_this_charge = false;
         
@@ -589,9 +583,9 @@ impl Device for ObstaclesGun {
             img.ui_data.is_visible = true;
         }
 
-        match player.team
+        match player.get_team_look()
         {
-            Team::Red =>
+            TeamLook::Red =>
             {
                 let bar = ui_system.get_mut_ui_element(&UIElementType::ObstacleGunBarRed);
 
@@ -606,7 +600,7 @@ impl Device for ObstaclesGun {
                 }
             }
 
-            Team::Blue =>
+            TeamLook::Blue =>
             {
                 let bar = ui_system.get_mut_ui_element(&UIElementType::ObstacleGunBarBlue);
 
@@ -43,9 +43,11 @@ use crate::{
 };
 
 use client_server_protocol::{
-    NetCommand, NetMessageToPlayer, RemoteMessage, Team
+    NetCommand, NetMessageToPlayer, RemoteMessage
 };
 
+use crate::engine::world::level::TeamLook;
+
 use super::{Device, DeviceType};
 
 
@@ -212,9 +214,9 @@ impl Device for Shotgun {
             }
         }
 
-        let bar = match player.team {
-            Team::Red => ui_system.get_mut_ui_element(&UIElementType::ShotgunBarRed),
-            Team::Blue => ui_system.get_mut_ui_element(&UIElementType::ShotgunBarBlue),
+        let bar = match player.get_team_look() {
+            TeamLook::Red => ui_system.get_mut_ui_element(&UIElementType::ShotgunBarRed),
+            TeamLook::Blue => ui_system.get_mut_ui_element(&UIElementType::ShotgunBarBlue),
         };
 
         if let UIElement::ProgressBar(bar) = bar {
@@ -257,9 +259,9 @@ impl Device for Shotgun {
         ) {
             self.cool_shotgun(delta);
 
-            let bar = match player.team {
-                Team::Red => ui_system.get_mut_ui_element(&UIElementType::ShotgunBarRed),
-                Team::Blue => ui_system.get_mut_ui_element(&UIElementType::ShotgunBarBlue),
+            let bar = match player.get_team_look() {
+                TeamLook::Red => ui_system.get_mut_ui_element(&UIElementType::ShotgunBarRed),
+                TeamLook::Blue => ui_system.get_mut_ui_element(&UIElementType::ShotgunBarBlue),
             };
 
             if let UIElement::ProgressBar(bar) = bar {
@@ -318,9 +320,9 @@ impl Device for Shotgun {
             img.ui_data.is_visible = true;
         }
     
-        match player.team
+        match player.get_team_look()
         {
-            Team::Red =>
+            TeamLook::Red =>
             {
                 let bar = ui_system.get_mut_ui_element(&UIElementType::ShotgunBarRed);
 
@@ -335,7 +337,7 @@ impl Device for Shotgun {
                 }
             }
 
-            Team::Blue =>
+            TeamLook::Blue =>
             {
                 let bar = ui_system.get_mut_ui_element(&UIElementType::ShotgunBarBlue);
 
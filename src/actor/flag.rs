@@ -152,6 +152,11 @@ impl Flag
             {
                 BLUE_TEAM_COLOR
             }
+
+            _ =>
+            {
+                panic!("Only Red and Blue teams have flags")
+            }
         };
 
         let opponent_color = match flag_owned_by_team
@@ -165,6 +170,11 @@ impl Flag
             {
                 RED_TEAM_COLOR
             }
+
+            _ =>
+            {
+                panic!("Only Red and Blue teams have flags")
+            }
         };
 
         let visual_area =  VolumeArea::SphericalVolumeArea(
@@ -407,6 +417,7 @@ impl Actor for Flag
 
                 *ui_flag.get_ui_data_mut().get_is_visible_mut() = self.flag_ui_tick_switch;
             }
+            _ => {}
         }
 
         self.next_target_swing_position_in_secs -= delta;
@@ -743,6 +754,7 @@ impl Actor for Flag
                                             ]
                                         );
                                     }
+                                    _ => {}
                                 }
                             }
                             SessionControllerMessage::JoinedToSession(
@@ -751,6 +763,7 @@ impl Actor for Flag
                                 blue_flag_status,
                                 _,
                                 _,
+                                _,
                                 _
                             ) =>
                             {
@@ -796,6 +809,7 @@ impl Actor for Flag
                                             }
                                         }
                                     }
+                                    _ => {}
                                 }
 
                             }
@@ -88,6 +88,11 @@ impl FlagBase
             {
                 BLUE_TEAM_COLOR
             }
+
+            _ =>
+            {
+                panic!("Only Red and Blue teams have flags")
+            }
         };
 
         let visual_area =  VolumeArea::SphericalVolumeArea(
@@ -20,6 +20,13 @@ use super::{Actor, ActorID};
 
 use glam::{FloatExt, Vec3, Vec4};
 
+const HOLE_SHRINK_SPEED: f32 = 0.2;
+const HOLE_COLORING_AREA_MULT: f32 = 1.1;
+
+// the same values as in ObstaclesGunShot
+const OBSTACLE_SHRINK_SPEED: f32 = 0.35;
+const OBSTACLE_COLORING_AREA_MULT: f32 = 1.2;
+const OBSTACLE_MATERIAL_INDEX: i32 = 3;
 
 
 pub struct Hole
@@ -31,6 +38,8 @@ pub struct Hole
     explode_current_time: f32,
     explode_final_time: f32,
     color: Vec3,
+    shrink_speed: f32,
+    coloring_area_mult: f32,
     coloring_areas: Vec<ColoringArea>,
     static_objects: Vec<StaticObject>,
 }
@@ -69,7 +78,7 @@ impl Hole
 
         let coloring_area = ColoringArea {
             translation: Vec4::ZERO,
-            radius: current_radius * 1.1,
+            radius: current_radius * HOLE_COLORING_AREA_MULT,
             color: color
         };
 
@@ -87,6 +96,65 @@ impl Hole
             explode_current_time,
             explode_final_time,
             color,
+            shrink_speed: HOLE_SHRINK_SPEED,
+            coloring_area_mult: HOLE_COLORING_AREA_MULT,
+            static_objects,
+            coloring_areas,
+        }
+    }
+
+    // positive shape left by the ObstaclesGun shot
+    pub fn new_obstacle(
+        transform: Transform,
+        current_radius: f32,
+        color: Vec3,
+        target_size_reached: bool,
+        target_radius: f32,
+        explode_current_time: f32,
+        explode_final_time: f32,
+    ) -> Self
+    {
+        let static_object = StaticObject {
+            collider: StaticCollider {
+                shape_type: ShapeType::Sphere,
+                position: Vec4::ZERO,
+                size: Vec4::new(current_radius, 0.0, 0.0, 0.0),
+                is_positive: true,
+                roundness: 0.0,
+                stickiness: true,
+                friction: 0.0,
+                bounce_rate: 1.0,
+                actor_id: None,
+                undestroyable: true,
+            },
+            material_index: OBSTACLE_MATERIAL_INDEX,
+        };
+
+        let mut static_objects = Vec::with_capacity(1);
+
+        static_objects.push(static_object);
+
+        let coloring_area = ColoringArea {
+            translation: Vec4::ZERO,
+            radius: current_radius * OBSTACLE_COLORING_AREA_MULT,
+            color: color
+        };
+
+        let mut coloring_areas = Vec::with_capacity(1);
+
+        coloring_areas.push(coloring_area);
+
+        Hole
+        {
+            transform,
+            id: None,
+            target_size: target_radius,
+            target_size_reached,
+            explode_current_time,
+            explode_final_time,
+            color,
+            shrink_speed: OBSTACLE_SHRINK_SPEED,
+            coloring_area_mult: OBSTACLE_COLORING_AREA_MULT,
             static_objects,
             coloring_areas,
         }
@@ -124,11 +192,11 @@ impl Actor for Hole
         if self.target_size_reached {
             
             for area in self.coloring_areas.iter_mut() {
-                area.radius -= delta * 0.2;
+                area.radius -= delta * self.shrink_speed;
             }
     
             for obj in self.static_objects.iter_mut() {
-                obj.collider.size.x -= delta * 0.2;
+                obj.collider.size.x -= delta * self.shrink_speed;
     
                 if obj.collider.size.x <= 0.0 {
                     engine_handle.send_command(
@@ -149,7 +217,7 @@ impl Actor for Hole
             for area in self.coloring_areas.iter_mut() {
                 area.radius = f32::lerp(
                     0.0,
-                    self.target_size*1.1,
+                    self.target_size*self.coloring_area_mult,
                     explode_coeff.clamp(0.0, 1.0)
                 );
             }
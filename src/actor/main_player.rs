@@ -49,7 +49,7 @@ use crate::{
             PhysicsSystem, colliders_container::PhysicalElement, kinematic_collider::KinematicColliderMessage
         }, render::{VisualElement, camera::Camera}, time::TimeSystem, ui::{
             UIElement, UIElementType, UISystem
-        }, world::{level::Spawn, static_object::VisualWave}
+        }, world::{level::{Spawn, TeamLook}, static_object::VisualWave}
     },
     transform::{BACKWARD, DOWN, FORWARD, LEFT, RIGHT, Transform, UP, W_DOWN, W_UP},
 };
@@ -1079,7 +1079,7 @@ impl Actor for MainPlayer {
                                 damage_dealer_id
                             ) =>
                             {
-                                if team != self.inner_state.team
+                                if team.is_opponent(&self.inner_state.team)
                                 {
                                     self.screen_effects.player_projections.update_or_add_projection(
                                         damage_dealer_id,
@@ -1185,7 +1185,7 @@ impl Actor for MainPlayer {
                             }
 
                             SessionControllerMessage::JoinedToSession(
-                                your_team, _, _, _, _, _,
+                                your_team, _, _, _, _, _, _,
                             ) =>
                             {
                                 println!("Joined to game session");
@@ -1214,6 +1214,16 @@ impl Actor for MainPlayer {
                                 self.inner_state.is_time_after_some_team_win = true;
                             }
 
+                            SessionControllerMessage::PlayerWin(_) =>
+                            {
+                                self.inner_state.is_time_after_some_team_win = true;
+                            }
+
+                            SessionControllerMessage::Draw =>
+                            {
+                                self.inner_state.is_time_after_some_team_win = true;
+                            }
+
                             _ => {}
                         }
                     }
@@ -1390,6 +1400,20 @@ impl Actor for MainPlayer {
                                 );
                             }
 
+                            PlayersDollMessage::YouKilledMe =>
+                            {
+                                engine_handle.send_command(
+                                    Command {
+                                        sender: self.get_id().expect("Main Player havn't ActorID"),
+                                        command_type: CommandType::NetCommand(
+                                            NetCommand::SendMessageToServer(
+                                                NetMessageToServer::IKilledPlayer(from)
+                                            )
+                                        )
+                                    }
+                                );
+                            }
+
                             _ => {}
                         }
                     }
@@ -2391,9 +2415,9 @@ pub fn process_w_scanner(
                     VisualWave {
                         translation: Vec4::ZERO,
                         radius: 0.001,
-                        color: match inner_state.team {
-                            Team::Blue => BLUE_SCANNER_WAVE_COLOR,
-                            Team::Red => RED_SCANNER_WAVE_COLOR,
+                        color: match inner_state.get_team_look() {
+                            TeamLook::Blue => BLUE_SCANNER_WAVE_COLOR,
+                            TeamLook::Red => RED_SCANNER_WAVE_COLOR,
                         }
                     }
                 );
@@ -2464,12 +2488,12 @@ pub fn process_w_scanner(
 
         w_scanner.visual_wave[0].radius = w_scanner.w_scanner_radius;
 
-        w_scanner.visual_wave[0].color = match inner_state.team
+        w_scanner.visual_wave[0].color = match inner_state.get_team_look()
         {
-            Team::Blue => {
+            TeamLook::Blue => {
                 BLUE_SCANNER_WAVE_COLOR * screen_effects.w_scanner_ring_intesity
             },
-            Team::Red =>
+            TeamLook::Red =>
             {
                 RED_SCANNER_WAVE_COLOR * screen_effects.w_scanner_ring_intesity
             }
@@ -2522,7 +2546,7 @@ pub fn process_w_scanner(
             let team = hit.hited_actors_team
                 .expect("scanned by W Scanner dynamic collider have not Team");
 
-            if team != inner_state.team
+            if team.is_opponent(&inner_state.team)
             {
                 let projection_id = hit.hited_actors_id
                     .expect("scanned by W Scanner dynamic collider have not ActorID");
@@ -2541,9 +2565,9 @@ pub fn process_w_scanner(
         }
     }
 
-    let scanner_ui = match inner_state.team {
-        Team::Blue => ui_system.get_mut_ui_element(&UIElementType::ScannerBlue),
-        Team::Red => ui_system.get_mut_ui_element(&UIElementType::ScannerRed),
+    let scanner_ui = match inner_state.get_team_look() {
+        TeamLook::Blue => ui_system.get_mut_ui_element(&UIElementType::ScannerBlue),
+        TeamLook::Red => ui_system.get_mut_ui_element(&UIElementType::ScannerRed),
     };
 
     if let UIElement::ProgressBar(bar) = scanner_ui {
@@ -2673,16 +2697,12 @@ pub fn send_player_state_to_remote_player_doll
     engine_handle.send_command(Command{
         sender: my_id,
         command_type: CommandType::NetCommand(
-            NetCommand::SendBoardcastNetMessageUnreliable(
-                NetMessageToPlayer::RemoteDirectMessage(
-                    my_id,
-                    RemoteMessage::SetPlayerDollState(
-                        inner_state.transform.to_serializable_transform(),
-                        player_doll_input_state.serialize(),
-                        remote_velocity,
-                        time_system.get_server_time()
-                    )
-                )
+            NetCommand::SendPlayerDollStateUnreliable(
+                my_id,
+                inner_state.transform.to_serializable_transform(),
+                player_doll_input_state.serialize(),
+                remote_velocity,
+                time_system.get_server_time()
             )
         )
     });
@@ -3152,9 +3172,12 @@ pub fn get_effected_by_base
                 1.0
         );
 
-        if inner_state.team == Team::Blue
+        match inner_state.team
         {
-            coef = 1.0 - coef;
+            Team::Red => {}
+            Team::Blue => coef = 1.0 - coef,
+            // only Red and Blue teams have bases
+            _ => coef = 0.0,
         }
 
         coef = (coef * 2.0) - 1.0;
@@ -3169,9 +3192,9 @@ pub fn get_effected_by_base
         inner_state.hp = PLAYER_MAX_HP;
     }
 
-    let health_bar = match inner_state.team {
-        Team::Red => ui_system.get_mut_ui_element(&UIElementType::HeathBarRed), 
-        Team::Blue => ui_system.get_mut_ui_element(&UIElementType::HeathBarBlue), 
+    let health_bar = match inner_state.get_team_look() {
+        TeamLook::Red => ui_system.get_mut_ui_element(&UIElementType::HeathBarRed), 
+        TeamLook::Blue => ui_system.get_mut_ui_element(&UIElementType::HeathBarBlue), 
     };
 
     if let UIElement::ProgressBar(bar) = health_bar {
@@ -3378,7 +3401,7 @@ pub fn die
 
         let players_death_explode = PlayersDeathExplosion::new(
             inner_state.transform.get_position(),
-            inner_state.team
+            inner_state.get_team_color(my_id)
         );
 
         inner_state.screen_effects.death_screen_effect = 0.0;
@@ -3406,6 +3429,19 @@ pub fn die
                 )
             }
         );
+
+        // the game server counts the kill in Team Deathmatch
+        // when the killer reports it too
+        engine_handle.send_command(
+            Command {
+                sender: my_id,
+                command_type: CommandType::NetCommand(
+                    NetCommand::SendMessageToServer(
+                        NetMessageToServer::IDied
+                    )
+                )
+            }
+        );
     }
 }
 
@@ -3517,9 +3553,9 @@ pub fn make_hud_transparency_as_death_screen_effect(
     hud_elem.get_ui_data_mut().rect.transparency = a;
 
     
-    match inner_state.team
+    match inner_state.get_team_look()
     {
-        Team::Red =>
+        TeamLook::Red =>
         {
             let hud_elem = ui.get_mut_ui_element(&UIElementType::ScannerRed);
             hud_elem.get_ui_data_mut().rect.transparency = a;
@@ -3545,7 +3581,7 @@ pub fn make_hud_transparency_as_death_screen_effect(
             hud_elem.get_ui_data_mut().rect.transparency = a;
         }
 
-        Team::Blue =>
+        TeamLook::Blue =>
         {
             let hud_elem = ui.get_mut_ui_element(&UIElementType::ScannerBlue);
             hud_elem.get_ui_data_mut().rect.transparency = a;
@@ -3598,9 +3634,9 @@ pub fn set_right_team_hud(
     *hud_elem.get_ui_data_mut().get_is_visible_mut() = true;
 
 
-    match inner_state.team
+    match inner_state.get_team_look()
     {
-        Team::Red =>
+        TeamLook::Red =>
         {
             let hud_elem = ui.get_mut_ui_element(&UIElementType::ScannerRed);
             *hud_elem.get_ui_data_mut().get_is_visible_mut() = true;
@@ -3627,7 +3663,7 @@ pub fn set_right_team_hud(
             *hud_elem.get_ui_data_mut().get_is_visible_mut() = false;
         }
 
-        Team::Blue =>
+        TeamLook::Blue =>
         {
             let hud_elem = ui.get_mut_ui_element(&UIElementType::ScannerRed);
             *hud_elem.get_ui_data_mut().get_is_visible_mut() = false;
@@ -3693,9 +3729,9 @@ pub fn get_damage_and_add_force(
     inner_state.hp -= damage as f32;
     inner_state.collider.add_force(force);
 
-    let health_bar = match inner_state.team {
-        Team::Red => ui_system.get_mut_ui_element(&UIElementType::HeathBarRed), 
-        Team::Blue => ui_system.get_mut_ui_element(&UIElementType::HeathBarBlue), 
+    let health_bar = match inner_state.get_team_look() {
+        TeamLook::Red => ui_system.get_mut_ui_element(&UIElementType::HeathBarRed), 
+        TeamLook::Blue => ui_system.get_mut_ui_element(&UIElementType::HeathBarBlue), 
     };
 
     if let UIElement::ProgressBar(bar) = health_bar {
@@ -3987,9 +4023,9 @@ impl ControlledActor for MainPlayer
             fyrox_sound::source::Status::Playing,
         );
 
-        let health_bar = match self.inner_state.team {
-            Team::Red => ui_system.get_mut_ui_element(&UIElementType::HeathBarRed), 
-            Team::Blue => ui_system.get_mut_ui_element(&UIElementType::HeathBarBlue), 
+        let health_bar = match self.inner_state.get_team_look() {
+            TeamLook::Red => ui_system.get_mut_ui_element(&UIElementType::HeathBarRed), 
+            TeamLook::Blue => ui_system.get_mut_ui_element(&UIElementType::HeathBarBlue), 
         };
 
         if let UIElement::ProgressBar(bar) = health_bar {
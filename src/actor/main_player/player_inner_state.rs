@@ -17,14 +17,15 @@
 use client_server_protocol::Team;
 use fyrox_core::pool::Handle;
 use fyrox_sound::source::SoundSource;
-use glam::{Mat4, Vec4};
+use glam::{Mat4, Vec3, Vec4};
 
-use crate::{actor::session_controller::DEFAULT_TEAM, engine::{audio::{AudioSystem, Sound}, physics::{dynamic_collider::PlayersDollCollider, kinematic_collider::KinematicCollider}, ui::{RectSize, UIElement, UIElementType, UISystem}}, transform::Transform};
+use crate::{actor::{session_controller::DEFAULT_TEAM, ActorID}, engine::{audio::{AudioSystem, Sound}, physics::{dynamic_collider::PlayersDollCollider, kinematic_collider::KinematicCollider}, ui::{RectSize, UIElement, UIElementType, UISystem}, world::level::{TeamLook, TeamsLooks}}, transform::Transform};
 
 use super::{player_settings::PlayerSettings, PlayerScreenEffects, CROSSHAIR_DECREASING_SPEED, CROSSHAIR_INCREASING_SPEED, CROSSHAIR_MAX_SIZE, CROSSHAIR_MIN_SIZE, CROSSHAIR_ROTATION_SPEED};
 
 pub struct PlayerInnerState {
     pub team: Team,
+    pub teams_looks: TeamsLooks,
     pub collider: KinematicCollider,
     pub collider_for_others: Vec<PlayersDollCollider>,
     pub transform: Transform,
@@ -123,6 +124,7 @@ impl PlayerInnerState {
 
         PlayerInnerState {
             team: DEFAULT_TEAM,
+            teams_looks: player_settings.teams_looks.clone(),
             collider: KinematicCollider::new(
                 player_settings.max_speed,
                 player_settings.max_accel,
@@ -185,6 +187,16 @@ impl PlayerInnerState {
         }
     }
 
+    pub fn get_team_look(&self) -> TeamLook
+    {
+        self.teams_looks.get_look(self.team)
+    }
+
+    pub fn get_team_color(&self, player_id: ActorID) -> Vec3
+    {
+        self.teams_looks.get_visuals(self.team, player_id).color
+    }
+
     pub fn get_eyes_offset(&self) -> Vec4
     {
         self.eyes_offset
@@ -19,6 +19,8 @@ use std::{fs::File, io::Read, sync::{Arc, Mutex}};
 use wasm_bindgen_futures::JsFuture;
 use serde_json::Value;
 
+use crate::engine::world::level::TeamsLooks;
+
 #[derive(Clone)]
 pub struct PlayerSettings {
 
@@ -64,7 +66,16 @@ pub struct PlayerSettings {
     pub turn_server_username: String,
     pub turn_server_credential: String,
 
+    // optional, join code of the private game server to join
+    pub private_game_server_join_code: Option<String>,
+
+    // optional, join code of the party to join
+    pub party_code: Option<String>,
+
     pub screen_resolution_scale: f32,
+
+    // teams' looks are defined by the map and set when the level is loaded
+    pub teams_looks: TeamsLooks,
 }
 
 
@@ -481,6 +492,30 @@ fn parse_json_into_settings(json_settigs: Value, json_settigs2: Value) -> Player
         .to_string()
     };
 
+    let private_game_server_join_code = {
+        object
+        .get("private_game_server_join_code")
+        .map(|value| {
+            value
+            .as_str()
+            .expect("private_game_server_join_code is not string value in settings.json")
+            .to_string()
+        })
+        .filter(|value| value != "")
+    };
+
+    let party_code = {
+        object
+        .get("party_code")
+        .map(|value| {
+            value
+            .as_str()
+            .expect("party_code is not string value in settings.json")
+            .to_string()
+        })
+        .filter(|value| value != "")
+    };
+
     let screen_resolution_scale = {
         object
         .get("screen_resolution_scale")
@@ -523,6 +558,9 @@ fn parse_json_into_settings(json_settigs: Value, json_settigs2: Value) -> Player
         bash_and_turn_servers,
         turn_server_username,
         turn_server_credential,
+        private_game_server_join_code,
+        party_code,
         screen_resolution_scale,
+        teams_looks: TeamsLooks::default(),
     }
 }
\ No newline at end of file
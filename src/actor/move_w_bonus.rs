@@ -272,13 +272,24 @@ impl Actor for MoveWBonusSpot
                                 _,
                                 _,
                                 _,
-                                bonus_status,
+                                collected_move_w_bonuses,
+                                _,
                                 _,
                                 _
                             ) =>
                             {
                                 self.area.clear_containing_colliders_list();
-                                self.status = BonusSpotStatus::from(bonus_status);
+
+                                // the game server doesn't send who collected the bonus
+                                // to a joined player, so it is marked as collected by nobody
+                                self.status = if collected_move_w_bonuses & (1u64 << self.index) != 0
+                                {
+                                    BonusSpotStatus::BonusCollected(0u128)
+                                }
+                                else
+                                {
+                                    BonusSpotStatus::BonusOnTheSpot
+                                };
                             }
                             
                             SessionControllerMessage::NewSessionStarted(_) =>
@@ -201,7 +201,7 @@ impl Actor for Observer {
                             }
 
                             SessionControllerMessage::JoinedToSession(
-                                your_team, _, _, _, _, _,
+                                your_team, _, _, _, _, _, _,
                             ) =>
                             {
                                 self.inner_state.team = your_team;
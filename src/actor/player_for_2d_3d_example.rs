@@ -54,7 +54,7 @@ use crate::{
             PhysicsSystem
         }, render::{camera::Camera, VisualElement}, time::TimeSystem, ui::{
             UIElement, UIElementType, UISystem
-        }, world::{level::Spawn, static_object::{BeamVolumeArea, SphericalVolumeArea, VolumeArea},}
+        }, world::{level::{Spawn, TeamLook}, static_object::{BeamVolumeArea, SphericalVolumeArea, VolumeArea},}
     },
     transform::{Transform, BACKWARD, DOWN, FORWARD, UP},
 };
@@ -318,7 +318,7 @@ impl Actor for PlayerFor2d3dExample {
                                 _
                             ) =>
                             {
-                                if team != self.inner_state.team
+                                if team.is_opponent(&self.inner_state.team)
                                 {
                                     self.screen_effects.player_projections.update_or_add_projection(
                                         from,
@@ -424,7 +424,7 @@ impl Actor for PlayerFor2d3dExample {
                             }
 
                             SessionControllerMessage::JoinedToSession(
-                                your_team, _, _, _, _, _,
+                                your_team, _, _, _, _, _, _,
                             ) =>
                             {
                                 println!("Joined to game session");
@@ -453,6 +453,16 @@ impl Actor for PlayerFor2d3dExample {
                                 self.inner_state.is_time_after_some_team_win = true;
                             }
 
+                            SessionControllerMessage::PlayerWin(_) =>
+                            {
+                                self.inner_state.is_time_after_some_team_win = true;
+                            }
+
+                            SessionControllerMessage::Draw =>
+                            {
+                                self.inner_state.is_time_after_some_team_win = true;
+                            }
+
                             _ => {}
                         }
                     }
@@ -474,6 +484,8 @@ impl Actor for PlayerFor2d3dExample {
                                         let ui_elem = ui_system.get_mut_ui_element(&UIElementType::RedFlagBacklight);
                                         *ui_elem.get_ui_data_mut().get_is_visible_mut() = true;
                                     }
+                                    // only Red and Blue teams have flags
+                                    _ => {}
                                 }
 
                                 engine_handle.send_direct_message(
@@ -621,6 +633,20 @@ impl Actor for PlayerFor2d3dExample {
                                 );
                             }
 
+                            PlayersDollMessage::YouKilledMe =>
+                            {
+                                engine_handle.send_command(
+                                    Command {
+                                        sender: self.get_id().expect("Player for Example have not ActorID"),
+                                        command_type: CommandType::NetCommand(
+                                            NetCommand::SendMessageToServer(
+                                                NetMessageToServer::IKilledPlayer(from)
+                                            )
+                                        )
+                                    }
+                                );
+                            }
+
                             _ => {}
                         }
                     }
@@ -1597,9 +1623,9 @@ impl ControlledActor for PlayerFor2d3dExample
             fyrox_sound::source::Status::Playing,
         );
 
-        let health_bar = match self.inner_state.team {
-            Team::Red => ui_system.get_mut_ui_element(&UIElementType::HeathBarRed), 
-            Team::Blue => ui_system.get_mut_ui_element(&UIElementType::HeathBarBlue), 
+        let health_bar = match self.inner_state.get_team_look() {
+            TeamLook::Red => ui_system.get_mut_ui_element(&UIElementType::HeathBarRed), 
+            TeamLook::Blue => ui_system.get_mut_ui_element(&UIElementType::HeathBarBlue), 
         };
 
         if let UIElement::ProgressBar(bar) = health_bar {
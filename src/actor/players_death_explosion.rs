@@ -14,10 +14,9 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use client_server_protocol::Team;
 use glam::{Vec3, Vec4};
 
-use crate::{actor::device::holegun::{HOLE_GUN_BLUE_COLOR, HOLE_GUN_RED_COLOR}, engine::{audio::AudioSystem, effects::EffectsSystem, engine_handle::{Command, CommandType, EngineHandle}, physics::{physics_system_data::ShapeType, static_collider::StaticCollider}, render::VisualElement, time::TimeSystem, ui::UISystem, world::static_object::{ColoringArea, SphericalVolumeArea, StaticObject, VolumeArea}}, transform::Transform};
+use crate::{engine::{audio::AudioSystem, effects::EffectsSystem, engine_handle::{Command, CommandType, EngineHandle}, physics::{physics_system_data::ShapeType, static_collider::StaticCollider}, render::VisualElement, time::TimeSystem, ui::UISystem, world::{static_object::{ColoringArea, SphericalVolumeArea, StaticObject, VolumeArea}}}, transform::Transform};
 
 use super::{Actor, ActorID};
 
@@ -33,14 +32,9 @@ pub struct PlayersDeathExplosion {
 }
 
 impl PlayersDeathExplosion {
-    pub fn new(position: Vec4, team: Team) -> Self {
+    pub fn new(position: Vec4, color: Vec3) -> Self {
         let mut volume_areas = Vec::with_capacity(1);
 
-        let color = match team {
-            Team::Red => HOLE_GUN_RED_COLOR,
-            Team::Blue => HOLE_GUN_BLUE_COLOR,
-        };
-
         let volume_area = VolumeArea::SphericalVolumeArea(
             SphericalVolumeArea {
                 translation: Vec4::ZERO,
@@ -33,14 +33,14 @@ use crate::{
             EngineHandle
         }, physics::{
             PhysicsSystem, colliders_container::PhysicalElement, dynamic_collider::PlayersDollCollider, kinematic_collider::KinematicCollider
-        }, render::VisualElement, time::TimeSystem, ui::UISystem, world::static_object::{
+        }, render::VisualElement, time::TimeSystem, ui::UISystem, world::{level::TeamLook, static_object::{
             SphericalVolumeArea, VisualWave, VolumeArea
-        }
+        }}
     }, transform::{BACKWARD, DOWN, FORWARD, LEFT, RIGHT, Transform, UP, W_DOWN}
 };
 
 use super::{
-    device::holegun::{HOLE_GUN_BLUE_COLOR, HOLE_GUN_RED_COLOR}, flag::FlagMessage, holegun_miss::HoleGunMiss, holegun_shot::HoleGunShot, machinegun_shot::MachinegunShot, main_player::{
+    flag::FlagMessage, holegun_miss::HoleGunMiss, holegun_shot::HoleGunShot, machinegun_shot::MachinegunShot, main_player::{
         player_settings::PlayerSettings, PlayerMessage, BLUE_SCANNER_WAVE_COLOR, PLAYER_MAX_HP, RED_SCANNER_WAVE_COLOR, TIME_TO_DIE_SLOWLY, W_SCANNER_EXPANDING_SPEED, W_SCANNER_MAX_RADIUS
     }, mover_w::MoverWMessage, players_death_explosion::PlayersDeathExplosion, session_controller::SessionControllerMessage, shooting_impact::ShootingImpact, shotgun_shot_source::ShotgunShotSource, Actor, ActorID, ActorWrapper, CommonActorsMessage, Message, MessageType, SpecificActorMessage
 };
@@ -84,6 +84,9 @@ impl PlayerDollInputState {
 
 
 const PLAYERS_DOLL_COLOR: Vec3 = Vec3::new(0.8, 0.8, 0.8);
+// time after the last damage during which the damage dealer
+// is considered the killer of the player
+const KILL_CLAIM_TIME: f32 = 3.0;
 pub struct PlayerDoll {
     team: Team,
     id: Option<ActorID>,
@@ -122,6 +125,9 @@ pub struct PlayerDoll {
     visual_wave: Vec<VisualWave>,
 
     anti_projection_mode_enabled_timer: f32, 
+
+    last_damage_dealer_id: Option<ActorID>,
+    last_damage_timer: f32,
 }
 
 #[derive(Clone)]
@@ -204,6 +210,7 @@ pub enum PlayersDollMessage{
         //my radius
         f32,
     ),
+    YouKilledMe,
 }
 
 
@@ -223,13 +230,13 @@ impl PlayerDoll {
         player_doll_for_2d_3d_example: bool,
     ) -> Self {
 
-        let my_color = match team {
-            Team::Red =>
+        let my_color = match player_settings.teams_looks.get_look(team) {
+            TeamLook::Red =>
             {
                 Vec3::new(1.0, 0.0, 0.0)
             }
 
-            Team::Blue =>
+            TeamLook::Blue =>
             {
                 Vec3::new(0.0, 0.0, 1.0)
             }
@@ -315,14 +322,49 @@ impl PlayerDoll {
             visual_wave: Vec::with_capacity(1),
             player_doll_for_2d_3d_example,
             anti_projection_mode_enabled_timer: 0.0,
+            last_damage_dealer_id: None,
+            last_damage_timer: 0.0,
         }
     }
 
 
 
+    fn set_last_damage_dealer(&mut self, damage_dealer_id: ActorID) {
+        self.last_damage_dealer_id = Some(damage_dealer_id);
+        self.last_damage_timer = KILL_CLAIM_TIME;
+    }
+
+
+
+    // the killer reports the kill to the game server (used in Team Deathmatch)
+    fn notify_killer(&mut self, engine_handle: &mut EngineHandle) {
+        if let Some(killer_id) = self.last_damage_dealer_id.take() {
+            if self.last_damage_timer > 0.0 {
+                engine_handle.send_direct_message(
+                    killer_id,
+                    Message {
+                        from: self.get_id().expect("Player Doll have not ActorID"),
+                        remote_sender: false,
+                        message: MessageType::SpecificActorMessage(
+                            SpecificActorMessage::PlayersDollMessage(
+                                PlayersDollMessage::YouKilledMe
+                            )
+                        )
+                    }
+                );
+            }
+        }
+
+        self.last_damage_timer = 0.0;
+    }
+
+
+
     fn die_immediately(&mut self, engine_handle: &mut EngineHandle, audio_system: &mut AudioSystem) {
         if self.is_alive {
 
+            self.notify_killer(engine_handle);
+
             self.w_scanner_enable = false;
             self.visual_wave.clear();
 
@@ -338,10 +380,21 @@ impl PlayerDoll {
 
 
 
+    fn get_team_look(&self) -> TeamLook {
+        self.player_settings.teams_looks.get_look(self.team)
+    }
+
+    fn get_team_color(&self) -> Vec3 {
+        self.player_settings.teams_looks
+            .get_visuals(self.team, self.id.expect("Player's Doll have not Actor's ID"))
+            .color
+    }
+
+
     fn play_die_effects(&mut self, engine_handle: &mut EngineHandle, audio_system: &mut AudioSystem) {
         let players_death_explode = PlayersDeathExplosion::new(
             self.get_transform().get_position(),
-            self.team
+            self.get_team_color()
         );
         
         engine_handle.send_command(
@@ -385,6 +438,8 @@ impl PlayerDoll {
     fn die_slowly(&mut self, engine_handle: &mut EngineHandle) {
         if self.is_alive {
 
+            self.notify_killer(engine_handle);
+
             self.volume_area.clear();
 
             self.is_alive = false;
@@ -448,6 +503,8 @@ impl PlayerDoll {
         self.on_way_to_next_w_level = false;
         self.is_alive = true;
         self.is_enable = true;
+        self.last_damage_dealer_id = None;
+        self.last_damage_timer = 0.0;
         self.transform = transform.clone();
         self.target_transform = transform;
         self.input_state = input_state;
@@ -591,12 +648,12 @@ impl PlayerDoll {
     
             self.visual_wave[0].radius = self.w_scanner_radius;
     
-            self.visual_wave[0].color = match self.team
+            self.visual_wave[0].color = match self.get_team_look()
             {
-                Team::Blue => {
+                TeamLook::Blue => {
                     BLUE_SCANNER_WAVE_COLOR * self.w_scanner_ring_intesity
                 },
-                Team::Red =>
+                TeamLook::Red =>
                 {
                     RED_SCANNER_WAVE_COLOR * self.w_scanner_ring_intesity
                 }
@@ -684,7 +741,7 @@ impl Actor for PlayerDoll {
                     {
                         match message
                         {
-                            SessionControllerMessage::JoinedToSession(_,_,_,_,_,_) =>
+                            SessionControllerMessage::JoinedToSession(_,_,_,_,_,_,_) =>
                             {
                                 self.prev_interpolating_model_set_target_time = 0u128;
                             }
@@ -746,6 +803,8 @@ impl Actor for PlayerDoll {
 
                             PlayerMessage::Telefrag =>
                             {
+                                self.set_last_damage_dealer(from);
+
                                 self.die_immediately(engine_handle, audio_system);
 
                                 engine_handle.send_command(
@@ -782,8 +841,10 @@ impl Actor for PlayerDoll {
                                 damage_dealer_id,
                             ) =>
                             {
-                                if team != self.team && damage > 0
+                                if team.is_opponent(&self.team) && damage > 0
                                 {
+                                    self.set_last_damage_dealer(damage_dealer_id);
+
                                     engine_handle.send_command(
                                         Command {
                                             sender: self.id.expect("Player's Doll have not Actor's ID"),
@@ -864,9 +925,9 @@ impl Actor for PlayerDoll {
                                     VisualWave {
                                         translation: Vec4::ZERO,
                                         radius: 0.001,
-                                        color: match self.team {
-                                            Team::Blue => BLUE_SCANNER_WAVE_COLOR,
-                                            Team::Red => RED_SCANNER_WAVE_COLOR,
+                                        color: match self.get_team_look() {
+                                            TeamLook::Blue => BLUE_SCANNER_WAVE_COLOR,
+                                            TeamLook::Red => RED_SCANNER_WAVE_COLOR,
                                         }
                                     }
                                 );
@@ -920,6 +981,8 @@ impl Actor for PlayerDoll {
 
                             PlayersDollMessage::YouHitedMe(_,_,_) => {}
 
+                            PlayersDollMessage::YouKilledMe => {}
+
                             PlayersDollMessage::SetInterploatedModelTargetState(
                                 transform,
                                 input,
@@ -942,10 +1005,7 @@ impl Actor for PlayerDoll {
                             {
                                 if self.volume_area.is_empty() {
 
-                                    let color = match self.team {
-                                        Team::Red => HOLE_GUN_RED_COLOR,
-                                        Team::Blue => HOLE_GUN_BLUE_COLOR,
-                                    };
+                                    let color = self.get_team_color();
 
                                     let volume_area = VolumeArea::SphericalVolumeArea(
                                         SphericalVolumeArea {
@@ -1243,9 +1303,9 @@ impl Actor for PlayerDoll {
                             {
                                 if self.team == team
                                 {
-                                    match team
+                                    match self.get_team_look()
                                     {
-                                        Team::Red =>
+                                        TeamLook::Red =>
                                         {
                                             effects_system.spawn_wave(
                                                 engine_handle,
@@ -1263,7 +1323,7 @@ impl Actor for PlayerDoll {
                                                 ]
                                             );
                                         }
-                                        Team::Blue =>
+                                        TeamLook::Blue =>
                                         {
                                             effects_system.spawn_wave(
                                                 engine_handle,
@@ -1361,6 +1421,8 @@ impl Actor for PlayerDoll {
         effects_system: &mut EffectsSystem,
         delta: f32
     ) {
+        self.last_damage_timer = (self.last_damage_timer - delta).max(0.0);
+
         if self.is_alive {
 
             self.process_player_doll_w_scanner(delta);
@@ -14,24 +14,27 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use client_server_protocol::Team;
+use client_server_protocol::{GameMode, PlayerStats, Team, MAX_TEAMS};
 use fyrox_sound::source::Status;
 use glam::{Vec3, Vec4};
 
 use crate::{
-    engine::{audio::Sound, effects::EffectsSystem, engine_handle::EngineHandle, time::TimeSystem, ui::{UIElementType, UISystem}},
+    engine::{audio::{AudioSystem, Sound}, effects::EffectsSystem, engine_handle::EngineHandle, time::TimeSystem, ui::{UIElement, UIElementType, UISystem}, world::level::{TeamLook, TeamsLooks}},
     transform::Transform
 };
 
 use super::{
-    flag::FlagStatus, main_player::{PlayerMessage, BLUE_TEAM_COLOR, RED_TEAM_COLOR}, move_w_bonus::BonusSpotStatus, Actor, ActorID, CommonActorsMessage, Message, MessageType, SpecificActorMessage
+    flag::FlagStatus, main_player::{PlayerMessage, BLUE_TEAM_COLOR, RED_TEAM_COLOR}, Actor, ActorID, CommonActorsMessage, Message, MessageType, SpecificActorMessage
 };
 
 pub const DEFAULT_TEAM: Team = Team::Blue;
+pub const DEFAULT_MAX_SCORE: u32 = 4;
+pub const SCORE_MARKS_AMOUNT: u32 = 4;
 
 pub const SHOW_TEAM_BACKLIGHT_TIME: f32 = 4.0;
 pub const SHOW_TEAM_JOIN_TITLE_TIME: f32 = 5.0;
 pub const SHOW_TEAM_WIN_TITLE_TIME: f32 = 15.0;
+pub const SHOW_OVERTIME_TITLE_TIME: f32 = 4.0;
 
 pub const UI_ELEM_FADE_IN_SPEED: f32 = 3.0;
 pub const UI_ELEM_FADE_OUT_SPEED: f32 = 3.0;
@@ -46,21 +49,44 @@ pub enum SessionControllerMessage
         FlagStatus,
         // blue flag status
         FlagStatus,
-        // bonus spot status
-        BonusSpotStatus,
-        // red team score
-        u32,
-        // blue team score
+        // bit mask of collected Move W bonus spots indexed by bonus spot index
+        u64,
+        // teams scores indexed by team id
+        [u32; MAX_TEAMS],
+        // score to win the game session (kill limit in Team Deathmatch and Free-for-all)
         u32,
+        // game mode of the game server
+        GameMode,
     ),
     NewSessionStarted(Team),
     SetScore(
-        // red team score
+        // teams scores indexed by team id
+        [u32; MAX_TEAMS]
+    ),
+    SetFreeForAllScore(
+        // your score
         u32,
-        // blue team score
+        // best score among your opponents
         u32
     ),
-    TeamWin(Team)
+    TeamWin(Team),
+    PlayerWin(
+        // true if you are the winner
+        bool
+    ),
+    SetMatchTimeLeft(
+        // time left in millis
+        u128,
+        // full duration of the game session (or overtime) in millis
+        u128,
+        // is it overtime
+        bool
+    ),
+    Draw,
+    SetScoreboard(
+        // statistics of all players in the game session
+        Vec<PlayerStats>
+    ),
 }
 
 
@@ -68,20 +94,38 @@ pub struct SessionController
 {
     transform: Transform,
     id: Option<ActorID>,
-    red_team_score: u32,
-    blue_team_score: u32,
+    teams_score: [u32; MAX_TEAMS],
+    // scores in Free-for-all
+    my_score: u32,
+    best_opponent_score: u32,
+    max_score: u32,
+    game_mode: GameMode,
     your_team: Team,
+    teams_looks: TeamsLooks,
     show_red_team_backlight_timer: f32,
     show_blue_team_backlight_timer: f32,
     show_red_team_win_title_timer: f32,
     show_blue_team_win_title_timer: f32,
     show_join_red_team_title_timer: f32,
     show_join_blue_team_title_timer: f32,
+    show_draw_title_timer: f32,
+    show_you_win_title_timer: f32,
+    show_you_lose_title_timer: f32,
+    show_overtime_title_timer: f32,
+
+    // in seconds
+    match_time_left: f32,
+    match_phase_duration: f32,
+    match_timer_is_active: bool,
+    is_overtime: bool,
 
     red_flag_base_position: Vec4,
     blue_flag_base_position: Vec4,
 
     observer_mode: bool,
+
+    // the last scoreboard received from the game server
+    scoreboard: Vec<PlayerStats>,
 }
 
 
@@ -91,24 +135,38 @@ impl SessionController
         ui_system: &mut UISystem,
         red_flag_base_position: Vec4,
         blue_flag_base_position: Vec4,
+        teams_looks: TeamsLooks,
         observer_mode: bool,
     ) -> Self
     {
         let session_controller = SessionController {
             transform: Transform::new(),
             id: None,
-            red_team_score: 4u32,
-            blue_team_score: 4u32,
+            teams_score: [DEFAULT_MAX_SCORE; MAX_TEAMS],
+            my_score: 0u32,
+            best_opponent_score: 0u32,
+            max_score: DEFAULT_MAX_SCORE,
+            game_mode: GameMode::CaptureTheFlag,
             your_team: DEFAULT_TEAM,
+            teams_looks,
             show_red_team_backlight_timer: 0.0,
             show_blue_team_backlight_timer: 0.0,
             show_red_team_win_title_timer: 0.0,
             show_blue_team_win_title_timer: 0.0,
             show_join_red_team_title_timer: 0.0,
             show_join_blue_team_title_timer: 0.0,
+            show_draw_title_timer: 0.0,
+            show_you_win_title_timer: 0.0,
+            show_you_lose_title_timer: 0.0,
+            show_overtime_title_timer: 0.0,
+            match_time_left: 0.0,
+            match_phase_duration: 0.0,
+            match_timer_is_active: false,
+            is_overtime: false,
             red_flag_base_position,
             blue_flag_base_position,
             observer_mode,
+            scoreboard: Vec::new(),
         };
 
         session_controller.set_score_ui(ui_system);
@@ -116,168 +174,279 @@ impl SessionController
         session_controller
     }
 
-    pub fn set_score_ui(&self, ui: &mut UISystem)
+    pub fn get_scoreboard(&self) -> &Vec<PlayerStats>
+    {
+        &self.scoreboard
+    }
+
+    fn set_match_timer_active(&mut self, is_active: bool, ui: &mut UISystem)
+    {
+        self.match_timer_is_active = is_active;
+
+        let timer_bar = ui.get_mut_ui_element(&UIElementType::MatchTimerBar);
+        *timer_bar.get_ui_data_mut().get_is_visible_mut() = is_active;
+    }
+
+    fn update_match_timer_ui(&mut self, delta: f32, ui: &mut UISystem)
     {
-        match self.red_team_score
+        if !self.match_timer_is_active
         {
-            0 =>
-            {
-                let score_mark = ui.get_mut_ui_element(&UIElementType::FirstScoreMarkRed);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = false;
+            return;
+        }
 
-                let score_mark = ui.get_mut_ui_element(&UIElementType::SecondScoreMarkRed);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = false;
+        self.match_time_left = (self.match_time_left - delta).max(0.0);
 
-                let score_mark = ui.get_mut_ui_element(&UIElementType::ThirdScoreMarkRed);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = false;
+        let timer_bar = ui.get_mut_ui_element(&UIElementType::MatchTimerBar);
 
-                let score_mark = ui.get_mut_ui_element(&UIElementType::FinalScoreMarkRed);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = false;
+        if let UIElement::ProgressBar(bar) = timer_bar {
+            let bar_value = if self.match_phase_duration > 0.0
+            {
+                (self.match_time_left / self.match_phase_duration).clamp(0.0, 1.0)
             }
-
-            1 =>
+            else
             {
-                let score_mark = ui.get_mut_ui_element(&UIElementType::FirstScoreMarkRed);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = true;
+                0.0
+            };
 
-                let score_mark = ui.get_mut_ui_element(&UIElementType::SecondScoreMarkRed);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = false;
+            bar.set_bar_value(bar_value);
+        } else {
+            panic!("Match timer UI is not UIProgressBar")
+        }
+    }
 
-                let score_mark = ui.get_mut_ui_element(&UIElementType::ThirdScoreMarkRed);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = false;
+    // None in Free-for-all, players have no team there
+    fn get_team_look(&self, team: Team) -> Option<TeamLook>
+    {
+        match team
+        {
+            Team::NoTeam => None,
+            _ => Some(self.teams_looks.get_look(team)),
+        }
+    }
 
-                let score_mark = ui.get_mut_ui_element(&UIElementType::FinalScoreMarkRed);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = false;
-            }
+    // in Team Deathmatch and Free-for-all flags HUD is replaced by kill counters
+    fn set_game_mode_hud(&self, ui: &mut UISystem)
+    {
+        let with_kill_counters = self.game_mode != GameMode::CaptureTheFlag;
 
-            2 =>
+        if with_kill_counters
+        {
+            for elem_type in [
+                UIElementType::ScoreBar,
+                UIElementType::RedFlagMark,
+                UIElementType::BlueFlagMark,
+                UIElementType::RedFlagBacklight,
+                UIElementType::BlueFlagBacklight,
+            ]
             {
-                let score_mark = ui.get_mut_ui_element(&UIElementType::FirstScoreMarkRed);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = true;
+                let elem = ui.get_mut_ui_element(&elem_type);
+                *elem.get_ui_data_mut().get_is_visible_mut() = false;
+            }
+        }
 
-                let score_mark = ui.get_mut_ui_element(&UIElementType::SecondScoreMarkRed);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = true;
+        let kills_bar = ui.get_mut_ui_element(&UIElementType::RedTeamKillsBar);
+        *kills_bar.get_ui_data_mut().get_is_visible_mut() = with_kill_counters;
 
-                let score_mark = ui.get_mut_ui_element(&UIElementType::ThirdScoreMarkRed);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = false;
+        let kills_bar = ui.get_mut_ui_element(&UIElementType::BlueTeamKillsBar);
+        *kills_bar.get_ui_data_mut().get_is_visible_mut() = with_kill_counters;
+    }
 
-                let score_mark = ui.get_mut_ui_element(&UIElementType::FinalScoreMarkRed);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = false;
-            }
+    fn get_my_and_best_opponent_score(&self) -> (u32, u32)
+    {
+        let my_team_id = match self.your_team.get_id()
+        {
+            Some(team_id) => team_id as usize,
+            // Free-for-all
+            None => return (self.my_score, self.best_opponent_score),
+        };
 
-            3 =>
-            {
-                let score_mark = ui.get_mut_ui_element(&UIElementType::FirstScoreMarkRed);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = true;
+        let best_opponent_score = self.teams_score
+            .iter()
+            .enumerate()
+            .filter(|(team_id, _)| *team_id != my_team_id)
+            .map(|(_, score)| *score)
+            .max()
+            .unwrap_or(0u32);
 
-                let score_mark = ui.get_mut_ui_element(&UIElementType::SecondScoreMarkRed);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = true;
+        (self.teams_score[my_team_id % MAX_TEAMS], best_opponent_score)
+    }
 
-                let score_mark = ui.get_mut_ui_element(&UIElementType::ThirdScoreMarkRed);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = true;
+    // your kills are shown on the bar of your team's look
+    // and the best opponent's kills are shown on the other bar
+    fn set_kills_ui(&self, ui: &mut UISystem)
+    {
+        let (my_score, best_opponent_score) = self.get_my_and_best_opponent_score();
 
-                let score_mark = ui.get_mut_ui_element(&UIElementType::FinalScoreMarkRed);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = false;
-            }
+        let (red_bar_score, blue_bar_score) = match self.teams_looks.get_look(self.your_team)
+        {
+            TeamLook::Red => (my_score, best_opponent_score),
+            TeamLook::Blue => (best_opponent_score, my_score),
+        };
 
-            4 =>
-            {
-                let score_mark = ui.get_mut_ui_element(&UIElementType::FirstScoreMarkRed);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = true;
+        let kills_bar = ui.get_mut_ui_element(&UIElementType::RedTeamKillsBar);
+
+        if let UIElement::ProgressBar(bar) = kills_bar {
+            bar.set_bar_value(get_kills_bar_value(red_bar_score, self.max_score));
+        } else {
+            panic!("Red team kills UI is not UIProgressBar")
+        }
+
+        let kills_bar = ui.get_mut_ui_element(&UIElementType::BlueTeamKillsBar);
+
+        if let UIElement::ProgressBar(bar) = kills_bar {
+            bar.set_bar_value(get_kills_bar_value(blue_bar_score, self.max_score));
+        } else {
+            panic!("Blue team kills UI is not UIProgressBar")
+        }
+    }
 
-                let score_mark = ui.get_mut_ui_element(&UIElementType::SecondScoreMarkRed);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = true;
+    fn show_team_scored(
+        &mut self,
+        scored_team: Team,
+        ui_system: &mut UISystem,
+        audio_system: &mut AudioSystem,
+        effects_system: &mut EffectsSystem,
+        engine_handle: &mut EngineHandle,
+    )
+    {
+        let (team_color, backlight) = match self.teams_looks.get_look(scored_team)
+        {
+            TeamLook::Red =>
+            {
+                self.show_red_team_backlight_timer = SHOW_TEAM_BACKLIGHT_TIME;
 
-                let score_mark = ui.get_mut_ui_element(&UIElementType::ThirdScoreMarkRed);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = true;
+                (RED_TEAM_COLOR, UIElementType::RedTeamBacklight)
+            }
+            TeamLook::Blue =>
+            {
+                self.show_blue_team_backlight_timer = SHOW_TEAM_BACKLIGHT_TIME;
 
-                let score_mark = ui.get_mut_ui_element(&UIElementType::FinalScoreMarkRed);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = true;
+                (BLUE_TEAM_COLOR, UIElementType::BlueTeamBacklight)
             }
+        };
 
-            _ => {panic!("ERROR: red team score > 4")}
+        // only Red and Blue teams have bases
+        let base_position = match scored_team
+        {
+            Team::Red => Some(self.red_flag_base_position),
+            Team::Blue => Some(self.blue_flag_base_position),
+            _ => None,
         };
 
-        match self.blue_team_score
+        if let Some(base_position) = base_position
         {
-            0 =>
-            {
-                let score_mark = ui.get_mut_ui_element(&UIElementType::FirstScoreMarkBlue);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = false;
+            effects_system.spawn_wave(
+                engine_handle,
+                base_position,
+                vec![
+                    0.0,
+                    15.0,
+                ],
+                vec![
+                    team_color,
+                    Vec3::ZERO
+                ],
+                vec![
+                    2.0,
+                ]
+            );
+        }
 
-                let score_mark = ui.get_mut_ui_element(&UIElementType::SecondScoreMarkBlue);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = false;
+        let elem = ui_system.get_mut_ui_element(&backlight);
+        *elem.get_ui_data_mut().get_is_visible_mut() = true;
 
-                let score_mark = ui.get_mut_ui_element(&UIElementType::ThirdScoreMarkBlue);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = false;
+        let sound = if scored_team == self.your_team || self.observer_mode
+        {
+            Sound::GetScore
+        }
+        else
+        {
+            Sound::LooseScore
+        };
 
-                let score_mark = ui.get_mut_ui_element(&UIElementType::FinalScoreMarkBlue);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = false;
-            }
+        audio_system.spawn_non_spatial_sound(
+            sound,
+            1.0,
+            1.0,
+            false,
+            true,
+            Status::Playing
+        );
+    }
 
-            1 =>
+    pub fn set_score_ui(&self, ui: &mut UISystem)
+    {
+        let (red_team_marks, blue_team_marks) = match self.game_mode
+        {
+            GameMode::CaptureTheFlag =>
             {
-                let score_mark = ui.get_mut_ui_element(&UIElementType::FirstScoreMarkBlue);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = true;
-
-                let score_mark = ui.get_mut_ui_element(&UIElementType::SecondScoreMarkBlue);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = false;
+                (
+                    get_score_marks_amount(self.teams_score[0], self.max_score),
+                    get_score_marks_amount(self.teams_score[1], self.max_score),
+                )
+            }
 
-                let score_mark = ui.get_mut_ui_element(&UIElementType::ThirdScoreMarkBlue);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = false;
+            GameMode::TeamDeathmatch | GameMode::FreeForAll =>
+            {
+                self.set_kills_ui(ui);
 
-                let score_mark = ui.get_mut_ui_element(&UIElementType::FinalScoreMarkBlue);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = false;
+                (0, 0)
             }
+        };
 
-            2 =>
-            {
-                let score_mark = ui.get_mut_ui_element(&UIElementType::FirstScoreMarkBlue);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = true;
+        let score_mark = ui.get_mut_ui_element(&UIElementType::FirstScoreMarkRed);
+        *score_mark.get_ui_data_mut().get_is_visible_mut() = red_team_marks >= 1;
 
-                let score_mark = ui.get_mut_ui_element(&UIElementType::SecondScoreMarkBlue);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = true;
+        let score_mark = ui.get_mut_ui_element(&UIElementType::SecondScoreMarkRed);
+        *score_mark.get_ui_data_mut().get_is_visible_mut() = red_team_marks >= 2;
 
-                let score_mark = ui.get_mut_ui_element(&UIElementType::ThirdScoreMarkBlue);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = false;
+        let score_mark = ui.get_mut_ui_element(&UIElementType::ThirdScoreMarkRed);
+        *score_mark.get_ui_data_mut().get_is_visible_mut() = red_team_marks >= 3;
 
-                let score_mark = ui.get_mut_ui_element(&UIElementType::FinalScoreMarkBlue);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = false;
-            }
+        let score_mark = ui.get_mut_ui_element(&UIElementType::FinalScoreMarkRed);
+        *score_mark.get_ui_data_mut().get_is_visible_mut() = red_team_marks >= 4;
 
-            3 =>
-            {
-                let score_mark = ui.get_mut_ui_element(&UIElementType::FirstScoreMarkBlue);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = true;
+        let score_mark = ui.get_mut_ui_element(&UIElementType::FirstScoreMarkBlue);
+        *score_mark.get_ui_data_mut().get_is_visible_mut() = blue_team_marks >= 1;
 
-                let score_mark = ui.get_mut_ui_element(&UIElementType::SecondScoreMarkBlue);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = true;
+        let score_mark = ui.get_mut_ui_element(&UIElementType::SecondScoreMarkBlue);
+        *score_mark.get_ui_data_mut().get_is_visible_mut() = blue_team_marks >= 2;
 
-                let score_mark = ui.get_mut_ui_element(&UIElementType::ThirdScoreMarkBlue);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = true;
+        let score_mark = ui.get_mut_ui_element(&UIElementType::ThirdScoreMarkBlue);
+        *score_mark.get_ui_data_mut().get_is_visible_mut() = blue_team_marks >= 3;
 
-                let score_mark = ui.get_mut_ui_element(&UIElementType::FinalScoreMarkBlue);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = false;
-            }
+        let score_mark = ui.get_mut_ui_element(&UIElementType::FinalScoreMarkBlue);
+        *score_mark.get_ui_data_mut().get_is_visible_mut() = blue_team_marks >= 4;
+    }
+}
 
-            4 =>
-            {
-                let score_mark = ui.get_mut_ui_element(&UIElementType::FirstScoreMarkBlue);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = true;
 
-                let score_mark = ui.get_mut_ui_element(&UIElementType::SecondScoreMarkBlue);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = true;
+// HUD has only four score marks, so the score is scaled to the score limit of
+// the game session. The final mark is shown only when the team reached the limit.
+fn get_score_marks_amount(score: u32, max_score: u32) -> u32
+{
+    if score >= max_score
+    {
+        return SCORE_MARKS_AMOUNT;
+    }
 
-                let score_mark = ui.get_mut_ui_element(&UIElementType::ThirdScoreMarkBlue);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = true;
+    if max_score <= 1
+    {
+        return 0;
+    }
 
-                let score_mark = ui.get_mut_ui_element(&UIElementType::FinalScoreMarkBlue);
-                *score_mark.get_ui_data_mut().get_is_visible_mut() = true;
-            }
+    score * (SCORE_MARKS_AMOUNT - 1) / (max_score - 1)
+}
 
-            _ => {panic!("ERROR: blue team score > 4")}
-        };
+
+fn get_kills_bar_value(kills: u32, kill_limit: u32) -> f32
+{
+    if kill_limit == 0
+    {
+        return 0.0;
     }
+
+    (kills as f32 / kill_limit as f32).clamp(0.0, 1.0)
 }
 
 
@@ -351,7 +520,42 @@ impl Actor for SessionController
             delta,
             ui_system,
         );
-        
+
+        process_ui_animation(
+            &mut self.show_draw_title_timer,
+            &UIElementType::DrawTitle,
+            delta,
+            ui_system,
+        );
+
+        process_ui_animation(
+            &mut self.show_you_win_title_timer,
+            &UIElementType::YouWinTitle,
+            delta,
+            ui_system,
+        );
+
+        process_ui_animation(
+            &mut self.show_you_lose_title_timer,
+            &UIElementType::YouLoseTitle,
+            delta,
+            ui_system,
+        );
+
+        process_ui_animation(
+            &mut self.show_overtime_title_timer,
+            &UIElementType::OvertimeTitle,
+            delta,
+            ui_system,
+        );
+
+        self.update_match_timer_ui(delta, ui_system);
+
+        // flags HUD can be made visible by other actors when the player (re)spawns
+        if self.game_mode != GameMode::CaptureTheFlag
+        {
+            self.set_game_mode_hud(ui_system);
+        }
     }
 
     fn recieve_message(
@@ -375,8 +579,9 @@ impl Actor for SessionController
                         match message {
                             SessionControllerMessage::NewSessionStarted(your_team) =>
                             {
-                                self.blue_team_score = 0u32;
-                                self.red_team_score = 0u32;
+                                self.teams_score = [0u32; MAX_TEAMS];
+                                self.my_score = 0u32;
+                                self.best_opponent_score = 0u32;
                                 self.your_team = your_team;
                                 self.show_blue_team_backlight_timer = 0.0;
                                 self.show_red_team_backlight_timer = 0.0;
@@ -384,10 +589,14 @@ impl Actor for SessionController
                                 self.show_join_red_team_title_timer = 0.0;
                                 self.show_blue_team_win_title_timer = 0.0;
                                 self.show_red_team_win_title_timer = 0.0;
+                                self.show_draw_title_timer = 0.0;
+                                self.show_you_win_title_timer = 0.0;
+                                self.show_you_lose_title_timer = 0.0;
+                                self.show_overtime_title_timer = 0.0;
 
-                                match your_team
+                                match self.get_team_look(your_team)
                                 {
-                                    Team::Red =>
+                                    Some(TeamLook::Red) =>
                                     {
                                         let elem = ui_system.get_mut_ui_element(&UIElementType::RedTeamBacklight);
                                         *elem.get_ui_data_mut().get_is_visible_mut() = true;
@@ -400,7 +609,7 @@ impl Actor for SessionController
 
                                     }
 
-                                    Team::Blue =>
+                                    Some(TeamLook::Blue) =>
                                     {
                                         let elem = ui_system.get_mut_ui_element(&UIElementType::BlueTeamBacklight);
                                         *elem.get_ui_data_mut().get_is_visible_mut() = true;
@@ -412,155 +621,61 @@ impl Actor for SessionController
                                         self.show_join_blue_team_title_timer = SHOW_TEAM_JOIN_TITLE_TIME;
 
                                     }
+
+                                    // Free-for-all
+                                    None => {}
                                 }
 
                                 self.set_score_ui(ui_system);
                             }
 
-                            SessionControllerMessage::SetScore(new_red_team_score, new_blue_team_score) =>
+                            SessionControllerMessage::SetScore(new_teams_score) =>
                             {
-                                if new_red_team_score > self.red_team_score
+                                for team_id in 0..MAX_TEAMS
                                 {
-                                    effects_system.spawn_wave(
-                                        engine_handle,
-                                        self.red_flag_base_position,
-                                        vec![
-                                            0.0,
-                                            15.0,
-                                        ],
-                                        vec![
-                                            RED_TEAM_COLOR,
-                                            Vec3::ZERO
-                                        ],
-                                        vec![
-                                            2.0,
-                                        ]
-                                    );
-
-                                    let elem = ui_system.get_mut_ui_element(&UIElementType::RedTeamBacklight);
-                                    *elem.get_ui_data_mut().get_is_visible_mut() = true;
-
-                                    self.show_red_team_backlight_timer = SHOW_TEAM_BACKLIGHT_TIME;
-
-                                    match self.your_team {
-                                        Team::Red =>
-                                        {
-                                            audio_system.spawn_non_spatial_sound(
-                                                Sound::GetScore,
-                                                1.0,
-                                                1.0,
-                                                false,
-                                                true,
-                                                Status::Playing
-                                            );
-                                        }
-                                        Team::Blue =>
-                                        {
-                                            if self.observer_mode
-                                            {
-                                                audio_system.spawn_non_spatial_sound(
-                                                    Sound::GetScore,
-                                                    1.0,
-                                                    1.0,
-                                                    false,
-                                                    true,
-                                                    Status::Playing
-                                                );
-                                            }
-                                            else
-                                            {
-                                                audio_system.spawn_non_spatial_sound(
-                                                    Sound::LooseScore,
-                                                    1.0,
-                                                    1.0,
-                                                    false,
-                                                    true,
-                                                    Status::Playing
-                                                ); 
-                                            }
-                                        }
+                                    if new_teams_score[team_id] > self.teams_score[team_id]
+                                    {
+                                        self.show_team_scored(
+                                            Team::from_id(team_id as u8),
+                                            ui_system,
+                                            audio_system,
+                                            effects_system,
+                                            engine_handle,
+                                        );
                                     }
                                 }
-                                if new_blue_team_score > self.blue_team_score
-                                {
-                                    effects_system.spawn_wave(
-                                        engine_handle,
-                                        self.blue_flag_base_position,
-                                        vec![
-                                            0.0,
-                                            15.0,
-                                        ],
-                                        vec![
-                                            BLUE_TEAM_COLOR,
-                                            Vec3::ZERO
-                                        ],
-                                        vec![
-                                            2.0,
-                                        ]
-                                    );
-
-                                    let elem = ui_system.get_mut_ui_element(&UIElementType::BlueTeamBacklight);
-                                    *elem.get_ui_data_mut().get_is_visible_mut() = true;
 
-                                    self.show_blue_team_backlight_timer = SHOW_TEAM_BACKLIGHT_TIME;
-
-                                    match self.your_team {
-                                        Team::Red =>
-                                        {
-                                            if self.observer_mode
-                                            {
-                                                audio_system.spawn_non_spatial_sound(
-                                                    Sound::GetScore,
-                                                    1.0,
-                                                    1.0,
-                                                    false,
-                                                    true,
-                                                    Status::Playing
-                                                );
-                                            }
-                                            else
-                                            {
-                                                audio_system.spawn_non_spatial_sound(
-                                                    Sound::LooseScore,
-                                                    1.0,
-                                                    1.0,
-                                                    false,
-                                                    true,
-                                                    Status::Playing
-                                                ); 
-                                            }
-                                        }
-                                        Team::Blue =>
-                                        {
-                                            audio_system.spawn_non_spatial_sound(
-                                                Sound::GetScore,
-                                                1.0,
-                                                1.0,
-                                                false,
-                                                true,
-                                                Status::Playing
-                                            );
-                                        }
-                                    }
-                                }
-                                self.red_team_score = new_red_team_score;
-                                self.blue_team_score = new_blue_team_score;
+                                self.teams_score = new_teams_score;
+
+                                self.set_score_ui(ui_system);
+                            }
+
+                            SessionControllerMessage::SetFreeForAllScore(my_score, best_opponent_score) =>
+                            {
+                                self.my_score = my_score;
+                                self.best_opponent_score = best_opponent_score;
 
                                 self.set_score_ui(ui_system);
                             }
 
                             SessionControllerMessage::TeamWin(win_team) =>
                             {
+                                self.set_match_timer_active(false, ui_system);
+
                                 self.show_blue_team_backlight_timer = 0.0;
                                 self.show_red_team_backlight_timer = 0.0;
                                 self.show_join_blue_team_title_timer = 0.0;
                                 self.show_join_red_team_title_timer = 0.0;
                                 self.show_blue_team_win_title_timer = 0.0;
                                 self.show_red_team_win_title_timer = 0.0;
+                                self.show_draw_title_timer = 0.0;
+                                self.show_you_win_title_timer = 0.0;
+                                self.show_you_lose_title_timer = 0.0;
+                                self.show_overtime_title_timer = 0.0;
 
-                                match win_team
+                                match self.teams_looks.get_look(win_team)
                                 {
-                                    Team::Red =>
+                                    TeamLook::Red =>
                                     {
                                         let elem = ui_system.get_mut_ui_element(&UIElementType::RedTeamBacklight);
                                         *elem.get_ui_data_mut().get_is_visible_mut() = true;
@@ -570,47 +685,8 @@ impl Actor for SessionController
 
                                         self.show_red_team_backlight_timer = SHOW_TEAM_WIN_TITLE_TIME;
                                         self.show_red_team_win_title_timer = SHOW_TEAM_WIN_TITLE_TIME;
-
-                                        match self.your_team {
-                                            Team::Red =>
-                                            {
-                                                audio_system.spawn_non_spatial_sound(
-                                                    Sound::TeamWin,
-                                                    1.0,
-                                                    1.0,
-                                                    false,
-                                                    true,
-                                                    Status::Playing
-                                                );  
-                                            }
-                                            Team::Blue =>
-                                            {
-                                                if self.observer_mode
-                                                {
-                                                    audio_system.spawn_non_spatial_sound(
-                                                        Sound::TeamWin,
-                                                        1.0,
-                                                        1.0,
-                                                        false,
-                                                        true,
-                                                        Status::Playing
-                                                    );
-                                                }
-                                                else
-                                                {
-                                                    audio_system.spawn_non_spatial_sound(
-                                                        Sound::TeamLoose,
-                                                        1.0,
-                                                        1.0,
-                                                        false,
-                                                        true,
-                                                        Status::Playing
-                                                    );
-                                                }
-                                            }
-                                        }
                                     }
-                                    Team::Blue =>
+                                    TeamLook::Blue =>
                                     {
                                         let elem = ui_system.get_mut_ui_element(&UIElementType::BlueTeamBacklight);
                                         *elem.get_ui_data_mut().get_is_visible_mut() = true;
@@ -620,56 +696,139 @@ impl Actor for SessionController
 
                                         self.show_blue_team_backlight_timer = SHOW_TEAM_WIN_TITLE_TIME;
                                         self.show_blue_team_win_title_timer = SHOW_TEAM_WIN_TITLE_TIME;
+                                    }
+                                }
+
+                                let sound = if win_team == self.your_team || self.observer_mode
+                                {
+                                    Sound::TeamWin
+                                }
+                                else
+                                {
+                                    Sound::TeamLoose
+                                };
+
+                                audio_system.spawn_non_spatial_sound(
+                                    sound,
+                                    1.0,
+                                    1.0,
+                                    false,
+                                    true,
+                                    Status::Playing
+                                );
+                            }
+
+                            SessionControllerMessage::PlayerWin(is_winner) =>
+                            {
+                                self.set_match_timer_active(false, ui_system);
 
-                                        match self.your_team {
-                                            Team::Red =>
-                                            {
-                                                if self.observer_mode
-                                                {
-                                                    audio_system.spawn_non_spatial_sound(
-                                                        Sound::TeamWin,
-                                                        1.0,
-                                                        1.0,
-                                                        false,
-                                                        true,
-                                                        Status::Playing
-                                                    );
-                                                }
-                                                else
-                                                {
-                                                    audio_system.spawn_non_spatial_sound(
-                                                        Sound::TeamLoose,
-                                                        1.0,
-                                                        1.0,
-                                                        false,
-                                                        true,
-                                                        Status::Playing
-                                                    );
-                                                } 
-                                            }
-                                            Team::Blue =>
-                                            {
-                                                audio_system.spawn_non_spatial_sound(
-                                                    Sound::TeamWin,
-                                                    1.0,
-                                                    1.0,
-                                                    false,
-                                                    true,
-                                                    Status::Playing
-                                                );
-                                            }
-                                        }
+                                self.show_blue_team_backlight_timer = 0.0;
+                                self.show_red_team_backlight_timer = 0.0;
+                                self.show_join_blue_team_title_timer = 0.0;
+                                self.show_join_red_team_title_timer = 0.0;
+                                self.show_blue_team_win_title_timer = 0.0;
+                                self.show_red_team_win_title_timer = 0.0;
+                                self.show_draw_title_timer = 0.0;
+                                self.show_you_win_title_timer = 0.0;
+                                self.show_you_lose_title_timer = 0.0;
+                                self.show_overtime_title_timer = 0.0;
+
+                                // observer is never the winner, so it doesn't get any title
+                                if !self.observer_mode
+                                {
+                                    if is_winner
+                                    {
+                                        let elem = ui_system.get_mut_ui_element(&UIElementType::YouWinTitle);
+                                        *elem.get_ui_data_mut().get_is_visible_mut() = true;
+
+                                        self.show_you_win_title_timer = SHOW_TEAM_WIN_TITLE_TIME;
+                                    }
+                                    else
+                                    {
+                                        let elem = ui_system.get_mut_ui_element(&UIElementType::YouLoseTitle);
+                                        *elem.get_ui_data_mut().get_is_visible_mut() = true;
+
+                                        self.show_you_lose_title_timer = SHOW_TEAM_WIN_TITLE_TIME;
                                     }
                                 }
+
+                                let sound = if is_winner || self.observer_mode
+                                {
+                                    Sound::TeamWin
+                                }
+                                else
+                                {
+                                    Sound::TeamLoose
+                                };
+
+                                audio_system.spawn_non_spatial_sound(
+                                    sound,
+                                    1.0,
+                                    1.0,
+                                    false,
+                                    true,
+                                    Status::Playing
+                                );
+                            }
+
+                            SessionControllerMessage::SetMatchTimeLeft(time_left, duration, is_overtime) =>
+                            {
+                                if is_overtime && !self.is_overtime
+                                {
+                                    let elem = ui_system.get_mut_ui_element(&UIElementType::OvertimeTitle);
+                                    *elem.get_ui_data_mut().get_is_visible_mut() = true;
+
+                                    self.show_overtime_title_timer = SHOW_OVERTIME_TITLE_TIME;
+                                }
+
+                                self.is_overtime = is_overtime;
+                                self.match_time_left = time_left as f32 / 1000.0;
+                                self.match_phase_duration = duration as f32 / 1000.0;
+
+                                self.set_match_timer_active(true, ui_system);
+                            }
+
+                            SessionControllerMessage::SetScoreboard(scoreboard) =>
+                            {
+                                self.scoreboard = scoreboard;
+                            }
+
+                            SessionControllerMessage::Draw =>
+                            {
+                                self.set_match_timer_active(false, ui_system);
+
+                                self.show_blue_team_backlight_timer = 0.0;
+                                self.show_red_team_backlight_timer = 0.0;
+                                self.show_join_blue_team_title_timer = 0.0;
+                                self.show_join_red_team_title_timer = 0.0;
+                                self.show_blue_team_win_title_timer = 0.0;
+                                self.show_red_team_win_title_timer = 0.0;
+                                self.show_you_win_title_timer = 0.0;
+                                self.show_you_lose_title_timer = 0.0;
+                                self.show_overtime_title_timer = 0.0;
+
+                                let elem = ui_system.get_mut_ui_element(&UIElementType::RedTeamBacklight);
+                                *elem.get_ui_data_mut().get_is_visible_mut() = true;
+
+                                let elem = ui_system.get_mut_ui_element(&UIElementType::BlueTeamBacklight);
+                                *elem.get_ui_data_mut().get_is_visible_mut() = true;
+
+                                let elem = ui_system.get_mut_ui_element(&UIElementType::DrawTitle);
+                                *elem.get_ui_data_mut().get_is_visible_mut() = true;
+
+                                self.show_red_team_backlight_timer = SHOW_TEAM_WIN_TITLE_TIME;
+                                self.show_blue_team_backlight_timer = SHOW_TEAM_WIN_TITLE_TIME;
+                                self.show_draw_title_timer = SHOW_TEAM_WIN_TITLE_TIME;
                             }
 
                             SessionControllerMessage::JoinedToSession(
                                 your_team,
                                 red_flag_status,
                                 blue_flag_status,
-                                bonus_spot_status,
-                                red_team_score,
-                                blue_team_score,
+                                _,
+                                teams_score,
+                                max_score,
+                                game_mode,
                             ) =>
                             {
                                 println!("Joined to session");
@@ -680,14 +839,23 @@ impl Actor for SessionController
                                 self.show_join_red_team_title_timer = 0.0;
                                 self.show_blue_team_win_title_timer = 0.0;
                                 self.show_red_team_win_title_timer = 0.0;
+                                self.show_draw_title_timer = 0.0;
+                                self.show_you_win_title_timer = 0.0;
+                                self.show_you_lose_title_timer = 0.0;
+                                self.show_overtime_title_timer = 0.0;
 
                                 self.your_team = your_team;
-                                self.red_team_score = red_team_score;
-                                self.blue_team_score = blue_team_score;
+                                self.teams_score = teams_score;
+                                self.my_score = 0u32;
+                                self.best_opponent_score = 0u32;
+                                self.max_score = max_score;
+                                self.game_mode = game_mode;
 
-                                match your_team
+                                self.set_game_mode_hud(ui_system);
+
+                                match self.get_team_look(your_team)
                                 {
-                                    Team::Red =>
+                                    Some(TeamLook::Red) =>
                                     {
                                         let elem = ui_system.get_mut_ui_element(&UIElementType::RedTeamBacklight);
                                         *elem.get_ui_data_mut().get_is_visible_mut() = true;
@@ -700,7 +868,7 @@ impl Actor for SessionController
 
                                     }
 
-                                    Team::Blue =>
+                                    Some(TeamLook::Blue) =>
                                     {
                                         let elem = ui_system.get_mut_ui_element(&UIElementType::BlueTeamBacklight);
                                         *elem.get_ui_data_mut().get_is_visible_mut() = true;
@@ -712,6 +880,9 @@ impl Actor for SessionController
                                         self.show_join_blue_team_title_timer = SHOW_TEAM_JOIN_TITLE_TIME;
 
                                     }
+
+                                    // Free-for-all
+                                    None => {}
                                 }
                                 self.set_score_ui(ui_system);
                             }
@@ -726,8 +897,8 @@ impl Actor for SessionController
                             {
                                 self.your_team = team;
 
-                                match team {
-                                    Team::Red =>
+                                match self.get_team_look(team) {
+                                    Some(TeamLook::Red) =>
                                     {
                                         let elem = ui_system.get_mut_ui_element(&UIElementType::RedTeamBacklight);
                                         *elem.get_ui_data_mut().get_is_visible_mut() = true;
@@ -738,7 +909,7 @@ impl Actor for SessionController
                                         self.show_red_team_backlight_timer = SHOW_TEAM_JOIN_TITLE_TIME;
                                         self.show_join_red_team_title_timer = SHOW_TEAM_JOIN_TITLE_TIME;
                                     }
-                                    Team::Blue =>
+                                    Some(TeamLook::Blue) =>
                                     {
                                         let elem = ui_system.get_mut_ui_element(&UIElementType::BlueTeamBacklight);
                                         *elem.get_ui_data_mut().get_is_visible_mut() = true;
@@ -749,6 +920,8 @@ impl Actor for SessionController
                                         self.show_red_team_backlight_timer = SHOW_TEAM_JOIN_TITLE_TIME;
                                         self.show_join_red_team_title_timer = SHOW_TEAM_JOIN_TITLE_TIME;
                                     }
+                                    // Free-for-all
+                                    None => {}
                                 }
                             }
 
@@ -770,8 +943,18 @@ impl Actor for SessionController
                         self.show_join_red_team_title_timer = 0.0;
                         self.show_blue_team_win_title_timer = 0.0;
                         self.show_red_team_win_title_timer = 0.0;
-                        self.red_team_score = 4;
-                        self.blue_team_score = 4;
+                        self.show_draw_title_timer = 0.0;
+                        self.show_you_win_title_timer = 0.0;
+                        self.show_you_lose_title_timer = 0.0;
+                        self.show_overtime_title_timer = 0.0;
+                        self.teams_score = [self.max_score; MAX_TEAMS];
+                        self.my_score = 0u32;
+                        self.best_opponent_score = 0u32;
+                        self.scoreboard.clear();
+                        self.is_overtime = false;
+                        self.game_mode = GameMode::CaptureTheFlag;
+                        self.set_game_mode_hud(ui_system);
+                        self.set_match_timer_active(false, ui_system);
                         self.set_score_ui(ui_system);
                     }
                     _ => {}
@@ -28,6 +28,7 @@ pub mod settings;
 
 #[cfg(target_arch = "wasm32")]
 use std::{
+    cell::RefCell,
     future::Future,
     pin::Pin,
     rc::Rc,
@@ -37,7 +38,11 @@ use std::{
     }
 };
 
-use crate::actor::main_player::player_settings::PlayerSettings;
+use crate::actor::{
+    main_player::player_settings::PlayerSettings,
+    ActorWrapper
+};
+use client_server_protocol::MapInfo;
 
 use self::{
     render::RenderSystem,
@@ -77,8 +82,16 @@ pub struct Engine {
 
     #[cfg(not(target_arch = "wasm32"))]
     pub runtime: tokio::runtime::Runtime,
+
+    // the map announced by the game server which is being fetched on the web
+    #[cfg(target_arch = "wasm32")]
+    map_being_fetched: Option<(MapInfo, FetchedLevel)>,
 }
 
+// set by the fetching task when the map is fetched
+#[cfg(target_arch = "wasm32")]
+type FetchedLevel = Rc<RefCell<Option<Result<(world::level::Level, Vec<ActorWrapper>), String>>>>;
+
 #[cfg(target_arch = "wasm32")]
 struct WindowReadyFuture<'a> {
     window: &'a winit::window::Window
@@ -221,6 +234,148 @@ impl Engine {
             
             #[cfg(not(target_arch = "wasm32"))]
             runtime,
+
+            #[cfg(target_arch = "wasm32")]
+            map_being_fetched: None,
+        }
+    }
+
+    // loads the map announced by the game server if the client
+    // is on another map, must be called before the world processes
+    // the commands received from the game server, the client disconnects
+    // from the game server if it can't play on the game server's map
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn change_map_if_needed(&mut self)
+    {
+        let map_info = match self.net.take_map_to_load()
+        {
+            Some(map_info) => map_info,
+            None => return,
+        };
+
+        let map_name = map_info.get_name();
+
+        if map_name != self.world.level.map_name
+        {
+            if !world::level::Level::is_level_available(&map_name)
+            {
+                println!(
+                    "ERROR: the game server's {} map can't be loaded, disconnecting",
+                    map_name
+                );
+
+                self.net.disconnect_because_map_is_not_available(map_name);
+
+                return;
+            }
+
+            log::info!("engine systems: changing the map to {}", map_name);
+
+            let (level, actors) = pollster::block_on(
+                world::level::Level::load_level(map_name.clone())
+            );
+
+            self.set_level(level, actors);
+        }
+
+        self.check_map_content_hash(&map_info);
+    }
+
+
+    // the web client can't block on fetching the map in the frame, so the map
+    // is fetched in the background and the level is changed when it's fetched
+    #[cfg(target_arch = "wasm32")]
+    pub fn change_map_if_needed(&mut self)
+    {
+        if let Some(map_info) = self.net.take_map_to_load()
+        {
+            let map_name = map_info.get_name();
+
+            // the map fetched for the previous announcement isn't needed anymore
+            self.map_being_fetched = None;
+
+            if map_name == self.world.level.map_name
+            {
+                self.check_map_content_hash(&map_info);
+
+                return;
+            }
+
+            log::info!("engine systems: fetching the {} map", map_name);
+
+            let fetched_level: FetchedLevel = Rc::new(RefCell::new(None));
+
+            let fetched_level_setter = fetched_level.clone();
+
+            wasm_bindgen_futures::spawn_local(async move {
+                let result = world::level::Level::fetch_level(map_name).await;
+
+                *fetched_level_setter.borrow_mut() = Some(result);
+            });
+
+            self.map_being_fetched = Some((map_info, fetched_level));
+
+            return;
+        }
+
+        let fetch_result = match &self.map_being_fetched
+        {
+            Some((_, fetched_level)) => fetched_level.borrow_mut().take(),
+            None => return,
+        };
+
+        let fetch_result = match fetch_result
+        {
+            Some(fetch_result) => fetch_result,
+            None => return,
+        };
+
+        let (map_info, _) = self.map_being_fetched.take().unwrap();
+
+        match fetch_result
+        {
+            Ok((level, actors)) =>
+            {
+                log::info!("engine systems: changing the map to {}", map_info.get_name());
+
+                self.set_level(level, actors);
+
+                self.check_map_content_hash(&map_info);
+            }
+            Err(e) =>
+            {
+                println!(
+                    "ERROR: the game server's {} map can't be fetched, disconnecting, err: {}",
+                    map_info.get_name(),
+                    e
+                );
+
+                self.net.disconnect_because_map_is_not_available(map_info.get_name());
+            }
+        }
+    }
+
+
+    fn set_level(&mut self, level: world::level::Level, actors: Vec<ActorWrapper>)
+    {
+        self.world.change_level(level, actors, &mut self.engine_handle);
+
+        self.physic = PhysicsSystem::new(&self.world);
+
+        self.render.reload_static_data(&self.world);
+    }
+
+
+    fn check_map_content_hash(&mut self, map_info: &MapInfo)
+    {
+        if map_info.content_hash != self.world.level.content_hash
+        {
+            println!(
+                "ERROR: the game server's {} map differs from the local one, disconnecting",
+                map_info.get_name()
+            );
+
+            self.net.disconnect_because_of_map_mismatch();
         }
     }
 }
@@ -16,12 +16,12 @@
 
 use crate::{
     actor::{
-        ActorID, ActorWrapper, Message
+        flag::FlagStatus, ActorID, ActorWrapper, Message
     },
     engine::effects::EffectType,
 };
 
-use client_server_protocol::NetCommand;
+use client_server_protocol::{GameMode, NetCommand};
 
 pub struct Command {
     pub sender: ActorID,
@@ -37,6 +37,8 @@ pub enum CommandType {
     RespawnPlayer(ActorID),
     RemoveAllHolesAndEffects,
     ShowConnectionStatusUI,
+    // flags statuses are used if flags must be spawned
+    SetGameMode(GameMode, FlagStatus, FlagStatus),
 }
 
 pub struct EngineHandle {
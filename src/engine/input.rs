@@ -73,6 +73,15 @@ pub struct ActionsFrameState {
     pub decrease_render_quality: Action,
     pub shadows_toggle: Action,
     pub connect_to_server: Action,
+    pub server_browser: Action,
+    pub create_private_game_server: Action,
+    pub enter_join_code: Action,
+    pub enter_party_code: Action,
+    pub create_party: Action,
+    pub start_next_map_vote: Action,
+    pub start_restart_match_vote: Action,
+    pub vote_yes: Action,
+    pub vote_no: Action,
     pub arrow_up: Action,
     pub arrow_down: Action,
     pub arrow_left: Action,
@@ -104,6 +113,15 @@ impl ActionsFrameState {
         let mut decrease_render_quality = Action:: new();
         let mut shadows_toggle = Action:: new();
         let mut connect_to_server = Action:: new();
+        let mut server_browser = Action::new();
+        let mut create_private_game_server = Action::new();
+        let mut enter_join_code = Action::new();
+        let mut enter_party_code = Action::new();
+        let mut create_party = Action::new();
+        let mut start_next_map_vote = Action::new();
+        let mut start_restart_match_vote = Action::new();
+        let mut vote_yes = Action::new();
+        let mut vote_no = Action::new();
         let mut arrow_up = Action::new();
         let mut arrow_down = Action::new();
         let mut arrow_left = Action::new();
@@ -135,6 +153,15 @@ impl ActionsFrameState {
                 ButtonActions::DecreaseRenderQuality => decrease_render_quality = action.clone(),
                 ButtonActions::ShadowsToggle => shadows_toggle = action.clone(),
                 ButtonActions::ConnectToServer => connect_to_server = action.clone(),
+                ButtonActions::ServerBrowser => server_browser = action.clone(),
+                ButtonActions::CreatePrivateGameServer => create_private_game_server = action.clone(),
+                ButtonActions::EnterJoinCode => enter_join_code = action.clone(),
+                ButtonActions::EnterPartyCode => enter_party_code = action.clone(),
+                ButtonActions::CreateParty => create_party = action.clone(),
+                ButtonActions::StartNextMapVote => start_next_map_vote = action.clone(),
+                ButtonActions::StartRestartMatchVote => start_restart_match_vote = action.clone(),
+                ButtonActions::VoteYes => vote_yes = action.clone(),
+                ButtonActions::VoteNo => vote_no = action.clone(),
                 ButtonActions::ArrowUp => arrow_up = action.clone(),
                 ButtonActions::ArrowDown => arrow_down = action.clone(),
                 ButtonActions::ArrowLeft => arrow_left = action.clone(),
@@ -167,6 +194,15 @@ impl ActionsFrameState {
             shadows_toggle,
             show_hide_controls,
             connect_to_server,
+            server_browser,
+            create_private_game_server,
+            enter_join_code,
+            enter_party_code,
+            create_party,
+            start_next_map_vote,
+            start_restart_match_vote,
+            vote_yes,
+            vote_no,
             arrow_up,
             arrow_down,
             arrow_left,
@@ -198,6 +234,15 @@ impl ActionsFrameState {
         let decrease_render_quality = Action:: new();
         let shadows_toggle = Action:: new();
         let connect_to_server = Action::new();
+        let server_browser = Action::new();
+        let create_private_game_server = Action::new();
+        let enter_join_code = Action::new();
+        let enter_party_code = Action::new();
+        let create_party = Action::new();
+        let start_next_map_vote = Action::new();
+        let start_restart_match_vote = Action::new();
+        let vote_yes = Action::new();
+        let vote_no = Action::new();
         let arrow_up = Action::new();
         let arrow_down = Action::new();
         let arrow_left = Action::new();
@@ -228,6 +273,15 @@ impl ActionsFrameState {
             decrease_render_quality,
             shadows_toggle,
             connect_to_server,
+            server_browser,
+            create_private_game_server,
+            enter_join_code,
+            enter_party_code,
+            create_party,
+            start_next_map_vote,
+            start_restart_match_vote,
+            vote_yes,
+            vote_no,
             arrow_up,
             arrow_down,
             arrow_left,
@@ -273,6 +327,15 @@ pub enum ButtonActions {
     IncreaseRenderQuality,
     DecreaseRenderQuality,
     ConnectToServer,
+    ServerBrowser,
+    CreatePrivateGameServer,
+    EnterJoinCode,
+    EnterPartyCode,
+    CreateParty,
+    StartNextMapVote,
+    StartRestartMatchVote,
+    VoteYes,
+    VoteNo,
     ShadowsToggle,
     ArrowUp,
     ArrowDown,
@@ -387,6 +450,42 @@ impl InputSystem {
             SomeButton::KeyCode(KeyCode::KeyG),
             (ButtonActions::ConnectToServer, Action::new())
         );
+        actions_table.insert(
+            SomeButton::KeyCode(KeyCode::KeyL),
+            (ButtonActions::ServerBrowser, Action::new())
+        );
+        actions_table.insert(
+            SomeButton::KeyCode(KeyCode::KeyC),
+            (ButtonActions::CreatePrivateGameServer, Action::new())
+        );
+        actions_table.insert(
+            SomeButton::KeyCode(KeyCode::KeyF),
+            (ButtonActions::CreateParty, Action::new())
+        );
+        actions_table.insert(
+            SomeButton::KeyCode(KeyCode::KeyV),
+            (ButtonActions::StartNextMapVote, Action::new())
+        );
+        actions_table.insert(
+            SomeButton::KeyCode(KeyCode::KeyB),
+            (ButtonActions::StartRestartMatchVote, Action::new())
+        );
+        actions_table.insert(
+            SomeButton::KeyCode(KeyCode::F1),
+            (ButtonActions::VoteYes, Action::new())
+        );
+        actions_table.insert(
+            SomeButton::KeyCode(KeyCode::F2),
+            (ButtonActions::VoteNo, Action::new())
+        );
+        actions_table.insert(
+            SomeButton::KeyCode(KeyCode::F3),
+            (ButtonActions::EnterJoinCode, Action::new())
+        );
+        actions_table.insert(
+            SomeButton::KeyCode(KeyCode::F4),
+            (ButtonActions::EnterPartyCode, Action::new())
+        );
         actions_table.insert(
             SomeButton::KeyCode(KeyCode::ArrowUp),
             (ButtonActions::ArrowUp, Action::new())
@@ -14,15 +14,29 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use std::time::Duration;
+use std::{
+    sync::{
+        atomic::{
+            AtomicBool,
+            Ordering
+        },
+        Arc,
+        Mutex
+    },
+    time::{
+        Duration,
+        Instant
+    }
+};
 
 use fyrox_core::futures::{SinkExt, StreamExt};
 use glam::{Vec3, Vec4};
-use client_server_protocol::{ClientMessage, NetMessageToServer, ServerMessage, Team};
+use client_server_protocol::{ClientMessage, HandshakeMessage, HandshakeRejectReason, MapBounds, MapInfo, NetMessageToServer, QuantizedPlayerState, ServerMessage, Team, VoteKind, FEATURE_RECONNECT, PROTOCOL_VERSION, SUPPORTED_FEATURES};
 
 #[cfg(not(target_arch = "wasm32"))]
 use tokio::runtime::Runtime;
 use tokio::task::JoinHandle;
+use winit::keyboard::KeyCode;
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::JsValue;
 
@@ -39,7 +53,7 @@ use matchbox_socket::{
 };
 
 use matchmaking_server_protocol::{
-    ClientMatchmakingServerProtocol, GameType, GameVersion, MatchmakingServerMessage
+    name_from_bytes, parse_join_code, ClientMatchmakingServerProtocol, GameServerListEntry, GameType, GameVersion, MatchmakingServerMessage, JOIN_CODE_ALPHABET, JOIN_CODE_LENGTH
 };
 
 use client_server_protocol::{
@@ -72,9 +86,13 @@ use super::{
         EngineHandle
     },
     input::ActionsFrameState,
+    time::TimeSystem,
     ui::{
+        UIElement,
         UIElementType,
-        UISystem
+        UISystem,
+        TEXT_LINES_MAX_LINES,
+        TEXT_LINES_MAX_LINE_LENGTH
     }
 };
 
@@ -90,14 +108,54 @@ enum ConnectionError {
     ConnectionLost(Error),
     ConnectionClosedByServer,
     ConnectionTimeout,
+    // u32 - game server's protocol version
+    HandshakeRejected(u32, HandshakeRejectReason),
+    // the game server chosen from the game servers list is not running anymore
+    GameServerNotFound,
+    // the party with the join code from the settings doesn't exist
+    PartyNotFound,
+    // the party leader left before the party was started
+    PartyDisbanded,
+    // the game server's map differs from the local one
+    MapMismatch,
+    // the game server's map with this name can't be loaded
+    MapNotAvailable(String),
 }
 
 enum ConnectionState {
     WaitingForUsersRequest,
     ConnectionFailure(u32, ConnectionError),
-    ConnectingToMatchmakingServer(Option<JoinHandle<Result<String, ConnectionError>>>, u64),
+    ConnectingToMatchmakingServer(Option<JoinHandle<Result<(String, Option<[u8; JOIN_CODE_LENGTH]>, Option<u128>), ConnectionError>>>, u64),
     ConnectingToGameServer(u64, u64, Option<WebRtcSocket>),
     ConnectedToGameServer(WebRtcSocket, PeerId, Vec<u128>),
+    // the game server notified about its shutdown, the message is shown
+    // for the timer's frames before connecting to the matchmaking server again
+    GameServerShutDown(u32),
+    LoadingGameServersList(Option<JoinHandle<Result<(Vec<GameServerListEntry>, u32), ConnectionError>>>),
+    // usize - index of the selected game server in the list,
+    // u32 - ping to the matchmaking server in milliseconds
+    ChoosingGameServer(Vec<GameServerListEntry>, usize, u32),
+}
+
+// what the client asks the matchmaking server for
+#[derive(Clone)]
+enum MatchmakingRequest {
+    // any free public game server
+    AnyGameServer,
+    // u16 - index of the game server chosen from the game servers list
+    SpecificGameServer(u16),
+    CreatePrivateGameServer,
+    PrivateGameServer([u8; JOIN_CODE_LENGTH]),
+    // the flag is set when the party leader starts the party
+    CreateParty(Arc<AtomicBool>),
+    JoinParty([u8; JOIN_CODE_LENGTH]),
+}
+
+// what the typed join code is used for
+#[derive(Clone, Copy)]
+enum JoinCodeKind {
+    PrivateGameServer,
+    Party,
 }
 
 struct ConnectionData {
@@ -106,10 +164,19 @@ struct ConnectionData {
     bash_and_turn_servers: Vec<String>,
     turn_server_username: Option<String>,
     turn_server_credential: Option<String>,
-
+    matchmaking_request: MatchmakingRequest,
+    // the client joins the private game server with this join code
+    // instead of a public game server if it's set in the settings
+    private_game_server_join_code: Option<[u8; JOIN_CODE_LENGTH]>,
+    // the client joins the party with this join code at the next G press
+    // if it's set in the settings
+    party_code: Option<[u8; JOIN_CODE_LENGTH]>,
 }
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// estimated wait in the matchmaking queue which fills the wait bar
+const QUEUE_WAIT_BAR_MAX_SECONDS: f32 = 60.0;
     
 pub struct NetSystem {
     connection_data: ConnectionData,
@@ -119,6 +186,36 @@ pub struct NetSystem {
     it_is_2d_3d_example: bool,
     current_visible_ui_elem: UIElementType,
     connection_status_visible: bool,
+    // observer clients join the game server as spectators
+    is_spectator: bool,
+    // the game server reserves the seat of the player for some time after
+    // the connection is lost, the token is used to take the seat back
+    reconnect_token: Option<u128>,
+    // the party members join the game server with the party id
+    // to be placed on the same team
+    party_id: Option<u128>,
+    // (position, players waiting, estimated wait in seconds) of the last
+    // QueueStatus, written by the task connected to the matchmaking server
+    queue_status: Arc<Mutex<Option<(u32, u32, u32)>>>,
+    // map announced by the game server, the engine loads it
+    // before the received messages are processed
+    map_to_load: Option<MapInfo>,
+    // bounds of the game server's current map, players' states
+    // are quantized relative to them
+    map_bounds: Option<MapBounds>,
+    // features enabled by the game server in the handshake
+    server_features: u64,
+    // messages from the game server which can't be deserialized
+    unknown_messages_counter: u64,
+    // join code of the private game server the player is connected to,
+    // it's shown to invite other players
+    shown_join_code: Option<[u8; JOIN_CODE_LENGTH]>,
+    // characters of the join code or the party code the player is typing,
+    // None if the code entry is closed
+    entered_join_code: Option<(JoinCodeKind, Vec<u8>)>,
+    // join code of the party created by the player, written by the task
+    // connected to the matchmaking server, it's shown to invite party members
+    created_party_code: Arc<Mutex<Option<[u8; JOIN_CODE_LENGTH]>>>,
 }
 
 impl NetSystem {
@@ -135,6 +232,12 @@ impl NetSystem {
             game_server_url: None,
             turn_server_username: Some(settings.turn_server_username.clone()),
             turn_server_credential: Some(settings.turn_server_credential.clone()),
+            matchmaking_request: MatchmakingRequest::AnyGameServer,
+            private_game_server_join_code: get_join_code_from_settings(
+                &settings.private_game_server_join_code,
+                "private_game_server_join_code"
+            ),
+            party_code: get_join_code_from_settings(&settings.party_code, "party_code"),
         };
 
         
@@ -147,10 +250,77 @@ impl NetSystem {
             it_is_2d_3d_example,
             current_visible_ui_elem: UIElementType::TitlePressPToPlayOnline,
             connection_status_visible: false,
+            is_spectator: false,
+            reconnect_token: None,
+            party_id: None,
+            queue_status: Arc::new(Mutex::new(None)),
+            map_to_load: None,
+            map_bounds: None,
+            server_features: 0u64,
+            unknown_messages_counter: 0u64,
+            shown_join_code: None,
+            entered_join_code: None,
+            created_party_code: Arc::new(Mutex::new(None)),
+        }
+    }
+
+
+    // the game server with another protocol version sends only unknown messages,
+    // the warning is printed once, the rest are counted
+    fn count_unknown_message(&mut self)
+    {
+        self.unknown_messages_counter += 1;
+
+        if self.unknown_messages_counter == 1
+        {
+            println!("WARNING: recieved a message from the game server which can't be deserialized, the game server may use another protocol version");
+        }
+        else if self.unknown_messages_counter % 1000 == 0
+        {
+            println!("WARNING: recieved {} messages from the game server which can't be deserialized", self.unknown_messages_counter);
+        }
+    }
+
+
+    pub fn take_map_to_load(&mut self) -> Option<MapInfo>
+    {
+        self.map_to_load.take()
+    }
+
+
+    // the client can't play on the game server's map, the connection
+    // to the game server is closed when its socket is dropped
+    pub fn disconnect_because_of_map_mismatch(&mut self)
+    {
+        if let Some(ConnectionState::ConnectedToGameServer(..)) = self.connection_state
+        {
+            self.connection_state = Some(
+                ConnectionState::ConnectionFailure(820, ConnectionError::MapMismatch)
+            );
+        }
+    }
+
+
+    pub fn disconnect_because_map_is_not_available(&mut self, map_name: String)
+    {
+        if let Some(ConnectionState::ConnectedToGameServer(..)) = self.connection_state
+        {
+            self.connection_state = Some(
+                ConnectionState::ConnectionFailure(820, ConnectionError::MapNotAvailable(map_name))
+            );
         }
     }
 
 
+    pub fn set_is_spectator(
+        &mut self,
+        is_spectator: bool
+    )
+    {
+        self.is_spectator = is_spectator;
+    }
+
+
     pub fn set_is_visible_for_connection_status(
         &mut self,
         connection_status_visible: bool
@@ -168,6 +338,7 @@ impl NetSystem {
         async_runtime: &mut Runtime,
         audio_system: &mut AudioSystem,
         ui_system: &mut UISystem,
+        time_system: &TimeSystem,
     ) {
 
         match self.connection_state.take().expect("ERROR: connection state in Net system is None")
@@ -195,6 +366,40 @@ impl NetSystem {
                 )
             }
 
+            ConnectionState::GameServerShutDown(timer) =>
+            {
+                self.connection_state = Some(
+                    self.handle_game_server_shut_down(
+                        timer,
+                        ui_system
+                    )
+                )
+            }
+
+            ConnectionState::LoadingGameServersList(game_servers_list_promise) =>
+            {
+                self.connection_state = Some(
+                    self.handle_loading_game_servers_list_state(
+                        game_servers_list_promise,
+                        async_runtime,
+                        ui_system
+                    )
+                )
+            }
+
+            ConnectionState::ChoosingGameServer(game_servers_list, selected_game_server, ping) =>
+            {
+                self.connection_state = Some(
+                    self.handle_choosing_game_server_state(
+                        game_servers_list,
+                        selected_game_server,
+                        ping,
+                        input,
+                        ui_system
+                    )
+                )
+            }
+
             ConnectionState::ConnectingToMatchmakingServer(
                 game_server_url_promise,
                 connection_attempts_counter
@@ -204,6 +409,7 @@ impl NetSystem {
                     self.handle_connecting_to_matchmaking_server_state(
                         game_server_url_promise,
                         connection_attempts_counter,
+                        input,
                         async_runtime,
                         ui_system
                     )
@@ -235,13 +441,124 @@ impl NetSystem {
                         webrtc_socket,
                         server_id,
                         players_id,
+                        input,
                         engine_handle,
                         audio_system,
-                        ui_system
+                        ui_system,
+                        time_system,
                     )
                 );
             }
         }
+
+        self.update_join_code_ui(ui_system);
+    }
+
+
+    fn update_join_code_ui(&self, ui_system: &mut UISystem)
+    {
+        let join_code_is_visible = match &self.connection_state
+        {
+            Some(ConnectionState::WaitingForUsersRequest) => self.entered_join_code.is_some(),
+            Some(ConnectionState::ConnectingToMatchmakingServer(..)) |
+            Some(ConnectionState::ConnectingToGameServer(..)) |
+            Some(ConnectionState::ConnectedToGameServer(..)) => self.shown_join_code.is_some(),
+            _ => false,
+        };
+
+        *ui_system.get_mut_ui_element(&UIElementType::JoinCode)
+            .get_ui_data_mut()
+            .get_is_visible_mut() = join_code_is_visible && self.connection_status_visible;
+    }
+
+
+    pub fn is_entering_join_code(&self) -> bool
+    {
+        self.entered_join_code.is_some()
+    }
+
+
+    // keys pressed while the code entry is open are handled here
+    // instead of the input system, Enter connects to the private game server
+    // or joins the party, Escape closes the entry
+    pub fn type_join_code_key(
+        &mut self,
+        key: KeyCode,
+        ui_system: &mut UISystem,
+    )
+    {
+        let (join_code_kind, entered_join_code) = match &mut self.entered_join_code
+        {
+            Some((join_code_kind, entered_join_code)) => (*join_code_kind, entered_join_code),
+            None => return,
+        };
+
+        match key
+        {
+            KeyCode::Escape =>
+            {
+                self.entered_join_code = None;
+
+                return;
+            }
+
+            KeyCode::Backspace =>
+            {
+                entered_join_code.pop();
+            }
+
+            KeyCode::Enter | KeyCode::NumpadEnter =>
+            {
+                let join_code = match parse_join_code(&String::from_utf8_lossy(entered_join_code))
+                {
+                    Some(join_code) => join_code,
+                    None => return,
+                };
+
+                self.entered_join_code = None;
+
+                if let Some(ConnectionState::WaitingForUsersRequest) = self.connection_state
+                {
+                    self.connection_data.matchmaking_request = match join_code_kind
+                    {
+                        JoinCodeKind::PrivateGameServer => MatchmakingRequest::PrivateGameServer(join_code),
+                        JoinCodeKind::Party => MatchmakingRequest::JoinParty(join_code),
+                    };
+
+                    self.connection_state = Some(ConnectionState::ConnectingToMatchmakingServer(None, 2));
+                }
+
+                return;
+            }
+
+            _ =>
+            {
+                if let Some(character) = join_code_character_from_key_code(key)
+                {
+                    if entered_join_code.len() < JOIN_CODE_LENGTH
+                    {
+                        entered_join_code.push(character);
+                    }
+                }
+            }
+        }
+
+        ui_system.set_join_code(entered_join_code);
+    }
+
+
+    // if the game server has reserved the seat for the player
+    // the client tries to rejoin the same game server
+    fn get_state_after_lost_connection(&mut self) -> ConnectionState
+    {
+        if self.reconnect_token.is_some() && self.server_features & FEATURE_RECONNECT != 0
+        {
+            println!("WARNING: connection to game server is lost, trying to rejoin the game session");
+
+            return ConnectionState::ConnectingToGameServer(240, 0, None);
+        }
+
+        ConnectionState::ConnectionFailure(820, ConnectionError::ConnectionClosedByServer)
     }
 
 
@@ -253,12 +570,40 @@ impl NetSystem {
         ui_system: &mut UISystem,
     ) -> ConnectionState
     {
+        self.reconnect_token = None;
+        self.map_bounds = None;
+
+        // the message is drawn into the text panel once
+        let text_panel_is_drawn = self.current_visible_ui_elem == UIElementType::TextPanel;
+
         *ui_system.get_mut_ui_element(&self.current_visible_ui_elem)
             .get_ui_data_mut()
             .get_is_visible_mut() = false;
 
         match &reason {
-            ConnectionError::WrongVersion(_) =>
+            // there is no title for it, the message is drawn into the text panel
+            ConnectionError::MapNotAvailable(map_name) =>
+            {
+                if !text_panel_is_drawn
+                {
+                    ui_system.set_text_panel(
+                        &[
+                            String::from("CONNECTION FAILED"),
+                            format!("MAP {} IS NOT AVAILABLE", map_name),
+                        ],
+                        None
+                    );
+                }
+
+                *ui_system.get_mut_ui_element(&UIElementType::TextPanel)
+                    .get_ui_data_mut()
+                    .get_is_visible_mut() = true;
+
+                self.current_visible_ui_elem = UIElementType::TextPanel;
+            },
+
+            ConnectionError::WrongVersion(_) |
+            ConnectionError::MapMismatch =>
             {
                 *ui_system.get_mut_ui_element(&UIElementType::TitleConnectionFailedOldVersion)
                     .get_ui_data_mut()
@@ -326,6 +671,40 @@ impl NetSystem {
                 
                 self.current_visible_ui_elem = UIElementType::TitleConnectionFailedLostConnection;
             },
+
+            ConnectionError::GameServerNotFound |
+            ConnectionError::PartyNotFound |
+            ConnectionError::PartyDisbanded =>
+            {
+                *ui_system.get_mut_ui_element(&UIElementType::TitleConnectionFailedServerNotFound)
+                    .get_ui_data_mut()
+                    .get_is_visible_mut() = true;
+                
+                self.current_visible_ui_elem = UIElementType::TitleConnectionFailedServerNotFound;
+            },
+
+            ConnectionError::HandshakeRejected(_, reason) =>
+            {
+                match reason
+                {
+                    HandshakeRejectReason::WrongProtocolVersion =>
+                    {
+                        *ui_system.get_mut_ui_element(&UIElementType::TitleConnectionFailedOldVersion)
+                            .get_ui_data_mut()
+                            .get_is_visible_mut() = true;
+                        
+                        self.current_visible_ui_elem = UIElementType::TitleConnectionFailedOldVersion;
+                    },
+                    HandshakeRejectReason::HandshakeRequired =>
+                    {
+                        *ui_system.get_mut_ui_element(&UIElementType::TitleConnectionFailedServerError)
+                            .get_ui_data_mut()
+                            .get_is_visible_mut() = true;
+                        
+                        self.current_visible_ui_elem = UIElementType::TitleConnectionFailedServerError;
+                    },
+                }
+            },
         }
 
         timer -= 1;
@@ -350,6 +729,40 @@ impl NetSystem {
         }
     }
 
+
+    fn handle_game_server_shut_down(
+        &mut self,
+        mut timer: u32,
+        ui_system: &mut UISystem,
+    ) -> ConnectionState
+    {
+        self.reconnect_token = None;
+        self.map_bounds = None;
+        // the chosen game server is shutting down, the matchmaking server picks another one
+        self.connection_data.matchmaking_request = MatchmakingRequest::AnyGameServer;
+
+        *ui_system.get_mut_ui_element(&self.current_visible_ui_elem)
+            .get_ui_data_mut()
+            .get_is_visible_mut() = false;
+
+        *ui_system.get_mut_ui_element(&UIElementType::TitleServerIsShuttingDown)
+            .get_ui_data_mut()
+            .get_is_visible_mut() = true;
+
+        self.current_visible_ui_elem = UIElementType::TitleServerIsShuttingDown;
+
+        timer -= 1;
+
+        if timer == 0u32
+        {
+            ConnectionState::ConnectingToMatchmakingServer(None, 2)
+        }
+        else
+        {
+            ConnectionState::GameServerShutDown(timer)
+        }
+    }
+
         
     fn handle_waiting_for_user_input(
         &mut self,
@@ -368,10 +781,59 @@ impl NetSystem {
         
         self.current_visible_ui_elem = UIElementType::TitlePressPToPlayOnline;
 
+        // the join code or the party code of the last connection isn't shown anymore
+        self.shown_join_code = None;
+
         if input.connect_to_server.is_action_just_pressed()
         {
+            // the party from the settings is joined once, the next G presses
+            // connect as usual
+            self.connection_data.matchmaking_request = match (
+                self.connection_data.party_code.take(),
+                self.connection_data.private_game_server_join_code
+            )
+            {
+                (Some(party_code), _) => MatchmakingRequest::JoinParty(party_code),
+                (None, Some(join_code)) => MatchmakingRequest::PrivateGameServer(join_code),
+                (None, None) => MatchmakingRequest::AnyGameServer,
+            };
+
+            ConnectionState::ConnectingToMatchmakingServer(None, 2)
+        }
+        else if input.create_party.is_action_just_pressed()
+        {
+            self.connection_data.matchmaking_request = MatchmakingRequest::CreateParty(
+                Arc::new(AtomicBool::new(false))
+            );
+
+            ConnectionState::ConnectingToMatchmakingServer(None, 2)
+        }
+        else if input.create_private_game_server.is_action_just_pressed()
+        {
+            self.connection_data.matchmaking_request = MatchmakingRequest::CreatePrivateGameServer;
+
             ConnectionState::ConnectingToMatchmakingServer(None, 2)
         }
+        else if input.server_browser.is_action_just_pressed()
+        {
+            ConnectionState::LoadingGameServersList(None)
+        }
+        else if input.enter_join_code.is_action_just_pressed()
+        {
+            self.entered_join_code = Some((JoinCodeKind::PrivateGameServer, Vec::with_capacity(JOIN_CODE_LENGTH)));
+
+            ui_system.set_join_code(&[]);
+
+            ConnectionState::WaitingForUsersRequest
+        }
+        else if input.enter_party_code.is_action_just_pressed()
+        {
+            self.entered_join_code = Some((JoinCodeKind::Party, Vec::with_capacity(JOIN_CODE_LENGTH)));
+
+            ui_system.set_join_code(&[]);
+
+            ConnectionState::WaitingForUsersRequest
+        }
         else
         {
             ConnectionState::WaitingForUsersRequest
@@ -379,10 +841,147 @@ impl NetSystem {
     }
 
 
+    fn handle_loading_game_servers_list_state(
+        &mut self,
+        game_servers_list_promise: Option<JoinHandle<Result<(Vec<GameServerListEntry>, u32), ConnectionError>>>,
+        async_runtime: &mut Runtime,
+        ui_system: &mut UISystem,
+    ) -> ConnectionState
+    {
+        *ui_system.get_mut_ui_element(&self.current_visible_ui_elem)
+            .get_ui_data_mut()
+            .get_is_visible_mut() = false;
+
+        *ui_system.get_mut_ui_element(&UIElementType::TitleConnectingToServer)
+            .get_ui_data_mut()
+            .get_is_visible_mut() = true;
+        
+        self.current_visible_ui_elem = UIElementType::TitleConnectingToServer;
+
+        let promise = match game_servers_list_promise
+        {
+            Some(promise) => promise,
+            None =>
+            {
+                let promise = async_runtime.spawn(
+                    get_game_servers_list(
+                        self.connection_data.matchmaking_server_url.clone()
+                    )
+                );
+
+                return ConnectionState::LoadingGameServersList(Some(promise));
+            }
+        };
+
+        if !promise.is_finished()
+        {
+            return ConnectionState::LoadingGameServersList(Some(promise));
+        }
+
+        let (game_servers_list, ping) = match async_runtime.block_on(promise)
+        {
+            Ok(Ok(game_servers_list)) => game_servers_list,
+            Ok(Err(e)) =>
+            {
+                println!("WARNING: Can't get the game servers list: {:?}", e);
+
+                return ConnectionState::ConnectionFailure(300, e);
+            }
+            Err(e) =>
+            {
+                panic!("ERROR: game servers list async task error: {}", e)
+            }
+        };
+
+        let game_type = match self.it_is_2d_3d_example
+        {
+            false => GameType::Slice4DShooter,
+            true => GameType::Slice3DExample,
+        };
+
+        let game_servers_list: Vec<GameServerListEntry> = game_servers_list
+            .into_iter()
+            .filter(|entry| entry.game_type == game_type)
+            .collect();
+
+        if game_servers_list.is_empty()
+        {
+            println!("INFO: there are no running game servers, press G to join a new one");
+
+            return ConnectionState::WaitingForUsersRequest;
+        }
+
+        print_game_servers_list(&game_servers_list, 0);
+
+        show_game_servers_list(&game_servers_list, 0, ping, ui_system);
+
+        ConnectionState::ChoosingGameServer(game_servers_list, 0, ping)
+    }
+
+
+    fn handle_choosing_game_server_state(
+        &mut self,
+        game_servers_list: Vec<GameServerListEntry>,
+        mut selected_game_server: usize,
+        ping: u32,
+        input: ActionsFrameState,
+        ui_system: &mut UISystem,
+    ) -> ConnectionState
+    {
+        *ui_system.get_mut_ui_element(&self.current_visible_ui_elem)
+            .get_ui_data_mut()
+            .get_is_visible_mut() = false;
+
+        *ui_system.get_mut_ui_element(&UIElementType::TextPanel)
+            .get_ui_data_mut()
+            .get_is_visible_mut() = self.connection_status_visible;
+        
+        self.current_visible_ui_elem = UIElementType::TextPanel;
+
+        if input.connect_to_server.is_action_just_pressed()
+        {
+            let server_index = game_servers_list[selected_game_server].server_index;
+
+            println!("INFO: connecting to [{}] game server", server_index);
+
+            self.connection_data.matchmaking_request = MatchmakingRequest::SpecificGameServer(server_index);
+
+            return ConnectionState::ConnectingToMatchmakingServer(None, 2);
+        }
+
+        if input.server_browser.is_action_just_pressed()
+        {
+            println!("INFO: game servers list is closed");
+
+            return ConnectionState::WaitingForUsersRequest;
+        }
+
+        if input.arrow_up.is_action_just_pressed()
+        {
+            selected_game_server = (selected_game_server + game_servers_list.len() - 1) % game_servers_list.len();
+
+            print_game_servers_list(&game_servers_list, selected_game_server);
+
+            show_game_servers_list(&game_servers_list, selected_game_server, ping, ui_system);
+        }
+        else if input.arrow_down.is_action_just_pressed()
+        {
+            selected_game_server = (selected_game_server + 1) % game_servers_list.len();
+
+            print_game_servers_list(&game_servers_list, selected_game_server);
+
+            show_game_servers_list(&game_servers_list, selected_game_server, ping, ui_system);
+        }
+
+        ConnectionState::ChoosingGameServer(game_servers_list, selected_game_server, ping)
+    }
+
+
     fn handle_connecting_to_matchmaking_server_state(
         &mut self,
-        game_server_url_promise:  Option<JoinHandle<Result<String, ConnectionError>>>,
+        game_server_url_promise:  Option<JoinHandle<Result<(String, Option<[u8; JOIN_CODE_LENGTH]>, Option<u128>), ConnectionError>>>,
         connection_attempts_counter: u64,
+        input: ActionsFrameState,
         async_runtime: &mut Runtime,
         ui_system: &mut UISystem,
     ) -> ConnectionState
@@ -397,6 +996,24 @@ impl NetSystem {
         
         self.current_visible_ui_elem = UIElementType::TitleConnectingToServer;
 
+        self.update_queue_status_ui(ui_system);
+
+        if let Some(party_code) = self.created_party_code.lock().unwrap().take()
+        {
+            self.shown_join_code = Some(party_code);
+
+            ui_system.set_join_code(&party_code);
+        }
+
+        // the party leader starts the party when all members have joined
+        if let MatchmakingRequest::CreateParty(start_flag) = &self.connection_data.matchmaking_request
+        {
+            if input.connect_to_server.is_action_just_pressed()
+            {
+                start_flag.store(true, Ordering::Relaxed);
+            }
+        }
+
         match game_server_url_promise {
             Some(promise) =>
             {
@@ -404,14 +1021,39 @@ impl NetSystem {
                     let connection_to_matchmaking_result =
                         async_runtime.block_on(promise);
 
+                    *self.queue_status.lock().unwrap() = None;
+
+                    self.update_queue_status_ui(ui_system);
+
                     match connection_to_matchmaking_result {
                         Ok(connection_result) =>
                         {
                             match connection_result {
-                                Ok(game_server_url) =>
+                                Ok((game_server_url, join_code, party_id)) =>
                                 {
                                     println!("got the url of game server: {}", game_server_url);
                                     self.connection_data.game_server_url = Some(game_server_url);
+                                    self.party_id = party_id;
+                                    self.shown_join_code = join_code;
+
+                                    if let Some(join_code) = join_code
+                                    {
+                                        ui_system.set_join_code(&join_code);
+                                    }
+
+                                    // the party is matched once, the next attempts
+                                    // look for any game server
+                                    if party_id.is_some()
+                                    {
+                                        self.connection_data.matchmaking_request = MatchmakingRequest::AnyGameServer;
+                                    }
+
+                                    // the next attempts join the created private game server
+                                    // instead of creating another one
+                                    if let Some(join_code) = join_code
+                                    {
+                                        self.connection_data.matchmaking_request = MatchmakingRequest::PrivateGameServer(join_code);
+                                    }
                                     
                                     return ConnectionState::ConnectingToGameServer(
                                         240,
@@ -425,6 +1067,17 @@ impl NetSystem {
                                     {
                                         println!("WARNING: Can't connect to game server: {:?}, trying to reconnect", e);
 
+                                        // the party is created again on the next attempt,
+                                        // the leader starts it when the members have joined it again
+                                        if let MatchmakingRequest::CreateParty(_) = self.connection_data.matchmaking_request
+                                        {
+                                            self.connection_data.matchmaking_request = MatchmakingRequest::CreateParty(
+                                                Arc::new(AtomicBool::new(false))
+                                            );
+
+                                            self.shown_join_code = None;
+                                        }
+
                                         return ConnectionState::ConnectingToMatchmakingServer(
                                             None,
                                             connection_attempts_counter - 1
@@ -459,7 +1112,10 @@ impl NetSystem {
 
                         get_game_server_url(
                             self.connection_data.matchmaking_server_url.clone(),
-                            self.it_is_2d_3d_example
+                            self.it_is_2d_3d_example,
+                            self.connection_data.matchmaking_request.clone(),
+                            self.queue_status.clone(),
+                            self.created_party_code.clone()
                         )
                     ));
                 
@@ -472,6 +1128,51 @@ impl NetSystem {
     }
 
 
+    // the upper bar is filled as the client moves to the head of the queue,
+    // the lower one shows the estimated wait, a full bar is a minute or longer
+    fn update_queue_status_ui(&self, ui_system: &mut UISystem)
+    {
+        let queue_status = *self.queue_status.lock().unwrap();
+
+        let (position_bar_value, wait_bar_value) = match queue_status
+        {
+            Some((position, players_waiting, estimated_wait)) =>
+            {
+                let position_bar_value = 1.0 - (position.saturating_sub(1) as f32 / players_waiting.max(1) as f32);
+
+                // there is no estimate until some queued client is matched
+                let wait_bar_value = if estimated_wait > 0
+                {
+                    Some((estimated_wait as f32 / QUEUE_WAIT_BAR_MAX_SECONDS).clamp(0.0, 1.0))
+                }
+                else
+                {
+                    None
+                };
+
+                (Some(position_bar_value), wait_bar_value)
+            }
+            None => (None, None),
+        };
+
+        for (bar_type, bar_value) in [
+            (UIElementType::QueuePositionBar, position_bar_value),
+            (UIElementType::QueueWaitBar, wait_bar_value),
+        ]
+        {
+            let bar = ui_system.get_mut_ui_element(&bar_type);
+
+            *bar.get_ui_data_mut().get_is_visible_mut() = bar_value.is_some() && self.connection_status_visible;
+
+            if let UIElement::ProgressBar(bar) = bar {
+                bar.set_bar_value(bar_value.unwrap_or(0.0));
+            } else {
+                panic!("Queue status UI is not UIProgressBar")
+            }
+        }
+    }
+
+
     fn handle_connecting_to_game_server_state(
         &mut self,
         mut connection_timeout_counter: u64,
@@ -540,6 +1241,43 @@ impl NetSystem {
                                 let server_id = peer_id;
                                 let players_id = Vec::new();
 
+                                // features are enabled again by the game server's Welcome message
+                                self.server_features = 0u64;
+
+                                webrtc_socket
+                                    .channel_mut(0)
+                                    .send(
+                                        HandshakeMessage::Hello(PROTOCOL_VERSION, SUPPORTED_FEATURES).to_packet(),
+                                        server_id
+                                    );
+
+                                // the game server adds the client to the game session
+                                // only after the handshake and the join request,
+                                // the reliable channel keeps their order
+                                let join_request = if self.is_spectator
+                                {
+                                    NetMessageToServer::JoinAsSpectator
+                                }
+                                else if let Some(reconnect_token) = self.reconnect_token
+                                {
+                                    NetMessageToServer::RejoinAsPlayer(reconnect_token)
+                                }
+                                else if let Some(party_id) = self.party_id
+                                {
+                                    NetMessageToServer::JoinAsPartyMember(party_id)
+                                }
+                                else
+                                {
+                                    NetMessageToServer::JoinAsPlayer
+                                };
+
+                                webrtc_socket
+                                    .channel_mut(0)
+                                    .send(
+                                        ClientMessage::MessageToServer(join_request).to_packet(),
+                                        server_id
+                                    );
+
                                 println!("INFO: Connected to the game server");
                                 return ConnectionState::ConnectedToGameServer(webrtc_socket, server_id, players_id);
                             }
@@ -609,9 +1347,11 @@ impl NetSystem {
         mut webrtc_socket: WebRtcSocket,
         server_id: PeerId,
         mut players_id: Vec<u128>,
+        input: ActionsFrameState,
         engine_handle: &mut EngineHandle,
         audio_system: &mut AudioSystem,
         ui_system: &mut UISystem,
+        time_system: &TimeSystem,
     ) -> ConnectionState
     {
         *ui_system.get_mut_ui_element(&self.current_visible_ui_elem)
@@ -661,7 +1401,9 @@ impl NetSystem {
                 }
             );
 
-            return ConnectionState::ConnectionFailure(820, ConnectionError::ConnectionClosedByServer);
+            Self::set_vote_bars_visible(false, ui_system);
+
+            return self.get_state_after_lost_connection();
         }
 
         if let Ok(peers) = webrtc_socket.try_update_peers() {
@@ -696,14 +1438,99 @@ impl NetSystem {
                             }
                         );
 
-                        return ConnectionState::ConnectionFailure(820, ConnectionError::ConnectionClosedByServer);
+                        Self::set_vote_bars_visible(false, ui_system);
+
+                        return self.get_state_after_lost_connection();
                     }
                 }   
             }
         }
 
+        let vote_message = if input.start_next_map_vote.is_action_just_pressed()
+        {
+            Some(NetMessageToServer::StartVote(VoteKind::NextMap))
+        }
+        else if input.start_restart_match_vote.is_action_just_pressed()
+        {
+            Some(NetMessageToServer::StartVote(VoteKind::RestartMatch))
+        }
+        else if input.vote_yes.is_action_just_pressed()
+        {
+            Some(NetMessageToServer::CastVote(true))
+        }
+        else if input.vote_no.is_action_just_pressed()
+        {
+            Some(NetMessageToServer::CastVote(false))
+        }
+        else
+        {
+            None
+        };
+
+        if let Some(message) = vote_message
+        {
+            webrtc_socket
+                .channel_mut(0)
+                .send(
+                    ClientMessage::MessageToServer(message).to_packet(),
+                    server_id
+                );
+        }
+
+        // engine's commands are executed in reverse order, so the command to remove all holes
+        // is sent after all received messages are processed, otherwise the holes of the game
+        // session, which are received right after JoinTheMatch, would be removed too
+        let mut remove_all_holes_and_effects = false;
+
         for (_, packet) in webrtc_socket.channel_mut(0).receive() {
 
+            if let Some(handshake) = HandshakeMessage::from_packet(&packet) {
+                match handshake
+                {
+                    HandshakeMessage::Welcome(protocol_version, features) =>
+                    {
+                        println!(
+                            "INFO: the game server accepted the handshake, protocol version: {}, features: {:#b}",
+                            protocol_version,
+                            features
+                        );
+
+                        self.server_features = features;
+                    }
+
+                    HandshakeMessage::Rejected(protocol_version, reason) =>
+                    {
+                        match reason
+                        {
+                            HandshakeRejectReason::WrongProtocolVersion =>
+                            {
+                                println!(
+                                    "ERROR: the game server rejected the connection, its protocol version is {}, the client's protocol version is {}. Please update the game",
+                                    protocol_version,
+                                    PROTOCOL_VERSION
+                                );
+                            }
+                            HandshakeRejectReason::HandshakeRequired =>
+                            {
+                                println!("ERROR: the game server rejected the connection, the join request was sent before the handshake");
+                            }
+                        }
+
+                        return ConnectionState::ConnectionFailure(
+                            300,
+                            ConnectionError::HandshakeRejected(protocol_version, reason)
+                        );
+                    }
+
+                    HandshakeMessage::Hello(_, _) =>
+                    {
+                        eprintln!("ERROR: recieved client's Hello handshake message from the game server");
+                    }
+                }
+
+                continue;
+            }
+
             if let Some(message) = ServerMessage::from_packet(packet) {
                 match message
                 {
@@ -712,10 +1539,15 @@ impl NetSystem {
                         your_team,
                         red_flag_status,
                         blue_flag_status,
-                        bonus_spot_status,
-                        red_team_score,
-                        blue_team_score,
+                        collected_move_w_bonuses,
+                        teams_score,
+                        max_score,
+                        game_mode,
+                        map_info,
                     ) => {
+                        self.map_to_load = Some(map_info);
+                        self.map_bounds = Some(map_info.bounds);
+
                         engine_handle.send_command(Command {
                             sender: 0_u128,
                             command_type: CommandType::NetCommand(
@@ -724,8 +1556,13 @@ impl NetSystem {
                         });
                         engine_handle.send_command(Command {
                             sender: 0_u128,
-                            command_type: CommandType::RemoveAllHolesAndEffects
+                            command_type: CommandType::SetGameMode(
+                                game_mode,
+                                FlagStatus::from(red_flag_status),
+                                FlagStatus::from(blue_flag_status),
+                            )
                         });
+                        remove_all_holes_and_effects = true;
                         engine_handle.send_boardcast_message(
                             Message {
                                 from: 0u128,
@@ -736,9 +1573,10 @@ impl NetSystem {
                                             your_team,
                                             FlagStatus::from(red_flag_status),
                                             FlagStatus::from(blue_flag_status),
-                                            BonusSpotStatus::from(bonus_spot_status),
-                                            red_team_score,
-                                            blue_team_score,
+                                            collected_move_w_bonuses,
+                                            teams_score,
+                                            max_score,
+                                            game_mode,
                                         )
                                     )
                                 )
@@ -787,18 +1625,21 @@ impl NetSystem {
                             audio_system,
                             &self.player_settings,
                             self.it_is_2d_3d_example,
+                            self.map_bounds,
+                            time_system.get_server_time(),
                         );
                     }
 
                     ServerMessage::NewSessionStarted(
                         server_time,
                         your_team,
+                        map_info,
                     ) =>
                     {
-                        engine_handle.send_command(Command {
-                            sender: 0_u128,
-                            command_type: CommandType::RemoveAllHolesAndEffects
-                        });
+                        self.map_to_load = Some(map_info);
+                        self.map_bounds = Some(map_info.bounds);
+
+                        remove_all_holes_and_effects = true;
                         engine_handle.send_boardcast_message(
                             Message {
                                 from: 0u128,
@@ -811,8 +1652,125 @@ impl NetSystem {
                             }
                         );
                     }
+
+                    ServerMessage::ServerIsFull =>
+                    {
+                        println!("WARNING: all slots on the game server are occupied");
+
+                        return ConnectionState::ConnectionFailure(300, ConnectionError::NoFreeServers);
+                    }
+
+                    ServerMessage::ReconnectToken(reconnect_token) =>
+                    {
+                        self.reconnect_token = Some(reconnect_token);
+                    }
+
+                    ServerMessage::VoteState(vote_kind, initiator, yes_votes, no_votes, required_votes, time_left) =>
+                    {
+                        println!(
+                            "INFO: player {} started the vote {:?}, yes: {}, no: {}, required: {}, {} seconds left, press F1 to vote yes or F2 to vote no",
+                            initiator,
+                            vote_kind,
+                            yes_votes,
+                            no_votes,
+                            required_votes,
+                            time_left / 1000
+                        );
+
+                        Self::set_vote_bars_visible(true, ui_system);
+
+                        let required_votes = required_votes.max(1) as f32;
+
+                        for (bar_type, votes) in [
+                            (UIElementType::VoteYesBar, yes_votes),
+                            (UIElementType::VoteNoBar, no_votes),
+                        ]
+                        {
+                            if let UIElement::ProgressBar(bar) = ui_system.get_mut_ui_element(&bar_type) {
+                                bar.set_bar_value((votes as f32 / required_votes).clamp(0.0, 1.0));
+                            } else {
+                                panic!("Vote UI is not UIProgressBar")
+                            }
+                        }
+                    }
+
+                    ServerMessage::VoteFinished(vote_kind, passed) =>
+                    {
+                        println!(
+                            "INFO: the vote {:?} is {}",
+                            vote_kind,
+                            if passed {"passed"} else {"failed"}
+                        );
+
+                        Self::set_vote_bars_visible(false, ui_system);
+                    }
+
+                    ServerMessage::YouAreKicked =>
+                    {
+                        println!("WARNING: kicked from the game server");
+
+                        return ConnectionState::ConnectionFailure(300, ConnectionError::ConnectionClosedByServer);
+                    }
+
+                    ServerMessage::ServerShuttingDown(reason) =>
+                    {
+                        println!("INFO: the game server is shutting down, reason: {:?}", reason);
+
+                        engine_handle.send_boardcast_message(
+                            Message {
+                                from: 0u128,
+                                remote_sender: false,
+                                message: MessageType::SpecificActorMessage(
+                                    SpecificActorMessage::SessionControllerMessage(
+                                        SessionControllerMessage::NewSessionStarted(
+                                            session_controller::DEFAULT_TEAM
+                                        )
+                                    )
+                                )
+                            }
+                        );
+
+                        engine_handle.send_boardcast_message(
+                            Message {
+                                from: 0u128,
+                                remote_sender: false,
+                                message: MessageType::CommonActorsMessages(
+                                    CommonActorsMessage::ClientDisconnectedFromGameServer
+                                )
+                            }
+                        );
+
+                        return ConnectionState::GameServerShutDown(180);
+                    }
+
+                    ServerMessage::Scoreboard(scoreboard) =>
+                    {
+                        engine_handle.send_boardcast_message(
+                            Message {
+                                from: 0u128,
+                                remote_sender: true,
+                                message: MessageType::SpecificActorMessage(
+                                    SpecificActorMessage::SessionControllerMessage(
+                                        SessionControllerMessage::SetScoreboard(scoreboard)
+                                    )
+                                )
+                            }
+                        );
+                    }
                 }
             }
+            else
+            {
+                self.count_unknown_message();
+            }
+        }
+
+        if remove_all_holes_and_effects
+        {
+            engine_handle.send_command(Command {
+                sender: 0_u128,
+                command_type: CommandType::RemoveAllHolesAndEffects
+            });
         }
 
         for (_, packet) in webrtc_socket.channel_mut(1).receive() {
@@ -820,12 +1778,12 @@ impl NetSystem {
             if let Some(message) = ServerMessage::from_packet(packet) {
                 match message {
 
-                    ServerMessage::NewSessionStarted(_,_) =>
+                    ServerMessage::NewSessionStarted(_,_,_) =>
                     {
                         eprintln!("ERROR: recieved NewSessionStarted message from unreliable channel")
                     }
 
-                    ServerMessage::JoinTheMatch(_,_,_,_,_,_,_) =>
+                    ServerMessage::JoinTheMatch(_,_,_,_,_,_,_,_,_) =>
                     {
                         eprintln!("ERROR: recieved JoinTheMatch message from unreliable channel")
                     }
@@ -839,6 +1797,41 @@ impl NetSystem {
                     {
                         eprintln!("ERROR: recieved PlayerDisconnected message from unreliable channel")
                     }
+
+                    ServerMessage::Scoreboard(_) =>
+                    {
+                        eprintln!("ERROR: recieved Scoreboard message from unreliable channel")
+                    }
+
+                    ServerMessage::ServerIsFull =>
+                    {
+                        eprintln!("ERROR: recieved ServerIsFull message from unreliable channel")
+                    }
+
+                    ServerMessage::ReconnectToken(_) =>
+                    {
+                        eprintln!("ERROR: recieved ReconnectToken message from unreliable channel")
+                    }
+
+                    ServerMessage::VoteState(..) =>
+                    {
+                        eprintln!("ERROR: recieved VoteState message from unreliable channel")
+                    }
+
+                    ServerMessage::VoteFinished(..) =>
+                    {
+                        eprintln!("ERROR: recieved VoteFinished message from unreliable channel")
+                    }
+
+                    ServerMessage::YouAreKicked =>
+                    {
+                        eprintln!("ERROR: recieved YouAreKicked message from unreliable channel")
+                    }
+
+                    ServerMessage::ServerShuttingDown(_) =>
+                    {
+                        eprintln!("ERROR: recieved ServerShuttingDown message from unreliable channel")
+                    }
                     
                     ServerMessage::NetMessageToPlayer(from_player, message) => {
                         process_message(
@@ -848,16 +1841,36 @@ impl NetSystem {
                             audio_system,
                             &self.player_settings,
                             self.it_is_2d_3d_example,
+                            self.map_bounds,
+                            time_system.get_server_time(),
                         );
                     }
                 }
             }
+            else
+            {
+                self.count_unknown_message();
+            }
         }
 
         return ConnectionState::ConnectedToGameServer(webrtc_socket, server_id, players_id);
     }
 
 
+    // the tally of the vote in progress: yes votes on the left bar, no votes on the right one,
+    // both relative to the votes required to pass the vote
+    fn set_vote_bars_visible(is_visible: bool, ui_system: &mut UISystem)
+    {
+        *ui_system.get_mut_ui_element(&UIElementType::VoteYesBar)
+            .get_ui_data_mut()
+            .get_is_visible_mut() = is_visible;
+
+        *ui_system.get_mut_ui_element(&UIElementType::VoteNoBar)
+            .get_ui_data_mut()
+            .get_is_visible_mut() = is_visible;
+    }
+
+
     pub fn send_message_to_game_server(&mut self, message: NetMessageToServer) {
         match &mut self.connection_state
             .as_mut()
@@ -905,6 +1918,39 @@ impl NetSystem {
     }
 
 
+    // the state is dropped until the client joins the game session
+    // and gets the map's bounds
+    pub fn send_player_doll_state_unreliable(
+        &mut self,
+        actor_id: u128,
+        transform: ([f32;4], [f32;16]),
+        input_state: (bool,bool,bool,bool,bool),
+        velocity: [f32;4],
+        server_time: u128,
+    ) {
+        let map_bounds = match self.map_bounds
+        {
+            Some(map_bounds) => map_bounds,
+            None => return,
+        };
+
+        let player_state = QuantizedPlayerState::new(
+            transform,
+            input_state,
+            velocity,
+            server_time,
+            &map_bounds
+        );
+
+        self.send_boardcast_message_unreliable(
+            NetMessageToPlayer::RemoteDirectMessage(
+                actor_id,
+                RemoteMessage::SetPlayerDollState(player_state)
+            )
+        );
+    }
+
+
     pub fn send_boardcast_message_unreliable(&mut self, message: NetMessageToPlayer) {
 
         match &mut self.connection_state
@@ -984,6 +2030,8 @@ fn process_message(
     audio_system: &mut AudioSystem,
     player_settings: &PlayerSettings,
     it_is_2d_3d_example: bool,
+    map_bounds: Option<MapBounds>,
+    server_time: u128,
 ) {
     match message
     {
@@ -1003,7 +2051,10 @@ fn process_message(
                 {
                     let position = Vec4::from_array(pos);
 
-                    let player_death_explode = PlayersDeathExplosion::new(position, team);
+                    let player_death_explode = PlayersDeathExplosion::new(
+                        position,
+                        player_settings.teams_looks.get_visuals(team, message_from_peer_id).color
+                    );
 
                     engine_handle.send_command(Command {
                         sender: 0u128,
@@ -1071,6 +2122,37 @@ fn process_message(
                         command_type: CommandType::SpawnActor(actor)
                     })
                 }
+
+                RemoteCommand::SpawnObstacle(
+                    position,
+                    radius,
+                    color,
+                    target_size_reached,
+                    target_radius,
+                    explode_current_time,
+                    explode_final_time,
+                ) =>
+                {
+                    let transform = Transform::from_position(Vec4::from_array(position));
+                    let color = Vec3::from_array(color);
+
+                    let obstacle = Hole::new_obstacle(
+                        transform,
+                        radius,
+                        color,
+                        target_size_reached,
+                        target_radius,
+                        explode_current_time,
+                        explode_final_time,
+                    );
+
+                    let actor = ActorWrapper::Hole(obstacle);
+
+                    engine_handle.send_command(Command {
+                        sender: 0u128,
+                        command_type: CommandType::SpawnActor(actor)
+                    })
+                }
             }
 
         },
@@ -1161,8 +2243,81 @@ fn process_message(
                             remote_sender: true,
                             message: MessageType::SpecificActorMessage(
                                 SpecificActorMessage::SessionControllerMessage(
-                                    SessionControllerMessage::TeamWin(
-                                        team
+                                    SessionControllerMessage::TeamWin(
+                                        team
+                                    )
+                                )
+                            )
+                        }
+                    );
+                }
+
+                RemoteMessage::SetMatchTimeLeft(time_left, duration, is_overtime) =>
+                {
+                    engine_handle.send_direct_message(
+                        actor_id,
+                        Message {
+                            from: message_from_peer_id,
+                            remote_sender: true,
+                            message: MessageType::SpecificActorMessage(
+                                SpecificActorMessage::SessionControllerMessage(
+                                    SessionControllerMessage::SetMatchTimeLeft(
+                                        time_left,
+                                        duration,
+                                        is_overtime
+                                    )
+                                )
+                            )
+                        }
+                    );
+                }
+
+                RemoteMessage::Draw =>
+                {
+                    engine_handle.send_direct_message(
+                        actor_id,
+                        Message {
+                            from: message_from_peer_id,
+                            remote_sender: true,
+                            message: MessageType::SpecificActorMessage(
+                                SpecificActorMessage::SessionControllerMessage(
+                                    SessionControllerMessage::Draw
+                                )
+                            )
+                        }
+                    );
+                }
+
+                RemoteMessage::UpdateTeamsScore(teams_score) =>
+                {
+                    engine_handle.send_direct_message(
+                        actor_id,
+                        Message {
+                            from: message_from_peer_id,
+                            remote_sender: true,
+                            message: MessageType::SpecificActorMessage(
+                                SpecificActorMessage::SessionControllerMessage(
+                                    SessionControllerMessage::SetScore(
+                                        teams_score
+                                    )
+                                )
+                            )
+                        }
+                    );
+                }
+
+                RemoteMessage::UpdateFreeForAllScore(my_score, best_opponent_score) =>
+                {
+                    engine_handle.send_direct_message(
+                        actor_id,
+                        Message {
+                            from: message_from_peer_id,
+                            remote_sender: true,
+                            message: MessageType::SpecificActorMessage(
+                                SpecificActorMessage::SessionControllerMessage(
+                                    SessionControllerMessage::SetFreeForAllScore(
+                                        my_score,
+                                        best_opponent_score
                                     )
                                 )
                             )
@@ -1170,7 +2325,7 @@ fn process_message(
                     );
                 }
 
-                RemoteMessage::UpdateTeamsScore(red_team_score, blue_team_score) =>
+                RemoteMessage::PlayerWin(is_winner) =>
                 {
                     engine_handle.send_direct_message(
                         actor_id,
@@ -1179,9 +2334,8 @@ fn process_message(
                             remote_sender: true,
                             message: MessageType::SpecificActorMessage(
                                 SpecificActorMessage::SessionControllerMessage(
-                                    SessionControllerMessage::SetScore(
-                                        red_team_score,
-                                        blue_team_score
+                                    SessionControllerMessage::PlayerWin(
+                                        is_winner
                                     )
                                 )
                             )
@@ -1243,16 +2397,22 @@ fn process_message(
                     );
                 }
 
-                RemoteMessage::SetPlayerDollState(
-                    tr,
-                    input,
-                    velocity,
-                    time
-                ) =>
+                RemoteMessage::SetPlayerDollState(player_state) =>
                 {
-                    let transform = Transform::from_serializable_transform(tr);
-                    let input_state = PlayerDollInputState::deserialize(input);
-                    let velocity = Vec4::from_array(velocity);
+                    // the state is quantized relative to the map's bounds,
+                    // they are known after joining the game session
+                    let map_bounds = match map_bounds
+                    {
+                        Some(map_bounds) => map_bounds,
+                        None => return,
+                    };
+
+                    let transform = Transform::from_serializable_transform(
+                        player_state.get_transform(&map_bounds)
+                    );
+                    let input_state = PlayerDollInputState::deserialize(player_state.get_input());
+                    let velocity = Vec4::from_array(player_state.get_velocity());
+                    let time = player_state.get_time(server_time);
 
                     engine_handle.send_direct_message(
                         actor_id,
@@ -1598,7 +2758,41 @@ fn process_message(
                     );
                 }
 
-                RemoteMessage::UpdateTeamsScore(red_team_score, blue_team_score) =>
+                RemoteMessage::SetMatchTimeLeft(time_left, duration, is_overtime) =>
+                {
+                    engine_handle.send_boardcast_message(
+                        Message {
+                            from: message_from_peer_id,
+                            remote_sender: true,
+                            message: MessageType::SpecificActorMessage(
+                                SpecificActorMessage::SessionControllerMessage(
+                                    SessionControllerMessage::SetMatchTimeLeft(
+                                        time_left,
+                                        duration,
+                                        is_overtime
+                                    )
+                                )
+                            )
+                        }
+                    );
+                }
+
+                RemoteMessage::Draw =>
+                {
+                    engine_handle.send_boardcast_message(
+                        Message {
+                            from: message_from_peer_id,
+                            remote_sender: true,
+                            message: MessageType::SpecificActorMessage(
+                                SpecificActorMessage::SessionControllerMessage(
+                                    SessionControllerMessage::Draw
+                                )
+                            )
+                        }
+                    );
+                }
+
+                RemoteMessage::UpdateTeamsScore(teams_score) =>
                 {
                     engine_handle.send_boardcast_message(
                         Message {
@@ -1607,8 +2801,42 @@ fn process_message(
                             message: MessageType::SpecificActorMessage(
                                 SpecificActorMessage::SessionControllerMessage(
                                     SessionControllerMessage::SetScore(
-                                        red_team_score,
-                                        blue_team_score
+                                        teams_score
+                                    )
+                                )
+                            )
+                        }
+                    );
+                }
+
+                RemoteMessage::UpdateFreeForAllScore(my_score, best_opponent_score) =>
+                {
+                    engine_handle.send_boardcast_message(
+                        Message {
+                            from: message_from_peer_id,
+                            remote_sender: true,
+                            message: MessageType::SpecificActorMessage(
+                                SpecificActorMessage::SessionControllerMessage(
+                                    SessionControllerMessage::SetFreeForAllScore(
+                                        my_score,
+                                        best_opponent_score
+                                    )
+                                )
+                            )
+                        }
+                    );
+                }
+
+                RemoteMessage::PlayerWin(is_winner) =>
+                {
+                    engine_handle.send_boardcast_message(
+                        Message {
+                            from: message_from_peer_id,
+                            remote_sender: true,
+                            message: MessageType::SpecificActorMessage(
+                                SpecificActorMessage::SessionControllerMessage(
+                                    SessionControllerMessage::PlayerWin(
+                                        is_winner
                                     )
                                 )
                             )
@@ -1667,16 +2895,20 @@ fn process_message(
                     );
                 }
 
-                RemoteMessage::SetPlayerDollState(
-                    transform,
-                    input_state,
-                    velocity,
-                    time,
-                ) =>
+                RemoteMessage::SetPlayerDollState(player_state) =>
                 {
-                    let transform = Transform::from_serializable_transform(transform);
-                    let input_state = PlayerDollInputState::deserialize(input_state);
-                    let velocity = Vec4::from_array(velocity);
+                    let map_bounds = match map_bounds
+                    {
+                        Some(map_bounds) => map_bounds,
+                        None => return,
+                    };
+
+                    let transform = Transform::from_serializable_transform(
+                        player_state.get_transform(&map_bounds)
+                    );
+                    let input_state = PlayerDollInputState::deserialize(player_state.get_input());
+                    let velocity = Vec4::from_array(player_state.get_velocity());
+                    let time = player_state.get_time(server_time);
 
                     engine_handle.send_boardcast_message(
                         Message {
@@ -1923,133 +3155,415 @@ fn process_message(
     }
 }
 
+fn get_join_code_from_settings(
+    join_code: &Option<String>,
+    setting_name: &str,
+) -> Option<[u8; JOIN_CODE_LENGTH]>
+{
+    let join_code = join_code.as_ref()?;
+
+    let parsed_join_code = parse_join_code(join_code);
+
+    if parsed_join_code.is_none()
+    {
+        println!("WARNING: {} {} in settings.json is not a correct join code", setting_name, join_code);
+    }
+
+    parsed_join_code
+}
+
+fn join_code_character_from_key_code(key: KeyCode) -> Option<u8>
+{
+    let character = match key
+    {
+        KeyCode::KeyA => b'A', KeyCode::KeyB => b'B', KeyCode::KeyC => b'C',
+        KeyCode::KeyD => b'D', KeyCode::KeyE => b'E', KeyCode::KeyF => b'F',
+        KeyCode::KeyG => b'G', KeyCode::KeyH => b'H', KeyCode::KeyI => b'I',
+        KeyCode::KeyJ => b'J', KeyCode::KeyK => b'K', KeyCode::KeyL => b'L',
+        KeyCode::KeyM => b'M', KeyCode::KeyN => b'N', KeyCode::KeyO => b'O',
+        KeyCode::KeyP => b'P', KeyCode::KeyQ => b'Q', KeyCode::KeyR => b'R',
+        KeyCode::KeyS => b'S', KeyCode::KeyT => b'T', KeyCode::KeyU => b'U',
+        KeyCode::KeyV => b'V', KeyCode::KeyW => b'W', KeyCode::KeyX => b'X',
+        KeyCode::KeyY => b'Y', KeyCode::KeyZ => b'Z',
+        KeyCode::Digit0 | KeyCode::Numpad0 => b'0',
+        KeyCode::Digit1 | KeyCode::Numpad1 => b'1',
+        KeyCode::Digit2 | KeyCode::Numpad2 => b'2',
+        KeyCode::Digit3 | KeyCode::Numpad3 => b'3',
+        KeyCode::Digit4 | KeyCode::Numpad4 => b'4',
+        KeyCode::Digit5 | KeyCode::Numpad5 => b'5',
+        KeyCode::Digit6 | KeyCode::Numpad6 => b'6',
+        KeyCode::Digit7 | KeyCode::Numpad7 => b'7',
+        KeyCode::Digit8 | KeyCode::Numpad8 => b'8',
+        KeyCode::Digit9 | KeyCode::Numpad9 => b'9',
+        _ => return None,
+    };
+
+    // characters which can't be in a join code are ignored
+    match JOIN_CODE_ALPHABET.contains(&character)
+    {
+        true => Some(character),
+        false => None,
+    }
+}
+
+fn print_game_servers_list(game_servers_list: &[GameServerListEntry], selected_game_server: usize)
+{
+    let version = GameVersion::from(VERSION);
+
+    println!("INFO: game servers list (arrow up and down to choose, G to connect, L to close):");
+
+    for (index, entry) in game_servers_list.iter().enumerate()
+    {
+        let map_name = name_from_bytes(&entry.map_name);
+        let game_mode = name_from_bytes(&entry.game_mode);
+
+        println!(
+            "{} [{}] map: {}, mode: {}, players: {}/{}, version: {}.{}.{}{}",
+            if index == selected_game_server {">"} else {" "},
+            entry.server_index,
+            if map_name.is_empty() {"unknown"} else {map_name.as_str()},
+            if game_mode.is_empty() {"unknown"} else {game_mode.as_str()},
+            entry.players_amount,
+            entry.max_players,
+            entry.game_version.0,
+            entry.game_version.1,
+            entry.game_version.2,
+            if GameVersion::from(entry.game_version) != version {" (another game version)"} else {""},
+        );
+    }
+}
+
+// draws the game servers list on the screen, when the list doesn't fit
+// only the entries around the selected one are shown
+fn show_game_servers_list(
+    game_servers_list: &[GameServerListEntry],
+    selected_game_server: usize,
+    ping: u32,
+    ui_system: &mut UISystem,
+)
+{
+    let version = GameVersion::from(VERSION);
+
+    // two lines are taken by the header
+    let shown_entries = TEXT_LINES_MAX_LINES - 2;
+
+    let first_shown_entry = selected_game_server
+        .saturating_sub(shown_entries / 2)
+        .min(game_servers_list.len().saturating_sub(shown_entries));
+
+    let mut lines = Vec::with_capacity(TEXT_LINES_MAX_LINES);
+
+    lines.push(String::from("UP/DOWN - CHOOSE   G - CONNECT   L - CLOSE"));
+    lines.push(String::from("  SERVER MAP          MODE       PLAYERS  PING"));
+
+    for (index, entry) in game_servers_list
+        .iter()
+        .enumerate()
+        .skip(first_shown_entry)
+        .take(shown_entries)
+    {
+        let map_name = name_from_bytes(&entry.map_name);
+        let game_mode = name_from_bytes(&entry.game_mode);
+
+        let mut line = format!(
+            "{} #{:<5} {:<12.12} {:<10.10} {:>3}/{:<3}  {}MS{}",
+            if index == selected_game_server {">"} else {" "},
+            entry.server_index,
+            if map_name.is_empty() {"?"} else {map_name.as_str()},
+            if game_mode.is_empty() {"?"} else {game_mode.as_str()},
+            entry.players_amount,
+            entry.max_players,
+            ping,
+            if GameVersion::from(entry.game_version) != version {" OLD"} else {""},
+        );
+
+        line.truncate(TEXT_LINES_MAX_LINE_LENGTH);
+
+        lines.push(line);
+    }
+
+    ui_system.set_text_panel(&lines, Some(2 + selected_game_server - first_shown_entry));
+}
+
+// game servers are run on the matchmaking server's host, so the round trip time
+// of the list request is returned as the ping to the game servers
+async fn get_game_servers_list(
+    matchmaking_server_url: String,
+) -> Result<(Vec<GameServerListEntry>, u32), ConnectionError>
+{
+    let connection_result = tokio::time::timeout(
+        Duration::from_secs(5),
+        connect_async(matchmaking_server_url)
+    ).await;
+
+    let mut ws_stream = match connection_result
+    {
+        Ok(Ok((ws_stream, _))) => ws_stream,
+        Ok(Err(e)) => return Err(ConnectionError::ConnectionLost(e)),
+        Err(_) => return Err(ConnectionError::ConnectionTimeout),
+    };
+
+    let message = ClientMatchmakingServerProtocol::ClientMessage(
+        matchmaking_server_protocol::ClientMessage::ListGameServers
+    ).to_packet();
+
+    let request_time = Instant::now();
+
+    if let Err(e) = ws_stream
+        .send(tokio_tungstenite::tungstenite::Message::binary(message))
+        .await
+    {
+        return Err(ConnectionError::ConnectionLost(e));
+    }
+
+    let message = match ws_stream.next().await
+    {
+        Some(Ok(message)) => message,
+        Some(Err(e)) => return Err(ConnectionError::ConnectionLost(e)),
+        None => return Err(ConnectionError::ConnectionClosedByServer),
+    };
+
+    let ping = request_time.elapsed().as_millis() as u32;
+
+    match alkahest::deserialize::<ClientMatchmakingServerProtocol, ClientMatchmakingServerProtocol>(&message.into_data())
+    {
+        Ok(ClientMatchmakingServerProtocol::MatchmakingServerMessage(
+            MatchmakingServerMessage::GameServersList(game_servers_list)
+        )) => Ok((game_servers_list, ping)),
+
+        _ => Err(ConnectionError::MatchmakingServerClientProtocolError),
+    }
+}
+
 async fn get_game_server_url(
     matchmaking_server_url: String,
     it_is_2d_3d_example: bool,
-) -> Result<String, ConnectionError>
+    matchmaking_request: MatchmakingRequest,
+    queue_status: Arc<Mutex<Option<(u32, u32, u32)>>>,
+    created_party_code: Arc<Mutex<Option<[u8; JOIN_CODE_LENGTH]>>>,
+) -> Result<(String, Option<[u8; JOIN_CODE_LENGTH]>, Option<u128>), ConnectionError>
 {
-
     let connection_result = tokio::time::timeout(
         Duration::from_secs(5),
         connect_async(matchmaking_server_url)
     ).await;
 
-    match connection_result
+    let mut ws_stream = match connection_result
+    {
+        Ok(Ok((ws_stream, _))) => ws_stream,
+        Ok(Err(e)) => return Err(ConnectionError::ConnectionLost(e)),
+        Err(_) => return Err(ConnectionError::ConnectionTimeout),
+    };
+
+    let version: (u32,u32,u32) = GameVersion::from(VERSION).into();
+    
+    let game_type = match it_is_2d_3d_example
+    {
+        false => GameType::Slice4DShooter,
+        true => GameType::Slice3DExample,
+    };
+
+    let mut join_code = None;
+
+    let mut party_id = None;
+
+    let mut party_start_flag = None;
+
+    let message = match matchmaking_request
+    {
+        MatchmakingRequest::AnyGameServer =>
+        {
+            matchmaking_server_protocol::ClientMessage::RequestToConnectToGameServer(version, game_type)
+        }
+        MatchmakingRequest::SpecificGameServer(server_index) =>
+        {
+            matchmaking_server_protocol::ClientMessage::RequestToConnectToSpecificServer(version, game_type, server_index)
+        }
+        MatchmakingRequest::CreatePrivateGameServer =>
+        {
+            matchmaking_server_protocol::ClientMessage::RequestToCreatePrivateGameServer(version, game_type)
+        }
+        MatchmakingRequest::PrivateGameServer(private_game_server_join_code) =>
+        {
+            join_code = Some(private_game_server_join_code);
+
+            matchmaking_server_protocol::ClientMessage::RequestToConnectToPrivateGameServer(
+                version,
+                private_game_server_join_code
+            )
+        }
+        MatchmakingRequest::CreateParty(start_flag) =>
+        {
+            party_start_flag = Some(start_flag);
+
+            matchmaking_server_protocol::ClientMessage::CreateParty(version, game_type)
+        }
+        MatchmakingRequest::JoinParty(party_code) =>
+        {
+            matchmaking_server_protocol::ClientMessage::JoinParty(version, party_code)
+        }
+    };
+
+    let message = ClientMatchmakingServerProtocol::ClientMessage(message).to_packet();
+
+    if let Err(e) = ws_stream
+        .send(tokio_tungstenite::tungstenite::Message::binary(message))
+        .await
+    {
+        return Err(ConnectionError::ConnectionLost(e));
+    }
+
+    // the join code of the created private game server, the party messages
+    // and the queue statuses are sent before the game server's address
+    loop
     {
-        Ok(connection_result) =>
+        let message = match ws_stream.next().await
+        {
+            Some(Ok(message)) => message,
+            Some(Err(e)) => return Err(ConnectionError::ConnectionLost(e)),
+            None => return Err(ConnectionError::ConnectionClosedByServer),
+        };
+
+        let message = match alkahest::deserialize::<ClientMatchmakingServerProtocol, ClientMatchmakingServerProtocol>(&message.into_data())
+        {
+            Ok(ClientMatchmakingServerProtocol::MatchmakingServerMessage(message)) => message,
+            _ => return Err(ConnectionError::MatchmakingServerClientProtocolError),
+        };
+
+        let url = match message
         {
-            match connection_result
+            MatchmakingServerMessage::GameServerAddress((ip, port)) =>
+            {
+                format!(
+                    "ws://{}.{}.{}.{}:{}/",
+                    ip[0], ip[1], ip[2], ip[3], port
+                )
+            }
+            MatchmakingServerMessage::GameServerAddressThroughProxy((proxy_ip, proxy_port, game_port)) =>
+            {
+                format!(
+                    "ws://{}.{}.{}.{}:{}/ws/{}",
+                    proxy_ip[0], proxy_ip[1], proxy_ip[2], proxy_ip[3], proxy_port, game_port
+                )
+            }
+            MatchmakingServerMessage::PrivateGameServerCreated(private_game_server_join_code) =>
+            {
+                println!(
+                    "INFO: private game server is created, its join code is {}",
+                    String::from_utf8_lossy(&private_game_server_join_code)
+                );
+
+                join_code = Some(private_game_server_join_code);
+
+                continue;
+            }
+            MatchmakingServerMessage::PartyCreated(party_code) =>
             {
-                Ok((mut ws_stream, _)) =>
+                println!(
+                    "INFO: party is created, its join code is {}, press G to start the party when all members have joined",
+                    String::from_utf8_lossy(&party_code)
+                );
+
+                *created_party_code.lock().unwrap() = Some(party_code);
+
+                let start_flag = match &party_start_flag
                 {
-                    let version = GameVersion::from(VERSION);
-                    
-                    let game_type = match it_is_2d_3d_example
-                    {
-                        false => GameType::Slice4DShooter,
-                        true => GameType::Slice3DExample,
-                    };
+                    Some(start_flag) => start_flag,
+                    None => return Err(ConnectionError::MatchmakingServerClientProtocolError),
+                };
 
-                    let message = ClientMatchmakingServerProtocol::ClientMessage(
-                        matchmaking_server_protocol::ClientMessage::RequestToConnectToGameServer(
-                            version.into(),
-                            game_type,
-                        )
-                    ).to_packet();
-        
-                    let sending_result = ws_stream
-                        .send(tokio_tungstenite::tungstenite::Message::binary(message.clone()))
-                        .await;
-                    
-                    match sending_result
-                    {
-                        Ok(_) =>
-                        {
-                            let recieving_result = ws_stream.next().await;
-        
-                            if recieving_result.is_none()
-                            {
-                                return Err(ConnectionError::ConnectionClosedByServer);
-                            }
-        
-                            match recieving_result.unwrap()
-                            {
-                                Ok(message) =>
-                                {
-                                    let deserializeing_result =
-                                        alkahest::deserialize::<ClientMatchmakingServerProtocol, ClientMatchmakingServerProtocol>(&message.into_data());
-                                    
-                                    match deserializeing_result
-                                    {
-                                        Ok(message) =>
-                                        {
-                                            match message
-                                            {
-                                                ClientMatchmakingServerProtocol::MatchmakingServerMessage(message) =>
-                                                {
-                                                    match message
-                                                    {
-                                                        MatchmakingServerMessage::GameServerAddress((ip, port)) =>
-                                                        {
-                                                            let url = format!(
-                                                                "ws://{}.{}.{}.{}:{}/",
-                                                                ip[0], ip[1], ip[2], ip[3], port
-                                                            );
-                    
-                                                            return Ok(url);
-                                                        }
-                                                        MatchmakingServerMessage::GameServerAddressThroughProxy((proxy_ip, proxy_port, game_port)) =>
-                                                        {
-                                                            let url = format!(
-                                                                "ws://{}.{}.{}.{}:{}/ws/{}",
-                                                                proxy_ip[0], proxy_ip[1], proxy_ip[2], proxy_ip[3], proxy_port, game_port
-                                                            );
-
-                                                            return Ok(url);
-                                                        }
-                                                        MatchmakingServerMessage::NoFreeServers =>
-                                                        {
-                                                            return Err(ConnectionError::NoFreeServers);
-                                                        }
-                                                        MatchmakingServerMessage::WrongGameVersionCorrectIs(correct_game_version) =>
-                                                        {
-                                                            return Err(ConnectionError::WrongVersion(correct_game_version.into()));
-                                                        }
-                                                    }
-                                                }
-                                                _ =>
-                                                {
-                                                    return Err(ConnectionError::MatchmakingServerClientProtocolError)
-                                                }
-                                            }
-                                        }
-                                        Err(_) =>
-                                        {
-                                            return Err(ConnectionError::MatchmakingServerClientProtocolError);
-                                        }
-                                    }
-                                }
-                                Err(e) =>
-                                {
-                                    return Err(ConnectionError::ConnectionLost(e));
-                                }
-                            }
-                        }
-                        Err(e) =>
-                        {
-                            return Err(ConnectionError::ConnectionLost(e));
-                        }
-                    }
+                while !start_flag.load(Ordering::Relaxed)
+                {
+                    tokio::time::sleep(Duration::from_millis(100)).await;
                 }
-                Err(e) =>
+
+                let message = ClientMatchmakingServerProtocol::ClientMessage(
+                    matchmaking_server_protocol::ClientMessage::StartPartyMatchmaking
+                ).to_packet();
+
+                if let Err(e) = ws_stream
+                    .send(tokio_tungstenite::tungstenite::Message::binary(message))
+                    .await
                 {
                     return Err(ConnectionError::ConnectionLost(e));
                 }
+
+                println!("INFO: party is started, looking for a game server");
+
+                continue;
             }
-        }
-        Err(_) =>
+            MatchmakingServerMessage::PartyJoined(members_amount) =>
+            {
+                println!(
+                    "INFO: joined the party of {} players, waiting for the party leader to start",
+                    members_amount
+                );
+
+                continue;
+            }
+            MatchmakingServerMessage::PartyIdIs(id) =>
+            {
+                party_id = Some(id);
+
+                continue;
+            }
+            MatchmakingServerMessage::QueueStatus(position, players_waiting, estimated_wait) =>
+            {
+                if estimated_wait > 0
+                {
+                    println!(
+                        "INFO: waiting for a game server, position in the queue: {}/{}, estimated wait: {} s",
+                        position,
+                        players_waiting,
+                        estimated_wait
+                    );
+                }
+                else
+                {
+                    println!(
+                        "INFO: waiting for a game server, position in the queue: {}/{}",
+                        position,
+                        players_waiting
+                    );
+                }
+
+                *queue_status.lock().unwrap() = Some((position, players_waiting, estimated_wait));
+
+                continue;
+            }
+            MatchmakingServerMessage::PartyNotFound =>
+            {
+                return Err(ConnectionError::PartyNotFound);
+            }
+            MatchmakingServerMessage::PartyDisbanded =>
+            {
+                return Err(ConnectionError::PartyDisbanded);
+            }
+            MatchmakingServerMessage::NoFreeServers =>
+            {
+                return Err(ConnectionError::NoFreeServers);
+            }
+            MatchmakingServerMessage::WrongGameVersionCorrectIs(correct_game_version) =>
+            {
+                return Err(ConnectionError::WrongVersion(correct_game_version.into()));
+            }
+            MatchmakingServerMessage::GameServerNotFound =>
+            {
+                return Err(ConnectionError::GameServerNotFound);
+            }
+            MatchmakingServerMessage::GameServersList(_) =>
+            {
+                return Err(ConnectionError::MatchmakingServerClientProtocolError);
+            }
+        };
+
+        // the signaling server of a private game server checks the join code
+        let url = match join_code
         {
-            return Err(ConnectionError::ConnectionTimeout);
-        }
+            Some(join_code) => format!("{}?join_code={}", url, String::from_utf8_lossy(&join_code)),
+            None => url,
+        };
+
+        return Ok((url, join_code, party_id));
     }
-}
\ No newline at end of file
+}
@@ -35,7 +35,10 @@ use crate::{
 
 use self::{
     renderer::Renderer,
-    render_data::RenderData,
+    render_data::{
+        static_render_data::StaticRenderData,
+        RenderData,
+    },
 };
 
 use client_server_protocol::Team;
@@ -334,6 +337,18 @@ impl RenderSystem
 
 
 
+    pub fn reload_static_data(&mut self, world: &World)
+    {
+        self.render_data.static_data = StaticRenderData::new(world);
+
+        self.renderer
+            .lock()
+            .unwrap()
+            .reload_static_data(&self.render_data.static_data);
+    }
+
+
+
     pub fn resize_frame_buffer(&mut self)
     {    
         self.window_size = self.window.inner_size();
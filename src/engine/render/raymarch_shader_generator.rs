@@ -1068,12 +1068,7 @@ fn generate_get_mats_function_body(bsp_tree: &Box<BSPElement>) -> String
             if d < MIN_DIST {
                 output.materials_count = 1u;
                 output.material_weights[0] = 1.0;
-                if shape.is_red.x == 1
-                {
-                    output.materials[0] = static_data.red_players_mat1;
-                } else {
-                    output.materials[0] = static_data.blue_players_mat1;
-                }
+                output.materials[0] = shape.look.y;
                 return output;
             }
     
@@ -1099,29 +1094,19 @@ fn generate_get_mats_function_body(bsp_tree: &Box<BSPElement>) -> String
                 if dd < 0.0 {
                     output.materials_count = 2u;
                     output.material_weights[0] = 0.26;
-                    if shape.is_red.x == 1
+                    if shape.look.x == 1
                     {
                         output.materials[0] = -3;
                     } else {
                         output.materials[0] = -4;
                     }
                     output.material_weights[1] = 0.74;
-                    if shape.is_red.x == 1
-                    {
-                        output.materials[1] = static_data.red_players_mat2;
-                    } else {
-                        output.materials[1] = static_data.blue_players_mat2;
-                    }
+                    output.materials[1] = shape.look.z;
                     return output;
                 }
                 output.materials_count = 1u;
                 output.material_weights[0] = 1.0;
-                if shape.is_red.x == 1
-                {
-                    output.materials[0] = static_data.red_players_mat2;
-                } else {
-                    output.materials[0] = static_data.blue_players_mat2;
-                }
+                output.materials[0] = shape.look.z;
                 return output;
             }
     
@@ -1149,12 +1134,7 @@ fn generate_get_mats_function_body(bsp_tree: &Box<BSPElement>) -> String
             if d < MIN_DIST {
                 output.materials_count = 1u;
                 output.material_weights[0] = 1.0;
-                if shape.is_red.x == 1
-                {
-                    output.materials[0] = static_data.red_players_mat1;
-                } else {
-                    output.materials[0] = static_data.blue_players_mat1;
-                }
+                output.materials[0] = shape.look.y;
                 return output;
             }
     
@@ -1190,12 +1170,7 @@ fn generate_get_mats_function_body(bsp_tree: &Box<BSPElement>) -> String
             if d < MIN_DIST {
                 output.materials_count = 1u;
                 output.material_weights[0] = 1.0;
-                if shape.is_red.x == 1
-                {
-                    output.materials[0] = static_data.red_players_mat2;
-                } else {
-                    output.materials[0] = static_data.blue_players_mat2;
-                }
+                output.materials[0] = shape.look.z;
                 return output;
             }
         }
@@ -108,7 +108,8 @@ pub struct Shape {
 #[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, Default)]
 pub struct PlayerForm {
     pos: [f32;4],
-    is_red: [u32; 4],
+    // x - 1 if the player has the red look, y and z - inner and outer materials
+    look: [i32; 4],
     color: [f32;3],
     radius: f32,
     rotation: [f32; 16],
@@ -31,6 +31,7 @@ use crate::{
         }, RenderQualityData},
         time::TimeSystem,
         world::{
+            level::{TeamLook, TeamVisuals},
             static_object::{ColoringArea, StaticObject, VisualWave, VolumeArea},
             World
         }
@@ -39,7 +40,6 @@ use crate::{
 
 use std::f32::consts::PI;
 
-use client_server_protocol::Team;
 use glam::{Mat4, Vec4};
 use winit::dpi::PhysicalSize;
 
@@ -301,32 +301,23 @@ impl DynamicRenderData {
         actor: &ActorWrapper,
         transform: &Transform,
         player_sphere: &PlayersDollCollider,
-        team: Team
+        team_visuals: &TeamVisuals
     )
     {
-        let player_form = match team {
-            Team::Red =>
-            {
-                PlayerForm {
-                    pos: (player_sphere.position + transform.get_position()).to_array(),
-                    is_red: [1;4],
-                    color: [1.0, 0.0, 0.0],
-                    radius: player_sphere.radius,
-                    rotation: actor.get_transform().get_rotation().transpose().to_cols_array(),
-                    weapon_offset: player_sphere.weapon_offset.to_array()
-                }
-            }
-            Team::Blue =>
-            {
-                PlayerForm {
-                    pos: (player_sphere.position + transform.get_position()).to_array(),
-                    is_red: [0;4],
-                    color: [1.0, 0.0, 0.0],
-                    radius: player_sphere.radius,
-                    rotation: actor.get_transform().get_rotation().transpose().to_cols_array(),
-                    weapon_offset: player_sphere.weapon_offset.to_array()
-                }
-            }
+        let is_red = match team_visuals.look {
+            TeamLook::Red => 1,
+            TeamLook::Blue => 0,
+        };
+
+        let (inner_material, outer_material) = team_visuals.players_visual_materials;
+
+        let player_form = PlayerForm {
+            pos: (player_sphere.position + transform.get_position()).to_array(),
+            look: [is_red, inner_material, outer_material, 0],
+            color: [1.0, 0.0, 0.0],
+            radius: player_sphere.radius,
+            rotation: actor.get_transform().get_rotation().transpose().to_cols_array(),
+            weapon_offset: player_sphere.weapon_offset.to_array()
         };
         
         self.frame_player_forms_buffer.push(player_form);
@@ -341,7 +332,7 @@ impl DynamicRenderData {
         clip_planes: (Vec4, Vec4, Vec4, Vec4, Vec4),
     )
     {
-        for (_, actor) in world.actors.iter() {
+        for (actor_id, actor) in world.actors.iter() {
 
             if let Some(visual_element) = actor.get_visual_element() {
 
@@ -402,7 +393,7 @@ impl DynamicRenderData {
                         actor,
                         transform,
                         player_sphere,
-                        team,
+                        world.level.teams_looks.get_visuals(team, *actor_id),
                     )
                 }
 
@@ -463,7 +454,7 @@ impl DynamicRenderData {
                             actor,
                             transform,
                             player_sphere,
-                            *team,
+                            world.level.teams_looks.get_visuals(*team, *actor_id),
                         )
                     }
                 }
@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use crate::engine::{render::{render_data::RenderData, ui_renderer::UIRenderer}, ui::UISystem};
+use crate::engine::{render::{raymarch_shader_generator::generate_raymarch_shader_with_static_bsp_tree, render_data::{static_render_data::StaticRenderData, RenderData}, ui_renderer::UIRenderer}, ui::UISystem};
 
 use image::{GenericImageView, ImageBuffer, Rgba};
 use winit::window::Window;
@@ -70,6 +70,7 @@ pub struct Renderer {
     size: winit::dpi::PhysicalSize<u32>,
 
     raymarch_render_pipeline: wgpu::RenderPipeline,
+    raymarch_render_pipeline_layout: wgpu::PipelineLayout,
     raymarch_target_texture: wgpu::Texture,
     raymarch_target_texture_view: wgpu::TextureView,
 
@@ -102,6 +103,10 @@ pub struct Renderer {
 
     ui_renderer: UIRenderer,
     with_ui_renderer: bool,
+
+    other_static_data: Buffer,
+    it_is_2d_3d_example: bool,
+    with_generated_raymarch_shader: bool,
 }
 
 impl Drop for Renderer {
@@ -703,45 +708,12 @@ impl Renderer {
 
         log::info!("renderer: wgpu render_pipeline_layout init");
 
-        let raymarch_render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("main shader render pipeline"),
-            layout: Some(&raymarch_render_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &raymarch_shader,
-                entry_point: Some("vs_main"),
-                compilation_options: PipelineCompilationOptions::default(), 
-                buffers: &[
-                    Vertex::desc(),
-                ],
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &raymarch_shader,
-                compilation_options: PipelineCompilationOptions::default(),
-                entry_point: Some("fs_main"),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: config.format,
-                    blend: Some(wgpu::BlendState::REPLACE),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: None,
-                polygon_mode: wgpu::PolygonMode::Fill,
-                unclipped_depth: false,
-                conservative: false,
-            },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            multiview: None,
-            cache: None,
-        });
+        let raymarch_render_pipeline = Renderer::create_raymarch_render_pipeline(
+            &device,
+            &raymarch_render_pipeline_layout,
+            &raymarch_shader,
+            config.format,
+        );
 
         let upscale_render_bind_group_layout = device.create_bind_group_layout(
             &wgpu::BindGroupLayoutDescriptor {
@@ -881,6 +853,7 @@ impl Renderer {
                 size,
 
                 raymarch_render_pipeline,
+                raymarch_render_pipeline_layout,
                 upscale_render_pipeline,
                 upscale_render_bind_group_layout,
                 upscale_render_bind_group,
@@ -912,6 +885,10 @@ impl Renderer {
 
                 ui_renderer,
                 with_ui_renderer,
+
+                other_static_data,
+                it_is_2d_3d_example,
+                with_generated_raymarch_shader,
             },
             
             RendererBuffers {
@@ -930,6 +907,102 @@ impl Renderer {
     }
 
 
+    fn create_raymarch_render_pipeline(
+        device: &wgpu::Device,
+        raymarch_render_pipeline_layout: &wgpu::PipelineLayout,
+        raymarch_shader: &wgpu::ShaderModule,
+        surface_format: wgpu::TextureFormat,
+    ) -> wgpu::RenderPipeline
+    {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("main shader render pipeline"),
+            layout: Some(raymarch_render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: raymarch_shader,
+                entry_point: Some("vs_main"),
+                compilation_options: PipelineCompilationOptions::default(), 
+                buffers: &[
+                    Vertex::desc(),
+                ],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: raymarch_shader,
+                compilation_options: PipelineCompilationOptions::default(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        })
+    }
+
+
+    // called when the game server switches to another map,
+    // the generated raymarch shader has the static shapes of the map inside
+    // so it is generated again for the new map
+    pub fn reload_static_data(&mut self, static_data: &StaticRenderData)
+    {
+        self.queue.write_buffer(
+            &self.other_static_data,
+            0,
+            bytemuck::cast_slice(&[static_data.other_static_data]),
+        );
+
+        if !self.with_generated_raymarch_shader
+        {
+            return;
+        }
+
+        let original_shader = if self.it_is_2d_3d_example
+        {
+            include_str!("shaders/raymarch_shader_for_2d_3d_example.wgsl")
+        }
+        else
+        {
+            include_str!("shaders/raymarch_shader.wgsl")
+        };
+
+        let generated_shader = generate_raymarch_shader_with_static_bsp_tree(
+            original_shader,
+            static_data
+        );
+
+        let raymarch_shader = unsafe {self.device.create_shader_module_trusted(
+            wgpu::ShaderModuleDescriptor {
+                label: Some("Raymarch Shader"),
+                source: wgpu::ShaderSource::Wgsl(generated_shader.into())
+            },
+            ShaderRuntimeChecks::unchecked()
+        )};
+
+        self.raymarch_render_pipeline = Renderer::create_raymarch_render_pipeline(
+            &self.device,
+            &self.raymarch_render_pipeline_layout,
+            &raymarch_shader,
+            self.surface_format,
+        );
+    }
+
+
     fn create_scaled_texture(
         scale_factor: f32,
         config: &wgpu::SurfaceConfiguration,
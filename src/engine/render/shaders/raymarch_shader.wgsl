@@ -32,7 +32,7 @@ struct Shape {
 
 struct PlayerForm {
     pos: vec4<f32>,
-    is_red: vec4<u32>,
+    look: vec4<i32>,
     color: vec3<f32>,
     radius: f32,
     rotation: mat4x4<f32>,
@@ -1451,12 +1451,7 @@ fn get_mats(
             if d < MIN_DIST {
                 output.materials_count = 1u;
                 output.material_weights[0] = 1.0;
-                if shape.is_red.x == 1
-                {
-                    output.materials[0] = static_data.red_players_mat1;
-                } else {
-                    output.materials[0] = static_data.blue_players_mat1;
-                }
+                output.materials[0] = shape.look.y;
                 return output;
             }
     
@@ -1482,29 +1477,19 @@ fn get_mats(
                 if dd < 0.0 {
                     output.materials_count = 2u;
                     output.material_weights[0] = 0.26;
-                    if shape.is_red.x == 1
+                    if shape.look.x == 1
                     {
                         output.materials[0] = -3;
                     } else {
                         output.materials[0] = -4;
                     }
                     output.material_weights[1] = 0.74;
-                    if shape.is_red.x == 1
-                    {
-                        output.materials[1] = static_data.red_players_mat2;
-                    } else {
-                        output.materials[1] = static_data.blue_players_mat2;
-                    }
+                    output.materials[1] = shape.look.z;
                     return output;
                 }
                 output.materials_count = 1u;
                 output.material_weights[0] = 1.0;
-                if shape.is_red.x == 1
-                {
-                    output.materials[0] = static_data.red_players_mat2;
-                } else {
-                    output.materials[0] = static_data.blue_players_mat2;
-                }
+                output.materials[0] = shape.look.z;
                 return output;
             }
     
@@ -1532,12 +1517,7 @@ fn get_mats(
             if d < MIN_DIST {
                 output.materials_count = 1u;
                 output.material_weights[0] = 1.0;
-                if shape.is_red.x == 1
-                {
-                    output.materials[0] = static_data.red_players_mat1;
-                } else {
-                    output.materials[0] = static_data.blue_players_mat1;
-                }
+                output.materials[0] = shape.look.y;
                 return output;
             }
     
@@ -1573,12 +1553,7 @@ fn get_mats(
             if d < MIN_DIST {
                 output.materials_count = 1u;
                 output.material_weights[0] = 1.0;
-                if shape.is_red.x == 1
-                {
-                    output.materials[0] = static_data.red_players_mat2;
-                } else {
-                    output.materials[0] = static_data.blue_players_mat2;
-                }
+                output.materials[0] = shape.look.z;
                 return output;
             }
         }
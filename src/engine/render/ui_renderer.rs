@@ -488,11 +488,14 @@ impl UIRenderer {
 
         let mut textures_views: HashMap<TextureType, (TextureView, (u32,u32))> = HashMap::new();
 
+        // the join code and the game servers list are drawn into their textures at runtime
+        let mut runtime_textures = Vec::new();
+
         for (_, ui_elem) in &mut ui_system.ui_elements {
             match ui_elem {
                 UIElement::Image(ui_image) => {
                     
-                    make_texture_view(
+                    let texture = make_texture_view(
                         &mut textures_views,
                         ui_image.get_texture_type(),
                         ui_system.texture_sources.get(ui_image.get_texture_type()).unwrap(),
@@ -500,6 +503,12 @@ impl UIRenderer {
                         queue
                     );
 
+                    if ui_image.get_texture_type().is_drawn_at_runtime() {
+                        if let Some(texture) = texture {
+                            runtime_textures.push((*ui_image.get_texture_type(), texture));
+                        }
+                    }
+
                     let (texture_view, (tex_width, tex_height)) = textures_views
                         .get(ui_image.get_texture_type())
                         .unwrap();
@@ -759,6 +768,10 @@ impl UIRenderer {
             }
         }
 
+        for (texture_type, texture) in runtime_textures {
+            ui_system.set_runtime_texture(texture_type, texture, queue.clone());
+        }
+
         UIRenderer {
             image_render_pipeline,
             progress_bar_render_pipeline,
@@ -868,15 +881,16 @@ impl UIRenderer {
 
 }
 
+// returns the texture if it's created by this call
 pub fn make_texture_view(
     textures_views: &mut HashMap<TextureType, (TextureView, (u32,u32))>,
     texture_type: &TextureType,
     texture_source: &[u8],
     device: &Device,
     queue: &Queue
-) {
+) -> Option<wgpu::Texture> {
     if textures_views.contains_key(texture_type) {
-        return;
+        return None;
     }
 
     let diffuse_image = image::load_from_memory(texture_source).unwrap();
@@ -926,4 +940,6 @@ pub fn make_texture_view(
     );
 
     textures_views.insert(texture_type.clone(), (texture_view, dimensions));
+
+    Some(diffuse_texture)
 }
\ No newline at end of file
@@ -26,7 +26,8 @@ use std::{
 };
 
 use glam::Vec2;
-use wgpu::{Buffer, Queue};
+use matchmaking_server_protocol::JOIN_CODE_LENGTH;
+use wgpu::{Buffer, Queue, TexelCopyBufferLayout, TexelCopyTextureInfoBase, Texture};
 
 #[repr(C)]
 #[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
@@ -148,6 +149,19 @@ pub enum UIElementType {
     FinalScoreMarkRed,
     RedTeamWinTitle,
     BlueTeamWinTitle,
+    DrawTitle,
+    YouWinTitle,
+    YouLoseTitle,
+    OvertimeTitle,
+    MatchTimerBar,
+    RedTeamKillsBar,
+    BlueTeamKillsBar,
+    VoteYesBar,
+    VoteNoBar,
+    QueuePositionBar,
+    QueueWaitBar,
+    JoinCode,
+    TextPanel,
     JoinRedTeamTitle,
     JoinBlueTeamTitle,
     BlueTeamBacklight,
@@ -165,6 +179,7 @@ pub enum UIElementType {
     TitleConnectionFailedServerError,
     TitleConnectionFailedOldVersion,
     TitleConnectionFailedLostConnection,
+    TitleServerIsShuttingDown,
     TutorialWindow,
 }
 
@@ -196,6 +211,14 @@ pub enum TextureType {
     BacklightRed,
     BlueTeamWinTitle,
     RedTeamWinTitle,
+    DrawTitle,
+    YouWinTitle,
+    YouLoseTitle,
+    OvertimeTitle,
+    MatchTimerBarTexture,
+    MatchTimerBarMask,
+    RedTeamKillsBarMask,
+    BlueTeamKillsBarMask,
     JoinBlueTeamTitle,
     JoinRedTeamTitle,
     ScoreBar,
@@ -218,7 +241,21 @@ pub enum TextureType {
     TitleConnectionFailedServerError,
     TitleConnectionFailedOldVersion,
     TitleConnectionFailedLostConnection,
+    TitleServerIsShuttingDown,
     TutorialWindow,
+    // blank textures, the text is drawn into them at runtime
+    JoinCode,
+    TextPanel,
+}
+
+impl TextureType {
+    pub fn is_drawn_at_runtime(&self) -> bool {
+        match self {
+            TextureType::JoinCode |
+            TextureType::TextPanel => true,
+            _ => false,
+        }
+    }
 }
 
 
@@ -227,6 +264,11 @@ pub struct UISystem {
     pub texture_sources: HashMap<TextureType, &'static [u8]>,
 
     pub ui_elements: HashMap<UIElementType, UIElement>,
+
+    // set by the UI renderer, there is no text in the UI,
+    // so the glyphs of the join code and the game servers list
+    // are written into these textures
+    runtime_textures: HashMap<TextureType, (Texture, Queue)>,
 }
 
 impl UISystem {
@@ -331,6 +373,38 @@ impl UISystem {
             TextureType::RedTeamWinTitle,
             include_bytes!("../assets/textures/orange_team_win.png").as_slice()
         );
+        texture_sources.insert(
+            TextureType::DrawTitle,
+            include_bytes!("../assets/textures/draw_title.png").as_slice()
+        );
+        texture_sources.insert(
+            TextureType::YouWinTitle,
+            include_bytes!("../assets/textures/you_win_title.png").as_slice()
+        );
+        texture_sources.insert(
+            TextureType::YouLoseTitle,
+            include_bytes!("../assets/textures/you_lose_title.png").as_slice()
+        );
+        texture_sources.insert(
+            TextureType::OvertimeTitle,
+            include_bytes!("../assets/textures/overtime_title.png").as_slice()
+        );
+        texture_sources.insert(
+            TextureType::MatchTimerBarTexture,
+            include_bytes!("../assets/textures/match_timer_bar.png").as_slice()
+        );
+        texture_sources.insert(
+            TextureType::MatchTimerBarMask,
+            include_bytes!("../assets/textures/match_timer_bar_mask.png").as_slice()
+        );
+        texture_sources.insert(
+            TextureType::RedTeamKillsBarMask,
+            include_bytes!("../assets/textures/red_kills_bar_mask.png").as_slice()
+        );
+        texture_sources.insert(
+            TextureType::BlueTeamKillsBarMask,
+            include_bytes!("../assets/textures/blue_kills_bar_mask.png").as_slice()
+        );
         texture_sources.insert(
             TextureType::JoinBlueTeamTitle,
             include_bytes!("../assets/textures/join_blue_team.png").as_slice()
@@ -423,6 +497,10 @@ impl UISystem {
             TextureType::TitleConnectionFailedLostConnection,
             include_bytes!("../assets/textures/connection_failed_lost_connection.png").as_slice()
         );
+        texture_sources.insert(
+            TextureType::TitleServerIsShuttingDown,
+            include_bytes!("../assets/textures/server_is_shutting_down.png").as_slice()
+        );
         texture_sources.insert(
             TextureType::TitleConnectionFailedOldVersion,
             include_bytes!("../assets/textures/connection_failed_old_version.png").as_slice()
@@ -431,6 +509,14 @@ impl UISystem {
             TextureType::TutorialWindow,
             include_bytes!("../assets/textures/tutorial_window.png").as_slice()
         );
+        texture_sources.insert(
+            TextureType::JoinCode,
+            include_bytes!("../assets/textures/join_code.png").as_slice()
+        );
+        texture_sources.insert(
+            TextureType::TextPanel,
+            include_bytes!("../assets/textures/text_panel.png").as_slice()
+        );
         
 
         let mut ui_elements = HashMap::with_capacity(10);
@@ -1420,6 +1506,233 @@ impl UISystem {
                 )
             )
         );
+        ui_elements.insert(
+            UIElementType::DrawTitle,
+            UIElement::Image(
+                UIImage::new(
+                    UIData::new(
+                        UIRect {
+                            anchor: RectAnchor::CenterCenter,
+                            position: Vec2::new(0.0, 0.0),
+                            size: RectSize::LockedWight(
+                                0.3,
+                            ),
+                            rotation_around_rect_center: 0.0,
+                            transparency: 1.0,
+                            drawing_order: 0,
+                            transform_buffer: None,
+                        },
+                        false,
+                        None,
+                    ),
+                    TextureType::DrawTitle
+                )
+            )
+        );
+        ui_elements.insert(
+            UIElementType::YouWinTitle,
+            UIElement::Image(
+                UIImage::new(
+                    UIData::new(
+                        UIRect {
+                            anchor: RectAnchor::CenterCenter,
+                            position: Vec2::new(0.0, 0.0),
+                            size: RectSize::LockedWight(
+                                0.3,
+                            ),
+                            rotation_around_rect_center: 0.0,
+                            transparency: 1.0,
+                            drawing_order: 0,
+                            transform_buffer: None,
+                        },
+                        false,
+                        None,
+                    ),
+                    TextureType::YouWinTitle
+                )
+            )
+        );
+        ui_elements.insert(
+            UIElementType::YouLoseTitle,
+            UIElement::Image(
+                UIImage::new(
+                    UIData::new(
+                        UIRect {
+                            anchor: RectAnchor::CenterCenter,
+                            position: Vec2::new(0.0, 0.0),
+                            size: RectSize::LockedWight(
+                                0.3,
+                            ),
+                            rotation_around_rect_center: 0.0,
+                            transparency: 1.0,
+                            drawing_order: 0,
+                            transform_buffer: None,
+                        },
+                        false,
+                        None,
+                    ),
+                    TextureType::YouLoseTitle
+                )
+            )
+        );
+        ui_elements.insert(
+            UIElementType::OvertimeTitle,
+            UIElement::Image(
+                UIImage::new(
+                    UIData::new(
+                        UIRect {
+                            anchor: RectAnchor::CenterCenter,
+                            position: Vec2::new(0.0, 0.0),
+                            size: RectSize::LockedWight(
+                                0.3,
+                            ),
+                            rotation_around_rect_center: 0.0,
+                            transparency: 1.0,
+                            drawing_order: 0,
+                            transform_buffer: None,
+                        },
+                        false,
+                        None,
+                    ),
+                    TextureType::OvertimeTitle
+                )
+            )
+        );
+        ui_elements.insert(
+            UIElementType::MatchTimerBar,
+            UIElement::ProgressBar(
+                UIProgressBar::new(
+                    UIData::new(
+                        UIRect {
+                            anchor: RectAnchor::CenterTop,
+                            position: Vec2::new(0.0, 0.9),
+                            size: RectSize::LockedWight(
+                                0.2
+                            ),
+                            rotation_around_rect_center: 0.0,
+                            transparency: 1.0,
+                            drawing_order: 0,
+                            transform_buffer: None,
+                        },
+                        false,
+                        None,
+                    ),
+                    TextureType::MatchTimerBarTexture,
+                    TextureType::MatchTimerBarMask,
+                    0.01,
+                    0.99,
+                    ProgressBarDirection::LeftRight,
+                )
+            )
+        );
+        ui_elements.insert(
+            UIElementType::RedTeamKillsBar,
+            UIElement::ProgressBar(
+                UIProgressBar::new(
+                    UIData::new(
+                        UIRect {
+                            anchor: RectAnchor::CenterTop,
+                            position: Vec2::new(-0.17, 0.98),
+                            size: RectSize::LockedWight(
+                                0.16
+                            ),
+                            rotation_around_rect_center: 0.0,
+                            transparency: 1.0,
+                            drawing_order: 0,
+                            transform_buffer: None,
+                        },
+                        false,
+                        None,
+                    ),
+                    TextureType::MatchTimerBarTexture,
+                    TextureType::RedTeamKillsBarMask,
+                    0.01,
+                    0.99,
+                    ProgressBarDirection::LeftRight,
+                )
+            )
+        );
+        ui_elements.insert(
+            UIElementType::BlueTeamKillsBar,
+            UIElement::ProgressBar(
+                UIProgressBar::new(
+                    UIData::new(
+                        UIRect {
+                            anchor: RectAnchor::CenterTop,
+                            position: Vec2::new(0.17, 0.98),
+                            size: RectSize::LockedWight(
+                                0.16
+                            ),
+                            rotation_around_rect_center: 0.0,
+                            transparency: 1.0,
+                            drawing_order: 0,
+                            transform_buffer: None,
+                        },
+                        false,
+                        None,
+                    ),
+                    TextureType::MatchTimerBarTexture,
+                    TextureType::BlueTeamKillsBarMask,
+                    0.01,
+                    0.99,
+                    ProgressBarDirection::RightLeft,
+                )
+            )
+        );
+        ui_elements.insert(
+            UIElementType::VoteYesBar,
+            UIElement::ProgressBar(
+                UIProgressBar::new(
+                    UIData::new(
+                        UIRect {
+                            anchor: RectAnchor::CenterTop,
+                            position: Vec2::new(-0.09, 0.84),
+                            size: RectSize::LockedWight(
+                                0.16
+                            ),
+                            rotation_around_rect_center: 0.0,
+                            transparency: 1.0,
+                            drawing_order: 0,
+                            transform_buffer: None,
+                        },
+                        false,
+                        None,
+                    ),
+                    TextureType::MatchTimerBarTexture,
+                    TextureType::MatchTimerBarMask,
+                    0.01,
+                    0.99,
+                    ProgressBarDirection::LeftRight,
+                )
+            )
+        );
+        ui_elements.insert(
+            UIElementType::VoteNoBar,
+            UIElement::ProgressBar(
+                UIProgressBar::new(
+                    UIData::new(
+                        UIRect {
+                            anchor: RectAnchor::CenterTop,
+                            position: Vec2::new(0.09, 0.84),
+                            size: RectSize::LockedWight(
+                                0.16
+                            ),
+                            rotation_around_rect_center: 0.0,
+                            transparency: 1.0,
+                            drawing_order: 0,
+                            transform_buffer: None,
+                        },
+                        false,
+                        None,
+                    ),
+                    TextureType::MatchTimerBarTexture,
+                    TextureType::MatchTimerBarMask,
+                    0.01,
+                    0.99,
+                    ProgressBarDirection::RightLeft,
+                )
+            )
+        );
         ui_elements.insert(
             UIElementType::JoinRedTeamTitle,
             UIElement::Image(
@@ -1606,6 +1919,106 @@ impl UISystem {
                 )
             )
         );
+        ui_elements.insert(
+            UIElementType::QueuePositionBar,
+            UIElement::ProgressBar(
+                UIProgressBar::new(
+                    UIData::new(
+                        UIRect {
+                            anchor: RectAnchor::TopRight,
+                            position: Vec2::new(1.0, 0.88),
+                            size: RectSize::LockedWight(
+                                0.23
+                            ),
+                            rotation_around_rect_center: 0.0,
+                            transparency: 1.0,
+                            drawing_order: 0,
+                            transform_buffer: None,
+                        },
+                        false,
+                        None,
+                    ),
+                    TextureType::MatchTimerBarTexture,
+                    TextureType::MatchTimerBarMask,
+                    0.01,
+                    0.99,
+                    ProgressBarDirection::LeftRight,
+                )
+            )
+        );
+        ui_elements.insert(
+            UIElementType::QueueWaitBar,
+            UIElement::ProgressBar(
+                UIProgressBar::new(
+                    UIData::new(
+                        UIRect {
+                            anchor: RectAnchor::TopRight,
+                            position: Vec2::new(1.0, 0.84),
+                            size: RectSize::LockedWight(
+                                0.23
+                            ),
+                            rotation_around_rect_center: 0.0,
+                            transparency: 1.0,
+                            drawing_order: 0,
+                            transform_buffer: None,
+                        },
+                        false,
+                        None,
+                    ),
+                    TextureType::MatchTimerBarTexture,
+                    TextureType::MatchTimerBarMask,
+                    0.01,
+                    0.99,
+                    ProgressBarDirection::LeftRight,
+                )
+            )
+        );
+        ui_elements.insert(
+            UIElementType::JoinCode,
+            UIElement::Image(
+                UIImage::new(
+                    UIData::new(
+                        UIRect {
+                            anchor: RectAnchor::TopRight,
+                            position: Vec2::new(1.0, 0.8),
+                            size: RectSize::LockedWight(
+                                0.23
+                            ),
+                            rotation_around_rect_center: 0.0,
+                            transparency: 1.0,
+                            drawing_order: 0,
+                            transform_buffer: None,
+                        },
+                        false,
+                        None,
+                    ),
+                    TextureType::JoinCode
+                )
+            )
+        );
+        ui_elements.insert(
+            UIElementType::TextPanel,
+            UIElement::Image(
+                UIImage::new(
+                    UIData::new(
+                        UIRect {
+                            anchor: RectAnchor::CenterCenter,
+                            position: Vec2::new(0.0, 0.0),
+                            size: RectSize::LockedWight(
+                                0.6
+                            ),
+                            rotation_around_rect_center: 0.0,
+                            transparency: 1.0,
+                            drawing_order: 2,
+                            transform_buffer: None,
+                        },
+                        false,
+                        None,
+                    ),
+                    TextureType::TextPanel
+                )
+            )
+        );
         ui_elements.insert(
             UIElementType::TitleConnectedToServer,
             UIElement::Image(
@@ -1701,6 +2114,30 @@ impl UISystem {
             )
         );
 
+        ui_elements.insert(
+            UIElementType::TitleServerIsShuttingDown,
+            UIElement::Image(
+                UIImage::new(
+                    UIData::new(
+                        UIRect {
+                            anchor: RectAnchor::TopRight,
+                            position: Vec2::new(1.0, 1.0),
+                            size: RectSize::LockedWight(
+                                0.23,
+                            ),
+                            rotation_around_rect_center: 0.0,
+                            transparency: 1.0,
+                            drawing_order: 0,
+                            transform_buffer: None,
+                        },
+                        false,
+                        None,
+                    ),
+                    TextureType::TitleServerIsShuttingDown
+                )
+            )
+        );
+
         ui_elements.insert(
             UIElementType::TitleConnectionFailedServerError,
             UIElement::Image(
@@ -1802,11 +2239,86 @@ impl UISystem {
         UISystem {
             ui_elements,
             texture_sources,
+            runtime_textures: HashMap::with_capacity(2),
         }
 
     }
 
 
+    pub fn set_runtime_texture(&mut self, texture_type: TextureType, texture: Texture, queue: Queue)
+    {
+        self.runtime_textures.insert(texture_type, (texture, queue));
+    }
+
+
+    // draws the join code into the JoinCode element's texture,
+    // missing characters of a code being typed are drawn as underscores
+    pub fn set_join_code(&mut self, join_code: &[u8])
+    {
+        let pixels = rasterize_join_code(join_code);
+
+        self.write_runtime_texture(
+            TextureType::JoinCode,
+            &pixels,
+            JOIN_CODE_TEXTURE_WIDTH,
+            JOIN_CODE_TEXTURE_HEIGHT,
+        );
+    }
+
+
+    // draws the lines into the TextPanel element's texture (the game servers list
+    // and messages which have no title), the selected line is highlighted
+    pub fn set_text_panel(&mut self, lines: &[String], selected_line: Option<usize>)
+    {
+        let pixels = rasterize_text_lines(lines, selected_line);
+
+        self.write_runtime_texture(
+            TextureType::TextPanel,
+            &pixels,
+            TEXT_LINES_TEXTURE_WIDTH,
+            TEXT_LINES_TEXTURE_HEIGHT,
+        );
+    }
+
+
+    fn write_runtime_texture(
+        &self,
+        texture_type: TextureType,
+        pixels: &[u8],
+        width: u32,
+        height: u32,
+    )
+    {
+        let (texture, queue) = match self.runtime_textures.get(&texture_type)
+        {
+            Some(runtime_texture) => runtime_texture,
+            None => return,
+        };
+
+        let texture_size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+
+        queue.write_texture(
+            TexelCopyTextureInfoBase {
+                texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            pixels,
+            TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * width),
+                rows_per_image: Some(height),
+            },
+            texture_size,
+        );
+    }
+
+
     pub fn get_texture_source(&self, texture_type: &TextureType) -> &[u8] {
         self.texture_sources
             .get(texture_type)
@@ -2605,4 +3117,208 @@ impl UIScannerDisplay {
             }
         }
     }
-}
\ No newline at end of file
+}
+
+// size of join_code.png, the texture fits JOIN_CODE_LENGTH glyphs
+const JOIN_CODE_TEXTURE_WIDTH: u32 = 352;
+const JOIN_CODE_TEXTURE_HEIGHT: u32 = 80;
+// glyphs are 5x7 cells of this size in pixels
+const JOIN_CODE_GLYPH_CELL_SIZE: u32 = 8;
+const JOIN_CODE_GLYPH_STEP: u32 = 56;
+const JOIN_CODE_GLYPHS_OFFSET: (u32, u32) = (16, 12);
+const JOIN_CODE_BACKGROUND_COLOR: [u8; 4] = [0, 0, 0, 140];
+
+// size of text_panel.png
+const TEXT_LINES_TEXTURE_WIDTH: u32 = 720;
+const TEXT_LINES_TEXTURE_HEIGHT: u32 = 300;
+const TEXT_LINES_GLYPH_CELL_SIZE: u32 = 2;
+const TEXT_LINES_GLYPH_STEP: u32 = 12;
+const TEXT_LINES_LINE_HEIGHT: u32 = 22;
+const TEXT_LINES_OFFSET: (u32, u32) = (12, 14);
+pub const TEXT_LINES_MAX_LINES: usize = 12;
+pub const TEXT_LINES_MAX_LINE_LENGTH: usize = 58;
+const TEXT_LINES_BACKGROUND_COLOR: [u8; 4] = [0, 0, 0, 160];
+const TEXT_LINES_SELECTED_LINE_COLOR: [u8; 4] = [60, 100, 180, 220];
+
+const GLYPH_COLOR: [u8; 4] = [255, 255, 255, 255];
+
+// 5x7 glyphs, every row's 5 low bits are the pixels from left to right,
+// lowercase letters are drawn as uppercase ones
+const FONT: [(u8, [u8; 7]); 47] = [
+    (b'A', [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11]),
+    (b'B', [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E]),
+    (b'C', [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E]),
+    (b'D', [0x1E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x1E]),
+    (b'E', [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F]),
+    (b'F', [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10]),
+    (b'G', [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F]),
+    (b'H', [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11]),
+    (b'I', [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E]),
+    (b'J', [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C]),
+    (b'K', [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11]),
+    (b'L', [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F]),
+    (b'M', [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11]),
+    (b'N', [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11]),
+    (b'O', [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E]),
+    (b'P', [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10]),
+    (b'Q', [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D]),
+    (b'R', [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11]),
+    (b'S', [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E]),
+    (b'T', [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04]),
+    (b'U', [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E]),
+    (b'V', [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04]),
+    (b'W', [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A]),
+    (b'X', [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11]),
+    (b'Y', [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04]),
+    (b'Z', [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F]),
+    (b'0', [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E]),
+    (b'1', [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E]),
+    (b'2', [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F]),
+    (b'3', [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E]),
+    (b'4', [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02]),
+    (b'5', [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E]),
+    (b'6', [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E]),
+    (b'7', [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08]),
+    (b'8', [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E]),
+    (b'9', [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C]),
+    (b' ', [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (b'_', [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F]),
+    (b'-', [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00]),
+    (b'.', [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C]),
+    (b':', [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00]),
+    (b'/', [0x01, 0x01, 0x02, 0x04, 0x08, 0x10, 0x10]),
+    (b'>', [0x08, 0x04, 0x02, 0x01, 0x02, 0x04, 0x08]),
+    (b'(', [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02]),
+    (b')', [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08]),
+    (b'#', [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A]),
+    (b'?', [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04]),
+];
+
+// characters which are not in the font are drawn as '?'
+fn get_glyph(character: u8) -> [u8; 7]
+{
+    let character = character.to_ascii_uppercase();
+
+    FONT
+        .iter()
+        .find(|(glyph_character, _)| *glyph_character == character)
+        .or(FONT.last())
+        .map(|(_, glyph)| *glyph)
+        .unwrap()
+}
+
+// draws the text into RGBA pixels of the texture, glyphs which don't fit are skipped
+fn draw_text(
+    pixels: &mut [u8],
+    texture_width: u32,
+    text: &[u8],
+    position: (u32, u32),
+    cell_size: u32,
+    glyph_step: u32,
+)
+{
+    for (index, character) in text.iter().enumerate()
+    {
+        let glyph_x = position.0 + index as u32 * glyph_step;
+
+        if glyph_x + 5 * cell_size > texture_width
+        {
+            return;
+        }
+
+        for (row, row_bits) in get_glyph(*character).iter().enumerate()
+        {
+            for column in 0..5u32
+            {
+                if row_bits & (0x10 >> column) == 0
+                {
+                    continue;
+                }
+
+                let cell_x = glyph_x + column * cell_size;
+                let cell_y = position.1 + row as u32 * cell_size;
+
+                for y in cell_y..cell_y + cell_size
+                {
+                    let row_start = ((y * texture_width + cell_x) * 4) as usize;
+                    let row_end = row_start + (cell_size * 4) as usize;
+
+                    if row_end > pixels.len()
+                    {
+                        return;
+                    }
+
+                    for pixel in pixels[row_start..row_end].chunks_exact_mut(4)
+                    {
+                        pixel.copy_from_slice(&GLYPH_COLOR);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// returns RGBA pixels of the join code texture
+fn rasterize_join_code(join_code: &[u8]) -> Vec<u8>
+{
+    let mut pixels = JOIN_CODE_BACKGROUND_COLOR.repeat(
+        (JOIN_CODE_TEXTURE_WIDTH * JOIN_CODE_TEXTURE_HEIGHT) as usize
+    );
+
+    let mut text = [b'_'; JOIN_CODE_LENGTH];
+
+    for (position, character) in join_code.iter().take(JOIN_CODE_LENGTH).enumerate()
+    {
+        text[position] = *character;
+    }
+
+    draw_text(
+        &mut pixels,
+        JOIN_CODE_TEXTURE_WIDTH,
+        &text,
+        JOIN_CODE_GLYPHS_OFFSET,
+        JOIN_CODE_GLYPH_CELL_SIZE,
+        JOIN_CODE_GLYPH_STEP,
+    );
+
+    pixels
+}
+
+// returns RGBA pixels of the text lines texture,
+// lines after TEXT_LINES_MAX_LINES are not drawn
+fn rasterize_text_lines(lines: &[String], selected_line: Option<usize>) -> Vec<u8>
+{
+    let mut pixels = TEXT_LINES_BACKGROUND_COLOR.repeat(
+        (TEXT_LINES_TEXTURE_WIDTH * TEXT_LINES_TEXTURE_HEIGHT) as usize
+    );
+
+    for (index, line) in lines.iter().take(TEXT_LINES_MAX_LINES).enumerate()
+    {
+        let line_y = TEXT_LINES_OFFSET.1 + index as u32 * TEXT_LINES_LINE_HEIGHT;
+
+        if selected_line == Some(index)
+        {
+            let highlight_start = line_y.saturating_sub(4);
+            let highlight_end = (highlight_start + TEXT_LINES_LINE_HEIGHT).min(TEXT_LINES_TEXTURE_HEIGHT);
+
+            let start = (highlight_start * TEXT_LINES_TEXTURE_WIDTH * 4) as usize;
+            let end = (highlight_end * TEXT_LINES_TEXTURE_WIDTH * 4) as usize;
+
+            for pixel in pixels[start..end].chunks_exact_mut(4)
+            {
+                pixel.copy_from_slice(&TEXT_LINES_SELECTED_LINE_COLOR);
+            }
+        }
+
+        draw_text(
+            &mut pixels,
+            TEXT_LINES_TEXTURE_WIDTH,
+            line.as_bytes(),
+            (TEXT_LINES_OFFSET.0, line_y),
+            TEXT_LINES_GLYPH_CELL_SIZE,
+            TEXT_LINES_GLYPH_STEP,
+        );
+    }
+
+    pixels
+}
@@ -21,6 +21,12 @@ use self::level::Level;
 
 use crate::{
     actor::{
+        flag::{
+            Flag,
+            FlagMessage,
+            FlagStatus,
+        },
+        flag_base::FlagBase,
         main_player::{
             player_settings::PlayerSettings,
             PlayerMessage
@@ -48,7 +54,7 @@ use super::{
     audio::AudioSystem, effects::EffectsSystem, engine_handle::Command, net::NetSystem, time::TimeSystem, ui::UISystem
 };
 
-use client_server_protocol::{NetCommand, Team};
+use client_server_protocol::{GameMode, NetCommand, Team};
 
 pub struct World {
     pub level: Level,
@@ -68,6 +74,10 @@ impl World {
 
         log::info!("world system: level downloaded and init");
 
+        let mut players_settings = players_settings;
+
+        players_settings.teams_looks = level.teams_looks.clone();
+
         let mut world = World {
             actors: HashMap::with_capacity(actors.len()*3),
             players_settings,
@@ -193,6 +203,11 @@ impl World {
                             keys_for_remove.push(*key);
                         }
 
+                        ActorWrapper::ObstaclesGunShot(_) =>
+                        {
+                            keys_for_remove.push(*key);
+                        }
+
                         ActorWrapper::ShootingImpact(_) =>
                         {
                             keys_for_remove.push(*key);
@@ -208,6 +223,26 @@ impl World {
                 }
             }
 
+            CommandType::SetGameMode(game_mode, red_flag_status, blue_flag_status) =>
+            {
+                match game_mode
+                {
+                    GameMode::CaptureTheFlag =>
+                    {
+                        self.spawn_flags_and_flag_bases_if_missing(
+                            red_flag_status,
+                            blue_flag_status,
+                            engine_handle,
+                        );
+                    }
+
+                    GameMode::TeamDeathmatch | GameMode::FreeForAll =>
+                    {
+                        self.remove_flags_and_flag_bases();
+                    }
+                }
+            }
+
             CommandType::SpawnEffect(_) => {}
 
             CommandType::SpawnActor(actor) =>
@@ -227,29 +262,15 @@ impl World {
 
                     if let Some(controlled_actor) = actor.get_actor_as_controlled_mut()
                     {
-                        match controlled_actor.get_team()
-                        {
-                            Team::Red =>
-                            {
-                                controlled_actor.spawn(
-                                    &mut self.level.red_spawns,
-                                    physics_system,
-                                    ui_system,
-                                    audio_system,
-                                    engine_handle
-                                );
-                            }
-                            Team::Blue =>
-                            {
-                                controlled_actor.spawn(
-                                    &mut self.level.blue_spawns,
-                                    physics_system,
-                                    ui_system,
-                                    audio_system,
-                                    engine_handle
-                                );
-                            }
-                        }
+                        let team = controlled_actor.get_team();
+
+                        controlled_actor.spawn(
+                            self.level.get_team_spawns_mut(team),
+                            physics_system,
+                            ui_system,
+                            audio_system,
+                            engine_handle
+                        );
                     }
                 } else {
                     //this case is possible when player send command to get respawn and before
@@ -262,29 +283,15 @@ impl World {
 
                     if let Some(controlled_actor) = actor.get_actor_as_controlled_mut()
                     {
-                        match controlled_actor.get_team()
-                        {
-                            Team::Red =>
-                            {
-                                controlled_actor.spawn(
-                                    &mut self.level.red_spawns,
-                                    physics_system,
-                                    ui_system,
-                                    audio_system,
-                                    engine_handle
-                                );
-                            }
-                            Team::Blue =>
-                            {
-                                controlled_actor.spawn(
-                                    &mut self.level.blue_spawns,
-                                    physics_system,
-                                    ui_system,
-                                    audio_system,
-                                    engine_handle
-                                );
-                            }
-                        }
+                        let team = controlled_actor.get_team();
+
+                        controlled_actor.spawn(
+                            self.level.get_team_spawns_mut(team),
+                            physics_system,
+                            ui_system,
+                            audio_system,
+                            engine_handle
+                        );
                     }
                 }
 
@@ -309,6 +316,16 @@ impl World {
                         net_system.send_boardcast_message_unreliable(message);
                     },
 
+                    NetCommand::SendPlayerDollStateUnreliable(actor_id, transform, input_state, velocity, server_time) => {
+                        net_system.send_player_doll_state_unreliable(
+                            actor_id,
+                            transform,
+                            input_state,
+                            velocity,
+                            server_time
+                        );
+                    },
+
                     NetCommand::SendDirectNetMessageReliable(message, peer) => {
                         net_system.send_direct_message_reliable(message, peer);
                     },
@@ -437,6 +454,133 @@ impl World {
         id
     }
 
+    fn spawn_flags_and_flag_bases_if_missing(
+        &mut self,
+        red_flag_status: FlagStatus,
+        blue_flag_status: FlagStatus,
+        engine_handle: &mut EngineHandle,
+    ) {
+        let flags_are_spawned = self.actors.values().any(|actor| {
+            match actor {
+                ActorWrapper::Flag(_) => true,
+                _ => false,
+            }
+        });
+
+        if flags_are_spawned {
+            return;
+        }
+
+        let red_flag_base = FlagBase::new(
+            Team::Red,
+            self.level.red_flag_base
+        );
+
+        let blue_flag_base = FlagBase::new(
+            Team::Blue,
+            self.level.blue_flag_base
+        );
+
+        self.add_actor_to_world(
+            ActorWrapper::FlagBase(red_flag_base),
+            engine_handle,
+        );
+
+        self.add_actor_to_world(
+            ActorWrapper::FlagBase(blue_flag_base),
+            engine_handle,
+        );
+
+        let red_flag = Flag::new(
+            Team::Red,
+            self.level.red_flag_base
+        );
+
+        self.add_actor_to_world(
+            ActorWrapper::Flag(red_flag),
+            engine_handle,
+        );
+
+        let blue_flag = Flag::new(
+            Team::Blue,
+            self.level.blue_flag_base
+        );
+
+        self.add_actor_to_world(
+            ActorWrapper::Flag(blue_flag),
+            engine_handle,
+        );
+
+        // new flags have not received JoinedToSession message,
+        // so they need to get the current flags statuses
+        for (team, status) in [(Team::Red, red_flag_status), (Team::Blue, blue_flag_status)]
+        {
+            engine_handle.send_boardcast_message(
+                Message {
+                    from: 0u128,
+                    remote_sender: false,
+                    message: MessageType::SpecificActorMessage(
+                        SpecificActorMessage::FlagMessage(
+                            FlagMessage::SetFlagStatus(team, status)
+                        )
+                    )
+                }
+            );
+        }
+    }
+
+    fn remove_flags_and_flag_bases(&mut self) {
+        self.actors.retain(|_, actor| {
+            match actor {
+                ActorWrapper::Flag(_) => false,
+                ActorWrapper::FlagBase(_) => false,
+                _ => true,
+            }
+        });
+    }
+
+    // replaces the level and the level's actors when the game server
+    // switches to another map, flags are moved to the new map's flag bases
+    pub fn change_level(
+        &mut self,
+        level: Level,
+        actors: Vec<ActorWrapper>,
+        engine_handle: &mut EngineHandle,
+    ) {
+        self.actors.retain(|_, actor| {
+            match actor {
+                ActorWrapper::WonderingActor(_) => false,
+                ActorWrapper::MoveWBonusSpot(_) => false,
+                _ => true,
+            }
+        });
+
+        let flags_were_spawned = self.actors.values().any(|actor| {
+            match actor {
+                ActorWrapper::FlagBase(_) => true,
+                _ => false,
+            }
+        });
+
+        self.remove_flags_and_flag_bases();
+
+        self.players_settings.teams_looks = level.teams_looks.clone();
+
+        self.level = level;
+
+        for actor in actors {
+            self.add_actor_to_world(actor, engine_handle);
+        }
+
+        if flags_were_spawned {
+            self.spawn_flags_and_flag_bases_if_missing(
+                FlagStatus::OnTheBase,
+                FlagStatus::OnTheBase,
+                engine_handle,
+            );
+        }
+    }
+
     pub fn add_main_actor_to_world(&mut self, actor: ActorWrapper, engine_handle: &mut EngineHandle) -> ActorID {
 
         let id = self.add_actor_to_world(actor, engine_handle);
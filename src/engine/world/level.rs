@@ -18,7 +18,7 @@ use std::{collections::HashMap, fs::File, io::Read};
 
 use crate::{
     actor::{
-        mover_w::MoverW, wandering_actor::{
+        device::holegun::{HOLE_GUN_BLUE_COLOR, HOLE_GUN_RED_COLOR}, mover_w::MoverW, move_w_bonus::MoveWBonusSpot, wandering_actor::{
             WanderingActor,
             WanderingActorMovementType,
         }, ActorWrapper
@@ -31,11 +31,11 @@ use crate::{
     }, transform::Transform
 };
 
-use client_server_protocol::Team;
+use client_server_protocol::{get_map_content_hash, Team, MAX_MOVE_W_BONUS_SPOTS, MAX_TEAMS};
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen_futures::JsFuture;
 use glam::{Vec4, Vec3};
-use serde_json::Value;
+use serde_json::{Map, Value};
 
 use super::static_object::{WFloor, WRoof};
 
@@ -56,6 +56,89 @@ pub struct Spawn {
     pub spawn_position: Vec4,
 }
 
+// built-in set of HUD elements
+#[derive(Clone, Copy, PartialEq)]
+pub enum TeamLook {
+    Red,
+    Blue,
+}
+
+#[derive(Clone, Copy)]
+pub struct TeamVisuals {
+    pub look: TeamLook,
+    // color of the team's shots and explosions
+    pub color: Vec3,
+    // inner and outer materials of the team's players
+    pub players_visual_materials: (i32, i32),
+}
+
+impl TeamVisuals {
+    fn new(look: TeamLook, red_players_visual_materials: (i32, i32), blue_players_visual_materials: (i32, i32)) -> Self {
+        match look {
+            TeamLook::Red => TeamVisuals {
+                look,
+                color: HOLE_GUN_RED_COLOR,
+                players_visual_materials: red_players_visual_materials,
+            },
+            TeamLook::Blue => TeamVisuals {
+                look,
+                color: HOLE_GUN_BLUE_COLOR,
+                players_visual_materials: blue_players_visual_materials,
+            },
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct TeamsLooks {
+    // indexed by team id
+    pub teams: [TeamVisuals; MAX_TEAMS],
+    // players in Free-for-all have no team, every player gets
+    // the visuals from this list by the player's id, never empty
+    pub free_for_all: Vec<TeamVisuals>,
+}
+
+impl Default for TeamsLooks {
+    fn default() -> Self {
+        TeamsLooks::new((0, 0), (0, 0))
+    }
+}
+
+impl TeamsLooks {
+    // teams with even ids look red and teams with odd ids look blue
+    fn new(red_players_visual_materials: (i32, i32), blue_players_visual_materials: (i32, i32)) -> Self {
+        let red = TeamVisuals::new(TeamLook::Red, red_players_visual_materials, blue_players_visual_materials);
+        let blue = TeamVisuals::new(TeamLook::Blue, red_players_visual_materials, blue_players_visual_materials);
+
+        let mut teams = [red; MAX_TEAMS];
+
+        for (team_id, visuals) in teams.iter_mut().enumerate() {
+            if team_id % 2 == 1 {
+                *visuals = blue;
+            }
+        }
+
+        TeamsLooks {
+            teams,
+            free_for_all: vec![red, blue],
+        }
+    }
+
+    pub fn get_look(&self, team: Team) -> TeamLook {
+        match team.get_id() {
+            Some(team_id) => self.teams[team_id as usize % MAX_TEAMS].look,
+            None => self.free_for_all[0].look,
+        }
+    }
+
+    pub fn get_visuals(&self, team: Team, player_id: u128) -> &TeamVisuals {
+        match team.get_id() {
+            Some(team_id) => &self.teams[team_id as usize % MAX_TEAMS],
+            None => &self.free_for_all[(player_id % self.free_for_all.len() as u128) as usize],
+        }
+    }
+}
+
 pub struct EnvirnomentVisualSettings {
     pub sky_box_name: String,
     pub sky_color: Vec4,
@@ -70,9 +153,16 @@ pub struct EnvirnomentVisualSettings {
 
 pub struct Level {
     pub level_name: String,
+    // name of the map file without the extension, the game server
+    // refers to maps by this name
+    pub map_name: String,
+    // hash of the map file content, compared with the game server's one
+    pub content_hash: u64,
     pub static_objects: Vec<StaticObject>,
-    pub red_spawns: Vec<Spawn>,
-    pub blue_spawns: Vec<Spawn>,
+    // indexed by team id
+    pub teams_spawns: Vec<Vec<Spawn>>,
+    pub free_for_all_spawns: Vec<Spawn>,
+    pub teams_looks: TeamsLooks,
     pub all_shapes_stickiness_radius: f32,
     // pub w_floor: Option<WFloor>,
     // pub w_roof: Option<WRoof>,
@@ -101,30 +191,37 @@ pub struct Level {
 impl Level {
     
     pub fn get_random_spawn_position(&self, team: Team) -> Spawn {
-        match team {
-            Team::Red =>
+        let spawns = self.get_team_spawns(team);
+
+        let random_index = {
+            let mut usize_bytes = 0usize.to_be_bytes();
+            getrandom::getrandom(&mut usize_bytes).expect("Can not make random usize in get_random_spawn_position func");
+            
+            usize::from_le_bytes(usize_bytes) % spawns.len()
+        };
+
+        spawns[random_index].clone()
+    }
+
+    // players of teams which are not described in the map
+    // are spawned at the Free-for-all spawns
+    pub fn get_team_spawns(&self, team: Team) -> &Vec<Spawn> {
+        match team.get_id() {
+            Some(team_id) if (team_id as usize) < self.teams_spawns.len() =>
             {
-                let random_index = {
-                    let mut usize_bytes = 0usize.to_be_bytes();
-                    getrandom::getrandom(&mut usize_bytes).expect("Can not make random usize in get_random_spawn_position func");
-                    
-                    usize::from_le_bytes(usize_bytes) % self.red_spawns.len()
-                };
-        
-                self.red_spawns[random_index].clone()
+                &self.teams_spawns[team_id as usize]
             }
+            _ => &self.free_for_all_spawns,
+        }
+    }
 
-            Team::Blue =>
+    pub fn get_team_spawns_mut(&mut self, team: Team) -> &mut Vec<Spawn> {
+        match team.get_id() {
+            Some(team_id) if (team_id as usize) < self.teams_spawns.len() =>
             {
-                let random_index = {
-                    let mut usize_bytes = 0usize.to_be_bytes();
-                    getrandom::getrandom(&mut usize_bytes).expect("Can not make random usize in get_random_spawn_position func");
-                    
-                    usize::from_le_bytes(usize_bytes) % self.blue_spawns.len()
-                };
-        
-                self.blue_spawns[random_index].clone()
+                &mut self.teams_spawns[team_id as usize]
             }
+            _ => &mut self.free_for_all_spawns,
         }
     }
 
@@ -133,101 +230,20 @@ impl Level {
     {
         #[cfg(target_arch = "wasm32")]
         {
-            let window = web_sys::window().unwrap();
-        
-            let target = format!("http://127.0.0.1:5500/src/assets/maps/{}.json", level_name);
-            
-            let promise = window.fetch_with_str(target);
-        
-            let result = JsFuture::from(promise).await;
-        
-            let output = match result {
-                Ok(val) => {
-                    let response: web_sys::Response = val.into();
-        
-                    let json_text = JsFuture::from(response.text().unwrap()).await;
-                    
-                    match json_text {
-                        Ok(text) => {
-                            let json_map = serde_json::from_str(&text.as_string().unwrap()).unwrap();
-                            
-                            parse_json_level(json_map)
-                        },
-                        Err(val) => {
-                            panic!(
-                                "ERROR: cannot converting map.json file into text, err is: {:?}",
-                                val
-                            );
-                        },
-                    }
-                },
-                Err(val) => {
-                    panic!(
-                        "ERROR: the map cannot be loaded, err: {}",
-                        val.as_string().unwrap_or("".to_string())
-                    );
-                }  
-            };
-        
-            return output;
+            return Level::fetch_level(level_name)
+                .await
+                .unwrap_or_else(|e| panic!("ERROR: the map cannot be loaded, err: {}", e));
         }
         #[cfg(not(target_arch = "wasm32"))]
         {
-            let json_map = {
-                let mut file = File::open(
-                    format!("./{}.json", level_name)
-                );
+            let map_content = {
+                let file = open_level_file(&level_name);
 
-                if file.is_err()
-                {
-                    file = File::open(
-                        format!("./assets/maps/{}.json", level_name)
-                    )
-                }
-
-                if file.is_err()
-                {
-                    file = File::open(
-                        format!("./src/assets/maps/{}.json", level_name)
-                    )
-                }
+                let mut file_content = String::new();
 
-                if file.is_err()
+                if file.is_ok() && file.unwrap().read_to_string(&mut file_content).is_ok()
                 {
-                    file = File::open(
-                        format!("../../../src/assets/maps/{}.json", level_name)
-                    );
-                }
-
-                if file.is_ok()
-                {
-                    let mut file_content = String::new();
-                    match file.unwrap().read_to_string(&mut file_content) {
-                        Ok(_) => {
-                            serde_json::from_str(&file_content)
-                                .expect("ERROR: Can't parse the map's JSON format")
-                        },
-                        Err(e) => {
-
-                            // panic!("ERROR: the {}.json cannot be loaded, err: {}", level_name, e);
-                            match level_name.as_str() {
-                                "map" => 
-                                {
-                                    serde_json::from_str(include_str!("../../../src/assets/maps/map.json"))
-                                        .expect("Can't parse map.json file")
-                                }
-                                "map_2d_3d" =>
-                                {
-                                    serde_json::from_str(include_str!("../../../src/assets/maps/map_2d_3d.json"))
-                                        .expect("Can't parse map.json file")
-                                }
-                                _ =>
-                                {
-                                    panic!("incorrect level name");
-                                }
-                            }
-                        }
-                    }
+                    file_content
                 }
                 else
                 {
@@ -235,13 +251,11 @@ impl Level {
                     match level_name.as_str() {
                         "map" => 
                         {
-                            serde_json::from_str(include_str!("../../../src/assets/maps/map.json"))
-                                .expect("Can't parse map.json file")
+                            include_str!("../../../src/assets/maps/map.json").to_string()
                         }
                         "map_2d_3d" =>
                         {
-                            serde_json::from_str(include_str!("../../../src/assets/maps/map_2d_3d.json"))
-                                .expect("Can't parse map.json file")
+                            include_str!("../../../src/assets/maps/map_2d_3d.json").to_string()
                         }
                         _ =>
                         {
@@ -251,9 +265,99 @@ impl Level {
                 }
             };
 
-            return parse_json_level(json_map);
+            let json_map = serde_json::from_str(&map_content)
+                .expect("ERROR: Can't parse the map's JSON format");
+
+            let (mut level, actors) = parse_json_level(json_map);
+
+            level.map_name = level_name;
+            level.content_hash = get_map_content_hash(map_content.as_bytes());
+
+            return (level, actors);
         }
     }
+
+
+    // the web client fetches the map from the web server, Err is returned
+    // if the map isn't there or can't be parsed
+    #[cfg(target_arch = "wasm32")]
+    pub async fn fetch_level(level_name: String) -> Result<(Level, Vec<ActorWrapper>), String>
+    {
+        let window = web_sys::window().unwrap();
+
+        let target = format!("http://127.0.0.1:5500/src/assets/maps/{}.json", level_name);
+
+        let response: web_sys::Response = JsFuture::from(window.fetch_with_str(&target))
+            .await
+            .map_err(|e| e.as_string().unwrap_or_default())?
+            .into();
+
+        if !response.ok()
+        {
+            return Err(format!("the web server answered {} for {}", response.status(), target));
+        }
+
+        let text_promise = response
+            .text()
+            .map_err(|e| e.as_string().unwrap_or_default())?;
+
+        let map_content = JsFuture::from(text_promise)
+            .await
+            .map_err(|e| e.as_string().unwrap_or_default())?
+            .as_string()
+            .ok_or(String::from("the map file is not a text"))?;
+
+        let json_map = serde_json::from_str(&map_content)
+            .map_err(|e| format!("can't parse the map's JSON format: {}", e))?;
+
+        let (mut level, actors) = parse_json_level(json_map);
+
+        level.map_name = level_name;
+        level.content_hash = get_map_content_hash(map_content.as_bytes());
+
+        Ok((level, actors))
+    }
+
+
+    // the maps embedded into the binary are always available,
+    // the rest have to be found next to the binary or in the assets
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn is_level_available(level_name: &str) -> bool
+    {
+        open_level_file(level_name).is_ok() || level_name == "map" || level_name == "map_2d_3d"
+    }
+}
+
+
+#[cfg(not(target_arch = "wasm32"))]
+fn open_level_file(level_name: &str) -> std::io::Result<File>
+{
+    let mut file = File::open(
+        format!("./{}.json", level_name)
+    );
+
+    if file.is_err()
+    {
+        file = File::open(
+            format!("./assets/maps/{}.json", level_name)
+        )
+    }
+
+    if file.is_err()
+    {
+        file = File::open(
+            format!("./src/assets/maps/{}.json", level_name)
+        )
+    }
+
+    if file.is_err()
+    {
+        file = File::open(
+            format!("../../../src/assets/maps/{}.json", level_name)
+        );
+    }
+
+    file
 }
 
 
@@ -283,51 +387,6 @@ fn parse_json_level(
             as f32
     };
 
-    let red_spawns = {
-        let spawns_array = json_level
-            .get("red_spawns")
-            .expect("Wrong JSON map format. JSON level must have red_spawns property")
-            .as_array()
-            .expect("red_spawns is not an array");
-
-        let mut spawns = Vec::new();
-
-        for value in spawns_array {
-            let transform = parse_json_into_transform(value, "spawn_position");
-
-            let spawn = Spawn {
-                spawn_position: transform.get_position(),
-            };
-
-            spawns.push(spawn);
-        }
-
-        spawns  
-    };
-
-    let blue_spawns = {
-        let spawns_array = json_level
-            .get("blue_spawns")
-            .expect("Wrong JSON map format. JSON level must have blue_spawns property")
-            .as_array()
-            .expect("blue_spawns is not an array");
-
-        let mut spawns = Vec::new();
-
-        for value in spawns_array {
-            let transform = parse_json_into_transform(value, "spawn_position");
-
-            let spawn = Spawn {
-                spawn_position: transform.get_position(),
-            };
-
-            spawns.push(spawn);
-        }
-
-        spawns  
-    };
-
-
     let (visual_materials, materials_table) = {
         let json_visual_materials = json_level
             .get("visual_materials")
@@ -360,6 +419,124 @@ fn parse_json_level(
         parse_players_visual_materials(json_players_visual_materials, &materials_table)
     };
 
+    let mut teams_looks = TeamsLooks::new(red_players_visual_materials, blue_players_visual_materials);
+
+    // maps with only Red and Blue teams can describe them with
+    // red_spawns and blue_spawns properties instead of teams property
+    let teams_spawns = match json_level.get("teams") {
+        Some(json_teams) => {
+            let teams_array = json_teams
+                .as_array()
+                .expect("Wrong JSON map format. teams value must be an array");
+
+            if teams_array.len() > MAX_TEAMS {
+                panic!("Wrong JSON map format. Map can't have more than {} teams", MAX_TEAMS);
+            }
+
+            if teams_array.is_empty() {
+                panic!("Wrong JSON map format. teams array must have at least one team");
+            }
+
+            let mut teams_spawns = Vec::with_capacity(teams_array.len());
+
+            for (team_id, json_team) in teams_array.iter().enumerate() {
+                let json_team = json_team
+                    .as_object()
+                    .expect("Wrong JSON map format. team in teams array must be an object");
+
+                let spawns = json_team
+                    .get("spawns")
+                    .expect("Wrong JSON map format. team in teams array must have spawns property");
+
+                teams_spawns.push(parse_json_spawns(spawns, "team's spawns"));
+
+                teams_looks.teams[team_id] = parse_json_team_visuals(
+                    json_team,
+                    teams_looks.teams[team_id],
+                    &materials_table,
+                    red_players_visual_materials,
+                    blue_players_visual_materials,
+                );
+            }
+
+            teams_spawns
+        }
+        None => {
+            let red_spawns = json_level
+                .get("red_spawns")
+                .expect("Wrong JSON map format. JSON level must have teams or red_spawns property");
+
+            let blue_spawns = json_level
+                .get("blue_spawns")
+                .expect("Wrong JSON map format. JSON level must have teams or blue_spawns property");
+
+            vec![
+                parse_json_spawns(red_spawns, "red_spawns"),
+                parse_json_spawns(blue_spawns, "blue_spawns"),
+            ]
+        }
+    };
+
+    // if the map doesn't describe Free-for-all looks
+    // players get the looks of the map's teams
+    teams_looks.free_for_all = teams_looks.teams[..teams_spawns.len()].to_vec();
+
+    // if the map doesn't describe Free-for-all spawns
+    // players are spawned at all teams' spawns
+    let free_for_all_spawns = match json_level.get("free_for_all") {
+        Some(json_free_for_all) => {
+            let json_free_for_all = json_free_for_all
+                .as_object()
+                .expect("Wrong JSON map format. free_for_all value must be an object");
+
+            let default_visuals = teams_looks.free_for_all[0];
+
+            if let Some(json_looks) = json_free_for_all.get("looks") {
+                let looks_array = json_looks
+                    .as_array()
+                    .expect("Wrong JSON map format. free_for_all's looks value must be an array");
+
+                if looks_array.is_empty() {
+                    panic!("Wrong JSON map format. free_for_all's looks array must have at least one look");
+                }
+
+                teams_looks.free_for_all = looks_array
+                    .iter()
+                    .map(|json_look| {
+                        let json_look = json_look
+                            .as_object()
+                            .expect("Wrong JSON map format. look in free_for_all's looks array must be an object");
+
+                        parse_json_team_visuals(
+                            json_look,
+                            default_visuals,
+                            &materials_table,
+                            red_players_visual_materials,
+                            blue_players_visual_materials,
+                        )
+                    })
+                    .collect();
+            }
+            else if json_free_for_all.contains_key("look") {
+                teams_looks.free_for_all = vec![parse_json_team_visuals(
+                    json_free_for_all,
+                    default_visuals,
+                    &materials_table,
+                    red_players_visual_materials,
+                    blue_players_visual_materials,
+                )];
+            }
+
+            let spawns = json_free_for_all
+                .get("spawns")
+                .expect("Wrong JSON map format. free_for_all must have spawns property");
+
+            parse_json_spawns(spawns, "free_for_all spawns")
+        }
+        None => teams_spawns.concat(),
+    };
+
+
     // let w_cups_visual_materials = {
     //     let json_players_visual_materials = json_level
     //         .get("w_cups_visual_materials")
@@ -384,7 +561,7 @@ fn parse_json_level(
         parse_json_static_objects(json_static_objects, &default_settings, &materials_table)
     };
 
-    let actors = {
+    let mut actors = {
         let json_actors = json_level
             .get("actors")
             .expect("Wrong JSON map format. JSON level must have static_objects property");
@@ -471,13 +648,51 @@ fn parse_json_level(
         parse_json_into_transform(blue_base_position_json, "blue_base_position").get_position()
     };
     
-    // let move_w_bonus_spot = {
-    //     let move_w_bonus_spot_json = json_level
-    //         .get("move_w_bonus_spot")
-    //         .expect("Wrong JSON map format. JSON level must have move_w_bonus_spot property");
+    // the index of a move w bonus spot is its position in the move_w_bonus_spots array,
+    // the game server tracks the spots by this index.
+    // Old maps have a single move_w_bonus_spot property
+    let move_w_bonus_spots = {
+        if let Some(move_w_bonus_spots_json) = json_level.get("move_w_bonus_spots")
+        {
+            let array = move_w_bonus_spots_json
+                .as_array()
+                .expect("Wrong JSON map format. JSON's move_w_bonus_spots property must be an array");
 
-    //     parse_json_into_transform(move_w_bonus_spot_json, "move_w_bonus_spot")
-    // };
+            let mut move_w_bonus_spots = Vec::with_capacity(array.len());
+
+            for value in array
+            {
+                move_w_bonus_spots.push(parse_json_into_transform(value, "move_w_bonus_spots"));
+            }
+
+            move_w_bonus_spots
+        }
+        else if let Some(move_w_bonus_spot_json) = json_level.get("move_w_bonus_spot")
+        {
+            vec![parse_json_into_transform(move_w_bonus_spot_json, "move_w_bonus_spot")]
+        }
+        else
+        {
+            Vec::new()
+        }
+    };
+
+    if move_w_bonus_spots.len() > MAX_MOVE_W_BONUS_SPOTS
+    {
+        panic!(
+            "Wrong JSON map format. JSON level can't have more than {} move_w_bonus_spots",
+            MAX_MOVE_W_BONUS_SPOTS
+        );
+    }
+
+    for (index, transform) in move_w_bonus_spots.into_iter().enumerate()
+    {
+        actors.push(
+            ActorWrapper::MoveWBonusSpot(
+                MoveWBonusSpot::new(transform, index as u32)
+            )
+        );
+    }
 
     // let blue_base_w_level = {
     //     json_level
@@ -519,9 +734,12 @@ fn parse_json_level(
         // blue_base_w_level,
         // red_base_w_level,
         level_name,
+        map_name: String::new(),
+        content_hash: 0u64,
         static_objects,
-        red_spawns,
-        blue_spawns,
+        teams_spawns,
+        free_for_all_spawns,
+        teams_looks,
         all_shapes_stickiness_radius,
         red_base_position,
         blue_base_position,
@@ -545,6 +763,78 @@ fn parse_json_level(
 }
 
 
+fn parse_json_spawns(json: &Value, spawns_name: &str) -> Vec<Spawn> {
+    let spawns_array = json
+        .as_array()
+        .expect(&format!("Wrong JSON map format. {} is not an array", spawns_name));
+
+    // players are spawned at a random spawn of the list
+    if spawns_array.is_empty() {
+        panic!("Wrong JSON map format. {} must have at least one spawn", spawns_name);
+    }
+
+    let mut spawns = Vec::new();
+
+    for value in spawns_array {
+        let transform = parse_json_into_transform(value, "spawn_position");
+
+        let spawn = Spawn {
+            spawn_position: transform.get_position(),
+        };
+
+        spawns.push(spawn);
+    }
+
+    spawns
+}
+
+
+fn parse_json_team_look(json: &Value) -> TeamLook {
+    let look_name = json
+        .as_str()
+        .expect("Wrong JSON map format. team's look value must be a string");
+
+    match look_name {
+        "red" => TeamLook::Red,
+        "blue" => TeamLook::Blue,
+        _ => panic!("Wrong JSON map format. team's look must be red or blue, got {}", look_name),
+    }
+}
+
+
+// a team or a Free-for-all look can override the color and the players' materials
+// of its built-in look
+fn parse_json_team_visuals(
+    json: &Map<String, Value>,
+    default_visuals: TeamVisuals,
+    materials_table: &HashMap<String, i32>,
+    red_players_visual_materials: (i32, i32),
+    blue_players_visual_materials: (i32, i32),
+) -> TeamVisuals {
+    let mut visuals = match json.get("look") {
+        Some(json_look) => TeamVisuals::new(
+            parse_json_team_look(json_look),
+            red_players_visual_materials,
+            blue_players_visual_materials,
+        ),
+        None => default_visuals,
+    };
+
+    if let Some(json_color) = json.get("color") {
+        visuals.color = parse_json_color_and_multiplier(json_color).truncate();
+    }
+
+    if let Some(json_players_visual_materials) = json.get("players_visual_materials") {
+        visuals.players_visual_materials = parse_players_visual_materials(
+            json_players_visual_materials,
+            materials_table
+        );
+    }
+
+    visuals
+}
+
+
 fn parse_mover_w(json_obj: &Value, w_levels: &Vec<f32>) -> MoverW
 {
     let transform = parse_json_into_transform(json_obj, "mover_w");
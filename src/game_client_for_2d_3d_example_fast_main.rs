@@ -128,6 +128,7 @@ fn main() {
                 &mut systems.ui,
                 systems.world.level.red_flag_base.get_position(),
                 systems.world.level.blue_flag_base.get_position(),
+                systems.world.level.teams_looks.clone(),
                 false,
             );
             
@@ -50,6 +50,8 @@ fn main() {
         specific_backend,
         Box::new(|systems| {
 
+            systems.net.set_is_spectator(true);
+
             let observer = Observer::new(
                 InputMaster::LocalMaster(
                     LocalMaster::new(ActionsFrameState::empty())
@@ -123,6 +125,7 @@ fn main() {
                 &mut systems.ui,
                 systems.world.level.red_flag_base.get_position(),
                 systems.world.level.blue_flag_base.get_position(),
+                systems.world.level.teams_looks.clone(),
                 true,
             );
             
@@ -173,6 +173,14 @@ impl ApplicationHandler for Slice4DShooter
             WindowEvent::KeyboardInput {event, ..} => {
 
                 if let PhysicalKey::Code(code) = event.physical_key {
+
+                    // the typed join code isn't treated as the game's controls
+                    if systems.net.is_entering_join_code() && event.state.is_pressed() {
+                        systems.net.type_join_code_key(code, &mut systems.ui);
+
+                        return;
+                    }
+
                     match code {
                         KeyCode::Escape => {
                             if event.state.is_pressed() {
@@ -360,8 +368,11 @@ fn main_loop_tick(
         &mut systems.runtime,
         &mut systems.audio,
         &mut systems.ui,
+        &systems.time,
     );
 
+    systems.change_map_if_needed();
+
     systems.input.set_input_to_controlled_actors(&mut systems.world, &mut systems.net);
 
     systems.world.tick(